@@ -1,3 +1,50 @@
+use std::path::Path;
+
+/// 事件日志消息表中的事件 ID（与 src/win_event_log.rs 中的 EVENT_ID_* 保持一致）
+const EVENT_IDS: &[u32] = &[1000, 1001, 1002, 1010];
+
+/// 生成 MESSAGETABLE 资源的二进制内容（与 mc.exe 的输出格式相同），每条消息原样输出事件文本
+fn message_table(ids: &[u32]) -> Vec<u8> {
+    // 连续的 ID 合并为一个块
+    let mut blocks: Vec<(u32, u32)> = Vec::new();
+    for &id in ids {
+        match blocks.last_mut() {
+            Some((_, high)) if *high + 1 == id => *high = id,
+            _ => blocks.push((id, id)),
+        }
+    }
+
+    // 消息文本为 "%1\r\n"，使用以 0 结尾并按 4 字节对齐的 UTF-16
+    let mut text: Vec<u8> = "%1\r\n\0"
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect();
+    while text.len() % 4 != 0 {
+        text.push(0);
+    }
+    let mut entry = Vec::new();
+    entry.extend_from_slice(&((text.len() + 4) as u16).to_le_bytes());
+    entry.extend_from_slice(&1u16.to_le_bytes());
+    entry.extend_from_slice(&text);
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&(blocks.len() as u32).to_le_bytes());
+    let mut offset = 4 + 12 * blocks.len();
+    for (low, high) in &blocks {
+        data.extend_from_slice(&low.to_le_bytes());
+        data.extend_from_slice(&high.to_le_bytes());
+        data.extend_from_slice(&(offset as u32).to_le_bytes());
+        offset += entry.len() * (high - low + 1) as usize;
+    }
+    for (low, high) in &blocks {
+        for _ in *low..=*high {
+            data.extend_from_slice(&entry);
+        }
+    }
+
+    data
+}
+
 fn main() {
     if std::env::var("CARGO_CFG_TARGET_OS")
         .map(|s| s == "windows")
@@ -25,6 +72,19 @@ fn main() {
             res.set("LegalCopyright", &license);
         }
 
+        // 事件查看器从注册的 EventMessageFile 中读取事件描述
+        let out_dir = std::env::var("OUT_DIR").unwrap_or_default();
+        let table_path = Path::new(&out_dir).join("event_messages.bin");
+        match std::fs::write(&table_path, message_table(EVENT_IDS)) {
+            Ok(()) => {
+                res.append_rc_content(&format!(
+                    "1 MESSAGETABLE \"{}\"",
+                    table_path.display().to_string().replace('\\', "\\\\")
+                ));
+            }
+            Err(e) => eprintln!("[build.rs] failed to write event message table: {}", e),
+        }
+
         if let Err(e) = res.compile() {
             eprintln!("[build.rs] failed to compile Windows resources: {}", e);
         }
@@ -0,0 +1,254 @@
+use crate::error::{ManagerError, Result};
+use crate::file_ops::{glob_matches, write_file_atomic};
+use crate::metrics::report_event;
+
+use std::path::{Path, PathBuf};
+
+const CONSOLE_SECTION: &str = "Logging.Console";
+const CONSOLE_KEY: &str = "Enabled";
+/// BepInEx.cfg 中未设置该项时 BepInEx 使用的默认值
+const CONSOLE_DEFAULT: bool = true;
+
+/// BepInEx.cfg 路径
+pub fn bepinex_cfg_path(game_root: &Path) -> PathBuf {
+    game_root.join("BepInEx").join("config").join("BepInEx.cfg")
+}
+
+fn bepinex_installed(game_root: &Path) -> bool {
+    !glob_matches(
+        &game_root
+            .join("BepInEx")
+            .join("core")
+            .join("BepInEx.Core.dll"),
+    )
+    .is_empty()
+}
+
+fn section_name(line: &str) -> Option<&str> {
+    let t = line.trim();
+    t.strip_prefix('[')
+        .and_then(|s| s.strip_suffix(']'))
+        .map(str::trim)
+}
+
+fn key_value(line: &str) -> Option<(&str, &str)> {
+    let t = line.trim_start();
+    if t.starts_with('#') || t.starts_with(';') {
+        return None;
+    }
+    t.split_once('=').map(|(k, v)| (k.trim(), v.trim()))
+}
+
+/// 读取 INI 内容中指定节下的键值
+pub fn get_ini_value(content: &str, section: &str, key: &str) -> Option<String> {
+    let mut in_section = false;
+    for line in content.lines() {
+        if let Some(name) = section_name(line) {
+            in_section = name.eq_ignore_ascii_case(section);
+        } else if in_section
+            && let Some((k, v)) = key_value(line)
+            && k.eq_ignore_ascii_case(key)
+        {
+            return Some(v.to_string());
+        }
+    }
+
+    None
+}
+
+/// 设置 INI 内容中指定节下的键值，其余内容（含注释与换行风格）保持不变
+pub fn set_ini_value(content: &str, section: &str, key: &str, value: &str) -> String {
+    let newline = if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let entry = format!("{} = {}", key, value);
+
+    let start = lines
+        .iter()
+        .position(|l| section_name(l).is_some_and(|n| n.eq_ignore_ascii_case(section)));
+
+    match start {
+        Some(start) => {
+            let end = lines[start + 1..]
+                .iter()
+                .position(|l| section_name(l).is_some())
+                .map_or(lines.len(), |i| start + 1 + i);
+
+            let existing = (start + 1..end)
+                .find(|&i| key_value(&lines[i]).is_some_and(|(k, _)| k.eq_ignore_ascii_case(key)));
+
+            match existing {
+                Some(i) => lines[i] = entry,
+                None => {
+                    // 插入到节内最后一个非空行之后
+                    let last = (start..end)
+                        .rev()
+                        .find(|&i| !lines[i].trim().is_empty())
+                        .unwrap_or(start);
+                    lines.insert(last + 1, entry);
+                }
+            }
+        }
+        None => {
+            if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(format!("[{}]", section));
+            lines.push(String::new());
+            lines.push(entry);
+        }
+    }
+
+    let mut out = lines.join(newline);
+    out.push_str(newline);
+    out
+}
+
+/// 读取当前是否显示 BepInEx 控制台（BepInEx 未安装时返回错误）
+pub fn bepinex_console_enabled(game_root: &Path) -> Result<bool> {
+    if !bepinex_installed(game_root) {
+        return Err(ManagerError::Other(
+            "未检测到 BepInEx，请先安装 MetaMystia Mod".to_string(),
+        ));
+    }
+
+    let content = std::fs::read_to_string(bepinex_cfg_path(game_root)).unwrap_or_default();
+    Ok(get_ini_value(&content, CONSOLE_SECTION, CONSOLE_KEY)
+        .map(|v| v.eq_ignore_ascii_case("true"))
+        .unwrap_or(CONSOLE_DEFAULT))
+}
+
+/// 设置是否显示 BepInEx 控制台，原配置备份为 BepInEx.cfg.old；返回修改前的值
+pub fn set_bepinex_console(game_root: &Path, enabled: bool) -> Result<bool> {
+    let previous = bepinex_console_enabled(game_root)?;
+
+    let cfg_path = bepinex_cfg_path(game_root);
+    let content = if cfg_path.is_file() {
+        let content = std::fs::read_to_string(&cfg_path).map_err(|e| {
+            ManagerError::from(std::io::Error::new(
+                e.kind(),
+                format!("读取 {} 失败：{}", cfg_path.display(), e),
+            ))
+        })?;
+
+        let backup = cfg_path.with_extension("cfg.old");
+        std::fs::copy(&cfg_path, &backup).map_err(|e| {
+            ManagerError::from(std::io::Error::new(
+                e.kind(),
+                format!("备份 {} 失败：{}", cfg_path.display(), e),
+            ))
+        })?;
+
+        content
+    } else {
+        if let Some(parent) = cfg_path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                ManagerError::from(std::io::Error::new(
+                    e.kind(),
+                    format!("创建 BepInEx 配置目录 {} 失败：{}", parent.display(), e),
+                ))
+            })?;
+        }
+        String::new()
+    };
+
+    let value = if enabled { "true" } else { "false" };
+    let updated = set_ini_value(&content, CONSOLE_SECTION, CONSOLE_KEY, value);
+    write_file_atomic(&cfg_path, updated.as_bytes())?;
+
+    report_event("Configure.BepInExConsole", Some(value));
+
+    Ok(previous)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_ini_value_reads_keys_within_section() {
+        let content = "# [Logging.Console]\n\
+                       Enabled = false\n\
+                       \n\
+                       [Logging.Console]\n\
+                       ; Enabled = false\n\
+                       # Enabled = false\n\
+                       enabled = true \n\
+                       LogLevels = Fatal, Error\n\
+                       \n\
+                       [Logging.Disk]\n\
+                       Enabled = false\n";
+
+        let cases = [
+            ("Logging.Console", "Enabled", Some("true")),
+            ("logging.console", "LOGLEVELS", Some("Fatal, Error")),
+            ("Logging.Disk", "Enabled", Some("false")),
+            ("Logging.Disk", "LogLevels", None),
+            ("Logging.Console", "Missing", None),
+            ("Missing.Section", "Enabled", None),
+        ];
+        for (section, key, expected) in cases {
+            assert_eq!(
+                get_ini_value(content, section, key).as_deref(),
+                expected,
+                "[{}] {}",
+                section,
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn set_ini_value_updates_or_inserts_entries() {
+        let cases = [
+            (
+                "existing key keeps comments",
+                "[Logging.Console]\n# 注释\nEnabled = true\n",
+                "[Logging.Console]\n# 注释\nEnabled = false\n",
+            ),
+            (
+                "commented key is not replaced",
+                "[Logging.Console]\n# Enabled = true\n\n[Logging.Disk]\nEnabled = true\n",
+                "[Logging.Console]\n# Enabled = true\nEnabled = false\n\n[Logging.Disk]\nEnabled = true\n",
+            ),
+            (
+                "missing key in section",
+                "[Logging.Console]\nLogLevels = All\n\n[Logging.Disk]\n",
+                "[Logging.Console]\nLogLevels = All\nEnabled = false\n\n[Logging.Disk]\n",
+            ),
+            (
+                "missing section",
+                "[Logging.Disk]\nEnabled = true",
+                "[Logging.Disk]\nEnabled = true\n\n[Logging.Console]\n\nEnabled = false\n",
+            ),
+            (
+                "empty content",
+                "",
+                "[Logging.Console]\n\nEnabled = false\n",
+            ),
+            (
+                "CRLF is preserved",
+                "[Logging.Console]\r\n; 注释\r\nEnabled = true\r\n",
+                "[Logging.Console]\r\n; 注释\r\nEnabled = false\r\n",
+            ),
+            (
+                "CRLF when inserting",
+                "[General]\r\nA = 1\r\n",
+                "[General]\r\nA = 1\r\n\r\n[Logging.Console]\r\n\r\nEnabled = false\r\n",
+            ),
+        ];
+        for (name, content, expected) in cases {
+            let updated = set_ini_value(content, "Logging.Console", "Enabled", "false");
+            assert_eq!(updated, expected, "{}", name);
+            assert_eq!(
+                get_ini_value(&updated, "Logging.Console", "Enabled").as_deref(),
+                Some("false"),
+                "{}",
+                name
+            );
+        }
+    }
+}
@@ -1,4 +1,5 @@
-use crate::config::UninstallMode;
+use crate::config::{BEPINEX_LOG_LEVELS, UninstallMode};
+use crate::model::UpdateChannel;
 
 use clap::{ArgGroup, Parser, ValueEnum};
 use std::path::PathBuf;
@@ -9,9 +10,17 @@ use std::path::PathBuf;
 #[command(about = env!("CARGO_PKG_DESCRIPTION"), long_about = None)]
 #[command(group(
     ArgGroup::new("operation")
-        .args(&["install", "upgrade", "uninstall"])
+        .args(&["install", "upgrade", "uninstall", "restore_undo", "configure"])
+))]
+#[command(group(
+    ArgGroup::new("json_report")
+        .args(&["status", "get_manager_url", "install", "upgrade", "uninstall"])
 ))]
 pub struct Cli {
+    /// Print the version and exit (used by the self-update script to verify the new executable).
+    #[arg(long = "health-check", hide = true, exclusive = true)]
+    pub health_check: bool,
+
     /// Specify the game root directory path (default: auto-detect or current directory).
     #[arg(short = 'p', long = "path", value_name = "PATH")]
     pub path: Option<PathBuf>,
@@ -21,10 +30,107 @@ pub struct Cli {
     #[arg(long)]
     pub skip_self_update: bool,
 
+    /// Directory used for staging downloads (default: game root; env: META_MYSTIA_TEMP).
+    #[arg(long = "temp-dir", value_name = "PATH")]
+    pub temp_dir: Option<PathBuf>,
+
+    /// Skip the write speed probe of the game directory's disk.
+    #[arg(long = "skip-slow-storage-check")]
+    pub skip_slow_storage_check: bool,
+
+    /// Client certificate (PEM) for networks that require mutual TLS
+    /// (env: METAMYSTIA_CLIENT_CERT_PATH).
+    #[arg(long = "client-cert", value_name = "PATH", requires = "client_key")]
+    pub client_cert: Option<PathBuf>,
+
+    /// Private key (PEM) for --client-cert (env: METAMYSTIA_CLIENT_CERT_KEY_PATH).
+    #[arg(long = "client-key", value_name = "PATH", requires = "client_cert")]
+    pub client_key: Option<PathBuf>,
+
+    /// Number of download attempts; other network operations scale proportionally.
+    #[arg(long = "retries", value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub retries: Option<u64>,
+
+    /// Base delay in seconds between download retries; other network operations scale proportionally.
+    #[arg(long = "retry-delay", value_name = "SECS")]
+    pub retry_delay: Option<u64>,
+
+    /// Download speed limit in KB/s for all sources (0 disables the default mirror limit).
+    #[arg(long = "rate-limit", value_name = "KBPS")]
+    pub rate_limit: Option<u64>,
+
+    /// Fail instead of using the version info bundled with the manager when the version API
+    /// is unreachable and no cached copy exists.
+    #[arg(long = "refuse-fallback-version")]
+    pub refuse_fallback_version: bool,
+
+    /// Manager update channel; the choice is remembered for later runs
+    /// (default: beta when running a pre-release build, otherwise stable).
+    #[arg(long = "channel", value_enum, value_name = "CHANNEL")]
+    pub channel: Option<UpdateChannelArg>,
+
+    /// Directory for the newline-delimited JSON log
+    /// (default: %APPDATA%\meta-mystia-manager\logs).
+    #[arg(long = "log-dir", value_name = "PATH")]
+    pub log_dir: Option<PathBuf>,
+
+    /// Show additional detail, such as every file removed during install cleanup.
+    #[arg(short = 'v', long, conflicts_with = "quiet")]
+    pub verbose: bool,
+
     /// Suppress descriptive output (errors still shown).
     #[arg(short = 'q', long)]
     pub quiet: bool,
 
+    /// Use ASCII symbols instead of emoji and box-drawing characters in the interactive console.
+    #[arg(long)]
+    pub plain: bool,
+
+    /// In the interactive console, answer yes/no prompts with their default after SECS seconds;
+    /// the choice is remembered for later runs (0 waits forever, the default).
+    #[arg(long = "prompt-timeout", value_name = "SECS")]
+    pub prompt_timeout: Option<u64>,
+
+    /// Disable anonymous usage statistics for this run.
+    #[arg(long = "no-telemetry")]
+    pub no_telemetry: bool,
+
+    /// Replace the anonymous user ID with a new random one and exit.
+    #[arg(long = "reset-user-id", conflicts_with = "operation")]
+    pub reset_user_id: bool,
+
+    /// Register "MetaMystia Manager" as a Windows Application Event Log source and exit
+    /// (requires administrator rights). A copy of the manager is kept under
+    /// %ProgramData%\meta-mystia-manager to provide the event descriptions.
+    #[arg(long = "register-event-source", conflicts_with_all = ["operation", "reset_user_id"])]
+    pub register_event_source: bool,
+
+    /// Print environment facts useful for diagnosing permission problems and exit.
+    #[arg(long = "status", conflicts_with_all = ["operation", "reset_user_id", "register_event_source"])]
+    pub status: bool,
+
+    /// Print the direct download URL of the latest manager executable and exit
+    /// (follows --channel).
+    #[arg(long = "get-manager-url", conflicts_with_all = ["operation", "reset_user_id", "register_event_source"])]
+    pub get_manager_url: bool,
+
+    /// Print the --status, --get-manager-url, --install, --upgrade or --uninstall result as JSON.
+    /// Install and upgrade print one object per downloaded file; install, upgrade and uninstall
+    /// end with a summary object listing the changed components. On failure, print an "error"
+    /// object with the error kind and message instead (for --uninstall, including each path
+    /// that could not be removed). Progress messages go to stderr.
+    #[arg(long = "json", requires = "json_report")]
+    pub json: bool,
+
+    /// Remove the manager itself: other manager versions, preferences, caches, crash logs and
+    /// temporary folders. The running executable is deleted after exit. Game files are not touched.
+    #[arg(long = "self-uninstall", conflicts_with_all = ["operation", "reset_user_id"])]
+    pub self_uninstall: bool,
+
+    /// Do not ask for confirmation (used with --self-uninstall).
+    #[arg(short = 'y', long = "yes", requires = "self_uninstall")]
+    pub yes: bool,
+
     /// Install MetaMystia Mod.
     #[arg(short = 'i', long)]
     pub install: bool,
@@ -49,17 +155,125 @@ pub struct Cli {
     )]
     pub resourceex_version: Option<String>,
 
+    /// Keep the installed BepInEx framework instead of overwriting it.
+    #[arg(long = "keep-bepinex", requires = "install")]
+    pub keep_bepinex: bool,
+
+    /// Keep the installed ResourceExample ZIP instead of overwriting it.
+    #[arg(long = "keep-resourceex", requires = "install")]
+    pub keep_resourceex: bool,
+
+    /// Comma-separated BepInEx console log levels, e.g. Fatal,Error,Warning
+    /// (Fatal, Error, Warning, Message, Info, Debug; default: BepInEx default).
+    #[arg(
+        long = "bepinex-log-levels",
+        value_name = "LEVELS",
+        value_delimiter = ',',
+        value_parser = parse_bepinex_log_level,
+        requires = "install"
+    )]
+    pub bepinex_log_levels: Option<Vec<String>>,
+
+    /// Pack the files removed by reinstall cleanup into an undo archive under
+    /// %APPDATA%\meta-mystia-manager\undo first.
+    #[arg(long = "backup-before-install", requires = "install")]
+    pub backup_before_install: bool,
+
+    /// Ask for confirmation after printing the install plan, before anything is downloaded or deleted.
+    #[arg(long = "confirm", requires = "install")]
+    pub confirm: bool,
+
+    /// Launch the game after a successful install or upgrade.
+    #[arg(long = "launch-game", conflicts_with = "uninstall")]
+    pub launch_game: bool,
+
     /// Upgrade MetaMystia Mod.
     #[arg(short = 'u', long)]
     pub upgrade: bool,
 
+    /// Show pending upgrades and ask for confirmation before downloading.
+    #[arg(long = "interactive-upgrade", requires = "upgrade")]
+    pub interactive_upgrade: bool,
+
     /// Uninstall MetaMystia Mod.
+    /// Exits with code 2 if some files could not be removed.
     #[arg(short = 'U', long)]
     pub uninstall: bool,
 
-    /// Uninstall mode: light (remove MetaMystia only) or full (remove all mods).
+    /// Uninstall mode: light (remove MetaMystia only), full (remove all mods)
+    /// or bep-in-ex-only (remove the BepInEx framework but keep plugins).
     #[arg(long, value_enum, default_value = "light", requires = "uninstall")]
     pub mode: UninstallModeArg,
+
+    /// Pack all files to be deleted into an undo archive under
+    /// %APPDATA%\meta-mystia-manager\undo before uninstalling.
+    #[arg(long = "create-undo-archive", requires = "uninstall")]
+    pub create_undo_archive: bool,
+
+    /// Restore files from an undo archive created by --create-undo-archive.
+    #[arg(long = "restore-undo", value_name = "ZIP")]
+    pub restore_undo: Option<PathBuf>,
+
+    /// Change a setting of the installed mod, e.g. bepinex-console=on|off.
+    #[arg(long = "configure", value_name = "SETTING", value_parser = parse_configure_setting)]
+    pub configure: Option<ConfigureSetting>,
+}
+
+/// 可通过 --configure 修改的设置
+#[derive(Clone, Copy, Debug)]
+pub enum ConfigureSetting {
+    BepInExConsole(bool),
+}
+
+fn parse_configure_setting(s: &str) -> std::result::Result<ConfigureSetting, String> {
+    let (key, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected <KEY>=<VALUE>, got '{}'", s))?;
+
+    let enabled = match value.trim().to_ascii_lowercase().as_str() {
+        "on" | "true" => true,
+        "off" | "false" => false,
+        other => return Err(format!("invalid value '{}', expected on or off", other)),
+    };
+
+    match key.trim() {
+        "bepinex-console" => Ok(ConfigureSetting::BepInExConsole(enabled)),
+        other => Err(format!(
+            "unknown setting '{}', supported: bepinex-console",
+            other
+        )),
+    }
+}
+
+fn parse_bepinex_log_level(s: &str) -> std::result::Result<String, String> {
+    BEPINEX_LOG_LEVELS
+        .iter()
+        .find(|l| l.eq_ignore_ascii_case(s.trim()))
+        .map(|l| l.to_string())
+        .ok_or_else(|| {
+            format!(
+                "invalid log level '{}', expected one of: {}",
+                s,
+                BEPINEX_LOG_LEVELS.join(", ")
+            )
+        })
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum UpdateChannelArg {
+    /// Stable releases only
+    Stable,
+    /// Also receive beta releases of the manager
+    Beta,
+}
+
+impl From<UpdateChannelArg> for UpdateChannel {
+    fn from(arg: UpdateChannelArg) -> Self {
+        match arg {
+            UpdateChannelArg::Stable => UpdateChannel::Stable,
+            UpdateChannelArg::Beta => UpdateChannel::Beta,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, ValueEnum)]
@@ -68,6 +282,8 @@ pub enum UninstallModeArg {
     Light,
     /// Remove all mod-related files (restore to vanilla game)
     Full,
+    /// Remove the BepInEx framework only (keep plugins and ResourceEx)
+    BepInExOnly,
 }
 
 impl From<UninstallModeArg> for UninstallMode {
@@ -75,6 +291,7 @@ impl From<UninstallModeArg> for UninstallMode {
         match mode {
             UninstallModeArg::Light => UninstallMode::Light,
             UninstallModeArg::Full => UninstallMode::Full,
+            UninstallModeArg::BepInExOnly => UninstallMode::BepInExOnly,
         }
     }
 }
@@ -85,6 +302,10 @@ pub struct InstallConfig {
     pub show_bepinex_console: bool,
     pub dll_version: Option<String>,
     pub resourceex_version: Option<String>,
+    pub keep_bepinex: bool,
+    pub keep_resourceex: bool,
+    pub bepinex_log_levels: Option<Vec<String>>,
+    pub backup_before_install: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -93,6 +314,11 @@ pub struct CliConfig {
     pub operation: CliOperation,
     pub quiet: bool,
     pub skip_self_update: bool,
+    pub launch_game: bool,
+    pub create_undo_archive: bool,
+    pub interactive_upgrade: bool,
+    pub confirm_install: bool,
+    pub json: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -100,6 +326,8 @@ pub enum CliOperation {
     Install(InstallConfig),
     Upgrade,
     Uninstall(UninstallMode),
+    RestoreUndo(PathBuf),
+    Configure(ConfigureSetting),
 }
 
 impl Cli {
@@ -111,13 +339,19 @@ impl Cli {
                 show_bepinex_console: self.with_bepinex_console,
                 dll_version: self.dll_version.clone(),
                 resourceex_version: self.resourceex_version.clone(),
+                keep_bepinex: self.keep_bepinex,
+                keep_resourceex: self.keep_resourceex,
+                bepinex_log_levels: self.bepinex_log_levels.clone(),
+                backup_before_install: self.backup_before_install,
             }))
         } else if self.upgrade {
             Some(CliOperation::Upgrade)
         } else if self.uninstall {
             Some(CliOperation::Uninstall(self.mode.into()))
+        } else if let Some(archive) = &self.restore_undo {
+            Some(CliOperation::RestoreUndo(archive.clone()))
         } else {
-            None
+            self.configure.map(CliOperation::Configure)
         };
 
         operation.map(|op| CliConfig {
@@ -125,6 +359,11 @@ impl Cli {
             operation: op,
             quiet: self.quiet,
             skip_self_update: self.skip_self_update,
+            launch_game: self.launch_game,
+            create_undo_archive: self.create_undo_archive,
+            interactive_upgrade: self.interactive_upgrade,
+            confirm_install: self.confirm,
+            json: self.json,
         })
     }
 }
@@ -1,45 +1,203 @@
-use crate::config::{OperationMode, UninstallMode};
-use crate::error::Result;
+use crate::config::{OperationMode, PRESERVED_BEPINEX_DIRS, UninstallMode};
+use crate::downloader::{DownloadReport, DownloadSizeEstimate, PrefetchResult};
+use crate::env_check::{GameProcess, PathSource};
+use crate::error::{ManagerError, Result, SourceFailure};
+use crate::file_ops::{DeletionResult, DeletionStatus, format_size};
 use crate::model::VersionInfo;
-use crate::ui::Ui;
-
+use crate::net::current_utc_time;
+use crate::progress::ProgressSnapshot;
+use crate::ui::{
+    InstallPlan, OperationSummary, OverwriteSelection, PendingUpgrade, SummaryAction, Ui,
+    WaitOutcome, verbose,
+};
+use crate::updater::{OUTDATED_WARN_THRESHOLD, versions_behind};
+
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread::sleep;
+use std::time::Duration;
+
+/// CLI 模式下重试前的最长等待时间（无人值守时无需等待用户关闭占用程序）
+const MAX_RETRY_WAIT_SECS: u64 = 5;
+
+/// 网络故障排查清单（诊断信息与所有下载源失败时共用）
+const NETWORK_CHECKLIST: &[&str] = &[
+    "Internet connectivity",
+    "Firewall or security software rules for this program",
+    "System proxy settings (or retry without a proxy)",
+    "GitHub (github.com) is reachable",
+    "System clock is accurate (clock skew breaks TLS)",
+];
+
+type Output = Mutex<Box<dyn Write + Send>>;
 
 /// CLI UI 实现
 pub struct CliUI {
     quiet: bool,
+    interactive_upgrade: bool,
+    confirm_install: bool,
+    json: bool,
+    out: Output,
+    err: Output,
 }
 
 impl CliUI {
     pub fn new(quiet: bool) -> Self {
-        Self { quiet }
+        Self {
+            quiet,
+            interactive_upgrade: false,
+            confirm_install: false,
+            json: false,
+            out: Mutex::new(Box::new(std::io::stdout())),
+            err: Mutex::new(Box::new(std::io::stderr())),
+        }
+    }
+
+    /// 将标准输出与标准错误重定向到指定位置（用于测试）
+    #[cfg(test)]
+    pub fn with_output(
+        mut self,
+        out: impl Write + Send + 'static,
+        err: impl Write + Send + 'static,
+    ) -> Self {
+        self.out = Mutex::new(Box::new(out));
+        self.err = Mutex::new(Box::new(err));
+        self
+    }
+
+    /// 升级前仍然询问用户确认（--interactive-upgrade）
+    pub fn with_interactive_upgrade(mut self, enabled: bool) -> Self {
+        self.interactive_upgrade = enabled;
+        self
     }
 
+    /// 安装前展示安装计划并询问用户确认（--confirm）
+    pub fn with_confirm_install(mut self, enabled: bool) -> Self {
+        self.confirm_install = enabled;
+        self
+    }
+
+    /// 标准输出只保留 JSON 结果，其余信息改为输出到标准错误（--json）
+    pub fn with_json(mut self, enabled: bool) -> Self {
+        self.json = enabled;
+        self
+    }
+
+    fn write_line(output: &Output, msg: &str) {
+        let mut output = output.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = writeln!(output, "{}", msg);
+        let _ = output.flush();
+    }
+
+    /// 警告与错误始终输出到标准错误（不受 --quiet 影响）
     fn stderr(&self, msg: &str) {
-        eprintln!("{}", msg);
+        Self::write_line(&self.err, msg);
+    }
+
+    fn network_checklist(&self) {
+        self.stderr("Please verify:");
+        for item in NETWORK_CHECKLIST {
+            self.stderr(&format!("  - {}", item));
+        }
     }
 
+    /// 一般信息输出到标准输出，--quiet 时不显示
     fn stdout(&self, msg: &str) {
         if !self.quiet {
-            println!("{}", msg);
+            self.prompt(msg);
         }
     }
+
+    /// 以单行 JSON 输出结果（仅 --json）
+    fn json_line(&self, value: &serde_json::Value) {
+        Self::write_line(&self.out, &value.to_string());
+    }
+
+    /// 确认前展示的内容输出到标准输出（不受 --quiet 影响）
+    fn prompt(&self, msg: &str) {
+        let output = if self.json { &self.err } else { &self.out };
+        Self::write_line(output, msg);
+    }
+
+    /// 在标准输入上询问是/否（默认否）
+    pub fn read_yes_no(prompt: &str) -> Result<bool> {
+        print!("{} [y/N] ", prompt);
+        std::io::stdout().flush()?;
+
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+
+        Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+    }
+
+    /// 仅交互模式支持的操作
+    fn not_supported() -> ManagerError {
+        ManagerError::Other("not supported in CLI mode".to_string())
+    }
 }
 
 impl Ui for CliUI {
-    fn display_welcome(&self) -> Result<()> {
+    fn display_welcome(&self, _is_first_run: bool) -> Result<()> {
+        Ok(())
+    }
+
+    fn ask_telemetry_consent(&self) -> Result<bool> {
+        // CLI 模式下不询问，保持默认行为
+        Ok(true)
+    }
+
+    fn display_update_channel_info(&self, channel: &str) -> Result<()> {
+        if channel != "stable" {
+            self.stdout(&format!("Update channel: {}", channel));
+        }
         Ok(())
     }
 
     fn display_version(&self, manager_version: Option<&str>) -> Result<()> {
         if let Some(version) = manager_version {
             self.stdout(&format!("Manager latest version: {}", version));
+
+            let current = env!("CARGO_PKG_VERSION");
+            if let Some(behind) = versions_behind(current, version)
+                && behind > OUTDATED_WARN_THRESHOLD
+            {
+                self.warn_old_manager_version(current, version, behind)?;
+            }
         }
         Ok(())
     }
 
-    fn display_game_running_warning(&self) -> Result<()> {
+    fn warn_old_manager_version(
+        &self,
+        current: &str,
+        latest: &str,
+        behind_versions: usize,
+    ) -> Result<()> {
+        self.stderr(&format!(
+            "[CRITICAL] Manager is {} versions behind (current {}, latest {}). Please update.",
+            behind_versions, current, latest
+        ));
+        Ok(())
+    }
+
+    fn display_game_running_warning(&self, processes: &[GameProcess]) -> Result<()> {
         self.stderr("Game is currently running. Please close the game and try again.");
+        for process in processes {
+            let started = process
+                .started_at
+                .map(|t| {
+                    format!(
+                        ", running for {} minute(s)",
+                        t.elapsed().unwrap_or_default().as_secs() / 60
+                    )
+                })
+                .unwrap_or_default();
+            self.stderr(&format!(
+                "  {} (PID {}{})",
+                process.name, process.pid, started
+            ));
+        }
         Ok(())
     }
 
@@ -57,8 +215,27 @@ impl Ui for CliUI {
         Ok(())
     }
 
+    fn display_upgrade_check_failed(&self, err: &str) -> Result<()> {
+        self.warn(&format!("Update check skipped: {}", err))
+    }
+
+    fn display_progress_cancelled(&self, snapshot: &ProgressSnapshot) -> Result<()> {
+        self.stderr(&format!(
+            "Interrupted: {} (completed step {}/{}{})",
+            snapshot.operation,
+            snapshot.completed_steps,
+            snapshot.total_steps,
+            snapshot
+                .current
+                .as_deref()
+                .map(|step| format!(", cancelled during: {}", step))
+                .unwrap_or_default()
+        ));
+        Ok(())
+    }
+
     fn select_operation_mode(&self) -> Result<OperationMode> {
-        unreachable!()
+        Err(Self::not_supported())
     }
 
     fn blank_line(&self) -> Result<()> {
@@ -84,6 +261,41 @@ impl Ui for CliUI {
         Ok(())
     }
 
+    fn display_operation_summary(&self, summary: &OperationSummary) -> Result<()> {
+        if self.json {
+            let value = serde_json::to_value(summary)
+                .map_err(|e| ManagerError::Other(format!("序列化操作摘要失败：{}", e)))?;
+            self.json_line(&value);
+            return Ok(());
+        }
+
+        for e in &summary.entries {
+            let action = match e.action {
+                SummaryAction::Installed => "installed",
+                SummaryAction::Upgraded => "upgraded",
+                SummaryAction::Kept => "kept",
+                SummaryAction::Removed => "removed",
+            };
+            let mut line = format!("Summary: {} {}", e.component, action);
+            if let Some(ref v) = e.version {
+                line.push_str(&format!(" {}", v));
+            }
+            if let Some(ref p) = e.path {
+                line.push_str(&format!(" ({})", p.display()));
+            }
+            self.stdout(&line);
+        }
+        Ok(())
+    }
+
+    fn display_controlled_folder_access_blocked(&self, app_path: &Path) -> Result<()> {
+        self.stderr(&format!(
+            "Write blocked by Windows Defender Controlled Folder Access. Running as administrator will not help; allow {} through Controlled Folder Access and retry.",
+            app_path.display()
+        ));
+        Ok(())
+    }
+
     fn path_display_steam_found(&self, app_id: u32, name: Option<&str>, path: &Path) -> Result<()> {
         self.stdout(&format!(
             "Found Steam game: {} (AppID: {}) at {}",
@@ -94,15 +306,132 @@ impl Ui for CliUI {
         Ok(())
     }
 
+    fn path_display_steam_stale(&self, app_id: u32, name: Option<&str>, path: &Path) -> Result<()> {
+        self.stderr(&format!(
+            "[WARN] Steam game directory looks stale (missing game data): {} (AppID: {}) at {}",
+            name.unwrap_or("Unknown"),
+            app_id,
+            path.display()
+        ));
+        Ok(())
+    }
+
     fn path_confirm_use_steam_found(&self) -> Result<bool> {
         Ok(true)
     }
 
+    fn select_game_path_from_candidates(
+        &self,
+        candidates: &[(PathBuf, PathSource)],
+    ) -> Result<PathBuf> {
+        // 使用 --path 时不会检测候选目录，这里仅在唯一的 Steam 目录时自动选择
+        let mut steam = candidates
+            .iter()
+            .filter(|(_, source)| matches!(source, PathSource::Steam(_)));
+
+        match (steam.next(), steam.next()) {
+            (Some((path, _)), None) => {
+                self.stdout(&format!(
+                    "Multiple game directories found, using Steam installation: {}",
+                    path.display()
+                ));
+                Ok(path.clone())
+            }
+            _ => {
+                for (path, source) in candidates {
+                    self.stderr(&format!("  {} ({})", path.display(), source.as_str()));
+                }
+                Err(ManagerError::Other(
+                    "Multiple game directories found, use --path to choose one".to_string(),
+                ))
+            }
+        }
+    }
+
+    fn path_ask_manual_entry(&self) -> Result<Option<PathBuf>> {
+        // 命令行模式请使用 --path 指定目录
+        Ok(None)
+    }
+
+    fn path_manual_entry_not_found(&self, input: &Path) -> Result<()> {
+        self.stderr(&format!("Game not found in {}", input.display()));
+        Ok(())
+    }
+
+    fn warn_conflicting_game_exe(&self, extras: &[PathBuf]) -> Result<bool> {
+        let names: Vec<String> = extras
+            .iter()
+            .map(|p| {
+                p.file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| p.display().to_string())
+            })
+            .collect();
+        self.stderr(&format!(
+            "[WARN] Other executables found in the game directory: {}",
+            names.join(", ")
+        ));
+        Ok(true)
+    }
+
+    fn warn_cloud_sync_directory(&self, provider: &str) -> Result<()> {
+        self.stderr(&format!(
+            "[WARN] The game directory is synced by {}; pause syncing during install/uninstall to avoid locked or reverted files.",
+            provider
+        ));
+        Ok(())
+    }
+
     fn install_display_step(&self, step: usize, description: &str) -> Result<()> {
         self.stdout(&format!("[Step {}] {}", step, description));
         Ok(())
     }
 
+    fn install_display_estimated_download_size(
+        &self,
+        estimate: &DownloadSizeEstimate,
+    ) -> Result<()> {
+        self.stdout(&format!(
+            "Estimated download: ~{}{}",
+            format_size(estimate.max_bytes),
+            if estimate.has_exact {
+                ""
+            } else {
+                " (approximate)"
+            }
+        ));
+        Ok(())
+    }
+
+    fn install_warn_slow_storage(&self, estimated_extra_mins: u64) -> Result<()> {
+        self.stderr(&format!(
+            "[WARN] Slow storage detected, installation may take ~{} extra minute(s); consider moving the game to an SSD.",
+            estimated_extra_mins.max(1)
+        ));
+        Ok(())
+    }
+
+    fn install_display_estimated_duration(&self, estimated_secs: u64) -> Result<()> {
+        self.stdout(&format!(
+            "Estimated time: {} seconds (depending on network speed)",
+            estimated_secs
+        ));
+        Ok(())
+    }
+
+    fn install_display_download_sources(&self, result: &PrefetchResult) -> Result<()> {
+        for (name, size) in &result.available {
+            match size {
+                Some(size) => self.stdout(&format!("Source available: {} ({} bytes)", name, size)),
+                None => self.stdout(&format!("Source available: {}", name)),
+            }
+        }
+        for (name, reason) in &result.unavailable {
+            self.warn(&format!("Source unavailable: {} ({})", name, reason))?;
+        }
+        Ok(())
+    }
+
     fn install_display_version_info(&self, version_info: &VersionInfo) -> Result<()> {
         self.stdout(&format!(
             "Versions - MetaMystia DLL: {}, ResourceExample ZIP: {}, BepInEx: {}",
@@ -118,9 +447,14 @@ impl Ui for CliUI {
         bepinex_installed: bool,
         metamystia_installed: bool,
         resourceex_installed: bool,
+        cleanup_plan: &[PathBuf],
     ) -> Result<()> {
         if bepinex_installed || metamystia_installed || resourceex_installed {
-            self.stdout("Existing installation detected, will overwrite.");
+            self.warn(&format!(
+                "Existing installation detected, will overwrite {} item(s) (keeping BepInEx/{{{}}}, resetting BepInEx/config/BepInEx.cfg).",
+                cleanup_plan.len(),
+                PRESERVED_BEPINEX_DIRS.join(",")
+            ))?;
         }
         Ok(())
     }
@@ -129,12 +463,93 @@ impl Ui for CliUI {
         Ok(true)
     }
 
+    fn install_select_overwrite(
+        &self,
+        _bepinex_installed: bool,
+        _metamystia_installed: bool,
+        _resourceex_installed: bool,
+    ) -> Result<OverwriteSelection> {
+        Ok(OverwriteSelection::all())
+    }
+
     fn install_ask_install_resourceex(&self) -> Result<bool> {
-        unreachable!()
+        Err(Self::not_supported())
     }
 
     fn install_ask_show_bepinex_console(&self) -> Result<bool> {
-        unreachable!()
+        Err(Self::not_supported())
+    }
+
+    fn install_ask_configure_advanced_options(&self) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn install_select_bepinex_log_levels(&self) -> Result<Vec<String>> {
+        Err(Self::not_supported())
+    }
+
+    fn install_ask_backup_before_install(&self) -> Result<bool> {
+        Err(Self::not_supported())
+    }
+
+    fn install_display_plan(&self, plan: &InstallPlan) -> Result<bool> {
+        let version_or =
+            |v: &Option<String>, none: &'static str| v.clone().unwrap_or_else(|| none.to_string());
+
+        // --confirm 时计划与确认提示不受 --quiet 影响
+        let print = |line: String| {
+            if self.confirm_install {
+                self.prompt(&line);
+            } else {
+                self.stdout(&line);
+            }
+        };
+
+        print("Install plan:".to_string());
+        print(format!(
+            "  BepInEx: {}",
+            version_or(&plan.bepinex_version, "keep installed")
+        ));
+        print(format!(
+            "  MetaMystia DLL: {}",
+            version_or(&plan.dll_version, "keep installed")
+        ));
+        print(format!(
+            "  ResourceExample ZIP: {}",
+            version_or(&plan.resourceex_version, "skip")
+        ));
+        print(format!(
+            "  BepInEx console: {}",
+            if plan.show_bepinex_console {
+                "on"
+            } else {
+                "off"
+            }
+        ));
+        if !plan.overwrite.is_empty() {
+            print(format!("  Overwrite: {}", plan.overwrite.join(", ")));
+        }
+        if !plan.cleanup_targets.is_empty() {
+            print(format!("  Cleanup: {} item(s)", plan.cleanup_targets.len()));
+            for path in &plan.cleanup_targets {
+                print(format!("    {}", path.display()));
+            }
+        }
+        if !plan.misplaced_resourceex.is_empty() {
+            print(format!(
+                "  Move to ResourceEx: {} file(s)",
+                plan.misplaced_resourceex.len()
+            ));
+            for path in &plan.misplaced_resourceex {
+                print(format!("    {}", path.display()));
+            }
+        }
+
+        if !self.confirm_install {
+            return Ok(true);
+        }
+
+        Self::read_yes_no("Proceed?")
     }
 
     fn install_downloads_completed(&self) -> Result<()> {
@@ -146,6 +561,23 @@ impl Ui for CliUI {
         Ok(())
     }
 
+    fn install_display_cleanup_detail(&self, items: &[DeletionResult]) -> Result<()> {
+        for item in items {
+            match &item.status {
+                DeletionStatus::Success => {
+                    self.stdout(&format!("Removed: {}", item.path.display()))
+                }
+                DeletionStatus::Failed(e) => {
+                    self.stderr(&format!("Failed to remove {}: {}", item.path.display(), e))
+                }
+                DeletionStatus::Skipped => {
+                    self.stdout(&format!("Skipped: {}", item.path.display()))
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn install_cleanup_result(&self, success_count: usize, failed_count: usize) -> Result<()> {
         self.stdout(&format!(
             "Cleanup: {} succeeded, {} failed.",
@@ -154,13 +586,67 @@ impl Ui for CliUI {
         Ok(())
     }
 
-    fn install_finished(&self, show_bepinex_console: bool) -> Result<()> {
+    fn install_finished(&self, show_bepinex_console: bool, first_install: bool) -> Result<()> {
         if show_bepinex_console {
             self.stdout("BepInEx console will be shown on game startup.");
         }
 
         self.stdout("Installation completed successfully.");
 
+        if first_install {
+            self.display_first_time_bepinex_info()?;
+        }
+
+        Ok(())
+    }
+
+    fn warn_bepinex_il2cpp_download(&self, source_url: &str) -> Result<()> {
+        self.warn(&format!(
+            "On first game launch BepInEx downloads Unity base libraries (~50-300 MB) from {}; the game may look frozen meanwhile.",
+            source_url
+        ))
+    }
+
+    fn display_first_time_bepinex_info(&self) -> Result<()> {
+        if !verbose() {
+            return Ok(());
+        }
+
+        self.stdout(
+            "First launch: BepInEx generates IL2CPP interop assemblies, which can take 10+ minutes on slow machines.",
+        );
+        self.stdout(
+            "No window is shown during this step unless the BepInEx console is enabled; do not start the game again.",
+        );
+        self.stdout("The generated assemblies are cached, so later launches are fast.");
+        Ok(())
+    }
+
+    fn install_warn_loader_quarantined(&self, path: &Path, detection: Option<&str>) -> Result<()> {
+        self.stderr(&format!(
+            "Warning: {} was removed after installation, likely quarantined by antivirus{}. Add an exclusion for this path and reinstall.",
+            path.display(),
+            detection
+                .map(|d| format!(" (detection: {})", d))
+                .unwrap_or_default()
+        ));
+        Ok(())
+    }
+
+    fn resourceex_display_misplaced(&self, files: &[PathBuf]) -> Result<()> {
+        self.warn("Found ResourceExample ZIP files outside the ResourceEx directory:")?;
+        for file in files {
+            self.stderr(&format!("  {}", file.display()));
+        }
+        Ok(())
+    }
+
+    fn resourceex_confirm_migrate(&self) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn resourceex_migrate_failed(&self, path: &Path, err: &str) -> Result<()> {
+        self.stderr(&format!("Failed to move {}: {}", path.display(), err));
         Ok(())
     }
 
@@ -172,6 +658,14 @@ impl Ui for CliUI {
         Ok(())
     }
 
+    fn upgrade_found_duplicate(&self, path: &Path) -> Result<()> {
+        self.stderr(&format!(
+            "Warning: Duplicate MetaMystia DLL found, moving aside: {}",
+            path.display()
+        ));
+        Ok(())
+    }
+
     fn upgrade_backup_failed(&self, err: &str) -> Result<()> {
         self.stderr(&format!("Backup failed: {}", err));
         Ok(())
@@ -240,6 +734,22 @@ impl Ui for CliUI {
         Ok(())
     }
 
+    fn confirm_batch_upgrade(&self, updates: &[PendingUpgrade]) -> Result<bool> {
+        if !self.interactive_upgrade {
+            return Ok(true);
+        }
+
+        // 确认提示不受 --quiet 影响
+        self.prompt("Pending upgrades:");
+        for u in updates {
+            self.prompt(&format!(
+                "  {}: {} -> {}",
+                u.component, u.from_version, u.to_version
+            ));
+        }
+        Self::read_yes_no("Proceed?")
+    }
+
     fn upgrade_downloading_dll(&self) -> Result<()> {
         self.stdout("Downloading MetaMystia DLL...");
         Ok(())
@@ -275,8 +785,39 @@ impl Ui for CliUI {
         Ok(())
     }
 
+    fn ask_launch_game(&self) -> Result<bool> {
+        Err(Self::not_supported())
+    }
+
+    fn launch_game_failed(&self, err: &str) -> Result<()> {
+        self.warn(&format!("failed to launch the game: {}", err))
+    }
+
+    fn ask_open_docs(&self) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn open_docs_failed(&self, url: &str) -> Result<()> {
+        self.stderr(&format!("Failed to open the browser, please visit {}", url));
+        Ok(())
+    }
+
+    fn configure_ask_bepinex_console(&self, _current: bool) -> Result<bool> {
+        Err(Self::not_supported())
+    }
+
+    fn configure_bepinex_console_changed(&self, previous: bool, enabled: bool) -> Result<()> {
+        let on_off = |v: bool| if v { "on" } else { "off" };
+        self.stdout(&format!(
+            "BepInEx console: {} -> {}",
+            on_off(previous),
+            on_off(enabled)
+        ));
+        Ok(())
+    }
+
     fn uninstall_select_mode(&self) -> Result<UninstallMode> {
-        unreachable!()
+        Err(Self::not_supported())
     }
 
     fn uninstall_no_files_found(&self) -> Result<()> {
@@ -293,11 +834,42 @@ impl Ui for CliUI {
         Ok(true)
     }
 
-    fn uninstall_files_in_use_warning(&self) -> Result<()> {
-        self.stderr("Warning: Some files are in use, will retry.");
+    fn uninstall_ask_create_undo_archive(&self, _estimated_size: u64) -> Result<bool> {
+        // CLI 模式仅在指定 --create-undo-archive 时创建
+        Ok(false)
+    }
+
+    fn uninstall_undo_archive_insufficient_space(
+        &self,
+        required: u64,
+        available: u64,
+    ) -> Result<()> {
+        self.stderr(&format!(
+            "Not enough disk space for the undo archive (required {}, available {}).",
+            format_size(required),
+            format_size(available)
+        ));
+        Ok(())
+    }
+
+    fn uninstall_undo_archive_created(&self, path: &Path) -> Result<()> {
+        self.stdout(&format!("Undo archive saved to: {}", path.display()));
         Ok(())
     }
 
+    fn restore_undo_finished(&self, archive: &Path, count: usize) -> Result<()> {
+        self.stdout(&format!(
+            "Restored {} files from {}.",
+            count,
+            archive.display()
+        ));
+        Ok(())
+    }
+
+    fn uninstall_files_in_use_warning(&self) -> Result<()> {
+        self.warn("Some files are in use, will retry.")
+    }
+
     fn uninstall_wait_before_retry(
         &self,
         delay_secs: u64,
@@ -306,11 +878,18 @@ impl Ui for CliUI {
     ) -> Result<()> {
         self.stdout(&format!(
             "Waiting {} seconds before retry {}/{}...",
-            delay_secs, attempt, attempts
+            delay_secs.min(MAX_RETRY_WAIT_SECS),
+            attempt,
+            attempts
         ));
         Ok(())
     }
 
+    fn uninstall_wait_or_skip(&self, delay_secs: u64) -> Result<WaitOutcome> {
+        sleep(Duration::from_secs(delay_secs.min(MAX_RETRY_WAIT_SECS)));
+        Ok(WaitOutcome::Elapsed)
+    }
+
     fn uninstall_ask_elevate_permission(&self) -> Result<bool> {
         Ok(true)
     }
@@ -329,7 +908,41 @@ impl Ui for CliUI {
         Ok(())
     }
 
-    fn deletion_start(&self) -> Result<()> {
+    fn uninstall_display_disk_space_freed(&self, bytes_freed: u64) -> Result<()> {
+        self.stdout(&format!("Disk space freed: {}", format_size(bytes_freed)));
+        Ok(())
+    }
+
+    fn uninstall_confirm_take_ownership(&self, paths: &[PathBuf]) -> Result<bool> {
+        // 修改文件所有权需要用户明确确认，命令行模式下只给出手动命令
+        self.warn(&format!(
+            "Access denied for {} item(s) even with administrator rights.",
+            paths.len()
+        ))?;
+        Ok(false)
+    }
+
+    fn uninstall_display_acl_fix_commands(&self, commands: &[String]) -> Result<()> {
+        self.stderr("To take ownership manually, run in an elevated Command Prompt:");
+        for command in commands {
+            self.stderr(&format!("  {}", command));
+        }
+        Ok(())
+    }
+
+    fn uninstall_display_failure_breakdown(
+        &self,
+        locked: usize,
+        acl_denied: usize,
+        other: usize,
+    ) -> Result<()> {
+        self.warn(&format!(
+            "Failures: {} locked, {} access denied, {} other.",
+            locked, acl_denied, other
+        ))
+    }
+
+    fn deletion_start(&self, _total: usize) -> Result<()> {
         Ok(())
     }
 
@@ -366,6 +979,14 @@ impl Ui for CliUI {
         Ok(())
     }
 
+    fn temp_dir_warn_cross_volume(&self, temp_dir: &Path, game_root: &Path) -> Result<()> {
+        self.warn(&format!(
+            "temp directory {} is on a different volume than {}; files will be copied instead of moved.",
+            temp_dir.display(),
+            game_root.display()
+        ))
+    }
+
     fn download_start(&self, filename: &str, total: Option<u64>) -> Result<usize> {
         if let Some(size) = total {
             self.stdout(&format!("Downloading {} ({} bytes)...", filename, size));
@@ -398,6 +1019,16 @@ impl Ui for CliUI {
         Ok(())
     }
 
+    fn download_version_info_cached(&self, cached_at: &str) -> Result<()> {
+        if verbose() {
+            self.stdout(&format!(
+                "Using cached version info (updated {})",
+                cached_at
+            ));
+        }
+        Ok(())
+    }
+
     fn download_version_info_parse_failed(&self, err: &str, snippet: &str) -> Result<()> {
         self.stderr(&format!(
             "Failed to parse version info: {}\nSnippet: {}",
@@ -435,6 +1066,14 @@ impl Ui for CliUI {
         Ok(())
     }
 
+    fn download_github_auth_required(&self) -> Result<()> {
+        self.stderr("GitHub API rate limit for unauthenticated requests (60/hour) exceeded.");
+        self.stderr(
+            "Create a token at https://github.com/settings/tokens (no scopes needed) and set the GITHUB_TOKEN environment variable, or retry later.",
+        );
+        Ok(())
+    }
+
     fn download_display_github_release_notes(
         &self,
         _tag: &str,
@@ -449,8 +1088,7 @@ impl Ui for CliUI {
     }
 
     fn download_switch_to_fallback(&self, reason: &str) -> Result<()> {
-        self.stdout(&format!("Switching to fallback source: {}", reason));
-        Ok(())
+        self.warn(&format!("Switching to fallback source: {}", reason))
     }
 
     fn download_try_fallback_metamystia(&self) -> Result<()> {
@@ -463,6 +1101,38 @@ impl Ui for CliUI {
         Ok(())
     }
 
+    fn download_all_sources_failed(&self, failures: &[SourceFailure]) -> Result<()> {
+        self.stderr("All download sources failed:");
+        for f in failures {
+            self.stderr(&format!("  - {} ({}): {}", f.source, f.url, f.reason));
+        }
+        self.network_checklist();
+        self.stderr("If all of the above look fine, retry later or from a different network");
+        Ok(())
+    }
+
+    fn display_download_summary(&self, reports: &[DownloadReport]) -> Result<()> {
+        for r in reports {
+            if self.json {
+                self.json_line(&serde_json::json!({
+                    "component": r.component,
+                    "source": r.source.id(),
+                    "bytes": r.bytes,
+                    "duration_ms": r.duration.as_millis() as u64,
+                }));
+                continue;
+            }
+            self.stdout(&format!(
+                "Downloaded {} from {} ({}, {:.1}s)",
+                r.component,
+                r.source.name(),
+                format_size(r.bytes),
+                r.duration.as_secs_f64()
+            ));
+        }
+        Ok(())
+    }
+
     fn download_bepinex_primary_failed(&self, err: &str) -> Result<()> {
         self.stderr(&format!(
             "Failed to download BepInEx from primary source: {}",
@@ -479,15 +1149,22 @@ impl Ui for CliUI {
         attempts: usize,
         err: &str,
     ) -> Result<()> {
-        self.stdout(&format!(
+        self.warn(&format!(
             "Retrying {} ({}/{}) after {} seconds: {}",
             op_desc, attempt, attempts, delay_secs, err
-        ));
-        Ok(())
+        ))
     }
 
     fn network_rate_limited(&self, secs: u64) -> Result<()> {
-        self.stdout(&format!("Rate limited, waiting {} seconds...", secs));
+        self.warn(&format!("Rate limited, waiting {} seconds...", secs))
+    }
+
+    fn display_network_diagnostics(&self, endpoint: &str, last_error: &str) -> Result<()> {
+        self.stderr("Network diagnostics: all retries exhausted");
+        self.stderr(&format!("  Endpoint: {}", endpoint));
+        self.stderr(&format!("  Last error: {}", last_error));
+        self.stderr(&format!("  System time: {}", current_utc_time()));
+        self.network_checklist();
         Ok(())
     }
 
@@ -499,6 +1176,11 @@ impl Ui for CliUI {
         Ok(true)
     }
 
+    fn display_self_update_downloaded(&self, path: &Path, version: &str) -> Result<()> {
+        self.stdout(&format!("Downloaded: {} (v{})", path.display(), version));
+        Ok(())
+    }
+
     fn manager_update_starting(&self) -> Result<()> {
         self.stdout("Starting manager self-update...");
         Ok(())
@@ -509,8 +1191,8 @@ impl Ui for CliUI {
         Ok(())
     }
 
-    fn manager_prompt_manual_update(&self) -> Result<()> {
-        self.stderr("Please update the manager manually.");
+    fn manager_prompt_manual_update(&self, reason: &str) -> Result<()> {
+        self.stderr(&format!("{}. Please update the manager manually.", reason));
         Ok(())
     }
 
@@ -548,3 +1230,162 @@ impl Ui for CliUI {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    /// 可在多处共享的输出缓冲区
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl SharedBuffer {
+        fn text(&self) -> String {
+            String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+        }
+    }
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// 依次输出一般信息、警告与错误，返回（标准输出，标准错误）
+    fn run_mixed_output(quiet: bool) -> (String, String) {
+        let (out, err) = (SharedBuffer::default(), SharedBuffer::default());
+        let ui = CliUI::new(quiet).with_output(out.clone(), err.clone());
+
+        ui.message("Fetching version info...").unwrap();
+        ui.upgrade_downloading_dll().unwrap();
+        ui.warn("version cache is stale").unwrap();
+        ui.install_warn_slow_storage(3).unwrap();
+        ui.warn_cloud_sync_directory("OneDrive").unwrap();
+        ui.error("download failed").unwrap();
+
+        (out.text(), err.text())
+    }
+
+    #[test]
+    fn json_mode_prints_one_object_per_download() {
+        let (out, err) = (SharedBuffer::default(), SharedBuffer::default());
+        let ui = CliUI::new(false)
+            .with_json(true)
+            .with_output(out.clone(), err.clone());
+
+        ui.message("Fetching version info...").unwrap();
+        ui.display_download_summary(&[
+            DownloadReport {
+                component: "BepInEx".to_string(),
+                source: crate::downloader::DownloadSource::BepInExPrimary,
+                bytes: 1024,
+                duration: Duration::from_millis(1500),
+            },
+            DownloadReport {
+                component: "MetaMystia DLL".to_string(),
+                source: crate::downloader::DownloadSource::Mirror,
+                bytes: 2048,
+                duration: Duration::from_millis(20),
+            },
+        ])
+        .unwrap();
+
+        let lines: Vec<serde_json::Value> = out
+            .text()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                serde_json::json!({
+                    "component": "BepInEx",
+                    "source": "bepinex",
+                    "bytes": 1024,
+                    "duration_ms": 1500,
+                }),
+                serde_json::json!({
+                    "component": "MetaMystia DLL",
+                    "source": "mirror",
+                    "bytes": 2048,
+                    "duration_ms": 20,
+                }),
+            ]
+        );
+        assert!(err.text().contains("Fetching version info..."));
+    }
+
+    #[test]
+    fn json_mode_prints_the_operation_summary_last() {
+        let (out, err) = (SharedBuffer::default(), SharedBuffer::default());
+        let ui = CliUI::new(false)
+            .with_json(true)
+            .with_output(out.clone(), err.clone());
+
+        let mut summary = OperationSummary::default();
+        summary.push("BepInEx", SummaryAction::Installed, Some("6.0.0"), None);
+        summary.push(
+            "MetaMystia DLL",
+            SummaryAction::Upgraded,
+            Some("1.2.0"),
+            Some(PathBuf::from("BepInEx/plugins/MetaMystia-v1.2.0.dll")),
+        );
+        ui.display_operation_summary(&summary).unwrap();
+        ui.message("Done.").unwrap();
+
+        let text = out.text();
+        let last: serde_json::Value = serde_json::from_str(text.lines().last().unwrap()).unwrap();
+        assert_eq!(
+            last,
+            serde_json::json!({
+                "entries": [
+                    {
+                        "component": "BepInEx",
+                        "action": "installed",
+                        "version": "6.0.0",
+                        "path": null,
+                    },
+                    {
+                        "component": "MetaMystia DLL",
+                        "action": "upgraded",
+                        "version": "1.2.0",
+                        "path": "BepInEx/plugins/MetaMystia-v1.2.0.dll",
+                    },
+                ]
+            })
+        );
+        assert!(!text.contains("Summary:"));
+        assert!(err.text().contains("Done."));
+    }
+
+    #[test]
+    fn quiet_mode_keeps_warnings_and_hides_chatter() {
+        let (out, err) = run_mixed_output(true);
+
+        assert_eq!(out, "");
+        assert!(err.contains("Warning: version cache is stale"));
+        assert!(err.contains("[WARN] Slow storage detected"));
+        assert!(err.contains("synced by OneDrive"));
+        assert!(err.contains("Error: download failed"));
+        assert!(!err.contains("Fetching version info"));
+        assert!(!err.contains("Downloading MetaMystia DLL"));
+    }
+
+    #[test]
+    fn normal_mode_prints_chatter_to_stdout_and_warnings_to_stderr() {
+        let (out, err) = run_mixed_output(false);
+
+        assert!(out.contains("Fetching version info..."));
+        assert!(out.contains("Downloading MetaMystia DLL..."));
+        assert!(!out.contains("Warning:"));
+        assert!(err.contains("Warning: version cache is stale"));
+        assert!(err.contains("Error: download failed"));
+    }
+}
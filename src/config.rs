@@ -1,17 +1,50 @@
+use crate::metrics::report_event;
+use crate::model::UninstallTarget;
+
 pub const GAME_EXECUTABLE: &str = "Touhou Mystia Izakaya.exe";
 pub const GAME_PROCESS_NAME: &str = "Touhou Mystia Izakaya.exe";
 pub const GAME_STEAM_APP_ID: u32 = 1_584_090;
+/// 游戏数据目录（与可执行文件同级，用于确认安装完整）
+pub const GAME_DATA_DIR: &str = "Touhou Mystia Izakaya_Data";
+/// 游戏可执行文件的最小合理大小，过小说明是残留文件
+pub const GAME_EXECUTABLE_MIN_SIZE: u64 = 64 * 1024;
+pub const BEPINEX_LOADER_DLL: &str = "winhttp.dll";
+/// 覆盖安装时保留的 BepInEx 子目录（其中包含其他插件的文件与配置）
+pub const PRESERVED_BEPINEX_DIRS: &[&str] =
+    &["plugins", "config", "patchers", "GeneratedMMHookDLLs"];
+/// 使用文档首页
+pub const DOC_URL: &str = "https://doc.meta-mystia.izakaya.cc/";
 pub const USER_AGENT: &str = concat!(
     "meta-mystia-manager/",
     env!("CARGO_PKG_VERSION"),
     " (+https://github.com/AnYiEE/meta-mystia-manager)"
 );
 
+/// 估算安装耗时所用的组件大致大小（接口不提供文件大小）
+pub const ESTIMATED_BEPINEX_SIZE: u64 = 30 * 1024 * 1024;
+pub const ESTIMATED_DLL_SIZE: u64 = 2 * 1024 * 1024;
+pub const ESTIMATED_RESOURCEEX_SIZE: u64 = 10 * 1024 * 1024;
+/// 游戏所在磁盘写入速度低于该值（MB/s）时提示存储较慢
+pub const SLOW_STORAGE_THRESHOLD_MBPS: f64 = 5.0;
+/// 无历史记录时假定的下载速度（KB/s）
+pub const DEFAULT_DOWNLOAD_SPEED_KBPS: u64 = 1024;
+
+/// ResourceEx 安装标记文件名（位于 ResourceEx 目录，内容为已安装的版本号）
+pub const RESOURCEEX_MARKER: &str = ".metamystia-resourceex";
+
+/// BepInEx 控制台可选的日志级别
+pub const BEPINEX_LOG_LEVELS: &[&str] = &["Fatal", "Error", "Warning", "Message", "Info", "Debug"];
+/// BepInEx 默认启用的日志级别
+pub const BEPINEX_DEFAULT_LOG_LEVELS: &[&str] = &["Fatal", "Error", "Warning", "Message", "Info"];
+
 /// 操作模式枚举
+#[derive(Debug)]
 pub enum OperationMode {
     Install,
     Upgrade,
     Uninstall,
+    Configure,
+    OpenDocs,
 }
 
 /// 卸载模式枚举
@@ -19,12 +52,14 @@ pub enum OperationMode {
 pub enum UninstallMode {
     Light,
     Full,
+    BepInExOnly,
 }
 
 impl UninstallMode {
     const LIGHT_TARGETS: &'static [(&'static str, bool)] = &[
         ("BepInEx/plugins/MetaMystia-*.dll", false),
         ("ResourceEx/ResourceExample-*.zip", false),
+        ("ResourceEx/.metamystia-resourceex", false),
     ];
 
     const FULL_TARGETS: &'static [(&'static str, bool)] = &[
@@ -37,6 +72,18 @@ impl UninstallMode {
         ("ResourceEx", true),
     ];
 
+    /// 仅 BepInEx 框架本身，不包含 BepInEx/plugins 与 ResourceEx
+    const BEPINEX_FRAMEWORK_TARGETS: &'static [(&'static str, bool)] = &[
+        ("BepInEx/core", true),
+        ("BepInEx/patchers", true),
+        ("BepInEx/config/BepInEx.cfg", false),
+        (".doorstop_version", false),
+        ("doorstop_config.ini", false),
+        ("winhttp.dll", false),
+        ("MinHook.x64.dll", false),
+        ("changelog.txt", false),
+    ];
+
     /// 获取卸载模式描述
     pub fn description(&self) -> &str {
         match self {
@@ -44,6 +91,9 @@ impl UninstallMode {
                 "仅移除 MetaMystia 相关文件（保留 BepInEx 框架和其他 Mod 相关文件）"
             }
             UninstallMode::Full => "移除所有和 Mod 有关的文件（还原为原版游戏）",
+            UninstallMode::BepInExOnly => {
+                "仅移除 BepInEx 框架（保留所有插件和 ResourceEx，便于更换 BepInEx 版本）"
+            }
         }
     }
 
@@ -52,8 +102,112 @@ impl UninstallMode {
         match self {
             UninstallMode::Light => Self::LIGHT_TARGETS,
             UninstallMode::Full => Self::FULL_TARGETS,
+            UninstallMode::BepInExOnly => Self::BEPINEX_FRAMEWORK_TARGETS,
+        }
+    }
+
+    /// 版本信息中使用的模式名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            UninstallMode::Light => "light",
+            UninstallMode::Full => "full",
+            UninstallMode::BepInExOnly => "bepInExOnly",
         }
     }
+
+    /// 合并内置卸载目标与远程下发的目标（仅保留属于当前模式且通过安全检查的项）
+    pub fn merged_targets(&self, remote: &[UninstallTarget]) -> Vec<(String, bool)> {
+        let mut targets: Vec<(String, bool)> = self
+            .targets()
+            .iter()
+            .map(|&(pattern, is_dir)| (pattern.to_string(), is_dir))
+            .collect();
+
+        for target in remote
+            .iter()
+            .filter(|t| t.mode.eq_ignore_ascii_case(self.name()))
+        {
+            let pattern = target.pattern.trim().replace('\\', "/");
+            if !is_safe_remote_uninstall_pattern(&pattern, target.is_dir) {
+                report_event("Uninstall.RemoteTarget.Rejected", Some(&pattern));
+                continue;
+            }
+            if !targets
+                .iter()
+                .any(|(p, _)| p.eq_ignore_ascii_case(&pattern))
+            {
+                targets.push((pattern, target.is_dir));
+            }
+        }
+
+        targets
+    }
+}
+
+/// 远程卸载目标允许位于其下的 BepInEx 框架目录（可以是目录本身或其中的文件）
+const REMOTE_UNINSTALL_ALLOWED_DIRS: &[&str] = &[
+    "BepInEx/core",
+    "BepInEx/cache",
+    "BepInEx/interop",
+    "BepInEx/unity-libs",
+    "BepInEx/patchers",
+    "dotnet",
+];
+
+/// 远程卸载目标允许的单个文件（须与模式完全一致，不区分大小写）
+const REMOTE_UNINSTALL_ALLOWED_FILES: &[&str] = &[
+    BEPINEX_LOADER_DLL,
+    "doorstop_config.ini",
+    ".doorstop_version",
+    "changelog.txt",
+    "MinHook.x64.dll",
+    "BepInEx/config/BepInEx.cfg",
+    "BepInEx/LogOutput.log",
+    "BepInEx/ErrorLog.log",
+    "BepInEx/plugins/MetaMystia-*.dll",
+    "ResourceEx/ResourceExample-*.zip",
+    "ResourceEx/.metamystia-resourceex",
+];
+
+/// 检查远程卸载目标是否安全：必须是游戏根目录下的相对路径，不含 `..`；
+/// 只能是已知的 BepInEx 框架目录（或其中的内容），或已知的 BepInEx 与 MetaMystia 文件
+fn is_safe_remote_uninstall_pattern(pattern: &str, is_dir: bool) -> bool {
+    if pattern.is_empty() || pattern.starts_with('/') || pattern.contains(':') {
+        return false;
+    }
+
+    let components: Vec<&str> = pattern.split('/').collect();
+    if components
+        .iter()
+        .any(|c| c.is_empty() || *c == "." || *c == "..")
+    {
+        return false;
+    }
+
+    let lower = pattern.to_lowercase();
+    let under_allowed_dir = REMOTE_UNINSTALL_ALLOWED_DIRS.iter().any(|dir| {
+        let dir = dir.to_lowercase();
+        lower == dir || lower.starts_with(&format!("{}/", dir))
+    });
+    if under_allowed_dir {
+        return true;
+    }
+
+    !is_dir
+        && REMOTE_UNINSTALL_ALLOWED_FILES
+            .iter()
+            .any(|file| file.eq_ignore_ascii_case(pattern))
+}
+
+/// 网络重试配置的类别
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RetryProfile {
+    /// 版本信息、下载链接等元数据请求（快速失败）
+    Metadata,
+    /// 文件下载（耐心重试）
+    Download,
+    /// GitHub API 请求
+    GitHubApi,
 }
 
 /// 通用重试配置
@@ -69,13 +223,39 @@ pub struct RetryConfig {
 }
 
 impl RetryConfig {
-    /// 网络操作的默认重试配置
-    pub const fn network() -> Self {
+    /// 各类网络操作的默认重试配置
+    pub const fn network(profile: RetryProfile) -> Self {
+        match profile {
+            RetryProfile::Metadata => Self {
+                attempts: 2,
+                base_delay_secs: 2,
+                multiplier: 2.0,
+                max_delay_secs: 4,
+            },
+            RetryProfile::Download => Self {
+                attempts: 4,
+                base_delay_secs: 5,
+                multiplier: 2.0,
+                max_delay_secs: 30,
+            },
+            RetryProfile::GitHubApi => Self {
+                attempts: 3,
+                base_delay_secs: 3,
+                multiplier: 2.0,
+                max_delay_secs: 10,
+            },
+        }
+    }
+
+    /// 按比例缩放重试次数与延迟
+    pub fn scaled(self, attempts_factor: f64, delay_factor: f64) -> Self {
+        let scale = |v: u64| ((v as f64) * delay_factor).round() as u64;
+
         Self {
-            attempts: 3,
-            base_delay_secs: 5,
-            multiplier: 2.0,
-            max_delay_secs: 15,
+            attempts: ((self.attempts as f64) * attempts_factor).round().max(1.0) as usize,
+            base_delay_secs: scale(self.base_delay_secs),
+            multiplier: self.multiplier,
+            max_delay_secs: scale(self.max_delay_secs),
         }
     }
 
@@ -89,3 +269,183 @@ impl RetryConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_ops::scan_existing_files;
+    use crate::test_utils::ScratchDir;
+
+    use std::path::Path;
+
+    fn remote(pattern: &str, is_dir: bool, mode: &str) -> UninstallTarget {
+        UninstallTarget {
+            pattern: pattern.to_string(),
+            is_dir,
+            mode: mode.to_string(),
+        }
+    }
+
+    #[test]
+    fn remote_patterns_allow_known_framework_dirs_and_files() {
+        for (pattern, is_dir) in [
+            ("dotnet", true),
+            ("BepInEx/interop", true),
+            ("bepinex/core/BepInEx.Core.dll", false),
+            ("BepInEx/unity-libs/*.zip", false),
+            ("winhttp.dll", false),
+            ("doorstop_config.ini", false),
+            ("BepInEx/plugins/MetaMystia-*.dll", false),
+            ("ResourceEx/ResourceExample-*.zip", false),
+        ] {
+            assert!(
+                is_safe_remote_uninstall_pattern(pattern, is_dir),
+                "{}",
+                pattern
+            );
+        }
+    }
+
+    #[test]
+    fn remote_patterns_reject_user_content_and_unknown_files() {
+        for (pattern, is_dir) in [
+            ("BepInEx", true),
+            ("BepInEx/plugins", true),
+            ("BepInEx/plugins/OtherPlugin.dll", false),
+            ("BepInEx/plugins/*.dll", false),
+            ("BepInEx/config", true),
+            ("BepInEx/config/other.plugin.cfg", false),
+            ("ResourceEx", true),
+            ("ResourceEx/custom.zip", false),
+            ("version.dll", false),
+            ("*.dll", false),
+            ("UnityPlayer.dll", false),
+            (GAME_EXECUTABLE, false),
+            ("Touhou Mystia Izakaya_Data", true),
+            ("winhttp.dll", true),
+            ("dotnet/../Touhou Mystia Izakaya_Data", true),
+            ("/dotnet", true),
+            ("C:/dotnet", true),
+            ("", false),
+        ] {
+            assert!(
+                !is_safe_remote_uninstall_pattern(pattern, is_dir),
+                "{}",
+                pattern
+            );
+        }
+    }
+
+    #[test]
+    fn merged_targets_add_only_safe_patterns_for_the_mode() {
+        let remote = [
+            remote("dotnet", true, "full"),
+            remote(r"BepInEx\interop", true, "bepInExOnly"),
+            remote("BepInEx/plugins", true, "full"),
+            remote("winhttp.dll", false, "full"),
+        ];
+
+        let full = UninstallMode::Full.merged_targets(&remote);
+        assert!(full.contains(&("dotnet".to_string(), true)));
+        assert!(!full.iter().any(|(p, _)| p == "BepInEx/plugins"));
+        assert!(!full.iter().any(|(p, _)| p == "BepInEx/interop"));
+        assert_eq!(
+            full.iter().filter(|(p, _)| p == "winhttp.dll").count(),
+            1,
+            "重复的目标不应再次添加"
+        );
+
+        let framework = UninstallMode::BepInExOnly.merged_targets(&remote);
+        assert!(framework.contains(&("BepInEx/interop".to_string(), true)));
+        assert!(!framework.iter().any(|(p, _)| p == "dotnet"));
+    }
+
+    /// 在目录中创建一份模拟已安装 Mod 的游戏目录
+    fn create_mock_game_root(dir: &Path) {
+        let files = [
+            GAME_EXECUTABLE,
+            "winhttp.dll",
+            "doorstop_config.ini",
+            ".doorstop_version",
+            "changelog.txt",
+            "MinHook.x64.dll",
+            "BepInEx/core/BepInEx.Core.dll",
+            "BepInEx/patchers/Patcher.dll",
+            "BepInEx/config/BepInEx.cfg",
+            "BepInEx/config/OtherMod.cfg",
+            "BepInEx/plugins/MetaMystia-v1.0.dll",
+            "BepInEx/plugins/OtherMod.dll",
+            "ResourceEx/ResourceExample-v1.0.zip",
+            "ResourceEx/.metamystia-resourceex",
+            "ResourceEx/CustomResource.zip",
+        ];
+        for file in files {
+            let path = dir.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, file).unwrap();
+        }
+    }
+
+    /// 扫描指定模式的卸载目标，返回相对于游戏目录的路径
+    fn scan_mode(root: &Path, mode: UninstallMode) -> Vec<String> {
+        let targets: Vec<(String, bool)> = mode
+            .targets()
+            .iter()
+            .map(|(pattern, is_dir)| (pattern.to_string(), *is_dir))
+            .collect();
+
+        let mut found: Vec<String> = scan_existing_files(root, &targets)
+            .iter()
+            .map(|p| {
+                p.strip_prefix(root)
+                    .unwrap()
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+            .collect();
+        found.sort();
+        found
+    }
+
+    #[test]
+    fn uninstall_modes_match_the_installed_files() {
+        let game = ScratchDir::new("uninstall-modes");
+        create_mock_game_root(game.path());
+
+        assert_eq!(
+            scan_mode(game.path(), UninstallMode::Light),
+            [
+                "BepInEx/plugins/MetaMystia-v1.0.dll",
+                "ResourceEx/.metamystia-resourceex",
+                "ResourceEx/ResourceExample-v1.0.zip",
+            ]
+        );
+        assert_eq!(
+            scan_mode(game.path(), UninstallMode::Full),
+            [
+                ".doorstop_version",
+                "BepInEx",
+                "MinHook.x64.dll",
+                "ResourceEx",
+                "changelog.txt",
+                "doorstop_config.ini",
+                "winhttp.dll",
+            ]
+        );
+        assert_eq!(
+            scan_mode(game.path(), UninstallMode::BepInExOnly),
+            [
+                ".doorstop_version",
+                "BepInEx/config/BepInEx.cfg",
+                "BepInEx/core",
+                "BepInEx/patchers",
+                "MinHook.x64.dll",
+                "changelog.txt",
+                "doorstop_config.ini",
+                "winhttp.dll",
+            ]
+        );
+    }
+}
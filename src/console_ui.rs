@@ -1,45 +1,349 @@
-use crate::config::{OperationMode, UninstallMode};
+use crate::config::{
+    BEPINEX_DEFAULT_LOG_LEVELS, BEPINEX_LOG_LEVELS, DOC_URL, GAME_EXECUTABLE, OperationMode,
+    PRESERVED_BEPINEX_DIRS, UninstallMode,
+};
+use crate::downloader::{DownloadReport, DownloadSizeEstimate, PrefetchResult};
+use crate::env_check::{GameProcess, PathSource};
 use crate::error::ManagerError;
-use crate::error::Result;
+use crate::error::{Result, SourceFailure};
+use crate::file_ops::{DeletionResult, DeletionStatus, format_size};
 use crate::metrics::{get_user_id, report_event};
 use crate::model::VersionInfo;
-use crate::ui::Ui;
-
-use console::{Term, style};
-use dialoguer::{Confirm, Input, theme::ColorfulTheme};
+use crate::net::current_utc_time;
+use crate::prefs::Preferences;
+use crate::progress::ProgressSnapshot;
+use crate::shutdown::{CleanupHandle, PRIORITY_PROGRESS, register_cleanup_with_priority};
+use crate::ui::{
+    InstallPlan, OperationSummary, OverwriteSelection, PendingUpgrade, SummaryAction, Ui,
+    WaitOutcome,
+};
+use crate::updater::{OUTDATED_WARN_THRESHOLD, versions_behind};
+
+use console::{Alignment, Term, measure_text_width, pad_str, style};
+use dialoguer::{Input, MultiSelect, theme::ColorfulTheme};
 use indicatif::{ProgressBar, ProgressStyle};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::sleep;
+use std::time::{Duration, Instant, SystemTime};
 use termimad::MadSkin;
 
+/// 文本输入连续失败（如标准输入已关闭）的最大次数，超过后视为用户取消
+const MAX_INPUT_FAILURES: usize = 3;
+
+/// 等待期间检查按键的间隔
+const KEY_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+const KEY_ESC: i32 = 0x1b;
+const KEY_ENTER: i32 = 0x0d;
+
+/// 安装计划中最多列出的清理项目数
+const PLAN_MAX_LISTED_TARGETS: usize = 5;
+
+/// 分隔线与提示框的最大宽度
+const MAX_LAYOUT_WIDTH: usize = 60;
+
+/// 命令行指定只使用 ASCII 符号（--plain）
+static PLAIN_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// 设置是否只使用 ASCII 符号
+pub fn configure_plain_output(plain: bool) {
+    PLAIN_OUTPUT.store(plain, Ordering::SeqCst);
+}
+
+/// 终端可以显示 Unicode 符号时返回 unicode，否则（或指定了 --plain 时）返回 ascii
+fn glyph(unicode: &'static str, ascii: &'static str) -> &'static str {
+    if !PLAIN_OUTPUT.load(Ordering::SeqCst) && Term::stdout().features().wants_emoji() {
+        unicode
+    } else {
+        ascii
+    }
+}
+
+/// 确认提示的等待时限（None 表示一直等待）
+static PROMPT_TIMEOUT: OnceLock<Option<Duration>> = OnceLock::new();
+
+/// 设置确认提示的等待时限：命令行指定的秒数会保存到偏好设置供之后使用，0 表示关闭
+pub fn configure_prompt_timeout(secs: Option<u64>) {
+    let mut prefs = Preferences::load();
+    if let Some(secs) = secs
+        && prefs.prompt_timeout_secs != Some(secs)
+    {
+        prefs.prompt_timeout_secs = Some(secs);
+        let _ = prefs.save();
+        report_event("UI.PromptTimeout.Changed", Some(&secs.to_string()));
+    }
+
+    let timeout = secs
+        .or(prefs.prompt_timeout_secs)
+        .filter(|&secs| secs > 0)
+        .map(Duration::from_secs);
+    let _ = PROMPT_TIMEOUT.set(timeout);
+}
+
+/// 确认提示接受的输入：是/否、y/n 及其全角形式（不区分大小写）
+fn confirm_answer(c: char) -> Option<bool> {
+    match c {
+        '是' | 'y' | 'Y' | 'ｙ' | 'Ｙ' => Some(true),
+        '否' | 'n' | 'N' | 'ｎ' | 'Ｎ' => Some(false),
+        _ => None,
+    }
+}
+
+/// 是/否确认（所有确认提示统一使用）：Enter 与 Esc 均采用默认选项；
+/// 设置了等待时限时在提示后显示倒计时，超时后同样采用默认选项
+fn confirm(prompt: &str, default: bool) -> Result<bool> {
+    let deadline = PROMPT_TIMEOUT
+        .get()
+        .copied()
+        .flatten()
+        .map(|timeout| Instant::now() + timeout);
+    confirm_until(prompt, default, deadline)
+}
+
+/// 是/否确认，deadline 为 None 时一直等待
+fn confirm_until(prompt: &str, default: bool, deadline: Option<Instant>) -> Result<bool> {
+    let term = Term::stdout();
+    let answer_text = |answer: bool| if answer { "是" } else { "否" };
+    let hint = if default { "[Y/n]" } else { "[y/N]" };
+    let finish = |suffix: String| -> Result<()> {
+        term.clear_line()?;
+        term.write_line(&format!("{} {}", style(prompt.trim()).bold(), suffix))?;
+        Ok(())
+    };
+
+    let mut shown_countdown = None;
+    loop {
+        let countdown = match deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    finish(format!(
+                        "{}",
+                        style(format!(
+                            "{}（等待超时，已采用默认选项）",
+                            answer_text(default)
+                        ))
+                        .dim()
+                    ))?;
+                    report_event(
+                        "UI.Prompt.TimedOut",
+                        Some(&format!(
+                            "{};default={}",
+                            prompt.trim(),
+                            if default { "yes" } else { "no" }
+                        )),
+                    );
+                    return Ok(default);
+                }
+                Some(remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0))
+            }
+            None => None,
+        };
+
+        if shown_countdown != Some(countdown) {
+            let suffix = countdown
+                .map(|secs| {
+                    style(format!(
+                        "（{} 秒后自动选择“{}”）",
+                        secs,
+                        answer_text(default)
+                    ))
+                    .dim()
+                    .to_string()
+                })
+                .unwrap_or_default();
+            term.clear_line()?;
+            term.write_str(&format!(
+                "{} {} {}",
+                style(prompt.trim()).bold(),
+                hint,
+                suffix
+            ))?;
+            shown_countdown = Some(countdown);
+        }
+
+        let answer = match poll_key() {
+            Some(KEY_ESC) => {
+                finish(format!(
+                    "{}",
+                    style(format!("{}（已采用默认选项）", answer_text(default))).dim()
+                ))?;
+                return Ok(default);
+            }
+            Some(KEY_ENTER) => default,
+            Some(key) => match char::from_u32(key as u32).and_then(confirm_answer) {
+                Some(answer) => answer,
+                None => continue,
+            },
+            None => {
+                sleep(KEY_POLL_INTERVAL);
+                continue;
+            }
+        };
+
+        finish(format!("{}", style(answer_text(answer)).green()))?;
+        return Ok(answer);
+    }
+}
+
+/// 按终端宽度收窄的布局宽度（留出一列避免自动换行）
+fn layout_width() -> usize {
+    let (_, cols) = Term::stdout().size();
+    (cols as usize)
+        .saturating_sub(1)
+        .clamp(20, MAX_LAYOUT_WIDTH)
+}
+
+/// 与终端同宽的分隔线
+fn separator(unicode: &'static str, ascii: &'static str) -> String {
+    glyph(unicode, ascii).repeat(layout_width())
+}
+
+/// 超出显示宽度时省略中间部分，保留开头与结尾（通常是文件名）
+fn truncate_middle(text: &str, max_width: usize) -> String {
+    if measure_text_width(text) <= max_width {
+        return text.to_string();
+    }
+
+    const ELLIPSIS: &str = "...";
+    let budget = max_width.saturating_sub(ELLIPSIS.len());
+    let chars: Vec<char> = text.chars().collect();
+    let char_width = |c: &char| measure_text_width(c.encode_utf8(&mut [0; 4]));
+
+    let mut tail_width = 0;
+    let mut tail_start = chars.len();
+    while tail_start > 0 {
+        let w = char_width(&chars[tail_start - 1]);
+        if tail_width + w > budget - budget / 2 {
+            break;
+        }
+        tail_width += w;
+        tail_start -= 1;
+    }
+
+    let mut head_width = 0;
+    let mut head_end = 0;
+    while head_end < tail_start {
+        let w = char_width(&chars[head_end]);
+        if head_width + w > budget - tail_width {
+            break;
+        }
+        head_width += w;
+        head_end += 1;
+    }
+
+    format!(
+        "{}{}{}",
+        chars[..head_end].iter().collect::<String>(),
+        ELLIPSIS,
+        chars[tail_start..].iter().collect::<String>()
+    )
+}
+
+/// 未完成的进度条（退出清理通过弱引用访问，不会延长 ConsoleUI 的生命周期）
+#[derive(Default)]
+struct ProgressRegistry {
+    bars: Mutex<HashMap<usize, ProgressBar>>,
+    deletion_bar: Mutex<Option<ProgressBar>>,
+}
+
+impl ProgressRegistry {
+    /// 清除所有未完成的进度条并恢复光标，使后续输出位于正确的位置
+    fn abort_all_progress(&self) {
+        let mut bars = match self.bars.lock() {
+            Ok(g) => g,
+            Err(e) => e.into_inner(),
+        };
+        for (_, pb) in bars.drain() {
+            pb.finish_and_clear();
+        }
+
+        let mut deletion_bar = match self.deletion_bar.lock() {
+            Ok(g) => g,
+            Err(e) => e.into_inner(),
+        };
+        if let Some(pb) = deletion_bar.take() {
+            pb.finish_and_clear();
+        }
+
+        let _ = Term::stdout().show_cursor();
+    }
+}
+
 /// 控制台 UI 实现
 pub struct ConsoleUI {
-    bars: Mutex<HashMap<usize, ProgressBar>>,
+    progress: Arc<ProgressRegistry>,
     next_id: AtomicUsize,
+    /// Ctrl+C 等中断时清除进度条，ConsoleUI 释放时自动注销
+    _progress_cleanup: CleanupHandle,
 }
 
 impl ConsoleUI {
     pub fn new() -> Self {
+        let progress = Arc::new(ProgressRegistry::default());
+        let weak = Arc::downgrade(&progress);
+        let progress_cleanup = register_cleanup_with_priority(PRIORITY_PROGRESS, move || {
+            if let Some(progress) = weak.upgrade() {
+                progress.abort_all_progress();
+            }
+        });
+
         Self {
-            bars: Mutex::new(HashMap::new()),
+            progress,
             next_id: AtomicUsize::new(1),
+            _progress_cleanup: progress_cleanup,
+        }
+    }
+
+    /// 在删除进度条上方输出一行，没有进度条时直接输出
+    fn deletion_println(&self, line: String) {
+        let guard = match self.progress.deletion_bar.lock() {
+            Ok(g) => g,
+            Err(e) => e.into_inner(),
+        };
+
+        match guard.as_ref() {
+            Some(pb) => pb.println(line),
+            None => println!("{}", line),
         }
     }
 }
 
 impl Ui for ConsoleUI {
-    fn display_welcome(&self) -> Result<()> {
-        display_welcome()
+    fn display_welcome(&self, is_first_run: bool) -> Result<()> {
+        display_welcome()?;
+        if is_first_run {
+            display_welcome_extended()?;
+        }
+        Ok(())
+    }
+
+    fn ask_telemetry_consent(&self) -> Result<bool> {
+        ask_telemetry_consent()
     }
 
     fn display_version(&self, manager_version: Option<&str>) -> Result<()> {
         display_version(manager_version)
     }
 
-    fn display_game_running_warning(&self) -> Result<()> {
-        display_game_running_warning()
+    fn display_update_channel_info(&self, channel: &str) -> Result<()> {
+        display_update_channel_info(channel)
+    }
+
+    fn warn_old_manager_version(
+        &self,
+        current: &str,
+        latest: &str,
+        behind_versions: usize,
+    ) -> Result<()> {
+        warn_old_manager_version(current, latest, behind_versions)
+    }
+
+    fn display_game_running_warning(&self, processes: &[GameProcess]) -> Result<()> {
+        display_game_running_warning(processes)
     }
 
     fn display_available_updates(
@@ -50,6 +354,14 @@ impl Ui for ConsoleUI {
         display_available_updates(dll_available, resourceex_available)
     }
 
+    fn display_upgrade_check_failed(&self, err: &str) -> Result<()> {
+        display_upgrade_check_failed(err)
+    }
+
+    fn display_progress_cancelled(&self, snapshot: &ProgressSnapshot) -> Result<()> {
+        display_progress_cancelled(snapshot)
+    }
+
     fn select_operation_mode(&self) -> Result<OperationMode> {
         select_operation_mode()
     }
@@ -78,14 +390,49 @@ impl Ui for ConsoleUI {
         Ok(())
     }
 
+    fn display_operation_summary(&self, summary: &OperationSummary) -> Result<()> {
+        display_operation_summary(summary)
+    }
+
+    fn display_controlled_folder_access_blocked(&self, app_path: &Path) -> Result<()> {
+        display_controlled_folder_access_blocked(app_path)
+    }
+
     fn path_display_steam_found(&self, app_id: u32, name: Option<&str>, path: &Path) -> Result<()> {
         path_display_steam_found(app_id, name, path)
     }
 
+    fn path_display_steam_stale(&self, app_id: u32, name: Option<&str>, path: &Path) -> Result<()> {
+        path_display_steam_stale(app_id, name, path)
+    }
+
     fn path_confirm_use_steam_found(&self) -> Result<bool> {
         path_confirm_use_steam_found()
     }
 
+    fn select_game_path_from_candidates(
+        &self,
+        candidates: &[(PathBuf, PathSource)],
+    ) -> Result<PathBuf> {
+        select_game_path_from_candidates(candidates)
+    }
+
+    fn path_ask_manual_entry(&self) -> Result<Option<PathBuf>> {
+        path_ask_manual_entry()
+    }
+
+    fn path_manual_entry_not_found(&self, input: &Path) -> Result<()> {
+        path_manual_entry_not_found(input)
+    }
+
+    fn warn_conflicting_game_exe(&self, extras: &[PathBuf]) -> Result<bool> {
+        warn_conflicting_game_exe(extras)
+    }
+
+    fn warn_cloud_sync_directory(&self, provider: &str) -> Result<()> {
+        warn_cloud_sync_directory(provider)
+    }
+
     fn install_display_step(&self, step: usize, description: &str) -> Result<()> {
         install_display_step(step, description)
     }
@@ -94,16 +441,37 @@ impl Ui for ConsoleUI {
         install_display_version_info(version_info)
     }
 
+    fn install_display_estimated_download_size(
+        &self,
+        estimate: &DownloadSizeEstimate,
+    ) -> Result<()> {
+        install_display_estimated_download_size(estimate)
+    }
+
+    fn install_display_estimated_duration(&self, estimated_secs: u64) -> Result<()> {
+        install_display_estimated_duration(estimated_secs)
+    }
+
+    fn install_display_download_sources(&self, result: &PrefetchResult) -> Result<()> {
+        install_display_download_sources(result)
+    }
+
+    fn install_warn_slow_storage(&self, estimated_extra_mins: u64) -> Result<()> {
+        install_warn_slow_storage(estimated_extra_mins)
+    }
+
     fn install_warn_existing(
         &self,
         bepinex_installed: bool,
         metamystia_installed: bool,
         resourceex_installed: bool,
+        cleanup_plan: &[PathBuf],
     ) -> Result<()> {
         install_warn_existing(
             bepinex_installed,
             metamystia_installed,
             resourceex_installed,
+            cleanup_plan,
         )
     }
 
@@ -111,6 +479,19 @@ impl Ui for ConsoleUI {
         install_confirm_overwrite()
     }
 
+    fn install_select_overwrite(
+        &self,
+        bepinex_installed: bool,
+        metamystia_installed: bool,
+        resourceex_installed: bool,
+    ) -> Result<OverwriteSelection> {
+        install_select_overwrite(
+            bepinex_installed,
+            metamystia_installed,
+            resourceex_installed,
+        )
+    }
+
     fn install_ask_install_resourceex(&self) -> Result<bool> {
         install_ask_install_resourceex()
     }
@@ -119,6 +500,22 @@ impl Ui for ConsoleUI {
         install_ask_show_bepinex_console()
     }
 
+    fn install_ask_configure_advanced_options(&self) -> Result<bool> {
+        install_ask_configure_advanced_options()
+    }
+
+    fn install_select_bepinex_log_levels(&self) -> Result<Vec<String>> {
+        install_select_bepinex_log_levels()
+    }
+
+    fn install_ask_backup_before_install(&self) -> Result<bool> {
+        install_ask_backup_before_install()
+    }
+
+    fn install_display_plan(&self, plan: &InstallPlan) -> Result<bool> {
+        install_display_plan(plan)
+    }
+
     fn install_downloads_completed(&self) -> Result<()> {
         install_downloads_completed()
     }
@@ -127,18 +524,54 @@ impl Ui for ConsoleUI {
         install_start_cleanup()
     }
 
+    fn install_display_cleanup_detail(&self, items: &[DeletionResult]) -> Result<()> {
+        install_display_cleanup_detail(items)
+    }
+
     fn install_cleanup_result(&self, success_count: usize, failed_count: usize) -> Result<()> {
         install_cleanup_result(success_count, failed_count)
     }
 
-    fn install_finished(&self, show_bepinex_console: bool) -> Result<()> {
-        install_finished(show_bepinex_console)
+    fn install_finished(&self, show_bepinex_console: bool, first_install: bool) -> Result<()> {
+        install_finished(show_bepinex_console)?;
+        if first_install {
+            self.display_first_time_bepinex_info()?;
+        }
+        Ok(())
+    }
+
+    fn display_first_time_bepinex_info(&self) -> Result<()> {
+        display_first_time_bepinex_info()
+    }
+
+    fn warn_bepinex_il2cpp_download(&self, source_url: &str) -> Result<()> {
+        warn_bepinex_il2cpp_download(source_url)
+    }
+
+    fn install_warn_loader_quarantined(&self, path: &Path, detection: Option<&str>) -> Result<()> {
+        install_warn_loader_quarantined(path, detection)
+    }
+
+    fn resourceex_display_misplaced(&self, files: &[PathBuf]) -> Result<()> {
+        resourceex_display_misplaced(files)
+    }
+
+    fn resourceex_confirm_migrate(&self) -> Result<bool> {
+        resourceex_confirm_migrate()
+    }
+
+    fn resourceex_migrate_failed(&self, path: &Path, err: &str) -> Result<()> {
+        resourceex_migrate_failed(path, err)
     }
 
     fn upgrade_warn_unparse_version(&self, filename: &str) -> Result<()> {
         upgrade_warn_unparse_version(filename)
     }
 
+    fn upgrade_found_duplicate(&self, path: &Path) -> Result<()> {
+        upgrade_found_duplicate(path)
+    }
+
     fn upgrade_backup_failed(&self, err: &str) -> Result<()> {
         upgrade_backup_failed(err)
     }
@@ -187,6 +620,10 @@ impl Ui for ConsoleUI {
         upgrade_resourceex_needs_upgrade()
     }
 
+    fn confirm_batch_upgrade(&self, updates: &[PendingUpgrade]) -> Result<bool> {
+        confirm_batch_upgrade(updates)
+    }
+
     fn upgrade_downloading_dll(&self) -> Result<()> {
         upgrade_downloading_dll()
     }
@@ -215,6 +652,30 @@ impl Ui for ConsoleUI {
         upgrade_done()
     }
 
+    fn ask_launch_game(&self) -> Result<bool> {
+        ask_launch_game()
+    }
+
+    fn launch_game_failed(&self, err: &str) -> Result<()> {
+        launch_game_failed(err)
+    }
+
+    fn ask_open_docs(&self) -> Result<bool> {
+        ask_open_docs()
+    }
+
+    fn open_docs_failed(&self, url: &str) -> Result<()> {
+        open_docs_failed(url)
+    }
+
+    fn configure_ask_bepinex_console(&self, current: bool) -> Result<bool> {
+        configure_ask_bepinex_console(current)
+    }
+
+    fn configure_bepinex_console_changed(&self, previous: bool, enabled: bool) -> Result<()> {
+        configure_bepinex_console_changed(previous, enabled)
+    }
+
     fn uninstall_select_mode(&self) -> Result<UninstallMode> {
         uninstall_select_uninstall_mode()
     }
@@ -231,6 +692,26 @@ impl Ui for ConsoleUI {
         uninstall_confirm_deletion()
     }
 
+    fn uninstall_ask_create_undo_archive(&self, estimated_size: u64) -> Result<bool> {
+        uninstall_ask_create_undo_archive(estimated_size)
+    }
+
+    fn uninstall_undo_archive_insufficient_space(
+        &self,
+        required: u64,
+        available: u64,
+    ) -> Result<()> {
+        uninstall_undo_archive_insufficient_space(required, available)
+    }
+
+    fn uninstall_undo_archive_created(&self, path: &Path) -> Result<()> {
+        uninstall_undo_archive_created(path)
+    }
+
+    fn restore_undo_finished(&self, archive: &Path, count: usize) -> Result<()> {
+        restore_undo_finished(archive, count)
+    }
+
     fn uninstall_files_in_use_warning(&self) -> Result<()> {
         uninstall_files_in_use_warning()
     }
@@ -244,6 +725,10 @@ impl Ui for ConsoleUI {
         uninstall_wait_before_retry(delay_secs, attempt, attempts)
     }
 
+    fn uninstall_wait_or_skip(&self, delay_secs: u64) -> Result<WaitOutcome> {
+        uninstall_wait_or_skip(delay_secs)
+    }
+
     fn uninstall_ask_elevate_permission(&self) -> Result<bool> {
         uninstall_ask_elevate_permission()
     }
@@ -260,28 +745,69 @@ impl Ui for ConsoleUI {
         uninstall_retrying_failed_items()
     }
 
-    fn deletion_start(&self) -> Result<()> {
+    fn uninstall_display_disk_space_freed(&self, bytes_freed: u64) -> Result<()> {
+        uninstall_display_disk_space_freed(bytes_freed)
+    }
+
+    fn uninstall_confirm_take_ownership(&self, paths: &[PathBuf]) -> Result<bool> {
+        uninstall_confirm_take_ownership(paths)
+    }
+
+    fn uninstall_display_acl_fix_commands(&self, commands: &[String]) -> Result<()> {
+        uninstall_display_acl_fix_commands(commands)
+    }
+
+    fn uninstall_display_failure_breakdown(
+        &self,
+        locked: usize,
+        acl_denied: usize,
+        other: usize,
+    ) -> Result<()> {
+        uninstall_display_failure_breakdown(locked, acl_denied, other)
+    }
+
+    fn deletion_start(&self, total: usize) -> Result<()> {
         println!();
+
+        let mut guard = match self.progress.deletion_bar.lock() {
+            Ok(g) => g,
+            Err(e) => e.into_inner(),
+        };
+
+        // 重试时会再次开始删除，先清除上一轮的进度条
+        if let Some(old) = guard.replace(deletion_start(total)) {
+            old.finish_and_clear();
+        }
+
         Ok(())
     }
 
     fn deletion_display_progress(&self, current: usize, total: usize, path: &str) -> Result<()> {
-        deletion_display_progress(current, total, path);
+        let guard = match self.progress.deletion_bar.lock() {
+            Ok(g) => g,
+            Err(e) => e.into_inner(),
+        };
+
+        match guard.as_ref() {
+            Some(pb) => deletion_display_progress(pb, current, path),
+            None => println!("{}", deletion_progress_line(current, total, path)),
+        }
+
         Ok(())
     }
 
     fn deletion_display_success(&self, path: &str) -> Result<()> {
-        deletion_display_success(path);
+        self.deletion_println(deletion_success_line(path));
         Ok(())
     }
 
     fn deletion_display_failure(&self, path: &str, error: &str) -> Result<()> {
-        deletion_display_failure(path, error);
+        self.deletion_println(deletion_failure_line(path, error));
         Ok(())
     }
 
     fn deletion_display_skipped(&self, path: &str) -> Result<()> {
-        deletion_display_skipped(path);
+        self.deletion_println(deletion_skipped_line(path));
         Ok(())
     }
 
@@ -291,10 +817,24 @@ impl Ui for ConsoleUI {
         failed_count: usize,
         skipped_count: usize,
     ) -> Result<()> {
+        let mut guard = match self.progress.deletion_bar.lock() {
+            Ok(g) => g,
+            Err(e) => e.into_inner(),
+        };
+
+        if let Some(pb) = guard.take() {
+            pb.finish_and_clear();
+        }
+        drop(guard);
+
         deletion_display_summary(success_count, failed_count, skipped_count);
         Ok(())
     }
 
+    fn temp_dir_warn_cross_volume(&self, temp_dir: &Path, game_root: &Path) -> Result<()> {
+        temp_dir_warn_cross_volume(temp_dir, game_root)
+    }
+
     fn download_start(&self, filename: &str, total: Option<u64>) -> Result<usize> {
         let id = self.next_id.fetch_add(1, Ordering::SeqCst);
         let pb = match total {
@@ -317,7 +857,7 @@ impl Ui for ConsoleUI {
             }
         };
 
-        let mut guard = match self.bars.lock() {
+        let mut guard = match self.progress.bars.lock() {
             Ok(g) => g,
             Err(e) => e.into_inner(),
         };
@@ -327,7 +867,7 @@ impl Ui for ConsoleUI {
     }
 
     fn download_update(&self, id: usize, downloaded: u64) -> Result<()> {
-        let guard = match self.bars.lock() {
+        let guard = match self.progress.bars.lock() {
             Ok(g) => g,
             Err(e) => e.into_inner(),
         };
@@ -340,7 +880,7 @@ impl Ui for ConsoleUI {
     }
 
     fn download_finish(&self, id: usize, message: &str) -> Result<()> {
-        let mut guard = match self.bars.lock() {
+        let mut guard = match self.progress.bars.lock() {
             Ok(g) => g,
             Err(e) => e.into_inner(),
         };
@@ -364,6 +904,10 @@ impl Ui for ConsoleUI {
         download_version_info_success()
     }
 
+    fn download_version_info_cached(&self, cached_at: &str) -> Result<()> {
+        download_version_info_cached(cached_at)
+    }
+
     fn download_version_info_parse_failed(&self, err: &str, snippet: &str) -> Result<()> {
         download_version_info_parse_failed(err, snippet)
     }
@@ -392,6 +936,10 @@ impl Ui for ConsoleUI {
         download_github_dll_not_found()
     }
 
+    fn download_github_auth_required(&self) -> Result<()> {
+        download_github_auth_required()
+    }
+
     fn download_display_github_release_notes(
         &self,
         tag: &str,
@@ -421,6 +969,14 @@ impl Ui for ConsoleUI {
         download_bepinex_primary_failed(err)
     }
 
+    fn download_all_sources_failed(&self, failures: &[SourceFailure]) -> Result<()> {
+        download_all_sources_failed(failures)
+    }
+
+    fn display_download_summary(&self, reports: &[DownloadReport]) -> Result<()> {
+        display_download_summary(reports)
+    }
+
     fn network_retrying(
         &self,
         op_desc: &str,
@@ -436,10 +992,18 @@ impl Ui for ConsoleUI {
         network_rate_limited(secs)
     }
 
+    fn display_network_diagnostics(&self, endpoint: &str, last_error: &str) -> Result<()> {
+        display_network_diagnostics(endpoint, last_error)
+    }
+
     fn manager_ask_self_update(&self, current_version: &str, latest_version: &str) -> Result<bool> {
         manager_ask_self_update(current_version, latest_version)
     }
 
+    fn display_self_update_downloaded(&self, path: &Path, version: &str) -> Result<()> {
+        display_self_update_downloaded(path, version)
+    }
+
     fn manager_update_starting(&self) -> Result<()> {
         manager_update_starting()
     }
@@ -448,8 +1012,8 @@ impl Ui for ConsoleUI {
         manager_update_failed(err)
     }
 
-    fn manager_prompt_manual_update(&self) -> Result<()> {
-        manager_prompt_manual_update()
+    fn manager_prompt_manual_update(&self, reason: &str) -> Result<()> {
+        manager_prompt_manual_update(reason)
     }
 
     fn select_version_ask_select(&self, component: &str) -> Result<bool> {
@@ -472,33 +1036,121 @@ impl Ui for ConsoleUI {
 
 // ==================== 通用 UI ====================
 
+fn read_input(prompt: &str) -> Result<String> {
+    let mut failures = 0;
+
+    loop {
+        match Input::<String>::with_theme(&ColorfulTheme::default())
+            .with_prompt(prompt)
+            .interact_text()
+        {
+            Ok(input) => return Ok(input),
+            Err(e) => {
+                failures += 1;
+                if failures >= MAX_INPUT_FAILURES {
+                    report_event("UI.Input.Cancelled", Some(&format!("{}", e)));
+                    return Err(ManagerError::UserCancelled);
+                }
+            }
+        }
+    }
+}
+
 fn display_welcome() -> Result<()> {
     let term = Term::stdout();
     term.clear_screen()?;
 
-    println!("{}", style("═".repeat(60)).cyan());
-    println!(
-        "{}{}（v{}）",
-        " ".repeat(7),
+    let width = layout_width();
+    let title = format!(
+        "{}（v{}）",
         style("MetaMystia Mod 一键安装/升级/卸载工具").cyan().bold(),
         env!("CARGO_PKG_VERSION")
     );
 
-    let user_id = get_user_id();
-    print!("{}", " ".repeat(14));
-    println!("{}", style(user_id).dim());
-
-    println!("{}", style("═".repeat(60)).cyan());
+    println!("{}", style(separator("═", "=")).cyan());
+    println!(
+        "{}",
+        pad_str(&title, width, Alignment::Center, None).trim_end()
+    );
+    println!(
+        "{}",
+        pad_str(
+            &style(get_user_id()).dim().to_string(),
+            width,
+            Alignment::Center,
+            None
+        )
+        .trim_end()
+    );
+    println!("{}", style(separator("═", "=")).cyan());
     println!();
 
     Ok(())
 }
 
-fn display_version(manager_version: Option<&str>) -> Result<()> {
-    if let Some(v) = manager_version {
-        println!();
-        println!("管理工具最新版本：{}", style(v).green());
-        if v != env!("CARGO_PKG_VERSION") {
+fn ask_telemetry_consent() -> Result<bool> {
+    println!("{}", style("关于匿名使用统计").cyan().bold());
+    println!();
+    println!("为了改进本工具，程序会发送匿名的使用事件（如操作类型、下载结果、错误类型），");
+    println!("并使用经过哈希处理的设备标识区分不同设备，不会收集任何个人信息。");
+    println!("{}", style("此选择会被记住，之后不再询问。").dim());
+    println!();
+
+    // 是否同意发送统计需要用户明确选择，不随确认提示的等待时限自动作答
+    let choice = confirm_until(" 是否允许发送匿名使用统计？", true, None)?;
+    println!();
+
+    Ok(choice)
+}
+
+fn display_welcome_extended() -> Result<()> {
+    println!("{}", style("欢迎使用！").green().bold());
+    println!();
+    println!("{}", style("本工具可以：").cyan());
+    println!("  • 安装 BepInEx 框架、MetaMystia Mod 及 ResourceExample 资源包");
+    println!("  • 检查并升级已安装的 MetaMystia Mod");
+    println!("  • 卸载 Mod，或将游戏还原为原版");
+    println!();
+    println!(
+        "{}{}",
+        style("使用说明：").cyan(),
+        style(DOC_URL).underlined()
+    );
+    println!(
+        "{}",
+        style("本工具会发送匿名使用统计以帮助改进，你可以随时选择关闭。").dim()
+    );
+    println!();
+
+    Ok(())
+}
+
+fn display_update_channel_info(channel: &str) -> Result<()> {
+    if channel != "stable" {
+        println!();
+        println!(
+            "{}",
+            style(format!(
+                "当前使用 {} 更新通道，将收到管理工具的测试版本。",
+                channel
+            ))
+            .yellow()
+        );
+    }
+    Ok(())
+}
+
+fn display_version(manager_version: Option<&str>) -> Result<()> {
+    if let Some(v) = manager_version {
+        println!();
+        println!("管理工具最新版本：{}", style(v).green());
+
+        let current = env!("CARGO_PKG_VERSION");
+        if let Some(behind) = versions_behind(current, v)
+            && behind > OUTDATED_WARN_THRESHOLD
+        {
+            warn_old_manager_version(current, v, behind)?;
+        } else if v != current {
             println!(
                 "{}",
                 style("升级提醒：您当前使用的不是最新版本，建议升级至最新版本。").yellow()
@@ -510,13 +1162,108 @@ fn display_version(manager_version: Option<&str>) -> Result<()> {
         println!();
     }
 
-    println!("{}", style("═".repeat(60)).cyan());
+    println!("{}", style(separator("═", "=")).cyan());
     println!();
 
     Ok(())
 }
 
-fn display_game_running_warning() -> Result<()> {
+fn warn_old_manager_version(current: &str, latest: &str, behind_versions: usize) -> Result<()> {
+    let inner_width = layout_width().saturating_sub(2);
+    let horizontal = glyph("═", "=");
+
+    let lines = [
+        format!(
+            "当前版本 {} 已落后最新版本 {} 共 {} 个版本，",
+            current, latest, behind_versions
+        ),
+        "旧版本可能无法正常下载或安装 Mod，请立即升级！".to_string(),
+    ];
+
+    let vertical = glyph("║", "|");
+    let title = format!("{}{}{} 重要提示 ", glyph("╔", "+"), horizontal, horizontal);
+    let top = format!(
+        "{}{}{}",
+        title,
+        horizontal.repeat((inner_width + 1).saturating_sub(measure_text_width(&title))),
+        glyph("╗", "+")
+    );
+
+    println!();
+    println!("{}", style(top).red().bold());
+    for line in &lines {
+        println!(
+            "{}",
+            style(format!(
+                "{} {} {}",
+                vertical,
+                pad_str(line, inner_width.saturating_sub(2), Alignment::Left, None),
+                vertical
+            ))
+            .red()
+            .bold()
+        );
+    }
+    println!(
+        "{}",
+        style(format!(
+            "{}{}{}",
+            glyph("╚", "+"),
+            horizontal.repeat(inner_width),
+            glyph("╝", "+")
+        ))
+        .red()
+        .bold()
+    );
+    println!(
+        "手动下载：https://doc.meta-mystia.izakaya.cc/user_guide/how_to_install.html#onclick_install"
+    );
+
+    report_event(
+        "UI.Manager.Outdated",
+        Some(&format!("{};behind={}", current, behind_versions)),
+    );
+
+    Ok(())
+}
+
+/// 将进程已运行的时长格式化为“N 分钟/小时/天前”
+fn format_started_ago(started_at: SystemTime) -> String {
+    let mins = started_at.elapsed().unwrap_or_default().as_secs() / 60;
+    match mins {
+        0 => "刚刚".to_string(),
+        1..60 => format!("{} 分钟前", mins),
+        60..1440 => format!("{} 小时前", mins / 60),
+        _ => format!("{} 天前", mins / 1440),
+    }
+}
+
+fn display_game_running_warning(processes: &[GameProcess]) -> Result<()> {
+    println!("{}", style("检测到以下游戏相关进程仍在运行：").yellow());
+    for process in processes {
+        let started = process
+            .started_at
+            .map(|t| format!("，{}启动", format_started_ago(t)))
+            .unwrap_or_default();
+        println!("  - {}（PID {}{}）", process.name, process.pid, started);
+    }
+
+    let game_count = processes.iter().filter(|p| !p.is_crash_handler()).count();
+    if processes.iter().any(GameProcess::is_crash_handler) {
+        println!(
+            "{}",
+            style("游戏可能已崩溃，但崩溃处理程序仍在运行并占用游戏文件。").dim()
+        );
+    } else if game_count > 1 {
+        println!(
+            "{}",
+            style("存在多个游戏实例，其中可能有已无窗口但仍占用游戏文件的进程。").dim()
+        );
+    }
+    println!(
+        "{}",
+        style("若游戏窗口已关闭，请在任务管理器中结束上述进程。").dim()
+    );
     println!("请先关闭游戏，然后重新运行本程序。");
     Ok(())
 }
@@ -536,30 +1283,69 @@ fn display_available_updates(dll_available: bool, resourceex_available: bool) ->
     Ok(())
 }
 
+fn display_upgrade_check_failed(err: &str) -> Result<()> {
+    println!(
+        "{}",
+        style(format!("无法获取版本信息，已跳过更新检查：{}", err)).yellow()
+    );
+    println!(
+        "{}",
+        style("仍可继续选择操作；卸载与设置不受影响，安装和升级时会再次尝试获取版本信息。").dim()
+    );
+    println!();
+
+    Ok(())
+}
+
+fn display_progress_cancelled(snapshot: &ProgressSnapshot) -> Result<()> {
+    let current = snapshot
+        .current
+        .as_deref()
+        .map(|step| format!("，正在{}时取消", step))
+        .unwrap_or_default();
+    println!();
+    println!(
+        "{}",
+        style(format!(
+            "{}被中断（已完成步骤 {}/{}{}）",
+            snapshot.operation, snapshot.completed_steps, snapshot.total_steps, current
+        ))
+        .yellow()
+    );
+    println!(
+        "{}",
+        style("临时文件将被清理，可稍后重新运行以完成操作。").dim()
+    );
+
+    Ok(())
+}
+
 fn select_operation_mode() -> Result<OperationMode> {
     println!("{}", style("请选择操作模式：").cyan().bold());
     println!();
     println!("  {} 安装 Mod", style("[1]").green());
     println!("  {} 升级 Mod", style("[2]").green());
     println!("  {} 卸载 Mod", style("[3]").green());
+    println!("  {} 设置", style("[4]").green());
+    println!("  {} 查看使用文档", style("[5]").green());
     println!("  {} 退出程序", style("[0]").dim());
     println!();
 
     loop {
-        let input: String = Input::with_theme(&ColorfulTheme::default())
-            .with_prompt(" 请输入选项")
-            .interact_text()?;
+        let input = read_input(" 请输入选项")?;
 
         match input.trim() {
             "1" => return Ok(OperationMode::Install),
             "2" => return Ok(OperationMode::Upgrade),
             "3" => return Ok(OperationMode::Uninstall),
+            "4" => return Ok(OperationMode::Configure),
+            "5" => return Ok(OperationMode::OpenDocs),
             "0" => {
                 return Err(ManagerError::UserCancelled);
             }
             _ => {
                 println!();
-                println!("{}", style("无效的选项，请输入 0、1、2 或 3").yellow());
+                println!("{}", style("无效的选项，请输入 0 到 5 之间的数字").yellow());
                 continue;
             }
         }
@@ -580,6 +1366,76 @@ fn wait_for_key() -> Result<()> {
     Ok(())
 }
 
+fn display_operation_summary(summary: &OperationSummary) -> Result<()> {
+    if summary.entries.is_empty() {
+        return Ok(());
+    }
+
+    let action_label = |action: SummaryAction| match action {
+        SummaryAction::Installed => "已安装",
+        SummaryAction::Upgraded => "已升级",
+        SummaryAction::Kept => "已保留",
+        SummaryAction::Removed => "已删除",
+    };
+
+    let width = summary
+        .entries
+        .iter()
+        .map(|e| measure_text_width(&e.component))
+        .max()
+        .unwrap_or(0);
+    let version_width = summary
+        .entries
+        .iter()
+        .map(|e| e.version.as_deref().map_or(1, measure_text_width))
+        .max()
+        .unwrap_or(0);
+
+    println!();
+    println!("{}", style("变更摘要：").cyan().bold());
+    for e in &summary.entries {
+        let path = e
+            .path
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
+        println!(
+            "  {}  {}  {}  {}",
+            pad_str(&e.component, width, Alignment::Left, None),
+            style(action_label(e.action)).green(),
+            pad_str(
+                e.version.as_deref().unwrap_or("-"),
+                version_width,
+                Alignment::Left,
+                None
+            ),
+            style(path).dim()
+        );
+    }
+    Ok(())
+}
+
+// ==================== 权限相关 UI ====================
+
+fn display_controlled_folder_access_blocked(app_path: &Path) -> Result<()> {
+    println!();
+    println!(
+        "{}",
+        style("写入被 Windows 安全中心的“受控文件夹访问”功能阻止。")
+            .red()
+            .bold()
+    );
+    println!("该功能会拦截所有未被允许的程序，以管理员身份运行也无法解决。");
+    println!(
+        "请打开“Windows 安全中心 → 病毒和威胁防护 → 勒索软件防护 → 允许应用通过受控文件夹访问”，"
+    );
+    println!("添加以下程序后重试：");
+    println!("  {}", style(app_path.display()).cyan());
+    println!();
+
+    Ok(())
+}
+
 // ==================== 目录相关 UI ====================
 
 fn path_display_steam_found(app_id: u32, name: Option<&str>, path: &Path) -> Result<()> {
@@ -598,12 +1454,141 @@ fn path_display_steam_found(app_id: u32, name: Option<&str>, path: &Path) -> Res
     Ok(())
 }
 
+fn path_display_steam_stale(app_id: u32, name: Option<&str>, path: &Path) -> Result<()> {
+    println!(
+        "{}",
+        style(format!(
+            "警告：Steam 记录的游戏目录可能已失效：{}（AppID {}）",
+            name.unwrap_or("未知"),
+            app_id
+        ))
+        .yellow()
+    );
+    println!("路径：{}", path.display());
+    println!(
+        "{}",
+        style("该目录缺少游戏数据文件，游戏可能已被移动到其他位置。若确认无误可继续使用，否则请将本程序放到游戏实际所在的目录中运行。").dim()
+    );
+    println!();
+
+    Ok(())
+}
+
+fn select_game_path_from_candidates(candidates: &[(PathBuf, PathSource)]) -> Result<PathBuf> {
+    println!(
+        "{}",
+        style("找到多个游戏目录，请选择要使用的目录：")
+            .cyan()
+            .bold()
+    );
+    println!();
+    for (i, (path, source)) in candidates.iter().enumerate() {
+        let label = match source {
+            PathSource::Steam(library) => format!("Steam 库 {}", library),
+            PathSource::CurrentDirectory => "当前目录".to_string(),
+            PathSource::Specified => "手动指定".to_string(),
+        };
+        println!(
+            "  {} {}",
+            style(format!("[{}]", i + 1)).green(),
+            path.display()
+        );
+        println!("      {}", style(label).dim());
+    }
+    println!();
+
+    loop {
+        let input = read_input(&format!(" 请选择目录编号（1-{}）", candidates.len()))?;
+
+        match input.trim().parse::<usize>() {
+            Ok(num) if num >= 1 && num <= candidates.len() => {
+                let (path, source) = &candidates[num - 1];
+                report_event("UI.GamePath.Selected", Some(source.as_str()));
+                return Ok(path.clone());
+            }
+            _ => {
+                println!();
+                println!(
+                    "{}",
+                    style(format!(
+                        "无效的输入，请输入 1 到 {} 之间的数字",
+                        candidates.len()
+                    ))
+                    .yellow()
+                );
+            }
+        }
+    }
+}
+
+fn path_ask_manual_entry() -> Result<Option<PathBuf>> {
+    println!("{}", style("未能自动找到游戏目录。").yellow());
+    println!("可以手动输入游戏所在目录，或 Steam 库目录（例如 D:\\SteamLibrary）。");
+    println!();
+
+    let choice = confirm(" 是否手动指定目录？", true)?;
+
+    report_event(
+        "UI.ManualPath.Choice",
+        Some(if choice { "yes" } else { "no" }),
+    );
+
+    if !choice {
+        return Ok(None);
+    }
+
+    let input = read_input(" 请输入目录路径")?;
+    let trimmed = input.trim().trim_matches('"');
+
+    Ok(Some(PathBuf::from(trimmed)))
+}
+
+fn path_manual_entry_not_found(input: &Path) -> Result<()> {
+    println!(
+        "{}",
+        style(format!(
+            "未在 {} 中找到游戏（{}），请重新输入。",
+            input.display(),
+            GAME_EXECUTABLE
+        ))
+        .red()
+    );
+    println!();
+    Ok(())
+}
+
+fn warn_conflicting_game_exe(extras: &[PathBuf]) -> Result<bool> {
+    println!("{}", style("警告：游戏目录中还有以下其他程序：").yellow());
+    for path in extras {
+        println!(
+            "  - {}",
+            path.file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.display().to_string())
+        );
+    }
+    println!(
+        "{}",
+        style(format!(
+            "请确认这是《{}》的目录，而不是备份副本或其他游戏的目录。",
+            GAME_EXECUTABLE.trim_end_matches(".exe")
+        ))
+        .dim()
+    );
+
+    let choice = confirm(" 是否确认在此目录中继续？", true)?;
+    println!();
+
+    report_event(
+        "UI.ConflictingExe.Choice",
+        Some(if choice { "yes" } else { "no" }),
+    );
+
+    Ok(choice)
+}
+
 fn path_confirm_use_steam_found() -> Result<bool> {
-    let confirm = Confirm::with_theme(&ColorfulTheme::default())
-        .with_prompt(" 是否将此路径作为运行目录并继续？")
-        .default(true)
-        .interact_on_opt(&Term::stdout())?;
-    let choice = confirm.unwrap_or(false);
+    let choice = confirm(" 是否将此路径作为运行目录并继续？", true)?;
 
     report_event(
         "UI.SteamPath.Choice",
@@ -615,6 +1600,102 @@ fn path_confirm_use_steam_found() -> Result<bool> {
 
 // ==================== 安装相关 UI ====================
 
+fn install_display_estimated_download_size(estimate: &DownloadSizeEstimate) -> Result<()> {
+    let text = if estimate.has_exact || estimate.min_bytes == 0 {
+        format!("预计下载：约 {}", format_size(estimate.max_bytes))
+    } else {
+        format!(
+            "预计下载：约 {} - {}",
+            format_size(estimate.min_bytes),
+            format_size(estimate.max_bytes)
+        )
+    };
+
+    println!("{}", style(text).dim());
+    Ok(())
+}
+
+fn install_display_download_sources(result: &PrefetchResult) -> Result<()> {
+    println!();
+    println!("下载源检查：");
+    for (name, size) in &result.available {
+        let size = size
+            .map(format_size)
+            .unwrap_or_else(|| "大小未知".to_string());
+        println!(
+            "  {} {} {}",
+            style(glyph("✔", "OK")).green(),
+            name,
+            style(format!("（{}）", size)).dim()
+        );
+    }
+    for (name, reason) in &result.unavailable {
+        println!(
+            "  {} {} {}",
+            style(glyph("✗", "X ")).red(),
+            name,
+            style(format!("（{}）", reason)).dim()
+        );
+    }
+
+    if !result.unavailable.is_empty() {
+        println!(
+            "{}",
+            style("部分下载源暂时不可用，下载时将自动改用其他下载源。").yellow()
+        );
+    }
+
+    Ok(())
+}
+
+fn install_display_estimated_duration(estimated_secs: u64) -> Result<()> {
+    let text = if estimated_secs < 60 {
+        "预计耗时：不到 1 分钟（取决于网络速度）".to_string()
+    } else {
+        let low = estimated_secs / 60;
+        let high = (estimated_secs * 3 / 2).div_ceil(60).max(low + 1);
+        format!("预计耗时：{}-{} 分钟（取决于网络速度）", low, high)
+    };
+
+    println!("{}", style(text).dim());
+    println!();
+    Ok(())
+}
+
+fn warn_cloud_sync_directory(provider: &str) -> Result<()> {
+    println!(
+        "{}",
+        style(format!("警告：游戏目录位于 {} 的同步目录中。", provider)).yellow()
+    );
+    println!(
+        "{}",
+        style("同步客户端可能占用文件导致安装或卸载失败，也可能将已部署的文件还原为旧版本。").dim()
+    );
+    println!(
+        "{}",
+        style("建议在安装、升级或卸载期间暂停同步，或将游戏移出同步目录。").dim()
+    );
+    println!();
+    Ok(())
+}
+
+fn install_warn_slow_storage(estimated_extra_mins: u64) -> Result<()> {
+    println!(
+        "{}",
+        style(format!(
+            "警告：游戏所在磁盘写入速度较慢，解压与安装可能额外需要约 {} 分钟。",
+            estimated_extra_mins.max(1)
+        ))
+        .yellow()
+    );
+    println!(
+        "{}",
+        style("建议将游戏移动到固态硬盘（SSD）上以获得更快的安装与游戏加载速度。").dim()
+    );
+    println!();
+    Ok(())
+}
+
 fn install_display_step(step: usize, description: &str) -> Result<()> {
     println!();
     println!(
@@ -648,6 +1729,7 @@ fn install_warn_existing(
     bepinex_installed: bool,
     metamystia_installed: bool,
     resourceex_installed: bool,
+    cleanup_plan: &[PathBuf],
 ) -> Result<()> {
     println!();
     println!("{}", style("警告：检测到已安装的组件").yellow());
@@ -665,20 +1747,26 @@ fn install_warn_existing(
 
     println!();
     println!("继续安装将会执行以下操作：");
-    println!("  • 覆盖 BepInEx 框架相关文件（不包含 plugins 文件夹）");
+    println!(
+        "  • 覆盖 BepInEx 框架相关文件（保留 {} 文件夹，仅重置 config/BepInEx.cfg）",
+        PRESERVED_BEPINEX_DIRS.join("、")
+    );
     println!("  • 覆盖 MetaMystia 相关文件");
     println!("  • 安装最新版本的 BepInEx 和 MetaMystia 相关文件");
+    if !cleanup_plan.is_empty() {
+        println!(
+            "  • 删除 {} 项现有文件，例如：",
+            style(cleanup_plan.len()).yellow()
+        );
+        print_cleanup_targets(cleanup_plan);
+    }
     println!();
 
     Ok(())
 }
 
 fn install_confirm_overwrite() -> Result<bool> {
-    let confirm = Confirm::with_theme(&ColorfulTheme::default())
-        .with_prompt(" 是否继续安装？")
-        .default(false)
-        .interact_on_opt(&Term::stdout())?;
-    let choice = confirm.unwrap_or(false);
+    let choice = confirm(" 是否继续安装？", false)?;
 
     report_event(
         "UI.Install.Confirm",
@@ -688,6 +1776,42 @@ fn install_confirm_overwrite() -> Result<bool> {
     Ok(choice)
 }
 
+fn install_ask_overwrite_component(name: &str) -> Result<bool> {
+    confirm(
+        &format!(" 是否覆盖已安装的 {}？（选择否将保留现有版本）", name),
+        true,
+    )
+}
+
+fn install_select_overwrite(
+    bepinex_installed: bool,
+    metamystia_installed: bool,
+    resourceex_installed: bool,
+) -> Result<OverwriteSelection> {
+    println!();
+
+    let mut selection = OverwriteSelection::all();
+    if bepinex_installed {
+        selection.bepinex = install_ask_overwrite_component("BepInEx 框架")?;
+    }
+    if metamystia_installed {
+        selection.metamystia = install_ask_overwrite_component("MetaMystia DLL")?;
+    }
+    if resourceex_installed {
+        selection.resourceex = install_ask_overwrite_component("ResourceExample ZIP")?;
+    }
+
+    report_event(
+        "UI.Install.Overwrite.Selection",
+        Some(&format!(
+            "bepinex={};metamystia={};resourceex={}",
+            selection.bepinex, selection.metamystia, selection.resourceex
+        )),
+    );
+
+    Ok(selection)
+}
+
 fn install_ask_install_resourceex() -> Result<bool> {
     println!();
     println!(
@@ -698,11 +1822,7 @@ fn install_ask_install_resourceex() -> Result<bool> {
     println!("更多介绍：https://doc.meta-mystia.izakaya.cc/resource_ex/use_resource-ex.html");
     println!();
 
-    let confirm = Confirm::with_theme(&ColorfulTheme::default())
-        .with_prompt(" 是否安装 ResourceExample ZIP？")
-        .default(true)
-        .interact_on_opt(&Term::stdout())?;
-    let choice = confirm.unwrap_or(false);
+    let choice = confirm(" 是否安装 ResourceExample ZIP？", true)?;
 
     report_event(
         "UI.Install.ResourceEx.Choice",
@@ -715,11 +1835,10 @@ fn install_ask_install_resourceex() -> Result<bool> {
 fn install_ask_show_bepinex_console() -> Result<bool> {
     println!();
 
-    let confirm = Confirm::with_theme(&ColorfulTheme::default())
-        .with_prompt(" 是否在游戏启动时弹出 BepInEx 的控制台窗口用于显示日志？")
-        .default(false)
-        .interact_on_opt(&Term::stdout())?;
-    let choice = confirm.unwrap_or(false);
+    let choice = confirm(
+        " 是否在游戏启动时弹出 BepInEx 的控制台窗口用于显示日志？",
+        false,
+    )?;
 
     report_event(
         "UI.Install.BepInExConsole.Choice",
@@ -729,6 +1848,140 @@ fn install_ask_show_bepinex_console() -> Result<bool> {
     Ok(choice)
 }
 
+fn install_ask_configure_advanced_options() -> Result<bool> {
+    println!();
+
+    let choice = confirm(
+        " 是否配置高级选项（日志级别、指定 ResourceExample 版本、安装前备份）？",
+        false,
+    )?;
+
+    report_event(
+        "UI.Install.Advanced.Choice",
+        Some(if choice { "yes" } else { "no" }),
+    );
+
+    Ok(choice)
+}
+
+fn install_select_bepinex_log_levels() -> Result<Vec<String>> {
+    println!();
+
+    let defaults: Vec<bool> = BEPINEX_LOG_LEVELS
+        .iter()
+        .map(|l| BEPINEX_DEFAULT_LOG_LEVELS.contains(l))
+        .collect();
+    let selected = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt(" 选择 BepInEx 控制台显示的日志级别（空格切换，回车确认）")
+        .items(BEPINEX_LOG_LEVELS)
+        .defaults(&defaults)
+        .interact_on_opt(&Term::stdout())?;
+
+    let levels: Vec<String> = match selected {
+        Some(indices) => indices
+            .into_iter()
+            .map(|i| BEPINEX_LOG_LEVELS[i].to_string())
+            .collect(),
+        None => BEPINEX_DEFAULT_LOG_LEVELS
+            .iter()
+            .map(|l| l.to_string())
+            .collect(),
+    };
+
+    report_event("UI.Install.Advanced.LogLevels", Some(&levels.join(",")));
+
+    Ok(levels)
+}
+
+fn install_ask_backup_before_install() -> Result<bool> {
+    let choice = confirm(" 是否在清理旧文件前将其打包为撤销存档？", false)?;
+
+    report_event(
+        "UI.Install.Advanced.Backup",
+        Some(if choice { "yes" } else { "no" }),
+    );
+
+    Ok(choice)
+}
+
+/// 列出前几项路径，其余仅显示总数
+fn print_cleanup_targets(targets: &[PathBuf]) {
+    for path in targets.iter().take(PLAN_MAX_LISTED_TARGETS) {
+        println!("    {}", style(path.display()).dim());
+    }
+    if targets.len() > PLAN_MAX_LISTED_TARGETS {
+        println!(
+            "    {}",
+            style(format!("……等共 {} 项", targets.len())).dim()
+        );
+    }
+}
+
+fn install_display_plan(plan: &InstallPlan) -> Result<bool> {
+    let version_or = |v: &Option<String>, none: &str| match v {
+        Some(v) => style(v.clone()).green().to_string(),
+        None => style(none.to_string()).dim().to_string(),
+    };
+
+    println!();
+    println!("{}", style("安装计划：").cyan().bold());
+    println!();
+    println!(
+        "  BepInEx：{}",
+        version_or(&plan.bepinex_version, "保留已有版本")
+    );
+    println!(
+        "  MetaMystia DLL：{}",
+        version_or(&plan.dll_version, "保留已有版本")
+    );
+    println!(
+        "  ResourceExample ZIP：{}",
+        version_or(&plan.resourceex_version, "不安装")
+    );
+    println!(
+        "  BepInEx 控制台窗口：{}",
+        if plan.show_bepinex_console {
+            "显示"
+        } else {
+            "不显示"
+        }
+    );
+
+    if !plan.overwrite.is_empty() {
+        println!();
+        println!(
+            "  将覆盖已安装的组件：{}",
+            style(plan.overwrite.join("、")).yellow()
+        );
+    }
+
+    if !plan.cleanup_targets.is_empty() {
+        println!(
+            "  安装前将清理 {} 项旧文件，例如：",
+            style(plan.cleanup_targets.len()).yellow()
+        );
+        print_cleanup_targets(&plan.cleanup_targets);
+    }
+
+    if !plan.misplaced_resourceex.is_empty() {
+        println!(
+            "  将移动 {} 个 ResourceExample ZIP 到 ResourceEx 文件夹：",
+            style(plan.misplaced_resourceex.len()).yellow()
+        );
+        print_cleanup_targets(&plan.misplaced_resourceex);
+    }
+    println!();
+
+    let choice = confirm(" 确认按以上计划开始安装？", true)?;
+
+    report_event(
+        "UI.Install.Plan.Choice",
+        Some(if choice { "yes" } else { "no" }),
+    );
+
+    Ok(choice)
+}
+
 fn install_downloads_completed() -> Result<()> {
     println!("所有文件下载完成");
     Ok(())
@@ -740,6 +1993,22 @@ fn install_start_cleanup() -> Result<()> {
     Ok(())
 }
 
+fn install_display_cleanup_detail(items: &[DeletionResult]) -> Result<()> {
+    println!();
+    println!("清理明细：");
+    for item in items {
+        let path = item.path.display().to_string();
+        match &item.status {
+            DeletionStatus::Success => println!("{}", deletion_success_line(&path)),
+            DeletionStatus::Failed(e) => {
+                println!("{}", deletion_failure_line(&path, &e.to_string()))
+            }
+            DeletionStatus::Skipped => println!("{}", deletion_skipped_line(&path)),
+        }
+    }
+    Ok(())
+}
+
 fn install_cleanup_result(success: usize, failed: usize) -> Result<()> {
     if failed > 0 {
         println!("旧版本删除完成（成功：{}，失败：{}）", success, failed);
@@ -774,6 +2043,105 @@ fn install_finished(show_bepinex_console: bool) -> Result<()> {
     Ok(())
 }
 
+fn display_first_time_bepinex_info() -> Result<()> {
+    println!();
+    let expand = confirm(" 是否查看首次启动耗时较长的详细说明？", false)?;
+
+    report_event(
+        "UI.Install.FirstLaunchInfo.Choice",
+        Some(if expand { "yes" } else { "no" }),
+    );
+
+    if !expand {
+        return Ok(());
+    }
+
+    println!();
+    println!("{}", style("关于首次启动：").cyan().bold());
+    println!(
+        "  1. BepInEx 首次运行时需要为游戏生成 IL2CPP 互操作程序集，配置较低的电脑上可能需要 10 分钟以上。"
+    );
+    println!(
+        "  2. 未开启 BepInEx 控制台时，生成过程中不会出现任何窗口，请不要重复启动游戏或强制结束进程。"
+    );
+    println!("  3. 生成的程序集会被缓存，之后的启动速度将恢复正常。");
+
+    Ok(())
+}
+
+fn warn_bepinex_il2cpp_download(source_url: &str) -> Result<()> {
+    println!();
+    println!(
+        "{}",
+        style("提示：首次启动游戏时，BepInEx 还需要额外下载 Unity 基础库（约 50-300 MB）。")
+            .yellow()
+    );
+    println!(
+        "{}",
+        style("下载期间游戏看起来像是卡住了，请保持网络连接并耐心等待。").yellow()
+    );
+    println!("下载地址：{}", style(source_url).underlined());
+
+    Ok(())
+}
+
+fn install_warn_loader_quarantined(path: &Path, detection: Option<&str>) -> Result<()> {
+    println!();
+    println!(
+        "{}",
+        style("警告：BepInEx 加载器在安装后被移除，很可能已被杀毒软件隔离！")
+            .red()
+            .bold()
+    );
+    if let Some(name) = detection {
+        println!("Windows Defender 检测名称：{}", style(name).yellow());
+    }
+    println!("缺少该文件时 Mod 将不会加载。请在杀毒软件中将以下路径添加为排除项，然后重新安装：");
+    println!("  {}", style(path.display()).cyan());
+    if let Some(parent) = path.parent() {
+        println!("或排除整个游戏目录：");
+        println!("  {}", style(parent.display()).cyan());
+    }
+
+    Ok(())
+}
+
+// ==================== ResourceEx 迁移相关 UI ====================
+
+fn resourceex_display_misplaced(files: &[PathBuf]) -> Result<()> {
+    println!();
+    println!(
+        "{}",
+        style("检测到放置在错误位置的 ResourceExample ZIP：").yellow()
+    );
+    for file in files {
+        println!("  • {}", file.display());
+    }
+    println!("这些文件需要位于 ResourceEx 文件夹中才能生效。");
+    println!();
+
+    Ok(())
+}
+
+fn resourceex_confirm_migrate() -> Result<bool> {
+    let choice = confirm(" 是否将其移动到 ResourceEx 文件夹？", true)?;
+
+    report_event(
+        "UI.MigrateResourceEx.Choice",
+        Some(if choice { "yes" } else { "no" }),
+    );
+
+    Ok(choice)
+}
+
+fn resourceex_migrate_failed(path: &Path, err: &str) -> Result<()> {
+    println!(
+        "{}",
+        style(format!("移动 {} 失败：{}", path.display(), err)).yellow()
+    );
+    Ok(())
+}
+
 // ==================== 升级相关 UI ====================
 
 fn upgrade_warn_unparse_version(filename: &str) -> Result<()> {
@@ -781,6 +2149,18 @@ fn upgrade_warn_unparse_version(filename: &str) -> Result<()> {
     Ok(())
 }
 
+fn upgrade_found_duplicate(path: &Path) -> Result<()> {
+    println!(
+        "{}",
+        style(format!(
+            "发现重复的 MetaMystia DLL，将移至备份：{}",
+            path.display()
+        ))
+        .yellow()
+    );
+    Ok(())
+}
+
 fn upgrade_backup_failed(err: &str) -> Result<()> {
     println!("{}", style(format!("备份失败：{}", err)).yellow());
     Ok(())
@@ -819,7 +2199,7 @@ fn upgrade_display_current_and_latest_dll(current: &str, latest: &str) -> Result
 
 fn upgrade_no_update_needed() -> Result<()> {
     println!();
-    println!("✔  已是最新版本，无需升级！");
+    println!("{}  已是最新版本，无需升级！", glyph("✔", "[OK]"));
     Ok(())
 }
 
@@ -838,7 +2218,33 @@ fn upgrade_dll_already_latest() -> Result<()> {
 fn upgrade_resourceex_needs_upgrade() -> Result<()> {
     println!("ResourceExample ZIP 需要升级");
     println!();
-    Ok(())
+    Ok(())
+}
+
+fn confirm_batch_upgrade(updates: &[PendingUpgrade]) -> Result<bool> {
+    let width = updates.iter().map(|u| u.component.len()).max().unwrap_or(0);
+
+    println!("{}", style("即将进行以下升级：").cyan().bold());
+    println!();
+    for u in updates {
+        println!(
+            "  {:<width$}  {} → {}",
+            u.component,
+            style(&u.from_version).dim(),
+            style(&u.to_version).green(),
+            width = width
+        );
+    }
+    println!();
+
+    let choice = confirm(" 是否开始下载并升级？", true)?;
+
+    report_event(
+        "UI.Upgrade.BatchConfirm.Choice",
+        Some(if choice { "yes" } else { "no" }),
+    );
+
+    Ok(choice)
 }
 
 fn upgrade_downloading_dll() -> Result<()> {
@@ -878,7 +2284,7 @@ fn upgrade_cleanup_start() -> Result<()> {
 
 fn upgrade_done() -> Result<()> {
     println!();
-    println!("✔  升级完成！");
+    println!("{}  升级完成！", glyph("✔", "[OK]"));
     Ok(())
 }
 
@@ -888,6 +2294,87 @@ fn upgrade_display_current_and_latest_resourceex(current: &str, latest: &str) ->
     Ok(())
 }
 
+// ==================== 启动游戏相关 UI ====================
+
+fn ask_launch_game() -> Result<bool> {
+    println!();
+    let choice = confirm(" 是否立即启动游戏？", true)?;
+
+    report_event(
+        "UI.LaunchGame.Choice",
+        Some(if choice { "yes" } else { "no" }),
+    );
+
+    Ok(choice)
+}
+
+fn launch_game_failed(err: &str) -> Result<()> {
+    println!("{}", style(format!("启动游戏失败：{}", err)).yellow());
+    println!("请手动启动游戏。");
+    Ok(())
+}
+
+// ==================== 使用文档相关 UI ====================
+
+fn ask_open_docs() -> Result<bool> {
+    println!();
+    let choice = confirm(" 是否在浏览器中打开使用文档？", false)?;
+
+    report_event(
+        "UI.OpenDocs.Choice",
+        Some(if choice { "yes" } else { "no" }),
+    );
+
+    Ok(choice)
+}
+
+fn open_docs_failed(url: &str) -> Result<()> {
+    println!("{}", style("无法打开浏览器，请手动访问：").yellow());
+    println!("  {}", url);
+    Ok(())
+}
+
+// ==================== 设置相关 UI ====================
+
+fn on_off(enabled: bool) -> &'static str {
+    if enabled { "开启" } else { "关闭" }
+}
+
+fn configure_ask_bepinex_console(current: bool) -> Result<bool> {
+    println!();
+    println!(
+        "当前 BepInEx 控制台窗口：{}",
+        style(on_off(current)).green()
+    );
+
+    let choice = confirm(
+        " 是否在游戏启动时弹出 BepInEx 的控制台窗口用于显示日志？",
+        current,
+    )?;
+
+    report_event(
+        "UI.Configure.BepInExConsole.Choice",
+        Some(if choice { "yes" } else { "no" }),
+    );
+
+    Ok(choice)
+}
+
+fn configure_bepinex_console_changed(previous: bool, enabled: bool) -> Result<()> {
+    println!();
+    if previous == enabled {
+        println!("BepInEx 控制台窗口保持{}，无需修改。", on_off(enabled));
+    } else {
+        println!(
+            "BepInEx 控制台窗口：{} → {}",
+            on_off(previous),
+            style(on_off(enabled)).green()
+        );
+        println!("原配置已备份为 BepInEx.cfg.old，重新启动游戏后生效。");
+    }
+    Ok(())
+}
+
 // ==================== 卸载相关 UI ====================
 
 fn uninstall_select_uninstall_mode() -> Result<UninstallMode> {
@@ -904,23 +2391,27 @@ fn uninstall_select_uninstall_mode() -> Result<UninstallMode> {
         style("[2]").green(),
         UninstallMode::Full.description()
     );
+    println!(
+        "  {} {}",
+        style("[3]").green(),
+        UninstallMode::BepInExOnly.description()
+    );
     println!("  {} 退出程序", style("[0]").dim());
     println!();
 
     loop {
-        let input: String = Input::with_theme(&ColorfulTheme::default())
-            .with_prompt(" 请输入选项")
-            .interact_text()?;
+        let input = read_input(" 请输入选项")?;
 
         match input.trim() {
             "1" => return Ok(UninstallMode::Light),
             "2" => return Ok(UninstallMode::Full),
+            "3" => return Ok(UninstallMode::BepInExOnly),
             "0" => {
                 return Err(ManagerError::UserCancelled);
             }
             _ => {
                 println!();
-                println!("{}", style("无效的选项，请输入 0、1 或 2").yellow());
+                println!("{}", style("无效的选项，请输入 0、1、2 或 3").yellow());
                 continue;
             }
         }
@@ -938,9 +2429,20 @@ fn uninstall_display_target_files(files: &[PathBuf]) -> Result<()> {
     println!("{}", style("即将删除以下文件/文件夹：").yellow().bold());
     println!();
 
+    // 为前缀“  • 📁 ”（ASCII 时为“  • [D] ”）留出宽度
+    let path_width = layout_width().saturating_sub(8);
     for file in files {
-        let file_type = if file.is_dir() { "📁" } else { "📄" };
-        println!("  {} {} {}", style("•").cyan(), file_type, file.display());
+        let file_type = if file.is_dir() {
+            glyph("📁", "[D]")
+        } else {
+            glyph("📄", "[F]")
+        };
+        println!(
+            "  {} {} {}",
+            style("•").cyan(),
+            file_type,
+            truncate_middle(&file.display().to_string(), path_width)
+        );
     }
 
     println!();
@@ -949,11 +2451,7 @@ fn uninstall_display_target_files(files: &[PathBuf]) -> Result<()> {
 }
 
 fn uninstall_confirm_deletion() -> Result<bool> {
-    let confirm = Confirm::with_theme(&ColorfulTheme::default())
-        .with_prompt(" 是否继续当前操作？")
-        .default(false)
-        .interact_on_opt(&Term::stdout())?;
-    let choice = confirm.unwrap_or(false);
+    let choice = confirm(" 是否继续当前操作？", false)?;
 
     report_event(
         "UI.Uninstall.Confirm.Choice",
@@ -963,6 +2461,53 @@ fn uninstall_confirm_deletion() -> Result<bool> {
     Ok(choice)
 }
 
+fn uninstall_ask_create_undo_archive(estimated_size: u64) -> Result<bool> {
+    println!();
+    println!(
+        "可在删除前将上述文件打包为撤销存档，以便之后恢复（预计占用 {}）。",
+        format_size(estimated_size)
+    );
+
+    let choice = confirm(" 是否创建撤销存档？", false)?;
+
+    report_event(
+        "UI.Uninstall.UndoArchive.Choice",
+        Some(if choice { "yes" } else { "no" }),
+    );
+
+    Ok(choice)
+}
+
+fn uninstall_undo_archive_insufficient_space(required: u64, available: u64) -> Result<()> {
+    println!();
+    println!(
+        "{}",
+        style(format!(
+            "磁盘空间不足，无法创建撤销存档（需要 {}，可用 {}）。",
+            format_size(required),
+            format_size(available)
+        ))
+        .yellow()
+    );
+    Ok(())
+}
+
+fn uninstall_undo_archive_created(path: &Path) -> Result<()> {
+    println!();
+    println!("撤销存档已保存至：{}", style(path.display()).green());
+    Ok(())
+}
+
+fn restore_undo_finished(archive: &Path, count: usize) -> Result<()> {
+    println!();
+    println!(
+        "已从 {} 恢复 {} 个文件。",
+        archive.display(),
+        style(count).green()
+    );
+    Ok(())
+}
+
 fn uninstall_files_in_use_warning() -> Result<()> {
     println!();
     println!(
@@ -981,6 +2526,56 @@ fn uninstall_wait_before_retry(delay_secs: u64, attempt: usize, attempts: usize)
     Ok(())
 }
 
+/// 非阻塞读取一次按键（UTF-16 字符，可读取输入法输入的中文），没有按键时返回 None
+fn poll_key() -> Option<i32> {
+    unsafe extern "C" {
+        fn _kbhit() -> i32;
+        fn _getwch() -> u16;
+    }
+
+    unsafe {
+        if _kbhit() == 0 {
+            return None;
+        }
+
+        let key = i32::from(_getwch());
+        // 功能键与方向键会产生两个字符，第二个字符一并读出丢弃
+        if key == 0 || key == 0xe0 {
+            _getwch();
+        }
+        Some(key)
+    }
+}
+
+fn uninstall_wait_or_skip(delay_secs: u64) -> Result<WaitOutcome> {
+    println!("{}", style("按任意键立即重试，按 Esc 放弃重试。").dim());
+
+    let deadline = Instant::now() + Duration::from_secs(delay_secs);
+    while Instant::now() < deadline {
+        match poll_key() {
+            Some(KEY_ESC) => {
+                let choice = confirm(" 是否放弃重试被占用的文件？", false)?;
+
+                report_event(
+                    "UI.Uninstall.AbortRetry.Choice",
+                    Some(if choice { "yes" } else { "no" }),
+                );
+
+                if choice {
+                    return Ok(WaitOutcome::Aborted);
+                }
+            }
+            Some(_) => {
+                report_event("UI.Uninstall.WaitSkipped", None);
+                return Ok(WaitOutcome::Skipped);
+            }
+            None => sleep(KEY_POLL_INTERVAL),
+        }
+    }
+
+    Ok(WaitOutcome::Elapsed)
+}
+
 fn uninstall_ask_elevate_permission() -> Result<bool> {
     println!();
     println!(
@@ -989,11 +2584,7 @@ fn uninstall_ask_elevate_permission() -> Result<bool> {
     );
     println!();
 
-    let confirm = Confirm::with_theme(&ColorfulTheme::default())
-        .with_prompt(" 是否以管理员权限重新运行？")
-        .default(false)
-        .interact_on_opt(&Term::stdout())?;
-    let choice = confirm.unwrap_or(false);
+    let choice = confirm(" 是否以管理员权限重新运行？", false)?;
 
     report_event(
         "UI.Uninstall.Elevate.Choice",
@@ -1012,11 +2603,7 @@ fn uninstall_restarting_elevated() -> Result<()> {
 fn uninstall_ask_retry_failures() -> Result<bool> {
     println!();
 
-    let confirm = Confirm::with_theme(&ColorfulTheme::default())
-        .with_prompt(" 是否重试失败的项目？")
-        .default(false)
-        .interact_on_opt(&Term::stdout())?;
-    let choice = confirm.unwrap_or(false);
+    let choice = confirm(" 是否重试失败的项目？", false)?;
 
     report_event(
         "UI.Uninstall.Retry.Choice",
@@ -1032,6 +2619,83 @@ fn uninstall_retrying_failed_items() -> Result<()> {
     Ok(())
 }
 
+fn uninstall_display_disk_space_freed(bytes_freed: u64) -> Result<()> {
+    println!("释放存储空间：{}", style(format_size(bytes_freed)).green());
+    Ok(())
+}
+
+fn uninstall_confirm_take_ownership(paths: &[PathBuf]) -> Result<bool> {
+    println!();
+    println!(
+        "{}",
+        style(
+            "以下项目在管理员权限下仍被拒绝访问，可能属于 TrustedInstaller 或设置了特殊的访问权限："
+        )
+        .yellow()
+    );
+    for path in paths {
+        println!("  - {}", path.display());
+    }
+    println!(
+        "{}",
+        style("可以将其所有者改为 Administrators 组并授予完全控制权限后再删除。").dim()
+    );
+    println!();
+
+    let choice = confirm(" 是否修改这些项目的所有权与访问权限？", false)?;
+
+    report_event(
+        "UI.Uninstall.TakeOwnership.Choice",
+        Some(if choice { "yes" } else { "no" }),
+    );
+
+    Ok(choice)
+}
+
+fn uninstall_display_acl_fix_commands(commands: &[String]) -> Result<()> {
+    println!();
+    println!(
+        "{}",
+        style("仍有项目无法删除，可在以管理员身份运行的命令提示符中执行以下命令后重试：").yellow()
+    );
+    for command in commands {
+        println!("  {}", style(command).cyan());
+    }
+    Ok(())
+}
+
+fn uninstall_display_failure_breakdown(
+    locked: usize,
+    acl_denied: usize,
+    other: usize,
+) -> Result<()> {
+    if locked > 0 {
+        println!("    · 文件被占用：{} 项", locked);
+    }
+    if acl_denied > 0 {
+        println!("    · 权限不足：{} 项", acl_denied);
+    }
+    if other > 0 {
+        println!("    · 其他原因：{} 项", other);
+    }
+    Ok(())
+}
+
+// ==================== 临时目录相关 UI ====================
+
+fn temp_dir_warn_cross_volume(temp_dir: &Path, game_root: &Path) -> Result<()> {
+    println!(
+        "{}",
+        style(format!(
+            "临时目录 {} 与游戏目录 {} 不在同一磁盘，文件将以复制方式放置，耗时可能更长。",
+            temp_dir.display(),
+            game_root.display()
+        ))
+        .yellow()
+    );
+    Ok(())
+}
+
 // ==================== 下载相关 UI ====================
 
 fn download_version_info_start() -> Result<()> {
@@ -1049,6 +2713,14 @@ fn download_version_info_success() -> Result<()> {
     Ok(())
 }
 
+fn download_version_info_cached(cached_at: &str) -> Result<()> {
+    println!(
+        "{}",
+        style(format!("版本信息来自缓存（更新于 {}）", cached_at)).dim()
+    );
+    Ok(())
+}
+
 fn download_version_info_parse_failed(err: &str, snippet: &str) -> Result<()> {
     println!(
         "{}",
@@ -1091,6 +2763,17 @@ fn download_github_dll_not_found() -> Result<()> {
     Ok(())
 }
 
+fn download_github_auth_required() -> Result<()> {
+    println!(
+        "{}",
+        style("已超出 GitHub API 未认证请求配额（每小时 60 次）").yellow()
+    );
+    println!("可以稍后重试，或使用 GitHub 个人访问令牌提高配额：");
+    println!("  1. 打开 https://github.com/settings/tokens 创建令牌（无需勾选任何权限）");
+    println!("  2. 将令牌设置为环境变量 GITHUB_TOKEN 后重新运行本程序");
+    Ok(())
+}
+
 fn download_display_github_release_notes(tag: &str, name: &str, body: &str) -> Result<()> {
     println!();
     println!(
@@ -1102,12 +2785,12 @@ fn download_display_github_release_notes(tag: &str, name: &str, body: &str) -> R
     if trimmed.is_empty() {
         println!("{}", style("（无 Release Notes）").dim());
     } else {
-        println!("{}", "-".repeat(60));
+        println!("{}", separator("-", "-"));
 
         let skin = MadSkin::default();
         skin.print_text(trimmed);
 
-        println!("{}", "-".repeat(60));
+        println!("{}", separator("-", "-"));
     }
 
     Ok(())
@@ -1116,11 +2799,7 @@ fn download_display_github_release_notes(tag: &str, name: &str, body: &str) -> R
 fn download_ask_continue_after_release_notes() -> Result<bool> {
     println!();
 
-    let confirm = Confirm::with_theme(&ColorfulTheme::default())
-        .with_prompt(" 是否继续当前操作？")
-        .default(false)
-        .interact_on_opt(&Term::stdout())?;
-    let choice = confirm.unwrap_or(false);
+    let choice = confirm(" 是否继续当前操作？", false)?;
 
     report_event(
         "UI.Download.GitHubReleaseNotes.Choice",
@@ -1151,33 +2830,109 @@ fn download_bepinex_primary_failed(err: &str) -> Result<()> {
     Ok(())
 }
 
+fn download_all_sources_failed(failures: &[SourceFailure]) -> Result<()> {
+    println!();
+    println!("{}", style("所有下载源均失败：").red().bold());
+    for f in failures {
+        println!(
+            "  • {}（{}）：{}",
+            f.source,
+            style(&f.url).dim(),
+            style(&f.reason).red()
+        );
+    }
+
+    println!();
+    print_network_checklist();
+    println!("如果以上均正常，请稍后重试或更换网络环境。");
+    println!();
+    Ok(())
+}
+
+fn display_download_summary(reports: &[DownloadReport]) -> Result<()> {
+    if reports.is_empty() {
+        return Ok(());
+    }
+
+    let width = reports
+        .iter()
+        .map(|r| measure_text_width(&r.component))
+        .max()
+        .unwrap_or(0);
+
+    println!();
+    println!("{}", style("下载摘要：").cyan().bold());
+    for r in reports {
+        println!(
+            "  {}  {}  {}  {}",
+            pad_str(&r.component, width, Alignment::Left, None),
+            pad_str(r.source.name(), 11, Alignment::Left, None),
+            format_size(r.bytes),
+            style(format!("{:.1} 秒", r.duration.as_secs_f64())).dim()
+        );
+    }
+
+    let summary = reports
+        .iter()
+        .map(|r| format!("{}={}", r.component, r.source.name()))
+        .collect::<Vec<_>>()
+        .join(";");
+    report_event("UI.Download.Summary", Some(&summary));
+
+    Ok(())
+}
+
 // ==================== 删除相关 UI ====================
 
-fn deletion_display_progress(current: usize, total: usize, path: &str) {
-    println!(
+fn deletion_start(total: usize) -> ProgressBar {
+    let pb = ProgressBar::new(total as u64);
+    let style = match ProgressStyle::default_bar().template("[{pos}/{len}] {msg}") {
+        Ok(s) => s,
+        Err(_) => ProgressStyle::default_bar(),
+    };
+    pb.set_style(style);
+    pb
+}
+
+fn deletion_display_progress(pb: &ProgressBar, current: usize, path: &str) {
+    let name = Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string());
+
+    pb.set_position(current as u64);
+    pb.set_message(format!("{}：{}", style("正在删除").cyan(), name));
+}
+
+fn deletion_progress_line(current: usize, total: usize, path: &str) -> String {
+    format!(
         "{} [{}/{}] {}",
         style("正在删除").cyan(),
         current,
         total,
         path
-    );
+    )
 }
 
-fn deletion_display_success(path: &str) {
-    println!("  {} {}", style("✔ ").green(), style(path).dim());
+fn deletion_success_line(path: &str) -> String {
+    format!(
+        "  {} {}",
+        style(glyph("✔ ", "OK")).green(),
+        style(path).dim()
+    )
 }
 
-fn deletion_display_failure(path: &str, error: &str) {
-    println!(
+fn deletion_failure_line(path: &str, error: &str) -> String {
+    format!(
         "  {} {} - {}",
-        style("✗ ").red(),
+        style(glyph("✗ ", "X ")).red(),
         style(path).dim(),
         style(error).red()
-    );
+    )
 }
 
-fn deletion_display_skipped(path: &str) {
-    println!("  {} {}", style("○ ").dim(), style(path).dim());
+fn deletion_skipped_line(path: &str) -> String {
+    format!("  {} {}", style(glyph("○ ", "- ")).dim(), style(path).dim())
 }
 
 fn deletion_display_summary(success_count: usize, failed_count: usize, skipped_count: usize) {
@@ -1187,7 +2942,7 @@ fn deletion_display_summary(success_count: usize, failed_count: usize, skipped_c
     if skipped_count > 0 {
         println!(
             "  {} 跳过：{} 项（文件不存在）",
-            style("○").dim(),
+            style(glyph("○", "-")).dim(),
             style(skipped_count).dim()
         );
     }
@@ -1196,7 +2951,7 @@ fn deletion_display_summary(success_count: usize, failed_count: usize, skipped_c
         println!("  删除失败：{} 项", style(failed_count).red());
     } else {
         println!();
-        println!("✔  卸载完成！");
+        println!("{}  卸载完成！", glyph("✔", "[OK]"));
     }
 }
 
@@ -1237,6 +2992,48 @@ fn network_rate_limited(secs: u64) -> Result<()> {
     Ok(())
 }
 
+/// 诊断信息中 URL 的最大显示长度
+const DIAGNOSTICS_URL_MAX_CHARS: usize = 60;
+
+fn display_network_diagnostics(endpoint: &str, last_error: &str) -> Result<()> {
+    let url = if endpoint.chars().count() > DIAGNOSTICS_URL_MAX_CHARS {
+        let head: String = endpoint
+            .chars()
+            .take(DIAGNOSTICS_URL_MAX_CHARS - 3)
+            .collect();
+        format!("{}...", head)
+    } else {
+        endpoint.to_string()
+    };
+
+    println!();
+    println!("{}", style("网络诊断：多次重试后仍无法连接").red().bold());
+    println!("  请求地址：{}", style(url).dim());
+    println!("  最后错误：{}", style(last_error).red());
+    println!("  系统时间：{}", current_utc_time());
+    println!();
+    print_network_checklist();
+    println!();
+
+    Ok(())
+}
+
+/// 网络故障排查清单（诊断信息与所有下载源失败时共用）
+const NETWORK_CHECKLIST: &[&str] = &[
+    "网络连接是否正常（能否打开其他网页）",
+    "防火墙或安全软件是否拦截了本程序",
+    "系统代理设置是否正确，或暂时关闭代理后重试",
+    "能否正常访问 GitHub（github.com）",
+    "系统时间是否准确（时间偏差会导致 TLS 证书校验失败）",
+];
+
+fn print_network_checklist() {
+    println!("{}", style("请检查：").cyan());
+    for item in NETWORK_CHECKLIST {
+        println!("  • {}", item);
+    }
+}
+
 // ==================== 自升级相关 UI ====================
 
 fn manager_ask_self_update(current_version: &str, latest_version: &str) -> Result<bool> {
@@ -1247,11 +3044,7 @@ fn manager_ask_self_update(current_version: &str, latest_version: &str) -> Resul
     );
     println!();
 
-    let confirm = Confirm::with_theme(&ColorfulTheme::default())
-        .with_prompt(" 是否立即升级？")
-        .default(true)
-        .interact_on_opt(&Term::stdout())?;
-    let choice = confirm.unwrap_or(false);
+    let choice = confirm(" 是否立即升级？", true)?;
 
     report_event(
         "UI.SelfUpdate.Choice",
@@ -1263,6 +3056,15 @@ fn manager_ask_self_update(current_version: &str, latest_version: &str) -> Resul
     Ok(choice)
 }
 
+fn display_self_update_downloaded(path: &Path, version: &str) -> Result<()> {
+    println!();
+    println!(
+        "{}",
+        style(format!("新版本 v{} 已下载到：{}", version, path.display())).green()
+    );
+    Ok(())
+}
+
 fn manager_update_starting() -> Result<()> {
     println!();
     println!("正在启动升级脚本，请稍候...");
@@ -1278,9 +3080,9 @@ fn manager_update_failed(err: &str) -> Result<()> {
     Ok(())
 }
 
-fn manager_prompt_manual_update() -> Result<()> {
+fn manager_prompt_manual_update(reason: &str) -> Result<()> {
     println!();
-    println!("无法向当前运行目录写入文件，请手动下载并升级管理工具。");
+    println!("{}，请手动下载并升级管理工具。", style(reason).yellow());
     println!();
     Ok(())
 }
@@ -1290,11 +3092,7 @@ fn manager_prompt_manual_update() -> Result<()> {
 fn select_version_ask_select(component: &str) -> Result<bool> {
     println!();
 
-    let confirm = Confirm::with_theme(&ColorfulTheme::default())
-        .with_prompt(format!(" 是否需要安装旧版本的 {}？", component))
-        .default(false)
-        .interact_on_opt(&Term::stdout())?;
-    let choice = confirm.unwrap_or(false);
+    let choice = confirm(&format!(" 是否需要安装旧版本的 {}？", component), false)?;
 
     report_event(
         &format!("UI.SelectHistoricalVersion.Choice.{}", component),
@@ -1354,17 +3152,15 @@ fn select_version_from_list(component: &str, versions: &[String]) -> Result<usiz
         }
 
         let current_page_count = end - start;
-        let input: String = Input::with_theme(&ColorfulTheme::default())
-            .with_prompt(format!(
-                " 请选择版本编号（1-{}）{}",
-                current_page_count,
-                if total_pages > 1 {
-                    "，或输入 P（上一页）/ N（下一页）翻页"
-                } else {
-                    ""
-                }
-            ))
-            .interact_text()?;
+        let input = read_input(&format!(
+            " 请选择版本编号（1-{}）{}",
+            current_page_count,
+            if total_pages > 1 {
+                "，或输入 P（上一页）/ N（下一页）翻页"
+            } else {
+                ""
+            }
+        ))?;
 
         let trimmed = input.trim().to_lowercase();
         if trimmed == "n" || trimmed == "next" {
@@ -1433,3 +3229,29 @@ fn select_version_not_available(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn confirm_answer_accepts_yes_variants() {
+        for c in ['是', 'y', 'Y', 'ｙ', 'Ｙ'] {
+            assert_eq!(confirm_answer(c), Some(true), "{}", c);
+        }
+    }
+
+    #[test]
+    fn confirm_answer_accepts_no_variants() {
+        for c in ['否', 'n', 'N', 'ｎ', 'Ｎ'] {
+            assert_eq!(confirm_answer(c), Some(false), "{}", c);
+        }
+    }
+
+    #[test]
+    fn confirm_answer_ignores_other_keys() {
+        for c in ['1', '0', 'x', ' ', '好', '不', 'ｘ', '\r', '\u{1b}'] {
+            assert_eq!(confirm_answer(c), None, "{:?}", c);
+        }
+    }
+}
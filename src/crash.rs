@@ -0,0 +1,130 @@
+use crate::file_ops::write_file_atomic;
+use crate::metrics::report_event;
+use crate::shutdown::run_shutdown;
+
+use std::backtrace::Backtrace;
+use std::panic::{AssertUnwindSafe, PanicHookInfo, catch_unwind};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    let payload = if let Some(s) = info.payload().downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = info.payload().downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "未知错误".to_string()
+    };
+
+    match info.location() {
+        Some(loc) => format!("{}（{}:{}）", payload, loc.file(), loc.line()),
+        None => payload,
+    }
+}
+
+fn crash_log_path() -> Option<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+
+    Some(dir.join(format!("meta-mystia-crash-{}.log", timestamp)))
+}
+
+fn write_crash_log(message: &str) -> Option<PathBuf> {
+    let path = crash_log_path()?;
+    let content = format!(
+        "{} v{}\n\n{}\n\n{}\n",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        message,
+        Backtrace::force_capture()
+    );
+
+    write_file_atomic(&path, content.as_bytes()).ok()?;
+
+    Some(path)
+}
+
+/// 安装 panic 钩子：执行清理、写入崩溃日志并提示用户
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        // 钩子内部不能再次 panic，所有步骤都尽力而为
+        let message = panic_message(info);
+        let log_path = catch_unwind(AssertUnwindSafe(|| write_crash_log(&message)))
+            .ok()
+            .flatten();
+
+        let _ = catch_unwind(AssertUnwindSafe(|| {
+            report_event("Crash", Some(&message));
+            run_shutdown();
+        }));
+
+        if cfg!(debug_assertions) {
+            default_hook(info);
+        }
+
+        eprintln!();
+        eprintln!("程序发生意外错误：{}", message);
+        match log_path {
+            Some(path) => eprintln!("崩溃日志已保存至：{}", path.display()),
+            None => eprintln!("崩溃日志写入失败。"),
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shutdown::register_cleanup;
+    use crate::test_utils::ScratchDir;
+
+    use std::process::Command;
+
+    /// 设置该环境变量时，测试在子进程中安装钩子并触发 panic
+    const CHILD_ENV: &str = "META_MYSTIA_CRASH_TEST_MARKER";
+    const CHILD_PANIC_MESSAGE: &str = "受控的测试 panic";
+
+    #[test]
+    fn panic_hook_cleans_up_and_writes_crash_log() {
+        if let Some(marker) = std::env::var_os(CHILD_ENV) {
+            install_panic_hook();
+            let _handle = register_cleanup(move || {
+                let _ = std::fs::write(&marker, b"cleaned");
+            });
+            panic!("{}", CHILD_PANIC_MESSAGE);
+        }
+
+        let dir = ScratchDir::new("crash");
+        let marker = dir.path().join("cleanup-ran");
+        let output = Command::new(std::env::current_exe().unwrap())
+            .args([
+                "--exact",
+                "crash::tests::panic_hook_cleans_up_and_writes_crash_log",
+                "--nocapture",
+                "--test-threads=1",
+            ])
+            .env(CHILD_ENV, &marker)
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        assert!(!output.status.success());
+        assert!(stderr.contains(&format!("程序发生意外错误：{}", CHILD_PANIC_MESSAGE)));
+        assert!(marker.exists(), "清理回调未执行");
+
+        let log_path = stderr
+            .lines()
+            .find_map(|l| l.trim().strip_prefix("崩溃日志已保存至："))
+            .map(PathBuf::from)
+            .expect("未提示崩溃日志位置");
+        let log = std::fs::read_to_string(&log_path).unwrap_or_default();
+        let _ = std::fs::remove_file(&log_path);
+
+        assert!(log.contains(CHILD_PANIC_MESSAGE));
+        assert!(log.contains(env!("CARGO_PKG_VERSION")));
+    }
+}
@@ -1,18 +1,31 @@
-use crate::error::{ManagerError, Result};
-use crate::file_ops::atomic_rename_or_copy;
+use crate::config::{
+    DEFAULT_DOWNLOAD_SPEED_KBPS, ESTIMATED_BEPINEX_SIZE, ESTIMATED_DLL_SIZE,
+    ESTIMATED_RESOURCEEX_SIZE, RetryProfile,
+};
+use crate::error::{ManagerError, Result, SourceFailure};
+use crate::extractor::Extractor;
+use crate::file_ops::{atomic_rename_or_copy, write_file_atomic};
 use crate::metrics::report_event;
 use crate::model::VersionInfo;
-use crate::net::{get_json_with_retry, get_response_with_retry, with_retry};
+use crate::net::{
+    HttpClient, RequestDeduplicator, format_utc_time, get_json_with_retry, load_client_identity,
+    map_send_error, with_retry,
+};
+use crate::prefs::{Preferences, data_dir};
 use crate::ui::Ui;
+use crate::updater::update_channel;
 
 use percent_encoding::{NON_ALPHANUMERIC, percent_encode};
+use reqwest::StatusCode;
 use reqwest::blocking::{Client, ClientBuilder};
+use reqwest::header::CONTENT_LENGTH;
 use std::cmp;
-use std::io::{Read, Write};
-use std::path::Path;
-use std::sync::Mutex;
+use std::io::{BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
 use std::thread::sleep;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 const FILE_API: &str = "https://file.izakaya.cc/api/public/dl";
 const REDIRECT_URL: &str = "https://url.izakaya.cc/getMetaMystia";
@@ -20,48 +33,530 @@ const VERSION_API: &str = "https://api.izakaya.cc/version/meta-mystia";
 
 const BEPINEX_PRIMARY: &str = "https://builds.bepinex.dev/projects/bepinex_be";
 const GITHUB_API_URL: &str = "https://api.github.com/repos/MetaMikuAI/MetaMystia/releases/latest";
+const MANAGER_GITHUB_API_URL: &str =
+    "https://api.github.com/repos/AnYiEE/meta-mystia-manager/releases/latest";
+/// GitHub API 配额查询接口（不消耗配额，无需认证）
+const GITHUB_RATE_LIMIT_URL: &str = "https://api.github.com/rate_limit";
+/// GitHub API 配额查询结果的缓存时长
+const GITHUB_RATE_LIMIT_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// 版本信息磁盘缓存文件名（位于管理器数据目录）
+const VERSION_CACHE_FILE_NAME: &str = "version-cache.json";
+
+/// 内置的版本信息（每次发布管理工具时更新），仅在无网络且无缓存时使用
+const FALLBACK_VERSION_INFO: &str = include_str!("fallback_version.json");
+
+/// GitHub 个人访问令牌的环境变量名（仅用于 GitHub API 请求）
+const GITHUB_TOKEN_ENV: &str = "GITHUB_TOKEN";
 
 const RATE_LIMIT: usize = 128 * 1024; // 128KB/s
 const CONNECT_TIMEOUT: Duration = Duration::from_secs(5); // 连接超时
 
+/// 下载进度更新的最短间隔与最小字节数（满足其一即更新）
+const PROGRESS_UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+const PROGRESS_UPDATE_BYTES: u64 = 256 * 1024;
+
+/// 实际提供文件的下载源
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DownloadSource {
+    GitHub,
+    BepInExPrimary,
+    Mirror,
+}
+
+impl DownloadSource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DownloadSource::GitHub => "GitHub",
+            DownloadSource::BepInExPrimary => "bepinex.dev",
+            DownloadSource::Mirror => "备用源",
+        }
+    }
+
+    /// 下载源标识（用于 JSON 输出）
+    pub fn id(&self) -> &'static str {
+        match self {
+            DownloadSource::GitHub => "github",
+            DownloadSource::BepInExPrimary => "bepinex",
+            DownloadSource::Mirror => "mirror",
+        }
+    }
+}
+
+/// 下载的文件类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DownloadArtifact {
+    BepInEx,
+    MetaMystiaDll,
+    ResourceEx,
+    Manager,
+}
+
+/// 单个下载的限速与重试策略
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DownloadPolicy {
+    /// 限速（字节/秒），None 表示不限速
+    pub rate_limit: Option<usize>,
+    pub retry_profile: RetryProfile,
+}
+
+/// 命令行指定的限速（KB/s，0 表示不限速），覆盖所有下载源
+static RATE_LIMIT_OVERRIDE: OnceLock<u64> = OnceLock::new();
+
+/// 是否禁止回退到内置版本信息
+static REFUSE_FALLBACK_VERSION: AtomicBool = AtomicBool::new(false);
+
+/// 设置是否禁止回退到内置版本信息（用于必须在数据过期时失败的自动化脚本）
+pub fn configure_refuse_fallback_version(refuse: bool) {
+    REFUSE_FALLBACK_VERSION.store(refuse, Ordering::SeqCst);
+}
+
+/// 设置命令行指定的限速
+pub fn configure_rate_limit(kbps: Option<u64>) {
+    if let Some(kbps) = kbps {
+        let _ = RATE_LIMIT_OVERRIDE.set(kbps);
+    }
+}
+
+/// 根据下载源与文件类型决定下载策略（所有下载都应通过此函数获取策略）
+pub fn download_policy(source: DownloadSource, artifact: DownloadArtifact) -> DownloadPolicy {
+    resolve_download_policy(source, artifact, RATE_LIMIT_OVERRIDE.get().copied())
+}
+
+/// 在给定命令行限速覆盖（KB/s）下决定下载策略
+fn resolve_download_policy(
+    source: DownloadSource,
+    artifact: DownloadArtifact,
+    rate_limit_override: Option<u64>,
+) -> DownloadPolicy {
+    // 仅备用源需要限速；管理工具自身体积小且会阻塞升级流程，不限速
+    let default_rate_limit = match (source, artifact) {
+        (DownloadSource::Mirror, DownloadArtifact::Manager) => None,
+        (DownloadSource::Mirror, _) => Some(RATE_LIMIT),
+        _ => None,
+    };
+
+    let rate_limit = match rate_limit_override {
+        Some(0) => None,
+        Some(kbps) => Some(kbps as usize * 1024),
+        None => default_rate_limit,
+    };
+
+    // BepInEx 主源失败时尽快切换到备用源
+    let retry_profile = match source {
+        DownloadSource::BepInExPrimary => RetryProfile::Metadata,
+        _ => RetryProfile::Download,
+    };
+
+    DownloadPolicy {
+        rate_limit,
+        retry_profile,
+    }
+}
+
+/// 下载器访问的服务地址（测试时替换为本地服务器）
+#[derive(Clone, Debug)]
+struct Endpoints {
+    file_api: String,
+    redirect_url: String,
+    version_api: String,
+    bepinex_primary: String,
+    github_api_url: String,
+    manager_github_api_url: String,
+    github_rate_limit_url: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            file_api: FILE_API.to_string(),
+            redirect_url: REDIRECT_URL.to_string(),
+            version_api: VERSION_API.to_string(),
+            bepinex_primary: BEPINEX_PRIMARY.to_string(),
+            github_api_url: GITHUB_API_URL.to_string(),
+            manager_github_api_url: MANAGER_GITHUB_API_URL.to_string(),
+            github_rate_limit_url: GITHUB_RATE_LIMIT_URL.to_string(),
+        }
+    }
+}
+
+impl Endpoints {
+    /// 所有服务都指向同一个地址（用于测试）
+    #[cfg(test)]
+    fn local(base: &str) -> Self {
+        Self {
+            file_api: format!("{}/dl", base),
+            redirect_url: format!("{}/getMetaMystia", base),
+            version_api: format!("{}/version", base),
+            bepinex_primary: format!("{}/bepinex", base),
+            github_api_url: format!("{}/repos/MetaMystia/releases/latest", base),
+            manager_github_api_url: format!("{}/repos/manager/releases/latest", base),
+            github_rate_limit_url: format!("{}/rate_limit", base),
+        }
+    }
+
+    /// 根据请求的接口决定元数据请求的重试策略（所有 API 请求都应通过此函数获取策略）
+    fn retry_profile(&self, endpoint: &str) -> RetryProfile {
+        // GitHub API 有配额限制，重试间隔更长
+        if endpoint == self.github_api_url || endpoint == self.manager_github_api_url {
+            RetryProfile::GitHubApi
+        } else {
+            // 版本信息与下载链接决定后续流程，快速失败
+            RetryProfile::Metadata
+        }
+    }
+}
+
+/// 单个文件的下载结果
+#[derive(Clone, Debug)]
+pub struct DownloadReport {
+    pub component: String,
+    pub source: DownloadSource,
+    pub bytes: u64,
+    pub duration: Duration,
+}
+
+impl DownloadReport {
+    fn new(component: &str, source: DownloadSource, bytes: u64, start: Instant) -> Self {
+        Self {
+            component: component.to_string(),
+            source,
+            bytes,
+            duration: start.elapsed(),
+        }
+    }
+}
+
+/// 下载前估算的下载总大小
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DownloadSizeEstimate {
+    /// 已知大小之和
+    pub min_bytes: u64,
+    /// 已知大小与未知组件的经验大小之和
+    pub max_bytes: u64,
+    /// 是否所有组件的大小都已知
+    pub has_exact: bool,
+}
+
+impl DownloadSizeEstimate {
+    fn add(&mut self, exact: Option<u64>, fallback: u64) {
+        match exact {
+            Some(n) => {
+                self.min_bytes += n;
+                self.max_bytes += n;
+            }
+            None => {
+                self.max_bytes += fallback;
+                self.has_exact = false;
+            }
+        }
+    }
+}
+
+/// 安装前检查各下载源的结果
+#[derive(Clone, Debug, Default)]
+pub struct PrefetchResult {
+    /// 可用的下载源（如“BepInEx（bepinex.dev）”）及文件大小
+    pub available: Vec<(String, Option<u64>)>,
+    /// 不可用的下载源及原因
+    pub unavailable: Vec<(String, String)>,
+}
+
+impl PrefetchResult {
+    /// 检查时获得的组件文件大小（取该组件第一个返回 Content-Length 的下载源）
+    fn known_size(&self, component: &str) -> Option<u64> {
+        self.available
+            .iter()
+            .filter(|(name, _)| {
+                name.strip_prefix(component)
+                    .is_some_and(|rest| rest.starts_with('（'))
+            })
+            .find_map(|(_, size)| *size)
+    }
+
+    /// 复用检查下载源时获得的文件大小估算下载总大小，大小未知的组件按经验大小计算
+    pub fn estimate_download_size(
+        &self,
+        include_bepinex: bool,
+        include_dll: bool,
+        include_resourceex: bool,
+    ) -> DownloadSizeEstimate {
+        let mut estimate = DownloadSizeEstimate {
+            has_exact: true,
+            ..Default::default()
+        };
+
+        if include_bepinex {
+            estimate.add(self.known_size("BepInEx"), ESTIMATED_BEPINEX_SIZE);
+        }
+        if include_dll {
+            estimate.add(self.known_size("MetaMystia DLL"), ESTIMATED_DLL_SIZE);
+        }
+        if include_resourceex {
+            estimate.add(
+                self.known_size("ResourceExample ZIP"),
+                ESTIMATED_RESOURCEEX_SIZE,
+            );
+        }
+
+        report_event(
+            "Download.SizeEstimate",
+            Some(&format!(
+                "min={};max={};exact={}",
+                estimate.min_bytes, estimate.max_bytes, estimate.has_exact
+            )),
+        );
+
+        estimate
+    }
+}
+
+/// 根据历史平均下载速度估算下载耗时（秒）
+pub fn estimate_download_secs(total_bytes: u64) -> u64 {
+    let kbps = Preferences::load()
+        .avg_download_speed_kbps
+        .filter(|&v| v > 0)
+        .unwrap_or(DEFAULT_DOWNLOAD_SPEED_KBPS);
+
+    (total_bytes / 1024).div_ceil(kbps)
+}
+
+/// 根据本次下载结果更新历史平均下载速度
+pub fn record_download_speed(reports: &[DownloadReport]) {
+    let bytes: u64 = reports.iter().map(|r| r.bytes).sum();
+    let millis: u128 = reports.iter().map(|r| r.duration.as_millis()).sum();
+    if bytes == 0 || millis == 0 {
+        return;
+    }
+
+    let speed = ((bytes as u128 * 1000 / 1024) / millis) as u64;
+    let mut prefs = Preferences::load();
+    let avg = match prefs.avg_download_speed_kbps {
+        Some(prev) => (prev + speed) / 2,
+        None => speed,
+    };
+    prefs.avg_download_speed_kbps = Some(avg.max(1));
+
+    if let Err(e) = prefs.save() {
+        report_event("Download.SpeedSaveFailed", Some(&format!("{}", e)));
+    }
+}
+
+fn version_cache_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(VERSION_CACHE_FILE_NAME))
+}
+
+/// 保存版本信息原文（失败时忽略，缓存仅用于离线回退）
+fn save_version_cache(text: &str) {
+    let Some(path) = version_cache_path() else {
+        return;
+    };
+    if let Some(parent) = path.parent()
+        && std::fs::create_dir_all(parent).is_err()
+    {
+        return;
+    }
+    let _ = write_file_atomic(&path, text.as_bytes());
+}
+
+/// 读取缓存的版本信息及其更新时间（UTC，精确到分钟）
+fn load_version_cache() -> Option<(VersionInfo, String)> {
+    let path = version_cache_path()?;
+    let text = std::fs::read_to_string(&path).ok()?;
+    let vi: VersionInfo = serde_json::from_str(&text).ok()?;
+    vi.validate().ok()?;
+
+    let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+    let mut cached_at = format_utc_time(modified);
+    cached_at.truncate("YYYY-MM-DD HH:MM".len());
+
+    Some((vi, format!("{} UTC", cached_at)))
+}
+
+/// 解析内置的版本信息
+fn load_fallback_version_info() -> Option<VersionInfo> {
+    let mut vi: VersionInfo = serde_json::from_str(FALLBACK_VERSION_INFO).ok()?;
+    vi.validate().ok()?;
+    vi.bundled = true;
+    Some(vi)
+}
+
+/// 删除目录中的全部内容并重新创建该目录
+fn reset_dir(dir: &Path) -> Result<()> {
+    match std::fs::remove_dir_all(dir) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => {
+            return Err(ManagerError::from(std::io::Error::new(
+                e.kind(),
+                format!("清空目录 {} 失败：{}", dir.display(), e),
+            )));
+        }
+    }
+
+    std::fs::create_dir_all(dir).map_err(|e| {
+        ManagerError::from(std::io::Error::new(
+            e.kind(),
+            format!("创建目录 {} 失败：{}", dir.display(), e),
+        ))
+    })
+}
+
+/// 按限速读取下载数据并按节流间隔更新下载进度
+struct ThrottledReader<'a, R: Read> {
+    inner: R,
+    ui: &'a dyn Ui,
+    id: usize,
+    rate_limit: Option<usize>,
+    start: Instant,
+    downloaded: u64,
+    reported: u64,
+    last_report: Instant,
+}
+
+impl<'a, R: Read> ThrottledReader<'a, R> {
+    fn new(inner: R, ui: &'a dyn Ui, id: usize, rate_limit: Option<usize>) -> Self {
+        let start = Instant::now();
+        Self {
+            inner,
+            ui,
+            id,
+            rate_limit,
+            start,
+            downloaded: 0,
+            reported: 0,
+            last_report: start,
+        }
+    }
+
+    /// 报告最终进度并返回已读取的字节数
+    fn finish(&self) -> Result<u64> {
+        if self.reported != self.downloaded {
+            self.ui.download_update(self.id, self.downloaded)?;
+        }
+        Ok(self.downloaded)
+    }
+}
+
+impl<R: Read> Read for ThrottledReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let chunk = cmp::min(self.rate_limit.unwrap_or(8192), 8192);
+        let len = cmp::min(buf.len(), chunk);
+
+        let n = self.inner.read(&mut buf[..len])?;
+        if n == 0 {
+            return Ok(0);
+        }
+        self.downloaded += n as u64;
+
+        // 每次读取都更新进度会带来明显的渲染开销，因此按时间或字节数节流
+        if self.downloaded - self.reported >= PROGRESS_UPDATE_BYTES
+            || self.last_report.elapsed() >= PROGRESS_UPDATE_INTERVAL
+        {
+            self.ui
+                .download_update(self.id, self.downloaded)
+                .map_err(std::io::Error::other)?;
+            self.reported = self.downloaded;
+            self.last_report = Instant::now();
+        }
+
+        if let Some(limit) = self.rate_limit {
+            let expected_secs = (self.downloaded as f64) / (limit as f64);
+            let elapsed = self.start.elapsed().as_secs_f64();
+            if expected_secs > elapsed {
+                let to_sleep = expected_secs - elapsed;
+                let sleep_dur = if cfg!(test) {
+                    Duration::from_millis(1)
+                } else {
+                    let ms = (to_sleep * 1000.0).max(1.0);
+                    Duration::from_millis(ms.ceil() as u64)
+                };
+                sleep(sleep_dur);
+            }
+        }
+
+        Ok(n)
+    }
+}
+
+/// GitHub API 未认证请求的剩余配额
+#[derive(Clone, Copy, Debug)]
+pub struct GitHubRateLimit {
+    pub remaining: u64,
+    /// 配额重置时间
+    pub reset_at: SystemTime,
+}
+
 /// 下载器
 pub struct Downloader<'a> {
-    client: Client,
+    client: HttpClient,
     ui: &'a dyn Ui,
-    cached_github_release: Mutex<Option<serde_json::Value>>,
-    cached_version: Mutex<Option<VersionInfo>>,
+    github_release_requests: RequestDeduplicator<serde_json::Value>,
+    cached_rate_limit: Mutex<Option<(Instant, GitHubRateLimit)>>,
+    version_requests: RequestDeduplicator<VersionInfo>,
+    share_code_requests: RequestDeduplicator<String>,
+    github_token: Option<String>,
+    endpoints: Endpoints,
 }
 
 impl<'a> Downloader<'a> {
     pub fn new(ui: &'a dyn Ui) -> Result<Self> {
-        let client = Self::build_client(CONNECT_TIMEOUT)?;
-        Ok(Self {
+        let client = HttpClient::new(|| Self::build_client(CONNECT_TIMEOUT))?;
+        Ok(Self::with_http_client(client, ui))
+    }
+
+    /// 使用预先构建的客户端创建下载器（用于测试）
+    #[cfg(test)]
+    fn with_client(client: Client, ui: &'a dyn Ui) -> Self {
+        Self::with_http_client(HttpClient::fixed(client), ui)
+    }
+
+    fn with_http_client(client: HttpClient, ui: &'a dyn Ui) -> Self {
+        Self {
             client,
             ui,
-            cached_github_release: Mutex::new(None),
-            cached_version: Mutex::new(None),
-        })
+            github_release_requests: RequestDeduplicator::new(),
+            cached_rate_limit: Mutex::new(None),
+            version_requests: RequestDeduplicator::new(),
+            share_code_requests: RequestDeduplicator::new(),
+            github_token: std::env::var(GITHUB_TOKEN_ENV)
+                .ok()
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty()),
+            endpoints: Endpoints::default(),
+        }
+    }
+
+    /// 将所有服务地址替换为本地测试服务器
+    #[cfg(test)]
+    fn with_local_endpoints(mut self, base: &str) -> Self {
+        self.endpoints = Endpoints::local(base);
+        self
     }
 
     fn build_client(connect_timeout: Duration) -> Result<Client> {
-        ClientBuilder::new()
+        let mut builder = ClientBuilder::new()
             .connect_timeout(connect_timeout)
-            .user_agent(crate::config::USER_AGENT)
-            .build()
-            .map_err(|e| {
-                report_event("Download.ClientBuildFailed", Some(&format!("{}", e)));
-                ManagerError::NetworkError(format!("创建 HTTP 客户端失败：{}", e))
-            })
+            .user_agent(crate::config::USER_AGENT);
+
+        if let Some(identity) = load_client_identity()? {
+            builder = builder.identity(identity);
+        }
+
+        builder.build().map_err(|e| {
+            report_event("Download.ClientBuildFailed", Some(&format!("{}", e)));
+            ManagerError::NetworkError(format!("创建 HTTP 客户端失败：{}", e))
+        })
     }
 
-    fn retry<F, T>(&self, op_desc: &str, f: F) -> Result<T>
+    fn retry<F, T>(&self, profile: RetryProfile, endpoint: &str, op_desc: &str, f: F) -> Result<T>
     where
         F: FnMut() -> Result<T>,
     {
-        with_retry(self.ui, op_desc, f)
+        with_retry(self.ui, Some(&self.client), profile, endpoint, op_desc, f)
     }
 
-    fn convert_reqwest_error(&self, e: reqwest::Error) -> String {
+    fn convert_reqwest_error(&self, e: &reqwest::Error) -> String {
         if e.is_timeout() {
             "请求超时".to_string()
         } else if e.is_connect() {
@@ -78,8 +573,8 @@ impl<'a> Downloader<'a> {
         }
     }
 
-    fn file_api_url(share_code: &str, filename: &str) -> String {
-        format!("{}/{}/{}", FILE_API, share_code, filename)
+    fn file_api_url(&self, share_code: &str, filename: &str) -> String {
+        format!("{}/{}/{}", self.endpoints.file_api, share_code, filename)
     }
 
     fn parse_share_code_from_url(url: &str) -> Option<String> {
@@ -92,35 +587,56 @@ impl<'a> Downloader<'a> {
 
     /// 获取版本信息
     pub fn get_version_info(&self) -> Result<VersionInfo> {
-        if let Ok(guard) = self.cached_version.lock()
-            && let Some(cached) = guard.clone()
-        {
-            return Ok(cached);
-        }
-
-        let vi = self.retry("获取版本信息", || self.try_get_version_info())?;
-        *self
-            .cached_version
-            .lock()
-            .unwrap_or_else(|e| e.into_inner()) = Some(vi.clone());
+        self.version_requests
+            .get_or_fetch(&self.endpoints.version_api, || self.fetch_version_info())
+    }
 
-        Ok(vi)
+    fn fetch_version_info(&self) -> Result<VersionInfo> {
+        match self.retry(
+            self.endpoints.retry_profile(&self.endpoints.version_api),
+            &self.endpoints.version_api,
+            "获取版本信息",
+            || self.try_get_version_info(),
+        ) {
+            Ok(vi) => Ok(vi),
+            Err(e) => {
+                // 网络不可用时回退到上次成功获取的版本信息，没有缓存时再回退到内置版本信息
+                if let Some((vi, cached_at)) = load_version_cache() {
+                    self.ui.download_version_info_cached(&cached_at)?;
+                    report_event("Download.VersionInfo.Cached", Some(&vi.to_string()));
+                    Ok(vi)
+                } else if !REFUSE_FALLBACK_VERSION.load(Ordering::SeqCst)
+                    && let Some(vi) = load_fallback_version_info()
+                {
+                    self.ui.warn("使用内置版本信息（可能不是最新版本）")?;
+                    report_event("Download.VersionInfo.Fallback", Some(&vi.to_string()));
+                    Ok(vi)
+                } else {
+                    Err(e)
+                }
+            }
+        }
     }
 
     fn try_get_version_info(&self) -> Result<VersionInfo> {
         self.ui.download_version_info_start()?;
 
-        let response = self.client.get(VERSION_API).send().map_err(|e| {
-            let msg = self.convert_reqwest_error(e);
-            let _ = self.ui.download_version_info_failed(&msg);
-            ManagerError::NetworkError(msg)
-        })?;
+        let response = self
+            .client
+            .get()
+            .get(&self.endpoints.version_api)
+            .send()
+            .map_err(|e| {
+                let msg = self.convert_reqwest_error(&e);
+                let _ = self.ui.download_version_info_failed(&msg);
+                map_send_error(&e, msg)
+            })?;
 
         if !response.status().is_success() {
-            return Err(ManagerError::NetworkError(format!(
-                "获取版本信息失败：HTTP {}",
-                response.status()
-            )));
+            return Err(ManagerError::HttpStatus {
+                status: response.status().as_u16(),
+                message: format!("获取版本信息失败：HTTP {}", response.status()),
+            });
         }
 
         let text = response
@@ -138,10 +654,11 @@ impl<'a> Downloader<'a> {
                 Some(&format!("err={};snippet={}", e, snippet)),
             );
 
-            ManagerError::Other(format!("解析版本信息失败：{}", e))
+            ManagerError::InvalidVersionInfo
         })?;
 
         vi.validate()?;
+        save_version_cache(&text);
 
         self.ui.download_version_info_success()?;
         report_event("Download.VersionInfo.Success", Some(&vi.to_string()));
@@ -151,23 +668,36 @@ impl<'a> Downloader<'a> {
 
     /// 获取分享码
     pub fn get_share_code(&self) -> Result<String> {
-        self.retry("获取下载链接", || self.try_get_share_code())
+        self.share_code_requests
+            .get_or_fetch(&self.endpoints.redirect_url, || {
+                self.retry(
+                    self.endpoints.retry_profile(&self.endpoints.redirect_url),
+                    &self.endpoints.redirect_url,
+                    "获取下载链接",
+                    || self.try_get_share_code(),
+                )
+            })
     }
 
     fn try_get_share_code(&self) -> Result<String> {
         self.ui.download_share_code_start()?;
 
-        let response = self.client.get(REDIRECT_URL).send().map_err(|e| {
-            let msg = self.convert_reqwest_error(e);
-            let _ = self.ui.download_share_code_failed(&msg);
-            ManagerError::NetworkError(msg)
-        })?;
+        let response = self
+            .client
+            .get()
+            .get(&self.endpoints.redirect_url)
+            .send()
+            .map_err(|e| {
+                let msg = self.convert_reqwest_error(&e);
+                let _ = self.ui.download_share_code_failed(&msg);
+                map_send_error(&e, msg)
+            })?;
 
         if !response.status().is_success() {
-            return Err(ManagerError::NetworkError(format!(
-                "获取下载链接失败：HTTP {}",
-                response.status()
-            )));
+            return Err(ManagerError::HttpStatus {
+                status: response.status().as_u16(),
+                message: format!("获取下载链接失败：HTTP {}", response.status()),
+            });
         }
 
         let final_url = response.url().as_str();
@@ -191,10 +721,10 @@ impl<'a> Downloader<'a> {
         url: &str,
         dest: &Path,
         file_size: Option<u64>,
-        rate_limit: bool,
-    ) -> Result<()> {
-        self.retry("下载文件", || {
-            self.try_download(url, dest, file_size, rate_limit)
+        policy: DownloadPolicy,
+    ) -> Result<u64> {
+        self.retry(policy.retry_profile, url, "下载文件", || {
+            self.try_download(url, dest, file_size, policy)
         })
     }
 
@@ -203,19 +733,20 @@ impl<'a> Downloader<'a> {
         url: &str,
         dest: &Path,
         file_size: Option<u64>,
-        rate_limit: bool,
-    ) -> Result<()> {
+        policy: DownloadPolicy,
+    ) -> Result<u64> {
         let mut response = self
             .client
+            .get()
             .get(url)
             .send()
-            .map_err(|e| ManagerError::NetworkError(e.to_string()))?;
+            .map_err(|e| map_send_error(&e, e.to_string()))?;
 
         if !response.status().is_success() {
-            return Err(ManagerError::NetworkError(format!(
-                "HTTP {}",
-                response.status()
-            )));
+            return Err(ManagerError::HttpStatus {
+                status: response.status().as_u16(),
+                message: format!("HTTP {}", response.status()),
+            });
         }
 
         let total_size = file_size.or_else(|| response.content_length());
@@ -226,7 +757,7 @@ impl<'a> Downloader<'a> {
 
         let id = self.ui.download_start(&filename, total_size)?;
 
-        self.write_response_to_file(&mut response, dest, id, rate_limit)
+        self.write_response_to_file(&mut response, dest, id, policy)
     }
 
     fn write_response_to_file<R: Read>(
@@ -234,8 +765,8 @@ impl<'a> Downloader<'a> {
         resp: &mut R,
         dest: &Path,
         id: usize,
-        rate_limit: bool,
-    ) -> Result<()> {
+        policy: DownloadPolicy,
+    ) -> Result<u64> {
         if let Some(parent) = dest.parent() {
             std::fs::create_dir_all(parent).map_err(|e| {
                 ManagerError::from(std::io::Error::new(
@@ -259,17 +790,12 @@ impl<'a> Downloader<'a> {
             ))
         })?;
 
-        let buf_len = cmp::min(RATE_LIMIT, 8192) as usize;
-        let mut buffer = vec![0; buf_len];
-
-        let mut downloaded = 0u64;
-        let start = Instant::now();
+        let mut reader = ThrottledReader::new(resp, self.ui, id, policy.rate_limit);
+        let mut buffer = vec![0; 8192];
 
         loop {
-            let to_read = buffer.len();
-
-            let n = resp
-                .read(&mut buffer[..to_read])
+            let n = reader
+                .read(&mut buffer)
                 .map_err(|e| ManagerError::NetworkError(e.to_string()))?;
             if n == 0 {
                 break;
@@ -281,26 +807,10 @@ impl<'a> Downloader<'a> {
                     format!("写入临时文件 {} 失败：{}", tmp_path.display(), e),
                 ))
             })?;
-            downloaded += n as u64;
-
-            self.ui.download_update(id, downloaded)?;
-
-            if rate_limit {
-                let expected_secs = (downloaded as f64) / (RATE_LIMIT as f64);
-                let elapsed = start.elapsed().as_secs_f64();
-                if expected_secs > elapsed {
-                    let to_sleep = expected_secs - elapsed;
-                    let sleep_dur = if cfg!(test) {
-                        Duration::from_millis(1)
-                    } else {
-                        let ms = (to_sleep * 1000.0).max(1.0);
-                        Duration::from_millis(ms.ceil() as u64)
-                    };
-                    sleep(sleep_dur);
-                }
-            }
         }
 
+        let downloaded = reader.finish()?;
+
         tmp_file.flush().map_err(|e| {
             ManagerError::from(std::io::Error::new(
                 e.kind(),
@@ -320,7 +830,7 @@ impl<'a> Downloader<'a> {
                             .unwrap_or_else(|| dest.display().to_string())
                     ),
                 )?;
-                Ok(())
+                Ok(downloaded)
             }
             Err(e) => {
                 let _ = std::fs::remove_file(&tmp_path);
@@ -333,33 +843,178 @@ impl<'a> Downloader<'a> {
         }
     }
 
-    fn fetch_github_release_json(&self) -> Result<serde_json::Value> {
-        if let Ok(guard) = self.cached_github_release.lock()
-            && let Some(json) = guard.clone()
+    /// 边下载边解压 ZIP 到指定目录，压缩包本身不写入磁盘（返回解压的文件与下载的字节数）
+    pub fn download_and_extract_streaming(
+        &self,
+        url: &str,
+        dest_dir: &Path,
+        exclude_patterns: &[&str],
+        policy: DownloadPolicy,
+    ) -> Result<(Vec<PathBuf>, u64)> {
+        self.retry(policy.retry_profile, url, "下载并解压文件", || {
+            self.try_download_and_extract(url, dest_dir, exclude_patterns, policy)
+        })
+    }
+
+    fn try_download_and_extract(
+        &self,
+        url: &str,
+        dest_dir: &Path,
+        exclude_patterns: &[&str],
+        policy: DownloadPolicy,
+    ) -> Result<(Vec<PathBuf>, u64)> {
+        let response = self
+            .client
+            .get()
+            .get(url)
+            .send()
+            .map_err(|e| map_send_error(&e, e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(ManagerError::HttpStatus {
+                status: response.status().as_u16(),
+                message: format!("HTTP {}", response.status()),
+            });
+        }
+
+        let total_size = response.content_length();
+        self.extract_response(response, total_size, dest_dir, exclude_patterns, policy)
+    }
+
+    /// 清空目标目录后边读取边解压，避免上一次失败尝试的残留文件与本次解压结果混在一起
+    fn extract_response<R: Read>(
+        &self,
+        response: R,
+        total_size: Option<u64>,
+        dest_dir: &Path,
+        exclude_patterns: &[&str],
+        policy: DownloadPolicy,
+    ) -> Result<(Vec<PathBuf>, u64)> {
+        reset_dir(dest_dir)?;
+
+        let name = dest_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| dest_dir.display().to_string());
+
+        let id = self.ui.download_start(&name, total_size)?;
+
+        let mut reader = BufReader::new(ThrottledReader::new(
+            response,
+            self.ui,
+            id,
+            policy.rate_limit,
+        ));
+        match Extractor::extract_zip_stream(&mut reader, dest_dir, exclude_patterns) {
+            Ok(files) => {
+                let downloaded = reader.get_ref().finish()?;
+                self.ui
+                    .download_finish(id, &format!("下载并解压完成：{}", name))?;
+                Ok((files, downloaded))
+            }
+            Err(e) => {
+                self.ui
+                    .download_finish(id, &format!("下载失败：{}", name))?;
+                Err(e)
+            }
+        }
+    }
+
+    /// 查询 GitHub API 剩余配额（结果缓存 60 秒）
+    pub fn check_github_rate_limit(&self) -> Result<GitHubRateLimit> {
+        if let Ok(guard) = self.cached_rate_limit.lock()
+            && let Some((checked_at, limit)) = *guard
+            && checked_at.elapsed() < GITHUB_RATE_LIMIT_CACHE_TTL
         {
-            return Ok(json);
+            return Ok(limit);
         }
 
         let json: serde_json::Value = get_json_with_retry(
             &self.client,
             self.ui,
-            GITHUB_API_URL,
+            &self.endpoints.github_rate_limit_url,
             Some("application/vnd.github+json"),
-            "请求 GitHub API ",
+            None,
+            self.endpoints
+                .retry_profile(&self.endpoints.github_rate_limit_url),
+            "查询 GitHub API 配额",
         )?;
 
+        let core = &json["resources"]["core"];
+        let (Some(remaining), Some(reset)) = (core["remaining"].as_u64(), core["reset"].as_u64())
+        else {
+            return Err(ManagerError::NetworkError(
+                "GitHub API 配额信息格式无效".to_string(),
+            ));
+        };
+
+        let limit = GitHubRateLimit {
+            remaining,
+            reset_at: UNIX_EPOCH + Duration::from_secs(reset),
+        };
         *self
-            .cached_github_release
+            .cached_rate_limit
             .lock()
-            .unwrap_or_else(|e| e.into_inner()) = Some(json.clone());
+            .unwrap_or_else(|e| e.into_inner()) = Some((Instant::now(), limit));
+
+        Ok(limit)
+    }
+
+    /// 未设置 GITHUB_TOKEN 时预先检查配额，配额用尽则直接返回限流错误（查询失败时不阻止请求）
+    fn ensure_github_quota(&self) -> Result<()> {
+        if self.github_token.is_some() {
+            return Ok(());
+        }
 
-        Ok(json)
+        let Ok(limit) = self.check_github_rate_limit() else {
+            return Ok(());
+        };
+        if limit.remaining > 0 {
+            return Ok(());
+        }
+
+        let secs = limit
+            .reset_at
+            .duration_since(SystemTime::now())
+            .unwrap_or_default()
+            .as_secs();
+        self.ui.network_rate_limited(secs)?;
+        report_event("Download.GitHub.RateLimited", Some(&secs.to_string()));
+
+        Err(ManagerError::RateLimited(format!(
+            "GitHub API 配额已用尽，将在 {} 秒后重置",
+            secs
+        )))
+    }
+
+    fn fetch_github_release_json(&self) -> Result<serde_json::Value> {
+        self.github_release_requests
+            .get_or_fetch(&self.endpoints.github_api_url, || {
+                self.ensure_github_quota()?;
+
+                get_json_with_retry(
+                    &self.client,
+                    self.ui,
+                    &self.endpoints.github_api_url,
+                    Some("application/vnd.github+json"),
+                    self.github_token.as_deref(),
+                    self.endpoints.retry_profile(&self.endpoints.github_api_url),
+                    "请求 GitHub API ",
+                )
+            })
     }
 
     fn get_dll_download_url_from_github(&self) -> Result<String> {
         self.ui.download_attempt_github_dll()?;
 
-        let json = self.fetch_github_release_json()?;
+        let json = match self.fetch_github_release_json() {
+            Ok(json) => json,
+            Err(e @ ManagerError::AuthRequired(_)) => {
+                self.ui.download_github_auth_required()?;
+                return Err(e);
+            }
+            Err(e) => return Err(e),
+        };
 
         if let Some(assets) = json["assets"].as_array() {
             for asset in assets {
@@ -387,6 +1042,163 @@ impl<'a> Downloader<'a> {
         ))
     }
 
+    /// 从管理工具的 GitHub Release 中查找指定文件名的下载链接
+    fn get_manager_download_url_from_github(&self, filename: &str) -> Result<String> {
+        self.ensure_github_quota()?;
+
+        let json: serde_json::Value = match get_json_with_retry(
+            &self.client,
+            self.ui,
+            &self.endpoints.manager_github_api_url,
+            Some("application/vnd.github+json"),
+            self.github_token.as_deref(),
+            self.endpoints
+                .retry_profile(&self.endpoints.manager_github_api_url),
+            "请求 GitHub API ",
+        ) {
+            Ok(json) => json,
+            Err(e @ ManagerError::AuthRequired(_)) => {
+                self.ui.download_github_auth_required()?;
+                return Err(e);
+            }
+            Err(e) => return Err(e),
+        };
+
+        json["assets"]
+            .as_array()
+            .and_then(|assets| {
+                assets
+                    .iter()
+                    .find(|asset| asset["name"].as_str() == Some(filename))
+            })
+            .and_then(|asset| asset["browser_download_url"].as_str())
+            .map(|url| {
+                report_event("Download.GitHub.Manager.Found", Some(filename));
+                url.to_string()
+            })
+            .ok_or_else(|| {
+                report_event("Download.GitHub.Manager.NotFound", Some(filename));
+                // GitHub 上的最新版本可能尚未与版本信息同步
+                ManagerError::NetworkError(format!("GitHub Release 中未找到 {}", filename))
+            })
+    }
+
+    /// 发送 HEAD 请求检查文件是否可以下载，返回文件大小（缺少 Content-Length 时为 None）
+    fn head_check(&self, url: &str) -> Result<Option<u64>> {
+        let resp = self
+            .client
+            .get()
+            .head(url)
+            .send()
+            .map_err(|e| map_send_error(&e, e.to_string()))?;
+        let status = resp.status();
+        if status != StatusCode::OK && status != StatusCode::PARTIAL_CONTENT {
+            return Err(ManagerError::HttpStatus {
+                status: status.as_u16(),
+                message: format!("HTTP {}", status),
+            });
+        }
+
+        Ok(resp
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok()))
+    }
+
+    /// 在创建临时目录或修改任何文件之前检查各下载源是否可用，全部不可用时返回错误
+    pub fn prefetch_all_urls(
+        &self,
+        version_info: &VersionInfo,
+        dll_version: Option<&str>,
+        include_bepinex: bool,
+        resourceex_version: Option<&str>,
+    ) -> Result<PrefetchResult> {
+        let mut result = PrefetchResult::default();
+        let mut record =
+            |name: String, url: Result<String>| match url.and_then(|url| self.head_check(&url)) {
+                Ok(size) => result.available.push((name, size)),
+                Err(e) => result.unavailable.push((name, e.to_string())),
+            };
+
+        if include_bepinex {
+            let url = version_info.bepinex_version().and_then(|version| {
+                Ok(format!(
+                    "{}/{}/{}",
+                    self.endpoints.bepinex_primary,
+                    version,
+                    version_info.bepinex_filename()?
+                ))
+            });
+            record(
+                format!("BepInEx（{}）", DownloadSource::BepInExPrimary.name()),
+                url,
+            );
+        }
+
+        let share_code = self.get_share_code();
+        let file_api_url = |filename: String| match &share_code {
+            Ok(code) => Ok(self.file_api_url(code, &filename)),
+            Err(e) => Err(ManagerError::NetworkError(e.to_string())),
+        };
+
+        if let Some(version) = dll_version {
+            let filename = VersionInfo::metamystia_filename(version);
+
+            // GitHub 只提供最新版本的 DLL
+            if version == version_info.latest_dll() {
+                let url = self.fetch_github_release_json().and_then(|json| {
+                    json["assets"]
+                        .as_array()
+                        .and_then(|assets| {
+                            assets
+                                .iter()
+                                .find(|asset| asset["name"].as_str() == Some(filename.as_str()))
+                        })
+                        .and_then(|asset| asset["browser_download_url"].as_str())
+                        .map(str::to_string)
+                        .ok_or_else(|| {
+                            ManagerError::NetworkError(format!(
+                                "GitHub Release 中未找到 {}",
+                                filename
+                            ))
+                        })
+                });
+                record(
+                    format!("MetaMystia DLL（{}）", DownloadSource::GitHub.name()),
+                    url,
+                );
+            }
+
+            record(
+                format!("MetaMystia DLL（{}）", DownloadSource::Mirror.name()),
+                file_api_url(filename),
+            );
+        }
+
+        if let Some(version) = resourceex_version {
+            record(
+                format!("ResourceExample ZIP（{}）", DownloadSource::Mirror.name()),
+                file_api_url(VersionInfo::resourceex_filename(version)),
+            );
+        }
+
+        report_event(
+            "Download.Prefetch",
+            Some(&format!(
+                "available={};unavailable={}",
+                result.available.len(),
+                result.unavailable.len()
+            )),
+        );
+
+        if result.available.is_empty() && !result.unavailable.is_empty() {
+            return Err(ManagerError::NetworkError("所有下载源均不可用".to_string()));
+        }
+
+        Ok(result)
+    }
+
     fn get_github_release_notes(&self) -> Result<Option<(String, String, String)>> {
         let json = self.fetch_github_release_json()?;
 
@@ -424,6 +1236,23 @@ impl<'a> Downloader<'a> {
         }
     }
 
+    /// 所有下载源均失败：展示各源的失败原因并汇总为一个错误
+    fn all_sources_failed(&self, component: &str, failures: Vec<SourceFailure>) -> ManagerError {
+        let _ = self.ui.download_all_sources_failed(&failures);
+
+        let detail = failures
+            .iter()
+            .map(|f| format!("{}={}", f.url, f.kind))
+            .collect::<Vec<_>>()
+            .join(";");
+        report_event(
+            "Download.AllSourcesFailed",
+            Some(&format!("{};{}", component, detail)),
+        );
+
+        ManagerError::AllSourcesFailed(failures)
+    }
+
     /// 下载 MetaMystia DLL
     pub fn download_metamystia(
         &self,
@@ -431,17 +1260,24 @@ impl<'a> Downloader<'a> {
         version: &str,
         dest: &Path,
         try_github: bool,
-    ) -> Result<()> {
+    ) -> Result<DownloadReport> {
         report_event("Download.Metamystia.Start", Some(version));
 
-        if !try_github {
-            let filename = VersionInfo::metamystia_filename(version);
-            let url = Self::file_api_url(share_code, &filename);
+        let start = Instant::now();
+        let filename = VersionInfo::metamystia_filename(version);
+        let fallback_url = self.file_api_url(share_code, &filename);
 
-            return match self.download_file_with_progress(&url, dest, None, true) {
-                Ok(()) => {
+        if !try_github {
+            let policy = download_policy(DownloadSource::Mirror, DownloadArtifact::MetaMystiaDll);
+            return match self.download_file_with_progress(&fallback_url, dest, None, policy) {
+                Ok(bytes) => {
                     report_event("Download.Metamystia.Success.Fallback", Some(version));
-                    Ok(())
+                    Ok(DownloadReport::new(
+                        "MetaMystia DLL",
+                        DownloadSource::Mirror,
+                        bytes,
+                        start,
+                    ))
                 }
                 Err(e) => {
                     report_event(
@@ -453,75 +1289,96 @@ impl<'a> Downloader<'a> {
             };
         }
 
-        match self.get_dll_download_url_from_github() {
-            Ok(url) => {
-                if let Err(e) = self.download_file_with_progress(&url, dest, None, false) {
+        let github_failure = match self.get_dll_download_url_from_github() {
+            Ok(url) => match self.download_file_with_progress(
+                &url,
+                dest,
+                None,
+                download_policy(DownloadSource::GitHub, DownloadArtifact::MetaMystiaDll),
+            ) {
+                Ok(bytes) => {
+                    report_event("Download.Metamystia.Success.GitHub", Some(version));
+                    return Ok(DownloadReport::new(
+                        "MetaMystia DLL",
+                        DownloadSource::GitHub,
+                        bytes,
+                        start,
+                    ));
+                }
+                Err(e) => {
                     self.ui.download_switch_to_fallback(&format!(
                         "从 GitHub 下载 MetaMystia DLL 失败：{}，切换到备用源...",
                         e
                     ))?;
-                    self.ui.download_try_fallback_metamystia()?;
                     report_event("Download.Metamystia.Failed.GitHub", Some(&format!("{}", e)));
-
-                    let filename = VersionInfo::metamystia_filename(version);
-                    let fallback_url = Self::file_api_url(share_code, &filename);
-
-                    match self.download_file_with_progress(&fallback_url, dest, None, true) {
-                        Ok(()) => {
-                            report_event("Download.Metamystia.Success.Fallback", Some(version));
-                            Ok(())
-                        }
-                        Err(e) => {
-                            report_event(
-                                "Download.Metamystia.Failed.Fallback",
-                                Some(&format!("{}", e)),
-                            );
-                            Err(e)
-                        }
-                    }
-                } else {
-                    report_event("Download.Metamystia.Success.GitHub", Some(version));
-                    Ok(())
+                    SourceFailure::new("GitHub", &url, &e)
                 }
-            }
-            Err(_) => {
+            },
+            Err(e) => {
                 self.ui.download_switch_to_fallback(
                     "从 GitHub 获取 MetaMystia DLL 下载链接失败，切换到备用源...",
                 )?;
-                self.ui.download_try_fallback_metamystia()?;
                 report_event("Download.Metamystia.GitHubUrlFailed", None);
-
-                let filename = VersionInfo::metamystia_filename(version);
-                let url = Self::file_api_url(share_code, &filename);
-
-                match self.download_file_with_progress(&url, dest, None, true) {
-                    Ok(()) => {
-                        report_event("Download.Metamystia.Success.Fallback", Some(version));
-                        Ok(())
-                    }
-                    Err(e) => {
-                        report_event(
-                            "Download.Metamystia.Failed.Fallback",
-                            Some(&format!("{}", e)),
-                        );
-                        Err(e)
-                    }
-                }
+                SourceFailure::new("GitHub", &self.endpoints.github_api_url, &e)
+            }
+        };
+
+        self.ui.download_try_fallback_metamystia()?;
+
+        let policy = download_policy(DownloadSource::Mirror, DownloadArtifact::MetaMystiaDll);
+        match self.download_file_with_progress(&fallback_url, dest, None, policy) {
+            Ok(bytes) => {
+                report_event("Download.Metamystia.Success.Fallback", Some(version));
+                Ok(DownloadReport::new(
+                    "MetaMystia DLL",
+                    DownloadSource::Mirror,
+                    bytes,
+                    start,
+                ))
+            }
+            Err(e) => {
+                report_event(
+                    "Download.Metamystia.Failed.Fallback",
+                    Some(&format!("{}", e)),
+                );
+                let fallback_failure = SourceFailure::new("备用源", &fallback_url, &e);
+                Err(self.all_sources_failed("Metamystia", vec![github_failure, fallback_failure]))
             }
         }
     }
 
     /// 下载 ResourceExample ZIP
-    pub fn download_resourceex(&self, share_code: &str, version: &str, dest: &Path) -> Result<()> {
+    pub fn download_resourceex(
+        &self,
+        share_code: &str,
+        version: &str,
+        dest: &Path,
+    ) -> Result<DownloadReport> {
         report_event("Download.ResourceEx.Start", Some(version));
 
+        let start = Instant::now();
         let filename = VersionInfo::resourceex_filename(version);
-        let url = Self::file_api_url(share_code, &filename);
-
-        match self.download_file_with_progress(&url, dest, None, true) {
-            Ok(()) => {
+        let url = self.file_api_url(share_code, &filename);
+
+        let policy = download_policy(DownloadSource::Mirror, DownloadArtifact::ResourceEx);
+        // 截断的压缩包视为下载失败，删除后重试
+        let result = self.retry(policy.retry_profile, &url, "下载文件", || {
+            let bytes = self.try_download(&url, dest, None, policy)?;
+            if let Err(e) = Extractor::validate_resourceex_zip(dest) {
+                let _ = std::fs::remove_file(dest);
+                return Err(e);
+            }
+            Ok(bytes)
+        });
+        match result {
+            Ok(bytes) => {
                 report_event("Download.ResourceEx.Success", Some(version));
-                Ok(())
+                Ok(DownloadReport::new(
+                    "ResourceExample ZIP",
+                    DownloadSource::Mirror,
+                    bytes,
+                    start,
+                ))
             }
             Err(e) => {
                 report_event("Download.ResourceEx.Failed", Some(&format!("{}", e)));
@@ -530,8 +1387,13 @@ impl<'a> Downloader<'a> {
         }
     }
 
-    /// 下载 BepInEx
-    pub fn download_bepinex(&self, version_info: &VersionInfo, dest: &Path) -> Result<bool> {
+    /// 下载 BepInEx 并直接解压到暂存目录
+    pub fn download_bepinex(
+        &self,
+        version_info: &VersionInfo,
+        staging_dir: &Path,
+    ) -> Result<DownloadReport> {
+        let start = Instant::now();
         let filename = version_info.bepinex_filename()?;
         let version = version_info.bepinex_version()?;
         let filename_with_version = percent_encode(
@@ -543,87 +1405,491 @@ impl<'a> Downloader<'a> {
         self.ui.download_bepinex_attempt_primary()?;
         report_event("Download.BepInEx.Start", Some(version));
 
-        let primary_url = format!("{}/{}/{}", BEPINEX_PRIMARY, version, filename);
-        let primary_result =
-            get_response_with_retry(&self.client, self.ui, &primary_url, "请求 BepInEx 主源");
-
-        match primary_result {
-            Ok(mut resp) => {
-                let total_size = resp.content_length();
-                let id = self
-                    .ui
-                    .download_start("BepInEx（bepinex.dev）", total_size)?;
-
-                if let Err(e) = self.write_response_to_file(&mut resp, dest, id, false) {
-                    self.ui.download_finish(id, "从 bepinex.dev 下载失败")?;
-                    self.ui.download_bepinex_primary_failed(&format!(
-                        "从 bepinex.dev 下载失败 ({}), 切换到备用源...",
-                        e
-                    ))?;
-                    report_event("Download.BepInEx.Failed.Primary", Some(&format!("{}", e)));
-
-                    let share_code = self.get_share_code()?;
-                    let fallback_url = Self::file_api_url(&share_code, &filename_with_version);
-
-                    match self.download_file_with_progress(&fallback_url, dest, None, true) {
-                        Ok(()) => {
-                            report_event("Download.BepInEx.Success.Fallback", Some(version));
-                            Ok(false)
-                        }
-                        Err(e) => {
-                            report_event(
-                                "Download.BepInEx.Failed.Fallback",
-                                Some(&format!("{}", e)),
-                            );
-                            Err(e)
-                        }
-                    }
-                } else {
-                    report_event("Download.BepInEx.Success.Primary", Some(version));
-                    Ok(true)
-                }
+        let primary_policy =
+            download_policy(DownloadSource::BepInExPrimary, DownloadArtifact::BepInEx);
+        let primary_url = format!(
+            "{}/{}/{}",
+            self.endpoints.bepinex_primary, version, filename
+        );
+
+        let primary_failure = match self.download_and_extract_streaming(
+            &primary_url,
+            staging_dir,
+            &[],
+            primary_policy,
+        ) {
+            Ok((_, bytes)) => {
+                report_event("Download.BepInEx.Success.Primary", Some(version));
+                return Ok(DownloadReport::new(
+                    "BepInEx",
+                    DownloadSource::BepInExPrimary,
+                    bytes,
+                    start,
+                ));
             }
-            Err(_) => {
-                self.ui.download_bepinex_primary_failed(
-                    "从 bepinex.dev 下载失败或超时，切换到备用源...",
-                )?;
-                report_event("Download.BepInEx.PrimaryRequestFailed", Some(version));
+            Err(e) => {
+                self.ui.download_bepinex_primary_failed(&format!(
+                    "从 bepinex.dev 下载失败 ({}), 切换到备用源...",
+                    e
+                ))?;
+                report_event("Download.BepInEx.Failed.Primary", Some(&format!("{}", e)));
+                SourceFailure::new("bepinex.dev", &primary_url, &e)
+            }
+        };
 
-                let share_code = self.get_share_code()?;
-                let fallback_url = Self::file_api_url(&share_code, &filename_with_version);
+        let fallback_failure = match self.get_share_code() {
+            Ok(share_code) => {
+                let fallback_url = self.file_api_url(&share_code, &filename_with_version);
 
-                match self.download_file_with_progress(&fallback_url, dest, None, true) {
-                    Ok(()) => {
+                let policy = download_policy(DownloadSource::Mirror, DownloadArtifact::BepInEx);
+                match self.download_and_extract_streaming(&fallback_url, staging_dir, &[], policy) {
+                    Ok((_, bytes)) => {
                         report_event("Download.BepInEx.Success.Fallback", Some(version));
-                        Ok(false)
+                        return Ok(DownloadReport::new(
+                            "BepInEx",
+                            DownloadSource::Mirror,
+                            bytes,
+                            start,
+                        ));
                     }
                     Err(e) => {
                         report_event("Download.BepInEx.Failed.Fallback", Some(&format!("{}", e)));
-                        Err(e)
+                        SourceFailure::new("备用源", &fallback_url, &e)
                     }
                 }
             }
-        }
+            Err(e) => {
+                report_event("Download.BepInEx.Failed.Fallback", Some(&format!("{}", e)));
+                SourceFailure::new("备用源", &self.endpoints.redirect_url, &e)
+            }
+        };
+
+        Err(self.all_sources_failed("BepInEx", vec![primary_failure, fallback_failure]))
     }
 
-    /// 下载管理工具可执行文件
-    pub fn download_manager(&self, version_info: &VersionInfo, dest: &Path) -> Result<()> {
-        let filename = version_info.manager_filename();
+    /// 获取最新管理工具在备用源上的直接下载链接（不下载文件）
+    pub fn get_manager_download_url(&self, version_info: &VersionInfo) -> Result<String> {
+        let filename = VersionInfo::manager_filename(version_info.latest_manager(update_channel()));
+        let share_code = self.get_share_code()?;
+        Ok(self.file_api_url(&share_code, &filename))
+    }
 
-        report_event("Download.Manager.Start", Some(&version_info.manager));
+    /// 下载管理工具可执行文件
+    pub fn download_manager(&self, version: &str, dest: &Path) -> Result<()> {
+        let filename = VersionInfo::manager_filename(version);
+
+        report_event("Download.Manager.Start", Some(version));
+
+        let github_failure = match self.get_manager_download_url_from_github(&filename) {
+            Ok(url) => match self.download_file_with_progress(
+                &url,
+                dest,
+                None,
+                download_policy(DownloadSource::GitHub, DownloadArtifact::Manager),
+            ) {
+                Ok(_) => {
+                    report_event("Download.Manager.Success.GitHub", Some(version));
+                    return Ok(());
+                }
+                Err(e) => {
+                    self.ui.download_switch_to_fallback(&format!(
+                        "从 GitHub 下载管理工具失败：{}，切换到备用源...",
+                        e
+                    ))?;
+                    report_event("Download.Manager.Failed.GitHub", Some(&format!("{}", e)));
+                    SourceFailure::new("GitHub", &url, &e)
+                }
+            },
+            Err(e) => {
+                self.ui.download_switch_to_fallback(
+                    "从 GitHub 获取管理工具下载链接失败，切换到备用源...",
+                )?;
+                report_event("Download.Manager.GitHubUrlFailed", None);
+                SourceFailure::new("GitHub", &self.endpoints.manager_github_api_url, &e)
+            }
+        };
 
         let share_code = self.get_share_code()?;
-        let url = Self::file_api_url(&share_code, &filename);
+        let url = self.file_api_url(&share_code, &filename);
 
-        match self.download_file_with_progress(&url, dest, None, true) {
-            Ok(()) => {
-                report_event("Download.Manager.Success", Some(&version_info.manager));
+        let policy = download_policy(DownloadSource::Mirror, DownloadArtifact::Manager);
+        match self.download_file_with_progress(&url, dest, None, policy) {
+            Ok(_) => {
+                report_event("Download.Manager.Success.Fallback", Some(version));
                 Ok(())
             }
             Err(e) => {
-                report_event("Download.Manager.Failed", Some(&format!("{}", e)));
-                Err(e)
+                report_event("Download.Manager.Failed.Fallback", Some(&format!("{}", e)));
+                let fallback_failure = SourceFailure::new("备用源", &url, &e);
+                Err(self.all_sources_failed("Manager", vec![github_failure, fallback_failure]))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::configure_retry_overrides;
+    use crate::test_utils::{ScratchDir, TestUI, UiCall, UiCallKind};
+
+    use std::io::{BufRead, Cursor, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    #[test]
+    fn api_requests_pick_intended_retry_profile() {
+        let endpoints = Endpoints::default();
+        assert_eq!(endpoints.retry_profile(VERSION_API), RetryProfile::Metadata);
+        assert_eq!(
+            endpoints.retry_profile(REDIRECT_URL),
+            RetryProfile::Metadata
+        );
+        assert_eq!(
+            endpoints.retry_profile(GITHUB_RATE_LIMIT_URL),
+            RetryProfile::Metadata
+        );
+        assert_eq!(
+            endpoints.retry_profile(GITHUB_API_URL),
+            RetryProfile::GitHubApi
+        );
+        assert_eq!(
+            endpoints.retry_profile(MANAGER_GITHUB_API_URL),
+            RetryProfile::GitHubApi
+        );
+    }
+
+    #[test]
+    fn size_estimate_reuses_prefetched_sizes() {
+        let prefetch = PrefetchResult {
+            available: vec![
+                ("BepInEx（bepinex.dev）".to_string(), Some(1000)),
+                ("MetaMystia DLL（GitHub）".to_string(), None),
+                ("MetaMystia DLL（镜像）".to_string(), Some(200)),
+            ],
+            unavailable: vec![(
+                "ResourceExample ZIP（镜像）".to_string(),
+                "HTTP 404".to_string(),
+            )],
+        };
+
+        let estimate = prefetch.estimate_download_size(true, true, false);
+        assert_eq!(estimate.min_bytes, 1200);
+        assert_eq!(estimate.max_bytes, 1200);
+        assert!(estimate.has_exact);
+
+        let estimate = prefetch.estimate_download_size(true, true, true);
+        assert_eq!(estimate.min_bytes, 1200);
+        assert_eq!(estimate.max_bytes, 1200 + ESTIMATED_RESOURCEEX_SIZE);
+        assert!(!estimate.has_exact);
+    }
+
+    const SOURCES: [DownloadSource; 3] = [
+        DownloadSource::GitHub,
+        DownloadSource::BepInExPrimary,
+        DownloadSource::Mirror,
+    ];
+    const ARTIFACTS: [DownloadArtifact; 4] = [
+        DownloadArtifact::BepInEx,
+        DownloadArtifact::MetaMystiaDll,
+        DownloadArtifact::ResourceEx,
+        DownloadArtifact::Manager,
+    ];
+
+    #[test]
+    fn download_policy_for_each_source_and_artifact() {
+        for source in SOURCES {
+            for artifact in ARTIFACTS {
+                let policy = resolve_download_policy(source, artifact, None);
+
+                let expected_rate_limit = match (source, artifact) {
+                    (DownloadSource::Mirror, DownloadArtifact::Manager) => None,
+                    (DownloadSource::Mirror, _) => Some(RATE_LIMIT),
+                    _ => None,
+                };
+                let expected_profile = if source == DownloadSource::BepInExPrimary {
+                    RetryProfile::Metadata
+                } else {
+                    RetryProfile::Download
+                };
+
+                assert_eq!(
+                    policy,
+                    DownloadPolicy {
+                        rate_limit: expected_rate_limit,
+                        retry_profile: expected_profile,
+                    },
+                    "{:?} / {:?}",
+                    source,
+                    artifact
+                );
             }
         }
     }
+
+    #[test]
+    fn rate_limit_override_applies_to_every_source() {
+        for source in SOURCES {
+            for artifact in ARTIFACTS {
+                let limited = resolve_download_policy(source, artifact, Some(64));
+                assert_eq!(limited.rate_limit, Some(64 * 1024));
+
+                let unlimited = resolve_download_policy(source, artifact, Some(0));
+                assert_eq!(unlimited.rate_limit, None);
+
+                // 限速覆盖不影响重试策略
+                assert_eq!(
+                    limited.retry_profile,
+                    resolve_download_policy(source, artifact, None).retry_profile
+                );
+            }
+        }
+    }
+
+    /// 构造包含指定文件的 ZIP
+    fn zip_bytes(names: &[&str]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for name in names {
+            let options =
+                SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(&[0x5a; 16 * 1024]).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn failed_attempt_leaves_nothing_behind_for_the_next_source() {
+        let dir = ScratchDir::new("download-restage");
+        let staging = dir.path().join("bepinex");
+        let ui = TestUI::new();
+        let downloader = Downloader::with_client(Client::new(), &ui);
+        let policy = DownloadPolicy {
+            rate_limit: None,
+            retry_profile: RetryProfile::Download,
+        };
+
+        let first = zip_bytes(&["BepInEx/core/Primary.dll", "primary_only.txt"]);
+        // 第一个文件完整写入，第二个文件在中途断开
+        let truncated = &first[..first.len() * 3 / 4];
+        assert!(
+            downloader
+                .extract_response(Cursor::new(truncated), None, &staging, &[], policy)
+                .is_err()
+        );
+        assert!(staging.join("BepInEx/core/Primary.dll").exists());
+
+        let second = zip_bytes(&["BepInEx/core/Mirror.dll", "winhttp.dll"]);
+        let (files, _) = downloader
+            .extract_response(Cursor::new(second), None, &staging, &[], policy)
+            .unwrap();
+        assert_eq!(files.len(), 2);
+
+        let mut remaining: Vec<_> = walkdir(&staging)
+            .into_iter()
+            .map(|p| p.strip_prefix(&staging).unwrap().to_path_buf())
+            .collect();
+        remaining.sort();
+        assert_eq!(
+            remaining,
+            vec![
+                PathBuf::from("BepInEx/core/Mirror.dll"),
+                PathBuf::from("winhttp.dll")
+            ]
+        );
+    }
+
+    /// 列出目录下的全部文件
+    fn walkdir(dir: &Path) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                files.extend(walkdir(&path));
+            } else {
+                files.push(path);
+            }
+        }
+        files
+    }
+
+    #[test]
+    fn progress_updates_are_throttled_for_large_downloads() {
+        const TOTAL: u64 = 32 * 1024 * 1024;
+
+        let dir = ScratchDir::new("download-progress");
+        let ui = TestUI::new();
+        let downloader = Downloader::with_client(Client::new(), &ui);
+        let policy = DownloadPolicy {
+            rate_limit: None,
+            retry_profile: RetryProfile::Download,
+        };
+
+        let start = Instant::now();
+        let mut response = Cursor::new(vec![0u8; TOTAL as usize]);
+        let written = downloader
+            .write_response_to_file(&mut response, &dir.path().join("large.zip"), 7, policy)
+            .unwrap();
+        let elapsed = start.elapsed();
+        assert_eq!(written, TOTAL);
+
+        let calls = ui.calls();
+        let updates: Vec<u64> = calls
+            .iter()
+            .filter_map(|c| match c {
+                UiCall::DownloadUpdate(7, n) => Some(*n),
+                _ => None,
+            })
+            .collect();
+
+        // 每 256KB 或每 100ms 最多一次，另加最终进度
+        let by_bytes = TOTAL / PROGRESS_UPDATE_BYTES;
+        let by_time = elapsed.as_millis() as u64 / PROGRESS_UPDATE_INTERVAL.as_millis() as u64;
+        assert!(
+            updates.len() as u64 <= by_bytes + by_time + 1,
+            "{} updates in {:?}",
+            updates.len(),
+            elapsed
+        );
+        assert!(updates.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(updates.last(), Some(&TOTAL));
+
+        // 最终进度在完成之前报告
+        let last_update = calls
+            .iter()
+            .rposition(|c| matches!(c, UiCall::DownloadUpdate(..)))
+            .unwrap();
+        let finish = calls
+            .iter()
+            .position(|c| matches!(c, UiCall::DownloadFinish(7, _)))
+            .unwrap();
+        assert!(last_update < finish);
+    }
+
+    /// 按顺序返回预设响应的本地 HTTP 服务器（每个连接处理一个请求）
+    struct MockServer {
+        base: String,
+        requests: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl MockServer {
+        fn start(responses: Vec<String>) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let base = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(Mutex::new(Vec::new()));
+
+            let log = Arc::clone(&requests);
+            std::thread::spawn(move || {
+                for response in responses {
+                    let Ok((mut stream, _)) = listener.accept() else {
+                        return;
+                    };
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                    let mut request_line = String::new();
+                    reader.read_line(&mut request_line).unwrap();
+                    log.lock()
+                        .unwrap()
+                        .push(request_line.trim_end().to_string());
+
+                    // 跳过其余请求头
+                    let mut header = String::new();
+                    while reader.read_line(&mut header).unwrap_or(0) > 2 {
+                        header.clear();
+                    }
+
+                    let _ = stream.write_all(response.as_bytes());
+                }
+            });
+
+            Self { base, requests }
+        }
+
+        /// 已收到的请求行（如 "GET /version HTTP/1.1"）
+        fn requests(&self) -> Vec<String> {
+            self.requests.lock().unwrap().clone()
+        }
+
+        fn downloader<'a>(&self, ui: &'a dyn Ui) -> Downloader<'a> {
+            let client = Client::builder().no_proxy().build().unwrap();
+            Downloader::with_client(client, ui).with_local_endpoints(&self.base)
+        }
+    }
+
+    fn http_response(status: &str, headers: &[(&str, &str)], body: &str) -> String {
+        let mut response = format!(
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+            status,
+            body.len()
+        );
+        for (name, value) in headers {
+            response.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        response.push_str("\r\n");
+        response.push_str(body);
+        response
+    }
+
+    #[test]
+    fn malformed_version_info_is_rejected() {
+        let server = MockServer::start(vec![http_response("200 OK", &[], "{\"dll\": [")]);
+        let ui = TestUI::new();
+        let downloader = server.downloader(&ui);
+
+        let err = downloader.try_get_version_info().unwrap_err();
+        assert!(matches!(err, ManagerError::InvalidVersionInfo), "{}", err);
+        assert!(ui.assert_called(UiCallKind::DownloadVersionInfoParseFailed));
+        assert_eq!(server.requests(), ["GET /version HTTP/1.1"]);
+    }
+
+    #[test]
+    fn server_errors_are_retried_and_redirects_followed() {
+        configure_retry_overrides(None, Some(0));
+        let server = MockServer::start(vec![
+            http_response("500 Internal Server Error", &[], ""),
+            http_response("302 Found", &[("Location", "/s/AbC123")], ""),
+            http_response("200 OK", &[], "ok"),
+        ]);
+        let ui = TestUI::new();
+        let downloader = server.downloader(&ui);
+
+        assert_eq!(downloader.get_share_code().unwrap(), "AbC123");
+        assert_eq!(
+            server.requests(),
+            [
+                "GET /getMetaMystia HTTP/1.1",
+                "GET /getMetaMystia HTTP/1.1",
+                "GET /s/AbC123 HTTP/1.1",
+            ]
+        );
+        assert!(ui.assert_called(UiCallKind::NetworkRetrying));
+    }
+
+    #[test]
+    fn rate_limited_requests_wait_for_retry_after() {
+        configure_retry_overrides(None, Some(0));
+        let server = MockServer::start(vec![
+            http_response("429 Too Many Requests", &[("Retry-After", "1")], ""),
+            http_response(
+                "200 OK",
+                &[("Content-Type", "application/json")],
+                r#"{"resources":{"core":{"remaining":42,"reset":1700000000}}}"#,
+            ),
+        ]);
+        let ui = TestUI::new();
+        let downloader = server.downloader(&ui);
+
+        let start = Instant::now();
+        let limit = downloader.check_github_rate_limit().unwrap();
+        assert!(start.elapsed() >= Duration::from_secs(1));
+        assert_eq!(limit.remaining, 42);
+        assert_eq!(server.requests().len(), 2);
+        assert!(
+            ui.calls()
+                .iter()
+                .any(|c| matches!(c, UiCall::NetworkRateLimited(1)))
+        );
+    }
 }
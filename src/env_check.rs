@@ -1,18 +1,35 @@
-use crate::config::{GAME_EXECUTABLE, GAME_PROCESS_NAME, GAME_STEAM_APP_ID};
+use crate::config::{
+    GAME_DATA_DIR, GAME_EXECUTABLE, GAME_EXECUTABLE_MIN_SIZE, GAME_PROCESS_NAME, GAME_STEAM_APP_ID,
+};
 use crate::error::{ManagerError, Result};
-use crate::metrics::report_event;
+use crate::file_ops::{LONG_PATHS_DOC_URL, check_path_length, filesystem_type, glob_matches};
+use crate::metrics::{UserIdSource, report_event, user_id_source};
+use crate::permission::is_elevated;
+use crate::prefs::Preferences;
 use crate::ui::Ui;
+use crate::updater::run_dir_write_problem;
 
-use std::path::PathBuf;
+use serde::Serialize;
+use std::os::windows::fs::MetadataExt;
+use std::os::windows::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use steamlocate::SteamDir;
 use windows::Win32::Foundation::{CloseHandle, HANDLE};
 use windows::Win32::System::Diagnostics::ToolHelp::{
     CreateToolhelp32Snapshot, PROCESSENTRY32W, Process32FirstW, Process32NextW, TH32CS_SNAPPROCESS,
 };
+use windows::Win32::System::Threading::{
+    CREATE_NO_WINDOW, GetProcessTimes, OpenProcess, PROCESS_NAME_WIN32,
+    PROCESS_QUERY_LIMITED_INFORMATION, QueryFullProcessImageNameW,
+};
+use windows::core::PWSTR;
 
-struct SnapshotHandle(HANDLE);
+/// drop 时自动关闭的句柄
+struct OwnedHandle(HANDLE);
 
-impl SnapshotHandle {
+impl OwnedHandle {
     fn new(handle: HANDLE) -> Self {
         Self(handle)
     }
@@ -22,7 +39,7 @@ impl SnapshotHandle {
     }
 }
 
-impl Drop for SnapshotHandle {
+impl Drop for OwnedHandle {
     fn drop(&mut self) {
         unsafe {
             let _ = CloseHandle(self.0);
@@ -30,37 +47,219 @@ impl Drop for SnapshotHandle {
     }
 }
 
-/// 检查游戏根目录
-pub fn check_game_directory(ui: &dyn Ui) -> Result<PathBuf> {
+/// 游戏目录来源
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathSource {
+    /// 通过 Steam 检测到（附带所在的 Steam 库目录）
+    Steam(String),
+    /// 当前目录
+    CurrentDirectory,
+    /// 通过 --path 指定
+    Specified,
+}
+
+impl PathSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PathSource::Steam(_) => "steam",
+            PathSource::CurrentDirectory => "current_dir",
+            PathSource::Specified => "specified",
+        }
+    }
+}
+
+/// 检查目录是否为完整的游戏安装（可执行文件大小正常且数据目录存在）
+fn is_complete_game_dir(dir: &Path) -> bool {
+    let exe_ok = std::fs::metadata(dir.join(GAME_EXECUTABLE))
+        .map(|m| m.is_file() && m.len() >= GAME_EXECUTABLE_MIN_SIZE)
+        .unwrap_or(false);
+
+    exe_ok && dir.join(GAME_DATA_DIR).is_dir()
+}
+
+/// 在用户输入的目录中查找游戏：可以是游戏目录本身，也可以是 Steam 库根目录
+fn resolve_manual_path(input: &Path) -> Option<(PathBuf, PathSource)> {
+    if input.join(GAME_EXECUTABLE).is_file() {
+        return Some((input.to_path_buf(), PathSource::Specified));
+    }
+
+    let common_dir = input.join("steamapps").join("common");
+    std::fs::read_dir(&common_dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|dir| dir.join(GAME_EXECUTABLE).is_file())
+        .map(|dir| (dir, PathSource::Steam(input.display().to_string())))
+}
+
+/// 询问用户手动指定目录，找到后确认并保存到偏好设置
+fn ask_manual_game_directory(ui: &dyn Ui) -> Result<Option<(PathBuf, PathSource)>> {
+    let mut prefs = Preferences::load();
+
+    // 优先提供上次手动指定的目录
+    if let Some(saved) = prefs.game_path.clone()
+        && saved.join(GAME_EXECUTABLE).is_file()
+    {
+        ui.path_display_steam_found(GAME_STEAM_APP_ID, None, &saved)?;
+        if ui.path_confirm_use_steam_found()? {
+            ui.blank_line()?;
+            report_event("Env.SavedPathUsed", Some(&saved.display().to_string()));
+            return Ok(Some((saved, PathSource::Specified)));
+        }
+        ui.blank_line()?;
+    }
+
+    while let Some(input) = ui.path_ask_manual_entry()? {
+        let Some((candidate, source)) = resolve_manual_path(&input) else {
+            ui.path_manual_entry_not_found(&input)?;
+            report_event("Env.ManualPathNotFound", None);
+            continue;
+        };
+
+        ui.path_display_steam_found(GAME_STEAM_APP_ID, None, &candidate)?;
+        if ui.path_confirm_use_steam_found()? {
+            ui.blank_line()?;
+            report_event(
+                "Env.ManualPathFound",
+                Some(&format!(
+                    "{};source={}",
+                    candidate.display(),
+                    source.as_str()
+                )),
+            );
+
+            prefs.game_path = Some(candidate.clone());
+            if let Err(e) = prefs.save() {
+                report_event("Env.ManualPathSaveFailed", Some(&format!("{}", e)));
+            }
+
+            return Ok(Some((candidate, source)));
+        }
+        ui.blank_line()?;
+    }
+
+    Ok(None)
+}
+
+/// 检查游戏根目录，确认目录中没有容易混淆的其他游戏程序，并提示云同步干扰
+pub fn check_game_directory(ui: &dyn Ui) -> Result<(PathBuf, PathSource)> {
+    let found = locate_game_directory(ui)?;
+    if check_path_length(&found.0, "定位游戏目录")? {
+        ui.warn(&format!(
+            "游戏目录路径较长，安装的文件可能超出 Windows 路径长度限制。可参考 {} 启用长路径支持。",
+            LONG_PATHS_DOC_URL
+        ))?;
+    }
+    confirm_game_executables(ui, &found.0)?;
+    warn_cloud_sync(ui, &found.0)?;
+    Ok(found)
+}
+
+/// 查找游戏根目录（不检查目录内容，也不提示云同步，供只读查询使用）
+pub fn locate_game_directory(ui: &dyn Ui) -> Result<(PathBuf, PathSource)> {
+    // Steam 上找到的完整安装目录
+    let mut steam_found = None;
+    // 疑似残留的 Steam 目录（游戏可能已被第三方工具移动），仅在找不到其他目录时提供
+    let mut stale_candidate = None;
+
     if let Ok(steam_dir) = SteamDir::locate()
         && let Ok(Some((app, library))) = steam_dir.find_app(GAME_STEAM_APP_ID)
     {
-        let install_dir = app.install_dir;
-        let candidate = library
-            .path()
-            .join("steamapps")
-            .join("common")
-            .join(&install_dir);
-        if candidate.join(GAME_EXECUTABLE).is_file() {
-            ui.path_display_steam_found(app.app_id, app.name.as_deref(), &candidate)?;
-            if ui.path_confirm_use_steam_found()? {
-                ui.blank_line()?;
-                report_event("Env.SteamFound", Some(&candidate.display().to_string()));
-                return Ok(candidate);
-            } else {
-                ui.blank_line()?;
+        let common_dir = |lib: &Path| lib.join("steamapps").join("common").join(&app.install_dir);
+
+        // 先检查 appmanifest 所在的库，再检查其他库中的同名目录
+        let mut libraries = vec![library.path().to_path_buf()];
+        if let Ok(others) = steam_dir.libraries() {
+            for lib in others.flatten() {
+                if !libraries.iter().any(|l| l == lib.path()) {
+                    libraries.push(lib.path().to_path_buf());
+                }
+            }
+        }
+
+        let mut found_path = None;
+        let mut stale_path = None;
+        for lib in libraries {
+            let candidate = common_dir(&lib);
+            if !candidate.join(GAME_EXECUTABLE).is_file() {
+                continue;
+            }
+
+            if is_complete_game_dir(&candidate) {
+                found_path = Some((candidate, lib));
+                break;
+            }
+
+            report_event("Env.SteamStale", Some(&candidate.display().to_string()));
+            if stale_path.is_none() {
+                stale_path = Some((candidate, lib));
             }
         }
+
+        if let Some((candidate, lib)) = found_path {
+            steam_found = Some((app.app_id, app.name, candidate, lib));
+        } else {
+            stale_candidate = stale_path.map(|(path, lib)| (app.app_id, app.name, path, lib));
+        }
     }
 
     let current_dir = std::env::current_dir()?;
-    let game_exe = current_dir.join(GAME_EXECUTABLE);
-    if game_exe.is_file() {
+    let current_dir_valid = current_dir.join(GAME_EXECUTABLE).is_file();
+
+    if let Some((app_id, name, candidate, lib)) = steam_found {
+        let steam_source = PathSource::Steam(lib.display().to_string());
+
+        // Steam 目录与当前目录都是有效的游戏目录时，由用户选择
+        if current_dir_valid && candidate != current_dir {
+            report_event("Env.MultipleCandidates", None);
+
+            let candidates = vec![
+                (candidate, steam_source),
+                (current_dir, PathSource::CurrentDirectory),
+            ];
+            let chosen = ui.select_game_path_from_candidates(&candidates)?;
+            ui.blank_line()?;
+
+            let found = candidates
+                .into_iter()
+                .find(|(path, _)| *path == chosen)
+                .ok_or(ManagerError::GameNotFound)?;
+            report_event("Env.CandidateSelected", Some(found.1.as_str()));
+            return Ok(found);
+        }
+
+        ui.path_display_steam_found(app_id, name.as_deref(), &candidate)?;
+        if ui.path_confirm_use_steam_found()? {
+            ui.blank_line()?;
+            report_event("Env.SteamFound", Some(&candidate.display().to_string()));
+            return Ok((candidate, steam_source));
+        }
+        ui.blank_line()?;
+    }
+
+    if current_dir_valid {
         report_event(
             "Env.CurrentDirFound",
             Some(&current_dir.display().to_string()),
         );
-        return Ok(current_dir);
+        return Ok((current_dir, PathSource::CurrentDirectory));
+    }
+
+    if let Some((app_id, name, candidate, lib)) = stale_candidate {
+        ui.path_display_steam_stale(app_id, name.as_deref(), &candidate)?;
+        if ui.path_confirm_use_steam_found()? {
+            ui.blank_line()?;
+            report_event(
+                "Env.SteamStaleAccepted",
+                Some(&candidate.display().to_string()),
+            );
+            return Ok((candidate, PathSource::Steam(lib.display().to_string())));
+        }
+        ui.blank_line()?;
+    }
+
+    if let Some(found) = ask_manual_game_directory(ui)? {
+        return Ok(found);
     }
 
     report_event("Env.GameNotFound", None);
@@ -68,14 +267,209 @@ pub fn check_game_directory(ui: &dyn Ui) -> Result<PathBuf> {
     Err(ManagerError::GameNotFound)
 }
 
-/// 检查游戏进程是否正在运行
-pub fn check_game_running() -> Result<bool> {
+/// 与游戏一同发布的 Unity 组件（小写）
+const KNOWN_COMPANION_EXES: &[&str] = &["unitycrashhandler64.exe", "unitycrashhandler32.exe"];
+
+/// 列出目录中的所有可执行文件
+pub fn scan_for_game_executables(dir: &Path) -> Vec<PathBuf> {
+    glob_matches(&dir.join("*.exe"))
+}
+
+/// 目录中还有其他程序时（如备份副本、启动器或其他游戏），提示用户确认目录是否正确
+pub fn confirm_game_executables(ui: &dyn Ui, dir: &Path) -> Result<()> {
+    if !dir.join(GAME_EXECUTABLE).is_file() {
+        return Ok(());
+    }
+
+    let extras: Vec<PathBuf> = scan_for_game_executables(dir)
+        .into_iter()
+        .filter(|p| {
+            let name = p
+                .file_name()
+                .map(|n| n.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            name != GAME_EXECUTABLE.to_lowercase()
+                && !KNOWN_COMPANION_EXES.contains(&name.as_str())
+                && !name.starts_with("meta-mystia-manager")
+        })
+        .collect();
+    if extras.is_empty() {
+        return Ok(());
+    }
+
+    report_event("Env.ConflictingExe", Some(&extras.len().to_string()));
+    if !ui.warn_conflicting_game_exe(&extras)? {
+        return Err(ManagerError::UserCancelled);
+    }
+
+    Ok(())
+}
+
+/// 云同步客户端在占位文件与固定文件上设置的属性
+const CLOUD_FILE_ATTRIBUTES: u32 = 0x0004_0000 // FILE_ATTRIBUTE_RECALL_ON_OPEN
+    | 0x0008_0000 // FILE_ATTRIBUTE_PINNED
+    | 0x0010_0000 // FILE_ATTRIBUTE_UNPINNED
+    | 0x0040_0000; // FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS
+
+/// 查询注册表中的字符串值（`reg query` 的附加参数由调用方指定），返回所有匹配的值
+fn reg_query_strings(key: &str, args: &[&str]) -> Vec<String> {
+    let Ok(out) = Command::new("reg")
+        .arg("query")
+        .arg(key)
+        .args(args)
+        .creation_flags(CREATE_NO_WINDOW.0)
+        .output()
+    else {
+        return Vec::new();
+    };
+    if !out.status.success() {
+        return Vec::new();
+    }
+
+    String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .filter_map(|line| line.split_once("REG_SZ"))
+        .map(|(_, value)| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+/// OneDrive 同步根目录（环境变量与各账户的注册表记录）
+fn onedrive_roots() -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = ["OneDrive", "OneDriveConsumer", "OneDriveCommercial"]
+        .iter()
+        .filter_map(std::env::var_os)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .collect();
+
+    roots.extend(
+        reg_query_strings(
+            r"HKCU\Software\Microsoft\OneDrive\Accounts",
+            &["/s", "/v", "UserFolder"],
+        )
+        .into_iter()
+        .map(PathBuf::from),
+    );
+
+    roots
+}
+
+/// Dropbox 同步根目录（info.json 中各账户的 path）
+fn dropbox_roots() -> Vec<PathBuf> {
+    ["APPDATA", "LOCALAPPDATA"]
+        .iter()
+        .filter_map(std::env::var_os)
+        .filter_map(|dir| {
+            std::fs::read_to_string(PathBuf::from(dir).join("Dropbox").join("info.json")).ok()
+        })
+        .filter_map(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+        .flat_map(|json| {
+            json.as_object()
+                .map(|accounts| {
+                    accounts
+                        .values()
+                        .filter_map(|account| account["path"].as_str().map(PathBuf::from))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Google Drive 挂载点（DriveFS 注册表中各账户的 mount_point_path）
+fn google_drive_roots() -> Vec<PathBuf> {
+    reg_query_strings(
+        r"HKCU\Software\Google\DriveFS",
+        &["/v", "PerAccountPreferences"],
+    )
+    .into_iter()
+    .filter_map(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+    .flat_map(|json| {
+        json["per_account_preferences"]
+            .as_array()
+            .map(|accounts| {
+                accounts
+                    .iter()
+                    .filter_map(|account| account["value"]["mount_point_path"].as_str())
+                    .map(|mount| {
+                        // 挂载点可能只记录盘符（如 "G"）
+                        if mount.len() == 1 {
+                            PathBuf::from(format!("{}:\\", mount))
+                        } else {
+                            PathBuf::from(mount)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    })
+    .collect()
+}
+
+/// 路径本身或其上级目录是否带有云同步客户端使用的属性
+fn has_cloud_file_attributes(path: &Path) -> bool {
+    path.ancestors()
+        .filter_map(|p| std::fs::metadata(p).ok())
+        .any(|m| m.file_attributes() & CLOUD_FILE_ATTRIBUTES != 0)
+}
+
+/// 检测路径是否位于云同步目录中，返回同步服务名称
+pub fn detect_cloud_sync(path: &Path) -> Option<&'static str> {
+    let lower = PathBuf::from(path.to_string_lossy().to_lowercase());
+    let under = |roots: Vec<PathBuf>| {
+        roots
+            .iter()
+            .any(|root| lower.starts_with(root.to_string_lossy().to_lowercase()))
+    };
+
+    if under(onedrive_roots()) {
+        Some("OneDrive")
+    } else if under(dropbox_roots()) {
+        Some("Dropbox")
+    } else if under(google_drive_roots()) {
+        Some("Google Drive")
+    } else if has_cloud_file_attributes(path) {
+        Some("Cloud Files")
+    } else {
+        None
+    }
+}
+
+/// 游戏目录位于云同步目录中时提示可能的干扰（仅提示一次）
+pub fn warn_cloud_sync(ui: &dyn Ui, dir: &Path) -> Result<()> {
+    let mut prefs = Preferences::load();
+    if prefs.cloud_sync_warned {
+        return Ok(());
+    }
+
+    let Some(provider) = detect_cloud_sync(dir) else {
+        return Ok(());
+    };
+
+    report_event("Env.CloudSync", Some(provider));
+    ui.warn_cloud_sync_directory(provider)?;
+
+    prefs.cloud_sync_warned = true;
+    let _ = prefs.save();
+
+    Ok(())
+}
+
+/// 获取进程快照（进程 ID，可执行文件名）
+/// 解码进程快照条目中以 NUL 结尾的 UTF-16 进程名（没有结尾时使用整个缓冲区）
+fn process_entry_name(buf: &[u16]) -> String {
+    let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+    String::from_utf16_lossy(&buf[..len])
+}
+
+fn snapshot_processes(event_prefix: &str) -> Result<Vec<(u32, String)>> {
     unsafe {
         let snapshot_handle = match CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) {
-            Ok(handle) => SnapshotHandle::new(handle),
+            Ok(handle) => OwnedHandle::new(handle),
             Err(e) => {
                 report_event(
-                    "Env.GameRunning.CheckFailed.CreateToolhelp32Snapshot",
+                    &format!("{}.CheckFailed.CreateToolhelp32Snapshot", event_prefix),
                     Some(&format!("{:?}", e)),
                 );
                 return Err(ManagerError::ProcessListError(format!(
@@ -95,7 +489,7 @@ pub fn check_game_running() -> Result<bool> {
             Ok(()) => {}
             Err(e) => {
                 report_event(
-                    "Env.GameRunning.CheckFailed.Process32FirstW",
+                    &format!("{}.CheckFailed.Process32FirstW", event_prefix),
                     Some(&format!("{:?}", e)),
                 );
                 return Err(ManagerError::ProcessListError(format!(
@@ -105,27 +499,348 @@ pub fn check_game_running() -> Result<bool> {
             }
         }
 
-        let target = GAME_PROCESS_NAME.to_lowercase();
+        let mut processes = Vec::new();
 
         loop {
-            let process_name = String::from_utf16_lossy(
-                &entry.szExeFile[..entry
-                    .szExeFile
-                    .iter()
-                    .position(|&c| c == 0)
-                    .unwrap_or(entry.szExeFile.len())],
-            );
-
-            if process_name.to_lowercase() == target {
-                report_event("Env.GameRunning", None);
-                return Ok(true);
-            }
+            processes.push((entry.th32ProcessID, process_entry_name(&entry.szExeFile)));
 
             if Process32NextW(snapshot, &mut entry).is_err() {
                 break;
             }
         }
 
-        Ok(false)
+        Ok(processes)
+    }
+}
+
+/// 正在运行的游戏相关进程
+#[derive(Clone, Debug)]
+pub struct GameProcess {
+    pub pid: u32,
+    pub name: String,
+    /// 进程启动时间（无法查询时为 None）
+    pub started_at: Option<SystemTime>,
+}
+
+impl GameProcess {
+    /// 是否为 Unity 崩溃处理程序（游戏崩溃后可能继续占用文件）
+    pub fn is_crash_handler(&self) -> bool {
+        KNOWN_COMPANION_EXES.contains(&self.name.to_lowercase().as_str())
+    }
+}
+
+/// FILETIME 起点（1601-01-01）与 UNIX 纪元之间的秒数
+const FILETIME_UNIX_EPOCH_OFFSET_SECS: u64 = 11_644_473_600;
+
+/// 查询进程的可执行文件路径与启动时间
+fn query_process_details(pid: u32) -> (Option<PathBuf>, Option<SystemTime>) {
+    unsafe {
+        let Ok(handle) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) else {
+            return (None, None);
+        };
+        let handle = OwnedHandle::new(handle);
+
+        let mut buf = [0u16; 1024];
+        let mut len = buf.len() as u32;
+        let image_path = QueryFullProcessImageNameW(
+            handle.as_raw(),
+            PROCESS_NAME_WIN32,
+            PWSTR(buf.as_mut_ptr()),
+            &mut len,
+        )
+        .ok()
+        .map(|()| PathBuf::from(String::from_utf16_lossy(&buf[..len as usize])));
+
+        let (mut created, mut exited, mut kernel, mut user) = Default::default();
+        let started_at = GetProcessTimes(
+            handle.as_raw(),
+            &mut created,
+            &mut exited,
+            &mut kernel,
+            &mut user,
+        )
+        .ok()
+        .and_then(|()| {
+            let ticks = ((created.dwHighDateTime as u64) << 32) | created.dwLowDateTime as u64;
+            let secs = (ticks / 10_000_000).checked_sub(FILETIME_UNIX_EPOCH_OFFSET_SECS)?;
+            Some(UNIX_EPOCH + Duration::from_secs(secs))
+        });
+
+        (image_path, started_at)
+    }
+}
+
+/// 进程名是否为游戏或 Unity 崩溃处理程序
+fn is_game_process_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name == GAME_PROCESS_NAME.to_lowercase() || KNOWN_COMPANION_EXES.contains(&name.as_str())
+}
+
+/// 列出所有正在运行的游戏进程与游戏目录中的崩溃处理程序
+pub fn check_game_running() -> Result<Vec<GameProcess>> {
+    check_game_running_with(
+        &|| snapshot_processes("Env.GameRunning"),
+        &query_process_details,
+    )
+}
+
+/// 使用指定的进程列表与进程信息来源查找游戏相关进程
+fn check_game_running_with(
+    process_provider: &dyn Fn() -> Result<Vec<(u32, String)>>,
+    details_provider: &dyn Fn(u32) -> (Option<PathBuf>, Option<SystemTime>),
+) -> Result<Vec<GameProcess>> {
+    let mut found = Vec::new();
+
+    for (pid, name) in process_provider()? {
+        if !is_game_process_name(&name) {
+            continue;
+        }
+
+        let (image_path, started_at) = details_provider(pid);
+        let process = GameProcess {
+            pid,
+            name,
+            started_at,
+        };
+
+        // 崩溃处理程序是 Unity 通用组件，仅计入位于游戏目录中的实例
+        if process.is_crash_handler()
+            && !image_path
+                .as_deref()
+                .and_then(Path::parent)
+                .is_some_and(|dir| dir.join(GAME_EXECUTABLE).is_file())
+        {
+            continue;
+        }
+
+        found.push(process);
+    }
+
+    if !found.is_empty() {
+        report_event("Env.GameRunning", Some(&found.len().to_string()));
+    }
+
+    Ok(found)
+}
+
+/// 检查指定 PID 的进程是否仍在运行
+pub fn is_process_alive(pid: u32) -> Result<bool> {
+    Ok(snapshot_processes("Env.ProcessAlive")?
+        .iter()
+        .any(|(id, _)| *id == pid))
+}
+
+/// 从 Windows Defender 威胁记录中查找指定文件的检测名称（尽力而为，失败时返回 None）
+pub fn query_defender_detection(path: &Path) -> Option<String> {
+    let escaped = path.display().to_string().replace('\'', "''");
+    let script = format!(
+        "Get-MpThreatDetection | Where-Object {{ $_.Resources -like '*{}*' }} | Select-Object -Last 1 | ForEach-Object {{ (Get-MpThreat -ThreatID $_.ThreatID).ThreatName }}",
+        escaped
+    );
+
+    let out = Command::new("powershell.exe")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .creation_flags(CREATE_NO_WINDOW.0)
+        .output()
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+
+    let name = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    if name.is_empty() { None } else { Some(name) }
+}
+
+/// 运行环境信息，用于远程排查权限问题
+#[derive(Clone, Debug, Serialize)]
+pub struct EnvironmentReport {
+    /// 当前进程是否具有管理员权限
+    pub elevated: bool,
+    /// 游戏根目录（未找到时为 None）
+    pub game_root: Option<PathBuf>,
+    /// 游戏根目录是否位于 Program Files、Windows 等受保护目录下
+    pub protected_path: bool,
+    /// 游戏所在卷的文件系统类型
+    pub filesystem: Option<String>,
+    /// 管理工具运行目录的写入探测是否成功
+    pub run_dir_writable: bool,
+    /// 写入探测失败的原因
+    pub run_dir_write_problem: Option<String>,
+    /// 游戏根目录所在的云同步服务
+    pub cloud_sync: Option<String>,
+    /// 偏好设置文件路径
+    pub preferences_path: Option<PathBuf>,
+    /// 匿名用户 ID 的来源
+    pub user_id_source: UserIdSource,
+}
+
+/// 受 UAC 保护、普通权限无法写入的系统目录
+fn protected_dirs() -> Vec<PathBuf> {
+    [
+        "ProgramFiles",
+        "ProgramFiles(x86)",
+        "ProgramW6432",
+        "SystemRoot",
+    ]
+    .iter()
+    .filter_map(std::env::var_os)
+    .filter(|v| !v.is_empty())
+    .map(|v| PathBuf::from(v.to_string_lossy().to_lowercase()))
+    .collect()
+}
+
+/// 检查路径是否位于受保护的系统目录下
+pub fn is_protected_path(path: &Path) -> bool {
+    let path = PathBuf::from(path.to_string_lossy().to_lowercase());
+    protected_dirs().iter().any(|dir| path.starts_with(dir))
+}
+
+/// 收集运行环境信息
+pub fn environment_report(game_root: Option<&Path>) -> EnvironmentReport {
+    let run_dir_write_problem = run_dir_write_problem();
+
+    EnvironmentReport {
+        elevated: is_elevated().unwrap_or(false),
+        game_root: game_root.map(Path::to_path_buf),
+        protected_path: game_root.is_some_and(is_protected_path),
+        filesystem: game_root.and_then(filesystem_type),
+        cloud_sync: game_root.and_then(detect_cloud_sync).map(str::to_string),
+        run_dir_writable: run_dir_write_problem.is_none(),
+        run_dir_write_problem,
+        preferences_path: Preferences::path(),
+        user_id_source: user_id_source(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::ScratchDir;
+
+    fn no_details(_pid: u32) -> (Option<PathBuf>, Option<SystemTime>) {
+        (None, None)
+    }
+
+    /// 构造与 PROCESSENTRY32W::szExeFile 相同大小的进程名缓冲区，名称之后填充残留数据
+    fn exe_file_buffer(name: &str) -> [u16; 260] {
+        let mut buf = [0x41u16; 260];
+        let encoded: Vec<u16> = name.encode_utf16().collect();
+        buf[..encoded.len()].copy_from_slice(&encoded);
+        buf[encoded.len()] = 0;
+        buf
+    }
+
+    #[test]
+    fn process_entry_name_stops_at_terminator() {
+        assert_eq!(
+            process_entry_name(&exe_file_buffer(GAME_PROCESS_NAME)),
+            GAME_PROCESS_NAME
+        );
+        assert_eq!(process_entry_name(&exe_file_buffer("")), "");
+        assert_eq!(
+            process_entry_name(&exe_file_buffer("东方夜雀食堂.exe")),
+            "东方夜雀食堂.exe"
+        );
+
+        let unterminated: Vec<u16> = "abc.exe".encode_utf16().collect();
+        assert_eq!(process_entry_name(&unterminated), "abc.exe");
+    }
+
+    #[test]
+    fn game_process_names_match_from_entry_buffers() {
+        for name in [
+            GAME_PROCESS_NAME,
+            "touhou mystia izakaya.EXE",
+            "UnityCrashHandler64.exe",
+            "UNITYCRASHHANDLER32.EXE",
+        ] {
+            assert!(
+                is_game_process_name(&process_entry_name(&exe_file_buffer(name))),
+                "{}",
+                name
+            );
+        }
+
+        for name in [
+            "explorer.exe",
+            "Touhou Mystia Izakaya",
+            "Touhou Mystia Izakaya.exe.bak",
+            "UnityCrashHandler.exe",
+            "",
+        ] {
+            assert!(
+                !is_game_process_name(&process_entry_name(&exe_file_buffer(name))),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn finds_game_process_by_name_case_insensitively() {
+        let processes = || {
+            Ok(vec![
+                (4, "System".to_string()),
+                (100, "explorer.exe".to_string()),
+                (200, GAME_PROCESS_NAME.to_uppercase()),
+            ])
+        };
+
+        let found = check_game_running_with(&processes, &no_details).unwrap();
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].pid, 200);
+        assert!(!found[0].is_crash_handler());
+    }
+
+    #[test]
+    fn no_game_process_returns_empty_list() {
+        let processes = || Ok(vec![(100, "explorer.exe".to_string())]);
+
+        assert!(
+            check_game_running_with(&processes, &no_details)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn process_list_errors_are_propagated() {
+        let processes = || Err(ManagerError::ProcessListError("denied".to_string()));
+
+        assert!(matches!(
+            check_game_running_with(&processes, &no_details),
+            Err(ManagerError::ProcessListError(_))
+        ));
+    }
+
+    #[test]
+    fn crash_handler_counts_only_inside_game_directory() {
+        let game_dir = ScratchDir::new("crash-handler");
+        game_dir.write(GAME_EXECUTABLE, b"");
+        let other_dir = ScratchDir::new("other-unity-game");
+
+        let processes = || {
+            Ok(vec![
+                (300, "UnityCrashHandler64.exe".to_string()),
+                (301, "UnityCrashHandler64.exe".to_string()),
+            ])
+        };
+        let started = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let details = |pid: u32| {
+            let dir = if pid == 300 {
+                game_dir.path()
+            } else {
+                other_dir.path()
+            };
+            (Some(dir.join("UnityCrashHandler64.exe")), Some(started))
+        };
+
+        let found = check_game_running_with(&processes, &details).unwrap();
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].pid, 300);
+        assert!(found[0].is_crash_handler());
+        assert_eq!(found[0].started_at, Some(started));
     }
 }
@@ -1,7 +1,31 @@
+use crate::file_ops::{DeletionResult, DeletionStatus};
 use crate::metrics::report_event;
 
 use thiserror::Error;
 
+/// 单个下载源的失败记录
+#[derive(Clone, Debug)]
+pub struct SourceFailure {
+    /// 下载源名称
+    pub source: String,
+    pub url: String,
+    /// 失败分类（用于统计）
+    pub kind: &'static str,
+    /// 失败原因
+    pub reason: String,
+}
+
+impl SourceFailure {
+    pub fn new(source: &str, url: &str, err: &ManagerError) -> Self {
+        Self {
+            source: source.to_string(),
+            url: url.to_string(),
+            kind: err.kind(),
+            reason: err.to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum ManagerError {
     #[error("未在游戏根目录下运行")]
@@ -22,9 +46,24 @@ pub enum ManagerError {
     #[error("网络错误：{0}")]
     NetworkError(String),
 
+    #[error("网络错误：{0}")]
+    ConnectFailed(String),
+
+    #[error("网络错误：{0}")]
+    Timeout(String),
+
+    #[error("网络错误：{message}")]
+    HttpStatus { status: u16, message: String },
+
     #[error("被限流：{0}")]
     RateLimited(String),
 
+    #[error("需要身份验证：{0}")]
+    AuthRequired(String),
+
+    #[error("所有下载源均失败：{}", format_source_failures(.0))]
+    AllSourcesFailed(Vec<SourceFailure>),
+
     #[error("解压失败：{0}")]
     ExtractFailed(String),
 
@@ -40,10 +79,98 @@ pub enum ManagerError {
     #[error("其他错误：{0}")]
     Other(String),
 
+    #[error("部分文件删除失败：{}/{} 项", .failed.len(), .total)]
+    PartialFailure {
+        /// 重试后仍然删除失败的项目
+        failed: Vec<DeletionResult>,
+        total: usize,
+    },
+
     #[error("用户取消了操作")]
     UserCancelled,
 }
 
+/// 逐个列出各下载源的失败原因
+fn format_source_failures(failures: &[SourceFailure]) -> String {
+    failures
+        .iter()
+        .map(|f| format!("{}：{}", f.source, f.reason))
+        .collect::<Vec<_>>()
+        .join("；")
+}
+
+impl ManagerError {
+    /// 错误分类（用于统计与 JSON 输出）
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ManagerError::GameNotFound => "game_not_found",
+            ManagerError::GameRunning => "game_running",
+            ManagerError::ProcessListError(_) => "process_list",
+            ManagerError::PermissionDenied(_) => "permission_denied",
+            ManagerError::FileInUse(_) => "file_in_use",
+            ManagerError::NetworkError(_) => "network",
+            ManagerError::ConnectFailed(_) => "connect",
+            ManagerError::Timeout(_) => "timeout",
+            ManagerError::HttpStatus { .. } => "http",
+            ManagerError::RateLimited(_) => "rate_limited",
+            ManagerError::AuthRequired(_) => "auth_required",
+            ManagerError::AllSourcesFailed(_) => "all_sources_failed",
+            ManagerError::ExtractFailed(_) => "extract",
+            ManagerError::InvalidVersionInfo => "invalid_version_info",
+            ManagerError::Io(_) => "io",
+            ManagerError::Ui(_) => "ui",
+            ManagerError::Other(_) => "other",
+            ManagerError::PartialFailure { .. } => "partial_failure",
+            ManagerError::UserCancelled => "cancelled",
+        }
+    }
+
+    /// JSON 形式的错误信息（错误分类、描述及各失败项的详情）
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut error = serde_json::json!({
+            "kind": self.kind(),
+            "message": self.to_string(),
+        });
+
+        match self {
+            ManagerError::AllSourcesFailed(failures) => {
+                error["sources"] = failures
+                    .iter()
+                    .map(|f| {
+                        serde_json::json!({
+                            "source": f.source,
+                            "url": f.url,
+                            "kind": f.kind,
+                            "reason": f.reason,
+                        })
+                    })
+                    .collect();
+            }
+            ManagerError::PartialFailure { failed, total } => {
+                error["failed"] = failed.len().into();
+                error["total"] = (*total).into();
+                error["failures"] = failed
+                    .iter()
+                    .map(|r| {
+                        let reason = match &r.status {
+                            DeletionStatus::Failed(e) => e.to_string(),
+                            _ => String::new(),
+                        };
+                        serde_json::json!({
+                            "path": r.path,
+                            "kind": r.failure_kind().map(|k| k.as_str()),
+                            "error": reason,
+                        })
+                    })
+                    .collect();
+            }
+            _ => {}
+        }
+
+        error
+    }
+}
+
 impl From<dialoguer::Error> for ManagerError {
     fn from(err: dialoguer::Error) -> Self {
         let s = err.to_string();
@@ -61,3 +188,88 @@ impl From<std::io::Error> for ManagerError {
 }
 
 pub type Result<T> = std::result::Result<T, ManagerError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kind_follows_variant_not_message() {
+        let timeout = ManagerError::Timeout("连接 HTTP 服务器".to_string());
+        assert_eq!(timeout.kind(), "timeout");
+
+        let http = ManagerError::HttpStatus {
+            status: 404,
+            message: "请求超时后返回 HTTP 404".to_string(),
+        };
+        assert_eq!(http.kind(), "http");
+
+        let network = ManagerError::NetworkError("请求超时（HTTP）".to_string());
+        assert_eq!(network.kind(), "network");
+
+        assert_eq!(ManagerError::GameNotFound.kind(), "game_not_found");
+        assert_eq!(ManagerError::UserCancelled.kind(), "cancelled");
+        assert_eq!(
+            ManagerError::AllSourcesFailed(Vec::new()).kind(),
+            "all_sources_failed"
+        );
+    }
+
+    #[test]
+    fn all_sources_failed_lists_each_source() {
+        let err = ManagerError::AllSourcesFailed(vec![
+            SourceFailure::new(
+                "镜像",
+                "https://mirror.example",
+                &ManagerError::Timeout("请求超时".to_string()),
+            ),
+            SourceFailure::new(
+                "GitHub",
+                "https://github.example",
+                &ManagerError::HttpStatus {
+                    status: 502,
+                    message: "HTTP 502".to_string(),
+                },
+            ),
+        ]);
+
+        let text = err.to_string();
+        assert!(text.contains("镜像：网络错误：请求超时"), "{}", text);
+        assert!(text.contains("GitHub：网络错误：HTTP 502"), "{}", text);
+    }
+
+    #[test]
+    fn partial_failure_json_lists_each_failed_path() {
+        use std::path::PathBuf;
+        use std::sync::Arc;
+
+        let failed = |path: &str, err: ManagerError| DeletionResult {
+            path: PathBuf::from(path),
+            status: DeletionStatus::Failed(Arc::new(err)),
+            bytes_freed: None,
+        };
+        let err = ManagerError::PartialFailure {
+            failed: vec![
+                failed(
+                    "BepInEx/plugins/MetaMystia.dll",
+                    ManagerError::FileInUse("MetaMystia.dll".to_string()),
+                ),
+                failed("winhttp.dll", ManagerError::Other("未知".to_string())),
+            ],
+            total: 5,
+        };
+        assert_eq!(err.to_string(), "部分文件删除失败：2/5 项");
+
+        let json = err.to_json();
+        assert_eq!(json["kind"], "partial_failure");
+        assert_eq!(json["failed"], 2);
+        assert_eq!(json["total"], 5);
+        assert_eq!(
+            json["failures"][0]["path"],
+            "BepInEx/plugins/MetaMystia.dll"
+        );
+        assert_eq!(json["failures"][0]["kind"], "locked");
+        assert_eq!(json["failures"][1]["kind"], "other");
+        assert_eq!(json["failures"][1]["error"], "其他错误：未知");
+    }
+}
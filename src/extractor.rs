@@ -1,9 +1,17 @@
+use crate::config::RESOURCEEX_MARKER;
 use crate::error::{ManagerError, Result};
-use crate::file_ops::atomic_rename_or_copy;
+use crate::file_ops::{atomic_rename_or_copy, clear_glob_cache, write_file_atomic};
 use crate::metrics::report_event;
 
+use std::io::Read;
 use std::path::{Component, Path, PathBuf};
 use zip::ZipArchive;
+use zip::read::{ZipFile, read_zipfile_from_stream};
+
+/// 出现在 ResourceExample ZIP 中即说明下发了错误文件的条目（BepInEx 或 Mod 本体的文件）
+const RESOURCEEX_FOREIGN_ENTRIES: &[&str] = &["BepInEx/", "winhttp.dll", "doorstop_config.ini"];
+/// 压缩工具生成的无关条目
+const ZIP_JUNK_ENTRIES: &[&str] = &["__MACOSX/", ".DS_Store", "Thumbs.db"];
 
 /// 文件解压器
 pub struct Extractor;
@@ -34,6 +42,7 @@ impl Extractor {
         exclude_patterns: &[&str],
     ) -> Result<Vec<PathBuf>> {
         report_event("Extract.Start", Some(&zip_path.display().to_string()));
+        clear_glob_cache();
 
         let file = match std::fs::File::open(zip_path) {
             Ok(f) => f,
@@ -67,153 +76,255 @@ impl Extractor {
                 ManagerError::ExtractFailed(format!("读取条目失败（index {}）：{}", i, e))
             })?;
 
-            let file_path = match file.enclosed_name() {
-                Some(p) => p.to_path_buf(),
-                None => {
+            if let Some(path) = Self::extract_entry(&mut file, i, dest_dir, exclude_patterns)? {
+                extracted_files.push(path);
+            }
+        }
+
+        report_event(
+            "Extract.Success",
+            Some(&format!("count:{}", extracted_files.len())),
+        );
+
+        Ok(extracted_files)
+    }
+
+    /// 从不可回退的数据流（如 HTTP 响应）中边读取边解压，不需要先保存 ZIP 文件
+    pub fn extract_zip_stream<R: Read>(
+        reader: &mut R,
+        dest_dir: &Path,
+        exclude_patterns: &[&str],
+    ) -> Result<Vec<PathBuf>> {
+        report_event(
+            "Extract.Stream.Start",
+            Some(&dest_dir.display().to_string()),
+        );
+        clear_glob_cache();
+
+        let mut extracted_files = Vec::new();
+
+        for i in 0.. {
+            let mut file = match read_zipfile_from_stream(reader) {
+                Ok(Some(f)) => f,
+                // 遇到中央目录，所有条目已读取完毕
+                Ok(None) => break,
+                Err(e) => {
                     report_event(
-                        "Extract.Entry.Failed.UnsafeEnclosedName",
-                        Some(&format!("index:{}", i)),
+                        "Extract.Stream.Failed.ReadEntry",
+                        Some(&format!("index:{};err={}", i, e)),
                     );
                     return Err(ManagerError::ExtractFailed(format!(
-                        "条目 {} 包含不安全的文件路径",
-                        i
+                        "读取条目失败（index {}）：{}",
+                        i, e
                     )));
                 }
             };
 
-            if !Self::is_safe_path(&file_path) {
-                report_event(
-                    "Extract.Entry.Failed.UnsafePath",
-                    Some(&format!("index:{};path={}", i, file_path.display())),
-                );
-                return Err(ManagerError::ExtractFailed(format!(
-                    "不安全的文件路径：{}",
-                    file_path.display()
-                )));
+            if let Some(path) = Self::extract_entry(&mut file, i, dest_dir, exclude_patterns)? {
+                extracted_files.push(path);
             }
+        }
+
+        report_event(
+            "Extract.Stream.Success",
+            Some(&format!("count:{}", extracted_files.len())),
+        );
+
+        Ok(extracted_files)
+    }
 
-            // 禁止符号链接
-            if file.is_symlink() {
+    /// 解压单个条目，返回写入的文件路径（目录与被排除的条目返回 None）
+    fn extract_entry<R: Read>(
+        file: &mut ZipFile<'_, R>,
+        i: usize,
+        dest_dir: &Path,
+        exclude_patterns: &[&str],
+    ) -> Result<Option<PathBuf>> {
+        let file_path = match file.enclosed_name() {
+            Some(p) => p.to_path_buf(),
+            None => {
                 report_event(
-                    "Extract.Entry.Failed.SymlinkNotAllowed",
-                    Some(&format!("index:{};path={}", i, file_path.display())),
+                    "Extract.Entry.Failed.UnsafeEnclosedName",
+                    Some(&format!("index:{}", i)),
                 );
                 return Err(ManagerError::ExtractFailed(format!(
-                    "条目 {} 为符号链接，禁止解压：{}",
-                    i,
-                    file_path.display()
+                    "条目 {} 包含不安全的文件路径",
+                    i
                 )));
             }
+        };
 
-            let should_exclude = exclude_patterns.iter().any(|pattern| {
-                let pat = Path::new(pattern);
-                file_path == pat || file_path.starts_with(pat.join(""))
-            });
+        if !Self::is_safe_path(&file_path) {
+            report_event(
+                "Extract.Entry.Failed.UnsafePath",
+                Some(&format!("index:{};path={}", i, file_path.display())),
+            );
+            return Err(ManagerError::ExtractFailed(format!(
+                "不安全的文件路径：{}",
+                file_path.display()
+            )));
+        }
 
-            if should_exclude {
-                continue;
-            }
+        // 禁止符号链接
+        if file.is_symlink() {
+            report_event(
+                "Extract.Entry.Failed.SymlinkNotAllowed",
+                Some(&format!("index:{};path={}", i, file_path.display())),
+            );
+            return Err(ManagerError::ExtractFailed(format!(
+                "条目 {} 为符号链接，禁止解压：{}",
+                i,
+                file_path.display()
+            )));
+        }
 
-            let outpath = dest_dir.join(&file_path);
+        let should_exclude = exclude_patterns.iter().any(|pattern| {
+            let pat = Path::new(pattern);
+            file_path == pat || file_path.starts_with(pat.join(""))
+        });
 
-            if file.name().ends_with('/') {
-                std::fs::create_dir_all(&outpath).map_err(|e| {
-                    ManagerError::from(std::io::Error::new(
-                        e.kind(),
-                        format!("创建目录 {} 失败：{}", outpath.display(), e),
-                    ))
-                })?;
-            } else {
-                if let Some(p) = outpath.parent() {
-                    std::fs::create_dir_all(p).map_err(|e| {
-                        ManagerError::from(std::io::Error::new(
-                            e.kind(),
-                            format!("创建父目录 {} 失败：{}", p.display(), e),
-                        ))
-                    })?;
-                }
+        if should_exclude {
+            return Ok(None);
+        }
 
-                let mut tmp_path = outpath.with_extension("tmp");
+        let outpath = dest_dir.join(&file_path);
 
-                let mut tmp_idx = 0;
-                while tmp_path.exists() {
-                    tmp_idx += 1;
-                    tmp_path = outpath.with_extension(format!("tmp{}", tmp_idx));
-                }
+        if file.name().ends_with('/') {
+            std::fs::create_dir_all(&outpath).map_err(|e| {
+                ManagerError::from(std::io::Error::new(
+                    e.kind(),
+                    format!("创建目录 {} 失败：{}", outpath.display(), e),
+                ))
+            })?;
+            return Ok(None);
+        }
 
-                let mut tmp_file = match std::fs::File::create(&tmp_path) {
-                    Ok(f) => f,
-                    Err(e) => {
-                        return Err(ManagerError::from(std::io::Error::new(
-                            e.kind(),
-                            format!("创建临时文件 {} 失败：{}", tmp_path.display(), e),
-                        )));
-                    }
-                };
-
-                if let Err(e) = std::io::copy(&mut file, &mut tmp_file) {
-                    return Err(ManagerError::from(std::io::Error::new(
-                        e.kind(),
-                        format!("写入临时文件 {} 失败：{}", tmp_path.display(), e),
-                    )));
-                }
+        if let Some(p) = outpath.parent() {
+            std::fs::create_dir_all(p).map_err(|e| {
+                ManagerError::from(std::io::Error::new(
+                    e.kind(),
+                    format!("创建父目录 {} 失败：{}", p.display(), e),
+                ))
+            })?;
+        }
 
-                match atomic_rename_or_copy(&tmp_path, &outpath) {
-                    Ok(_) => {
-                        let _ = std::fs::remove_file(&tmp_path);
-                        extracted_files.push(outpath);
-                    }
-                    Err(e) => {
-                        let _ = std::fs::remove_file(&tmp_path);
-                        return Err(ManagerError::from(std::io::Error::other(format!(
-                            "重命名或复制临时文件 {} 失败：{}",
-                            tmp_path.display(),
-                            e
-                        ))));
-                    }
-                }
-            }
+        let mut tmp_path = outpath.with_extension("tmp");
+
+        let mut tmp_idx = 0;
+        while tmp_path.exists() {
+            tmp_idx += 1;
+            tmp_path = outpath.with_extension(format!("tmp{}", tmp_idx));
         }
 
-        report_event(
-            "Extract.Success",
-            Some(&format!("count:{}", extracted_files.len())),
-        );
+        let mut tmp_file = match std::fs::File::create(&tmp_path) {
+            Ok(f) => f,
+            Err(e) => {
+                return Err(ManagerError::from(std::io::Error::new(
+                    e.kind(),
+                    format!("创建临时文件 {} 失败：{}", tmp_path.display(), e),
+                )));
+            }
+        };
 
-        Ok(extracted_files)
+        if let Err(e) = std::io::copy(file, &mut tmp_file) {
+            return Err(ManagerError::from(std::io::Error::new(
+                e.kind(),
+                format!("写入临时文件 {} 失败：{}", tmp_path.display(), e),
+            )));
+        }
+
+        match atomic_rename_or_copy(&tmp_path, &outpath) {
+            Ok(_) => {
+                let _ = std::fs::remove_file(&tmp_path);
+                Ok(Some(outpath))
+            }
+            Err(e) => {
+                let _ = std::fs::remove_file(&tmp_path);
+                Err(ManagerError::from(std::io::Error::other(format!(
+                    "重命名或复制临时文件 {} 失败：{}",
+                    tmp_path.display(),
+                    e
+                ))))
+            }
+        }
     }
 
-    /// 安装 BepInEx 到游戏根目录
-    pub fn deploy_bepinex(zip_path: &Path, game_root: &Path, skip_plugins: bool) -> Result<()> {
+    /// 将已解压的 BepInEx 从暂存目录移动到游戏根目录
+    pub fn deploy_bepinex(staging_dir: &Path, game_root: &Path, skip_plugins: bool) -> Result<()> {
         report_event(
             "Deploy.BepInEx.Start",
-            Some(&zip_path.display().to_string()),
+            Some(&staging_dir.display().to_string()),
         );
+        clear_glob_cache();
 
-        let res = if skip_plugins {
-            Self::extract_zip_safe_with_exclusions(zip_path, game_root, &["BepInEx/plugins"])
+        let skip = if skip_plugins {
+            Some(Path::new("BepInEx").join("plugins"))
         } else {
-            Self::extract_zip_safe(zip_path, game_root)
+            None
         };
 
-        match res {
-            Ok(_) => {
+        match Self::move_staged_dir(staging_dir, staging_dir, game_root, skip.as_deref()) {
+            Ok(()) => {
                 report_event(
                     "Deploy.BepInEx.Success",
-                    Some(&zip_path.display().to_string()),
+                    Some(&staging_dir.display().to_string()),
                 );
                 Ok(())
             }
             Err(e) => {
                 report_event(
                     "Deploy.BepInEx.Failed",
-                    Some(&format!("path={};err={}", zip_path.display(), e)),
+                    Some(&format!("path={};err={}", staging_dir.display(), e)),
                 );
                 Err(e)
             }
         }
     }
 
+    /// 递归移动暂存目录中的文件，保持相对路径不变
+    fn move_staged_dir(
+        staging_root: &Path,
+        dir: &Path,
+        dest_root: &Path,
+        skip: Option<&Path>,
+    ) -> Result<()> {
+        let entries = std::fs::read_dir(dir).map_err(|e| {
+            ManagerError::from(std::io::Error::new(
+                e.kind(),
+                format!("读取目录 {} 失败：{}", dir.display(), e),
+            ))
+        })?;
+
+        for entry in entries {
+            let path = entry?.path();
+            let relative = path.strip_prefix(staging_root).unwrap_or(&path);
+            if skip.is_some_and(|s| relative == s || relative.starts_with(s)) {
+                continue;
+            }
+
+            let dest = dest_root.join(relative);
+            if path.is_dir() {
+                std::fs::create_dir_all(&dest).map_err(|e| {
+                    ManagerError::from(std::io::Error::new(
+                        e.kind(),
+                        format!("创建目录 {} 失败：{}", dest.display(), e),
+                    ))
+                })?;
+                Self::move_staged_dir(staging_root, &path, dest_root, skip)?;
+            } else {
+                atomic_rename_or_copy(&path, &dest).map_err(|e| {
+                    ManagerError::from(std::io::Error::other(format!(
+                        "安装 {} 失败：{}",
+                        dest.display(),
+                        e
+                    )))
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// 安装 MetaMystia DLL 到 BepInEx/plugins/ 目录
     pub fn deploy_metamystia(dll_path: &Path, game_root: &Path) -> Result<()> {
         let plugins_dir = game_root.join("BepInEx/plugins");
@@ -264,8 +375,92 @@ impl Extractor {
         }
     }
 
+    /// 检查 ZIP 文件结构是否完整（能打开且所有条目头可读）
+    pub fn validate_zip(zip_path: &Path) -> Result<()> {
+        let file = std::fs::File::open(zip_path).map_err(|e| {
+            ManagerError::from(std::io::Error::new(
+                e.kind(),
+                format!("打开 ZIP 文件 {} 失败：{}", zip_path.display(), e),
+            ))
+        })?;
+
+        let mut archive = ZipArchive::new(file).map_err(|e| {
+            ManagerError::ExtractFailed(format!(
+                "{} 不是有效的 ZIP 文件：{}",
+                zip_path.display(),
+                e
+            ))
+        })?;
+
+        for i in 0..archive.len() {
+            archive.by_index(i).map_err(|e| {
+                ManagerError::ExtractFailed(format!(
+                    "{} 已损坏（index {}）：{}",
+                    zip_path.display(),
+                    i,
+                    e
+                ))
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// 检查 ResourceExample ZIP 的内容是否像资源包（非空且不包含其他组件的文件）
+    pub fn validate_resourceex_zip(zip_path: &Path) -> Result<()> {
+        Self::validate_zip(zip_path)?;
+
+        let file = std::fs::File::open(zip_path).map_err(|e| {
+            ManagerError::from(std::io::Error::new(
+                e.kind(),
+                format!("打开 ZIP 文件 {} 失败：{}", zip_path.display(), e),
+            ))
+        })?;
+        let archive = ZipArchive::new(file)
+            .map_err(|e| ManagerError::ExtractFailed(format!("读取 ZIP 失败：{}", e)))?;
+
+        let mut resource_files = 0;
+        for name in archive.file_names() {
+            let is_junk = ZIP_JUNK_ENTRIES
+                .iter()
+                .any(|junk| name.starts_with(junk) || name.rsplit('/').next() == Some(*junk));
+            if name.ends_with('/') || is_junk {
+                continue;
+            }
+
+            if let Some(foreign) = RESOURCEEX_FOREIGN_ENTRIES
+                .iter()
+                .find(|f| name.eq_ignore_ascii_case(f) || name.starts_with(*f))
+            {
+                report_event(
+                    "Validate.ResourceEx.Invalid",
+                    Some(&format!("foreign={};path={}", foreign, zip_path.display())),
+                );
+                return Err(ManagerError::ExtractFailed(format!(
+                    "{} 的内容不是有效的 ResourceExample 资源包",
+                    zip_path.display()
+                )));
+            }
+
+            resource_files += 1;
+        }
+
+        if resource_files == 0 {
+            report_event(
+                "Validate.ResourceEx.Invalid",
+                Some(&format!("empty;path={}", zip_path.display())),
+            );
+            return Err(ManagerError::ExtractFailed(format!(
+                "{} 的内容不是有效的 ResourceExample 资源包",
+                zip_path.display()
+            )));
+        }
+
+        Ok(())
+    }
+
     /// 安装 ResourceExample ZIP 到 ResourceEx/ 目录
-    pub fn deploy_resourceex(zip_path: &Path, game_root: &Path) -> Result<()> {
+    pub fn deploy_resourceex(zip_path: &Path, game_root: &Path, version: &str) -> Result<()> {
         let resourceex_dir = game_root.join("ResourceEx");
 
         if !resourceex_dir.exists() {
@@ -294,13 +489,18 @@ impl Extractor {
                 format!("复制文件 {} 失败：{}", zip_path.display(), e),
             ))
         })?;
+        if let Err(e) = Self::validate_resourceex_zip(&tmp_dest) {
+            let _ = std::fs::remove_file(&tmp_dest);
+            report_event("Deploy.ResourceEx.Invalid", Some(&format!("{}", e)));
+            return Err(e);
+        }
         match atomic_rename_or_copy(&tmp_dest, &dest) {
             Ok(_) => {
                 report_event(
                     "Deploy.ResourceEx.Success",
                     Some(&dest.display().to_string()),
                 );
-                Ok(())
+                Self::write_resourceex_marker(&resourceex_dir, version)
             }
             Err(e) => Err(ManagerError::from(std::io::Error::other(format!(
                 "安装 {} 失败：{}",
@@ -309,4 +509,75 @@ impl Extractor {
             )))),
         }
     }
+
+    /// 写入 ResourceEx 安装标记（用户解压并删除压缩包后仍可识别已安装的版本）
+    pub fn write_resourceex_marker(resourceex_dir: &Path, version: &str) -> Result<()> {
+        write_file_atomic(
+            &resourceex_dir.join(RESOURCEEX_MARKER),
+            version.trim().as_bytes(),
+        )
+    }
+
+    /// 读取 ResourceEx 安装标记中的版本号
+    pub fn read_resourceex_marker(resourceex_dir: &Path) -> Option<String> {
+        std::fs::read_to_string(resourceex_dir.join(RESOURCEEX_MARKER))
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::ScratchDir;
+
+    use std::io::{Cursor, Write};
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    /// 构造一个包含若干资源文件的 ResourceExample ZIP
+    fn resourceex_zip_bytes() -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for i in 0..8 {
+            zip.start_file(
+                format!("ResourceExample/sprite{}.png", i),
+                SimpleFileOptions::default(),
+            )
+            .unwrap();
+            zip.write_all(&[i as u8; 4096]).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn truncated_resourceex_zip_is_rejected_and_removed() {
+        let dir = ScratchDir::new("resourceex-truncated");
+        let bytes = resourceex_zip_bytes();
+        let zip_path = dir.write("ResourceExample-v1.0.0.zip", &bytes[..bytes.len() / 2]);
+        let game = dir.path().join("game");
+
+        let err = Extractor::deploy_resourceex(&zip_path, &game, "1.0.0").unwrap_err();
+        assert!(matches!(err, ManagerError::ExtractFailed(_)), "{}", err);
+
+        let resourceex_dir = game.join("ResourceEx");
+        assert_eq!(std::fs::read_dir(&resourceex_dir).unwrap().count(), 0);
+        assert_eq!(Extractor::read_resourceex_marker(&resourceex_dir), None);
+    }
+
+    #[test]
+    fn complete_resourceex_zip_is_deployed_with_marker() {
+        let dir = ScratchDir::new("resourceex-complete");
+        let zip_path = dir.write("ResourceExample-v1.0.0.zip", &resourceex_zip_bytes());
+        let game = dir.path().join("game");
+
+        Extractor::deploy_resourceex(&zip_path, &game, "1.0.0").unwrap();
+
+        let resourceex_dir = game.join("ResourceEx");
+        assert!(resourceex_dir.join("ResourceExample-v1.0.0.zip").is_file());
+        assert_eq!(
+            Extractor::read_resourceex_marker(&resourceex_dir).as_deref(),
+            Some("1.0.0")
+        );
+    }
 }
@@ -1,10 +1,19 @@
-use crate::config::UninstallMode;
 use crate::error::ManagerError;
+use crate::metrics::report_event;
 use crate::ui::Ui;
 
 use glob::glob;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Write;
+use std::os::windows::ffi::OsStrExt;
+use std::os::windows::process::CommandExt;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::process::Command;
+use std::sync::{Arc, OnceLock};
+use windows::Win32::Storage::FileSystem::{GetDiskFreeSpaceExW, GetVolumeInformationW};
+use windows::Win32::System::Threading::CREATE_NO_WINDOW;
+use windows::core::PCWSTR;
 
 fn ensure_owner_writable(metadata: &std::fs::Metadata) -> std::fs::Permissions {
     let mut perms = metadata.permissions();
@@ -40,8 +49,139 @@ pub fn map_io_error_to_uninstall_error(err: &std::io::Error, path: &Path) -> Man
     ManagerError::from(std::io::Error::new(err.kind(), err.to_string()))
 }
 
+/// 获取路径所在卷对当前用户可用的空间（字节），路径尚不存在时查询最近的已存在上级目录
+pub fn available_space(path: &Path) -> Option<u64> {
+    let existing = path.ancestors().find(|p| p.exists())?;
+    let wide: Vec<u16> = existing
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    let mut free: u64 = 0;
+
+    unsafe {
+        GetDiskFreeSpaceExW(
+            PCWSTR(wide.as_ptr()),
+            Some(&mut free as *mut u64),
+            None,
+            None,
+        )
+    }
+    .ok()?;
+
+    Some(free)
+}
+
+/// 获取路径所在卷的文件系统类型（如 NTFS、exFAT）
+pub fn filesystem_type(path: &Path) -> Option<String> {
+    // 卷根目录：盘符或 UNC 共享加上根分隔符
+    let root: PathBuf = path.components().take(2).collect();
+    let mut wide: Vec<u16> = root.as_os_str().encode_wide().collect();
+    if wide.last() != Some(&(b'\\' as u16)) {
+        wide.push(b'\\' as u16);
+    }
+    wide.push(0);
+
+    let mut name = [0u16; 32];
+    unsafe {
+        GetVolumeInformationW(
+            PCWSTR(wide.as_ptr()),
+            None,
+            None,
+            None,
+            None,
+            Some(&mut name),
+        )
+    }
+    .ok()?;
+
+    let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+    Some(String::from_utf16_lossy(&name[..len]))
+}
+
+/// 格式化字节数为便于阅读的大小
+pub fn format_size(bytes: u64) -> String {
+    const MB: f64 = 1024.0 * 1024.0;
+
+    if bytes as f64 >= 1024.0 * MB {
+        format!("{:.2} GB", bytes as f64 / 1024.0 / MB)
+    } else {
+        format!("{:.1} MB", bytes as f64 / MB)
+    }
+}
+
+/// 超过该长度时提示路径接近 MAX_PATH（260）
+const PATH_LENGTH_WARN_THRESHOLD: usize = 240;
+/// 超过该长度时未开启长路径支持的系统无法访问
+const PATH_LENGTH_LIMIT: usize = 258;
+/// 启用 Windows 长路径支持的说明文档
+pub const LONG_PATHS_DOC_URL: &str = "https://learn.microsoft.com/windows/win32/fileio/maximum-file-path-limitation#enable-long-paths-in-windows-10-version-1607-and-later";
+
+/// 系统是否已启用长路径支持（LongPathsEnabled，读取一次后缓存）
+fn long_paths_enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+
+    *ENABLED.get_or_init(|| {
+        let Ok(out) = Command::new("reg")
+            .args([
+                "query",
+                r"HKLM\SYSTEM\CurrentControlSet\Control\FileSystem",
+                "/v",
+                "LongPathsEnabled",
+            ])
+            .creation_flags(CREATE_NO_WINDOW.0)
+            .output()
+        else {
+            return false;
+        };
+        if !out.status.success() {
+            return false;
+        }
+
+        let s = String::from_utf8_lossy(&out.stdout);
+        s.lines()
+            .map(str::trim)
+            .filter(|t| t.starts_with("LongPathsEnabled"))
+            .filter_map(|t| t.split_whitespace().last())
+            .any(|v| v.eq_ignore_ascii_case("0x1"))
+    })
+}
+
+/// 检查路径长度，未启用长路径支持且超过上限时返回错误；返回值表示路径是否已接近上限
+pub fn check_path_length(path: &Path, operation: &str) -> Result<bool, ManagerError> {
+    check_path_length_with(path, operation, long_paths_enabled())
+}
+
+fn check_path_length_with(
+    path: &Path,
+    operation: &str,
+    long_paths_enabled: bool,
+) -> Result<bool, ManagerError> {
+    // 已启用长路径支持时不受 MAX_PATH 限制
+    if long_paths_enabled {
+        return Ok(false);
+    }
+
+    let len = path.as_os_str().encode_wide().count();
+
+    if len > PATH_LENGTH_LIMIT {
+        report_event("Path.TooLong", Some(&format!("{};len={}", operation, len)));
+        return Err(ManagerError::Other(format!(
+            "{}失败：路径过长（{} 个字符），请在 Windows 设置中启用 LongPathsEnabled：{}",
+            operation,
+            len,
+            path.display()
+        )));
+    }
+
+    Ok(len > PATH_LENGTH_WARN_THRESHOLD)
+}
+
 /// 原子重命名或回退到 copy + remove
 pub fn atomic_rename_or_copy(src: &Path, dst: &Path) -> Result<(), ManagerError> {
+    check_path_length(dst, "写入文件")?;
+    clear_glob_cache();
+
     if let Some(parent) = dst.parent() {
         std::fs::create_dir_all(parent).map_err(ManagerError::from)?;
     }
@@ -88,6 +228,37 @@ pub fn atomic_rename_or_copy(src: &Path, dst: &Path) -> Result<(), ManagerError>
     }
 }
 
+/// 原子写入文件：先写入同目录下的 .tmp 文件并同步到磁盘，再重命名为目标文件
+pub fn write_file_atomic(path: &Path, content: &[u8]) -> Result<(), ManagerError> {
+    clear_glob_cache();
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(ManagerError::from)?;
+    }
+
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let write_tmp = || -> std::io::Result<()> {
+        let mut file = std::fs::File::create(&tmp_path)?;
+        file.write_all(content)?;
+        file.sync_all()
+    };
+
+    if let Err(e) = write_tmp() {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(ManagerError::from(std::io::Error::new(
+            e.kind(),
+            format!("写入临时文件 {} 失败：{}", tmp_path.display(), e),
+        )));
+    }
+
+    atomic_rename_or_copy(&tmp_path, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&tmp_path);
+    })
+}
+
 fn backup_with_index(path: &Path, ext_suffix: &str) -> Result<PathBuf, ManagerError> {
     if !path.exists() {
         return Err(ManagerError::from(std::io::Error::new(
@@ -134,6 +305,8 @@ pub struct RemoveGlobResult {
 
 /// 删除匹配 glob 模式的文件/目录
 pub fn remove_glob_files(pattern: &Path) -> RemoveGlobResult {
+    clear_glob_cache();
+
     let mut removed = Vec::new();
     let mut failed = Vec::new();
 
@@ -163,17 +336,33 @@ pub fn backup_paths_with_index(
     paths: &[PathBuf],
     ext_suffix: &str,
 ) -> Vec<Result<PathBuf, ManagerError>> {
+    clear_glob_cache();
+
     paths
         .iter()
         .map(|p| backup_with_index(p, ext_suffix))
         .collect()
 }
 
+thread_local! {
+    /// glob 结果缓存（键为规范化后的模式），文件系统发生变更前需调用 clear_glob_cache
+    static GLOB_CACHE: RefCell<HashMap<String, Vec<PathBuf>>> = RefCell::new(HashMap::new());
+}
+
+/// 清空 glob 结果缓存（删除、备份或写入文件前调用，避免返回过期结果）
+pub fn clear_glob_cache() {
+    GLOB_CACHE.with(|c| c.borrow_mut().clear());
+}
+
 /// 根据 glob 模式获取匹配的路径列表
 pub fn glob_matches(pattern: &Path) -> Vec<PathBuf> {
-    let mut matches = Vec::new();
     let s = normalize_path_for_glob(pattern);
 
+    if let Some(cached) = GLOB_CACHE.with(|c| c.borrow().get(&s).cloned()) {
+        return cached;
+    }
+
+    let mut matches = Vec::new();
     if let Ok(entries) = glob(&s) {
         for entry in entries.flatten() {
             if entry.exists() {
@@ -182,29 +371,71 @@ pub fn glob_matches(pattern: &Path) -> Vec<PathBuf> {
         }
     }
 
+    GLOB_CACHE.with(|c| c.borrow_mut().insert(s, matches.clone()));
+
     matches
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub enum DeletionStatus {
     Success,
     Failed(Arc<ManagerError>),
     Skipped,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct DeletionResult {
     pub path: PathBuf,
     pub status: DeletionStatus,
+    /// 删除成功时释放的字节数（删除前测得）
+    pub bytes_freed: Option<u64>,
+}
+
+/// 删除失败的原因分类
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FailureKind {
+    /// 文件被其他进程占用
+    Locked,
+    /// 文件所有者或访问控制列表拒绝访问
+    AclDenied,
+    Other,
+}
+
+impl FailureKind {
+    /// 分类名称（用于 JSON 输出）
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FailureKind::Locked => "locked",
+            FailureKind::AclDenied => "acl_denied",
+            FailureKind::Other => "other",
+        }
+    }
+}
+
+impl DeletionResult {
+    /// 删除失败时的原因分类
+    pub fn failure_kind(&self) -> Option<FailureKind> {
+        let DeletionStatus::Failed(e) = &self.status else {
+            return None;
+        };
+
+        Some(match &**e {
+            ManagerError::FileInUse(_) => FailureKind::Locked,
+            ManagerError::PermissionDenied(_) => FailureKind::AclDenied,
+            ManagerError::Io(io) if io.kind() == std::io::ErrorKind::PermissionDenied => {
+                FailureKind::AclDenied
+            }
+            _ => FailureKind::Other,
+        })
+    }
 }
 
 /// 扫描实际存在的文件
-pub fn scan_existing_files(base: &Path, mode: UninstallMode) -> Vec<PathBuf> {
-    let targets = mode.targets();
+pub fn scan_existing_files(base: &Path, targets: &[(String, bool)]) -> Vec<PathBuf> {
     let mut existing_files = Vec::new();
 
-    for &(pattern, is_dir) in targets {
-        scan_target(base, pattern, is_dir, &mut existing_files);
+    for (pattern, is_dir) in targets {
+        scan_target(base, pattern, *is_dir, &mut existing_files);
     }
 
     existing_files
@@ -235,19 +466,25 @@ fn scan_target(base: &Path, pattern: &str, is_directory: bool, existing_files: &
 
 /// 执行删除操作
 pub fn execute_deletion(files: &[PathBuf], ui: &dyn Ui) -> Vec<DeletionResult> {
+    clear_glob_cache();
+
     let total = files.len();
     let mut results = Vec::new();
 
-    let _ = ui.deletion_start();
+    let _ = ui.deletion_start(total);
 
     for (index, path) in files.iter().enumerate() {
         let _ = ui.deletion_display_progress(index + 1, total, &path.display().to_string());
 
-        let result = if path.is_dir() {
+        let size = path_size(path);
+        let mut result = if path.is_dir() {
             delete_directory(path)
         } else {
             delete_file(path)
         };
+        if matches!(result.status, DeletionStatus::Success) {
+            result.bytes_freed = Some(size);
+        }
 
         match &result.status {
             DeletionStatus::Success => {
@@ -268,12 +505,37 @@ pub fn execute_deletion(files: &[PathBuf], ui: &dyn Ui) -> Vec<DeletionResult> {
     results
 }
 
+/// 统计文件或目录（递归）占用的字节数，无法读取的项目计为 0
+fn path_size(path: &Path) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| path_size(&e.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// 统计删除结果中释放的总字节数
+pub fn total_bytes_freed(results: &[DeletionResult]) -> u64 {
+    results.iter().filter_map(|r| r.bytes_freed).sum()
+}
+
 /// 删除单个文件
 fn delete_file(path: &Path) -> DeletionResult {
     if !path.exists() {
         return DeletionResult {
             path: path.to_path_buf(),
             status: DeletionStatus::Skipped,
+            bytes_freed: None,
         };
     }
 
@@ -285,11 +547,13 @@ fn delete_file(path: &Path) -> DeletionResult {
                     status: DeletionStatus::Failed(Arc::new(ManagerError::Other(
                         "执行删除后文件仍存在".to_string(),
                     ))),
+                    bytes_freed: None,
                 }
             } else {
                 DeletionResult {
                     path: path.to_path_buf(),
                     status: DeletionStatus::Success,
+                    bytes_freed: None,
                 }
             }
         }
@@ -301,6 +565,7 @@ fn delete_file(path: &Path) -> DeletionResult {
                     status: DeletionStatus::Failed(Arc::new(ManagerError::FileInUse(
                         path.display().to_string(),
                     ))),
+                    bytes_freed: None,
                 };
             }
 
@@ -314,6 +579,7 @@ fn delete_file(path: &Path) -> DeletionResult {
                     return DeletionResult {
                         path: path.to_path_buf(),
                         status: DeletionStatus::Success,
+                        bytes_freed: None,
                     };
                 }
             }
@@ -326,6 +592,7 @@ fn delete_file(path: &Path) -> DeletionResult {
                     return DeletionResult {
                         path: path.to_path_buf(),
                         status: DeletionStatus::Skipped,
+                        bytes_freed: None,
                     };
                 }
                 _ => map_io_error_to_uninstall_error(&e, path),
@@ -334,6 +601,7 @@ fn delete_file(path: &Path) -> DeletionResult {
             DeletionResult {
                 path: path.to_path_buf(),
                 status: DeletionStatus::Failed(Arc::new(error)),
+                bytes_freed: None,
             }
         }
     }
@@ -345,6 +613,7 @@ fn delete_directory(path: &Path) -> DeletionResult {
         return DeletionResult {
             path: path.to_path_buf(),
             status: DeletionStatus::Skipped,
+            bytes_freed: None,
         };
     }
 
@@ -356,11 +625,13 @@ fn delete_directory(path: &Path) -> DeletionResult {
                     status: DeletionStatus::Failed(Arc::new(ManagerError::Other(
                         "执行删除后文件夹仍存在".to_string(),
                     ))),
+                    bytes_freed: None,
                 }
             } else {
                 DeletionResult {
                     path: path.to_path_buf(),
                     status: DeletionStatus::Success,
+                    bytes_freed: None,
                 }
             }
         }
@@ -372,6 +643,7 @@ fn delete_directory(path: &Path) -> DeletionResult {
                     status: DeletionStatus::Failed(Arc::new(ManagerError::FileInUse(
                         path.display().to_string(),
                     ))),
+                    bytes_freed: None,
                 };
             }
 
@@ -385,6 +657,7 @@ fn delete_directory(path: &Path) -> DeletionResult {
                     return DeletionResult {
                         path: path.to_path_buf(),
                         status: DeletionStatus::Success,
+                        bytes_freed: None,
                     };
                 }
             }
@@ -397,6 +670,7 @@ fn delete_directory(path: &Path) -> DeletionResult {
                     return DeletionResult {
                         path: path.to_path_buf(),
                         status: DeletionStatus::Skipped,
+                        bytes_freed: None,
                     };
                 }
                 _ => map_io_error_to_uninstall_error(&e, path),
@@ -405,6 +679,7 @@ fn delete_directory(path: &Path) -> DeletionResult {
             DeletionResult {
                 path: path.to_path_buf(),
                 status: DeletionStatus::Failed(Arc::new(error)),
+                bytes_freed: None,
             }
         }
     }
@@ -437,3 +712,123 @@ pub fn count_results(results: &[DeletionResult]) -> (usize, usize, usize) {
 
     (success, failed, skipped)
 }
+
+/// 按原因统计删除失败项（占用，权限，其他）
+pub fn count_failures(results: &[DeletionResult]) -> (usize, usize, usize) {
+    let mut locked = 0;
+    let mut acl_denied = 0;
+    let mut other = 0;
+
+    for kind in results.iter().filter_map(DeletionResult::failure_kind) {
+        match kind {
+            FailureKind::Locked => locked += 1,
+            FailureKind::AclDenied => acl_denied += 1,
+            FailureKind::Other => other += 1,
+        }
+    }
+
+    (locked, acl_denied, other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{ScratchDir, TestUI, UiCall, UiCallKind};
+
+    #[test]
+    fn deletion_reports_each_item_to_ui() {
+        let dir = ScratchDir::new("deletion");
+        let file = dir.write("winhttp.dll", b"12345");
+        let folder = dir.mkdir("BepInEx");
+        dir.write("BepInEx/core/BepInEx.Core.dll", b"123");
+        let missing = dir.path().join("doorstop_config.ini");
+
+        let ui = TestUI::new();
+        let results = execute_deletion(&[file.clone(), folder.clone(), missing], &ui);
+
+        assert_eq!(count_results(&results), (2, 0, 1));
+        assert_eq!(total_bytes_freed(&results), 8);
+        assert!(!file.exists() && !folder.exists());
+
+        assert_eq!(
+            ui.kinds(),
+            vec![
+                UiCallKind::DeletionStart,
+                UiCallKind::DeletionDisplayProgress,
+                UiCallKind::DeletionDisplaySuccess,
+                UiCallKind::DeletionDisplayProgress,
+                UiCallKind::DeletionDisplaySuccess,
+                UiCallKind::DeletionDisplayProgress,
+                UiCallKind::DeletionDisplaySkipped,
+            ]
+        );
+        assert!(matches!(ui.calls()[0], UiCall::DeletionStart(3)));
+    }
+
+    #[test]
+    fn scan_matches_files_and_directories_by_kind() {
+        let dir = ScratchDir::new("scan");
+        dir.write("winhttp.dll", b"");
+        dir.write("BepInEx/plugins/MetaMystia-v1.0.0.dll", b"");
+        dir.write("BepInEx/plugins/Other.dll", b"");
+        dir.mkdir("dotnet");
+
+        let targets = [
+            ("winhttp.dll".to_string(), false),
+            ("BepInEx/plugins/MetaMystia-*.dll".to_string(), false),
+            ("dotnet".to_string(), true),
+            // 类型不符的目标不应匹配
+            ("winhttp.dll".to_string(), true),
+        ];
+        let found = scan_existing_files(dir.path(), &targets);
+
+        assert_eq!(found.len(), 3);
+        assert!(
+            found
+                .iter()
+                .all(|p| !p.ends_with("Other.dll") && p.starts_with(dir.path()))
+        );
+    }
+
+    #[test]
+    fn path_length_limit_applies_only_without_long_path_support() {
+        let near = PathBuf::from(format!(r"C:\{}", "a".repeat(PATH_LENGTH_WARN_THRESHOLD)));
+        let long = PathBuf::from(format!(r"C:\{}", "a".repeat(PATH_LENGTH_LIMIT)));
+
+        assert!(!check_path_length_with(Path::new(r"C:\Games"), "测试", false).unwrap());
+        assert!(check_path_length_with(&near, "测试", false).unwrap());
+        assert!(check_path_length_with(&long, "测试", false).is_err());
+
+        assert!(!check_path_length_with(&near, "测试", true).unwrap());
+        assert!(!check_path_length_with(&long, "测试", true).unwrap());
+    }
+
+    #[test]
+    fn backup_never_overwrites_existing_backups() {
+        let dir = ScratchDir::new("backup-index");
+
+        for n in 0..=100 {
+            let source = dir.write(&format!("{}/MetaMystia-v1.0.dll", n), b"original");
+            let mut existing = Vec::new();
+            for i in 0..n {
+                let backup = if i == 0 {
+                    source.with_extension("dll.old")
+                } else {
+                    source.with_extension(format!("dll.old.{}", i))
+                };
+                std::fs::write(&backup, b"previous backup").unwrap();
+                existing.push(backup);
+            }
+
+            let results = backup_paths_with_index(std::slice::from_ref(&source), "dll.old");
+            let backup = results.into_iter().next().unwrap().unwrap();
+
+            assert!(!existing.contains(&backup), "n={}：覆盖了已有备份", n);
+            assert_eq!(std::fs::read(&backup).unwrap(), b"original", "n={}", n);
+            assert!(!source.exists(), "n={}：源文件仍然存在", n);
+            for path in &existing {
+                assert_eq!(std::fs::read(path).unwrap(), b"previous backup");
+            }
+        }
+    }
+}
@@ -1,16 +1,120 @@
+use crate::bepinex_config::set_ini_value;
 use crate::cli::InstallConfig;
-use crate::config::UninstallMode;
-use crate::downloader::Downloader;
+use crate::config::{
+    BEPINEX_LOADER_DLL, PRESERVED_BEPINEX_DIRS, RESOURCEEX_MARKER, SLOW_STORAGE_THRESHOLD_MBPS,
+    UninstallMode,
+};
+use crate::downloader::{
+    DownloadSource, Downloader, estimate_download_secs, record_download_speed,
+};
+use crate::env_check::query_defender_detection;
 use crate::error::{ManagerError, Result};
 use crate::extractor::Extractor;
-use crate::file_ops::{atomic_rename_or_copy, count_results, execute_deletion, glob_matches};
+use crate::file_ops::{
+    DeletionResult, atomic_rename_or_copy, available_space, count_results, execute_deletion,
+    format_size, glob_matches, write_file_atomic,
+};
 use crate::metrics::report_event;
-use crate::model::VersionInfo;
-use crate::temp_dir::create_temp_dir_with_guard;
-use crate::ui::Ui;
+use crate::model::{VersionInfo, find_version_index};
+use crate::progress::ProgressScope;
+use crate::temp_dir::{
+    create_temp_dir_with_guard, is_cross_volume, probe_write_speed, slow_storage_check_enabled,
+};
+use crate::ui::{
+    AdvancedInstallOptions, InstallPlan, OperationSummary, OverwriteSelection, SummaryAction, Ui,
+    verbose,
+};
+use crate::undo::create_undo_archive;
 
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+/// 部署后最多等待杀毒软件扫描的时间，期间加载器消失即视为被隔离
+const LOADER_CHECK_DELAY: Duration = Duration::from_secs(3);
+/// 等待期间检查加载器是否存在的间隔
+const LOADER_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// 安装流程的步骤总数
+const INSTALL_STEPS: usize = 4;
+
+/// 从备用源安装 BepInEx 时写入配置的 Unity 基础库下载地址
+const UNITY_LIBRARY_SOURCE: &str = "https://url.izakaya.cc/unity-library";
+
+/// ResourceExample ZIP 可能被误放置的位置（相对游戏根目录）
+const MISPLACED_RESOURCEEX_DIRS: &[&str] = &["BepInEx/plugins", "BepInEx/config"];
+
+/// 查找误放在其他目录中的 ResourceExample ZIP，并询问是否迁移，返回待迁移的文件（不修改任何文件）
+pub fn find_misplaced_resourceex(game_root: &Path, ui: &dyn Ui) -> Result<Vec<PathBuf>> {
+    let mut misplaced = Vec::new();
+    for dir in MISPLACED_RESOURCEEX_DIRS {
+        misplaced.extend(glob_matches(
+            &game_root.join(dir).join("ResourceExample-*.zip"),
+        ));
+    }
+
+    if misplaced.is_empty() {
+        return Ok(misplaced);
+    }
+
+    report_event(
+        "Env.MisplacedResourceEx",
+        Some(&misplaced.len().to_string()),
+    );
+
+    ui.resourceex_display_misplaced(&misplaced)?;
+    if !ui.resourceex_confirm_migrate()? {
+        return Ok(Vec::new());
+    }
+
+    Ok(misplaced)
+}
+
+/// 将给定的 ResourceExample ZIP 移动到 ResourceEx 目录，返回迁移的文件数
+pub fn migrate_resourceex_files(game_root: &Path, files: &[PathBuf], ui: &dyn Ui) -> Result<usize> {
+    if files.is_empty() {
+        return Ok(0);
+    }
+
+    let resourceex_dir = game_root.join("ResourceEx");
+    std::fs::create_dir_all(&resourceex_dir).map_err(|e| {
+        ManagerError::from(std::io::Error::new(
+            e.kind(),
+            format!("创建目录 {} 失败：{}", resourceex_dir.display(), e),
+        ))
+    })?;
+
+    let mut migrated = 0;
+    for src in files {
+        let Some(name) = src.file_name() else {
+            continue;
+        };
+        let dest = resourceex_dir.join(name);
+        if dest.exists() {
+            // 目标位置已有同名文件，保留原文件不做覆盖
+            continue;
+        }
+
+        match atomic_rename_or_copy(src, &dest) {
+            Ok(()) => migrated += 1,
+            Err(e) => ui.resourceex_migrate_failed(src, &format!("{}", e))?,
+        }
+    }
+
+    report_event(
+        "Env.MisplacedResourceEx.Migrated",
+        Some(&migrated.to_string()),
+    );
+
+    Ok(migrated)
+}
+
+/// 将误放在其他目录中的 ResourceExample ZIP 迁移到 ResourceEx 目录，返回迁移的文件数
+pub fn migrate_misplaced_resourceex(game_root: &Path, ui: &dyn Ui) -> Result<usize> {
+    let misplaced = find_misplaced_resourceex(game_root, ui)?;
+    migrate_resourceex_files(game_root, &misplaced, ui)
+}
 
 /// 安装管理器
 pub struct Installer<'a> {
@@ -61,8 +165,12 @@ impl<'a> Installer<'a> {
         }
     }
 
-    /// 执行安装前的清理：全量卸载但保留 BepInEx/plugins（除了 MetaMystia DLL）
-    fn execute_install_cleanup(game_root: &Path, ui: &dyn Ui) -> Result<(usize, usize)> {
+    /// 收集安装前需要清理的项目：全量卸载但保留 PRESERVED_BEPINEX_DIRS 中的目录（除了 MetaMystia DLL 与 BepInEx.cfg），
+    /// 用户选择保留的组件不做清理
+    pub fn plan_install_cleanup(
+        game_root: &Path,
+        selection: OverwriteSelection,
+    ) -> Result<Vec<PathBuf>> {
         let mut targets = Vec::new();
         let mut seen = HashSet::new();
 
@@ -73,25 +181,35 @@ impl<'a> Installer<'a> {
             }
         };
 
-        // 1. 删除 BepInEx 目录下的所有项目（跳过 plugins）
+        // 1. 删除 BepInEx 目录下的所有项目（跳过需要保留的目录）
         let bepinex_dir = game_root.join("BepInEx");
-        if bepinex_dir.exists() {
+        if selection.bepinex && bepinex_dir.exists() {
             for entry in std::fs::read_dir(&bepinex_dir).map_err(ManagerError::from)? {
                 let entry = entry.map_err(ManagerError::from)?;
                 let path = entry.path();
                 let name = entry.file_name();
+                let name = name.to_string_lossy();
 
-                if name.to_string_lossy().eq_ignore_ascii_case("plugins") {
+                if PRESERVED_BEPINEX_DIRS
+                    .iter()
+                    .any(|d| name.eq_ignore_ascii_case(d))
+                {
                     continue;
                 }
 
                 push(path);
             }
+
+            // BepInEx 自身的配置会由安装流程重新写入
+            let bepinex_cfg = bepinex_dir.join("config").join("BepInEx.cfg");
+            if bepinex_cfg.is_file() {
+                push(bepinex_cfg);
+            }
         }
 
         // 2. 删除 plugins 目录中的 MetaMystia DLL
         let plugins_dir = bepinex_dir.join("plugins");
-        if plugins_dir.exists() {
+        if selection.metamystia && plugins_dir.exists() {
             let metamystia_pattern = plugins_dir.join("MetaMystia-*.dll");
             for entry in glob_matches(&metamystia_pattern) {
                 push(entry);
@@ -100,15 +218,24 @@ impl<'a> Installer<'a> {
 
         // 3. 删除 ResourceEx 目录中的 ResourceExample ZIP
         let resourceex_dir = game_root.join("ResourceEx");
-        if resourceex_dir.exists() {
+        if selection.resourceex && resourceex_dir.exists() {
             let resourceex_pattern = resourceex_dir.join("ResourceExample-*.zip");
             for entry in glob_matches(&resourceex_pattern) {
                 push(entry);
             }
+
+            let marker = resourceex_dir.join(RESOURCEEX_MARKER);
+            if marker.is_file() {
+                push(marker);
+            }
         }
 
-        // 4. 删除完全卸载模式中的其他文件
-        let full_targets = UninstallMode::Full.targets();
+        // 4. 删除完全卸载模式中的其他文件（均属于 BepInEx 框架）
+        let full_targets = if selection.bepinex {
+            UninstallMode::Full.targets()
+        } else {
+            &[]
+        };
         for &(pattern, is_dir) in full_targets {
             if pattern == "BepInEx" || pattern == "ResourceEx" {
                 continue;
@@ -129,10 +256,18 @@ impl<'a> Installer<'a> {
             }
         }
 
-        let results = execute_deletion(&targets, ui);
+        Ok(targets)
+    }
+
+    /// 执行安装前的清理
+    fn execute_install_cleanup(
+        targets: &[PathBuf],
+        ui: &dyn Ui,
+    ) -> Result<(usize, usize, Vec<DeletionResult>)> {
+        let results = execute_deletion(targets, ui);
         let (success, failed, _skipped) = count_results(&results);
 
-        Ok((success, failed))
+        Ok((success, failed, results))
     }
 
     /// 执行安装流程
@@ -140,22 +275,32 @@ impl<'a> Installer<'a> {
         &self,
         cleanup_before_deploy: bool,
         config: Option<&InstallConfig>,
+        selection: OverwriteSelection,
     ) -> Result<()> {
         report_event("Install.Start", None);
+        let progress = ProgressScope::begin("安装", INSTALL_STEPS);
+
+        // 全新安装 BepInEx 时首次启动需要生成互操作程序集
+        let first_install = !self.check_bepinex_installed();
+
+        // 误放的 ResourceExample ZIP 仅记录在安装计划中，确认计划后再移动
+        let misplaced_resourceex = find_misplaced_resourceex(&self.game_root, self.ui)?;
 
         // 1. 获取版本信息
-        self.ui.install_display_step(1, "获取版本信息")?;
+        self.display_step(&progress, 1, "获取版本信息")?;
         let version_info = self.downloader.get_version_info()?;
         self.ui.install_display_version_info(&version_info)?;
         report_event("Install.VersionInfo", Some(&version_info.to_string()));
 
         // 2. 获取分享码
-        self.ui.install_display_step(2, "获取下载链接")?;
+        self.display_step(&progress, 2, "获取下载链接")?;
         let share_code = self.downloader.get_share_code()?;
         report_event("Install.ShareCode", Some(&share_code));
 
-        // 2.1. 询问是否安装 ResourceEx（如果 config 存在则使用，否则询问用户）
-        let install_resourceex = if let Some(cfg) = config {
+        // 2.1. 询问是否安装 ResourceEx（选择保留已有版本时不安装；如果 config 存在则使用，否则询问用户）
+        let install_resourceex = if !selection.resourceex {
+            false
+        } else if let Some(cfg) = config {
             cfg.install_resourceex
         } else if cleanup_before_deploy {
             let resourceex_pattern = self
@@ -173,56 +318,76 @@ impl<'a> Installer<'a> {
         };
 
         // 2.2. 询问是否在游戏启动时弹出 BepInEx 控制台窗口（如果 config 存在则使用，否则询问用户）
-        let show_bepinex_console = if let Some(cfg) = config {
+        let show_bepinex_console = if !selection.bepinex {
+            false
+        } else if let Some(cfg) = config {
             cfg.show_bepinex_console
         } else {
             self.ui.install_ask_show_bepinex_console()?
         };
 
-        // 2.3. 选择 DLL 版本
-        let dll_version = if let Some(cfg) = config
+        // 2.3. 高级选项（如果 config 存在则使用，否则询问用户是否进入高级设置）
+        let advanced_menu = config.is_none() && self.ui.install_ask_configure_advanced_options()?;
+        let advanced = if let Some(cfg) = config {
+            AdvancedInstallOptions {
+                bepinex_log_levels: cfg.bepinex_log_levels.clone(),
+                backup_before_install: cfg.backup_before_install,
+            }
+        } else if advanced_menu {
+            AdvancedInstallOptions {
+                bepinex_log_levels: if selection.bepinex {
+                    Some(self.ui.install_select_bepinex_log_levels()?)
+                } else {
+                    None
+                },
+                backup_before_install: cleanup_before_deploy
+                    && self.ui.install_ask_backup_before_install()?,
+            }
+        } else {
+            AdvancedInstallOptions::default()
+        };
+
+        // 2.4. 选择 DLL 版本（选择保留已有 DLL 时跳过）
+        let dll_version = if !selection.metamystia {
+            None
+        } else if let Some(cfg) = config
             && let Some(ref v) = cfg.dll_version
         {
-            if !version_info.dlls.contains(v) {
-                self.ui
-                    .select_version_not_available("MetaMystia DLL", v, &version_info.dlls)?;
-                return Err(ManagerError::Other(format!(
-                    "Specified MetaMystia DLL version \"{}\" is not available",
-                    v
-                )));
-            }
-            v.clone()
+            Some(self.resolve_requested_version("MetaMystia DLL", &version_info.dlls, v)?)
         } else if self.ui.select_version_ask_select("MetaMystia DLL")? {
             let idx = self
                 .ui
                 .select_version_from_list("MetaMystia DLL", &version_info.dlls)?;
-            version_info.dlls[idx].clone()
+            Some(
+                version_info
+                    .dll_at(idx)
+                    .ok_or(ManagerError::InvalidVersionInfo)?
+                    .to_string(),
+            )
         } else {
-            version_info.latest_dll().to_string()
+            Some(version_info.latest_dll().to_string())
         };
 
-        // 2.4. 选择 ResourceEx 版本（仅在安装时）
+        // 2.5. 选择 ResourceEx 版本（仅在安装时；进入高级设置时直接从列表选择）
         let resourceex_version = if install_resourceex {
             if let Some(cfg) = config
                 && let Some(ref v) = cfg.resourceex_version
             {
-                if !version_info.zips.contains(v) {
-                    self.ui.select_version_not_available(
-                        "ResourceExample ZIP",
-                        v,
-                        &version_info.zips,
-                    )?;
-                    return Err(ManagerError::Other(format!(
-                        "Specified ResourceExample ZIP version \"{}\" is not available",
-                        v
-                    )));
-                }
-                Some(v.clone())
-            } else if self.ui.select_version_ask_select("ResourceExample ZIP")? {
+                Some(self.resolve_requested_version(
+                    "ResourceExample ZIP",
+                    &version_info.zips,
+                    v,
+                )?)
+            } else if advanced_menu || self.ui.select_version_ask_select("ResourceExample ZIP")? {
                 let idx = self
                     .ui
                     .select_version_from_list("ResourceExample ZIP", &version_info.zips)?;
-                Some(version_info.zips[idx].clone())
+                Some(
+                    version_info
+                        .zip_at(idx)
+                        .ok_or(ManagerError::InvalidVersionInfo)?
+                        .to_string(),
+                )
             } else {
                 Some(version_info.latest_resourceex().to_string())
             }
@@ -234,13 +399,13 @@ impl<'a> Installer<'a> {
             "Install.Version.Selected",
             Some(&format!(
                 "dll={};resourceex={}",
-                dll_version,
+                dll_version.as_deref().unwrap_or("kept"),
                 resourceex_version.as_ref().unwrap_or(&"none".to_string())
             )),
         );
 
         // 显示 GitHub Release Notes（仅当安装最新 DLL 版本时）
-        if dll_version == version_info.latest_dll() {
+        if dll_version.as_deref() == Some(version_info.latest_dll()) {
             match self.downloader.fetch_and_display_github_release_notes() {
                 Ok(Some(_)) => {
                     if !self.ui.download_ask_continue_after_release_notes()? {
@@ -252,6 +417,51 @@ impl<'a> Installer<'a> {
             }
         }
 
+        // 所有选项确定后，在下载或删除任何文件之前统一确认
+        let cleanup_targets = if cleanup_before_deploy {
+            Self::plan_install_cleanup(&self.game_root, selection)?
+        } else {
+            Vec::new()
+        };
+
+        let mut overwrite = Vec::new();
+        if cleanup_before_deploy {
+            if selection.bepinex && self.check_bepinex_installed() {
+                overwrite.push("BepInEx".to_string());
+            }
+            if selection.metamystia && self.check_metamystia_installed() {
+                overwrite.push("MetaMystia DLL".to_string());
+            }
+            if selection.resourceex && self.check_resourceex_installed() {
+                overwrite.push("ResourceExample ZIP".to_string());
+            }
+        }
+
+        let plan = InstallPlan {
+            bepinex_version: selection.bepinex.then(|| version_info.bep_in_ex.clone()),
+            dll_version: dll_version.clone(),
+            resourceex_version: resourceex_version.clone(),
+            overwrite,
+            cleanup_targets,
+            misplaced_resourceex,
+            show_bepinex_console,
+        };
+        if !self.ui.install_display_plan(&plan)? {
+            report_event("Install.Plan.Declined", None);
+            return Err(ManagerError::UserCancelled);
+        }
+
+        migrate_resourceex_files(&self.game_root, &plan.misplaced_resourceex, self.ui)?;
+
+        // 创建临时目录前确认至少有一个下载源可用，避免在无法下载时修改任何文件
+        let prefetch = self.downloader.prefetch_all_urls(
+            &version_info,
+            dll_version.as_deref(),
+            selection.bepinex,
+            resourceex_version.as_deref(),
+        )?;
+        self.ui.install_display_download_sources(&prefetch)?;
+
         // 3. 创建临时下载目录
         let (temp_dir, _temp_guard) = create_temp_dir_with_guard(&self.game_root).map_err(|e| {
             ManagerError::from(std::io::Error::new(
@@ -259,38 +469,114 @@ impl<'a> Installer<'a> {
                 format!("创建临时目录失败：{}", e),
             ))
         })?;
+        if is_cross_volume(&temp_dir, &self.game_root) {
+            self.ui
+                .temp_dir_warn_cross_volume(&temp_dir, &self.game_root)?;
+            report_event("Install.TempDir.CrossVolume", None);
+        }
 
         // 4. 下载文件
-        self.ui.install_display_step(3, "下载必要文件")?;
+        self.display_step(&progress, 3, "下载必要文件")?;
+
+        let estimate = prefetch.estimate_download_size(
+            selection.bepinex,
+            dll_version.is_some(),
+            resourceex_version.is_some(),
+        );
+        self.ui.install_display_estimated_download_size(&estimate)?;
+        self.ui
+            .install_display_estimated_duration(estimate_download_secs(estimate.max_bytes))?;
+
+        // 检测游戏所在磁盘的写入速度（解压与部署最终都写入游戏目录，临时目录可能位于其他磁盘）
+        if slow_storage_check_enabled()
+            && let Some(speed) = probe_write_speed(&self.game_root)
+            && speed < SLOW_STORAGE_THRESHOLD_MBPS
+        {
+            report_event("Env.SlowStorage", Some(&format!("{:.1}MB/s", speed)));
+
+            // 下载、解压与部署大约会写入三倍于下载大小的数据
+            let written_mb = (estimate.max_bytes * 3) as f64 / (1024.0 * 1024.0);
+            let extra_mins = (written_mb / speed / 60.0).ceil() as u64;
+            self.ui.install_warn_slow_storage(extra_mins)?;
+        }
+
+        // 检查临时目录所在磁盘的剩余空间是否足够下载
+        if let Some(available) = available_space(&temp_dir)
+            && available < estimate.max_bytes
+        {
+            report_event(
+                "Install.InsufficientSpace",
+                Some(&format!(
+                    "required={};available={}",
+                    estimate.max_bytes, available
+                )),
+            );
+            return Err(ManagerError::Other(format!(
+                "磁盘空间不足：预计需要 {}，可用 {}",
+                format_size(estimate.max_bytes),
+                format_size(available)
+            )));
+        }
+
+        let mut download_reports = Vec::new();
 
         // 下载 BepInEx
-        let bepinex_path = temp_dir.join(version_info.bepinex_filename()?);
-        let bepinex_from_primary = self
-            .downloader
-            .download_bepinex(&version_info, &bepinex_path)?;
+        let bepinex_download = if selection.bepinex {
+            // 压缩包边下载边解压到暂存目录，部署时再移动到游戏目录
+            let path =
+                temp_dir.join(Path::new(&version_info.bepinex_filename()?).with_extension(""));
+            let report = self.downloader.download_bepinex(&version_info, &path)?;
+            let from_primary = report.source == DownloadSource::BepInExPrimary;
+            download_reports.push(report);
+            Some((path, from_primary))
+        } else {
+            None
+        };
 
         // 下载 MetaMystia DLL
-        let dll_path = temp_dir.join(VersionInfo::metamystia_filename(&dll_version));
-        let try_github = dll_version == version_info.latest_dll();
-        self.downloader
-            .download_metamystia(&share_code, &dll_version, &dll_path, try_github)?;
+        let dll_path = if let Some(ref version) = dll_version {
+            let path = temp_dir.join(VersionInfo::metamystia_filename(version));
+            let try_github = version == version_info.latest_dll();
+            download_reports.push(self.downloader.download_metamystia(
+                &share_code,
+                version,
+                &path,
+                try_github,
+            )?);
+            Some(path)
+        } else {
+            None
+        };
 
         // 下载 ResourceExample ZIP
         let resourceex_path = if let Some(ref version) = resourceex_version {
             let path = temp_dir.join(VersionInfo::resourceex_filename(version));
-            self.downloader
-                .download_resourceex(&share_code, version, &path)?;
+            download_reports.push(self.downloader.download_resourceex(
+                &share_code,
+                version,
+                &path,
+            )?);
             Some(path)
         } else {
             None
         };
 
+        record_download_speed(&download_reports);
         self.ui.install_downloads_completed()?;
 
         // 5. 在安装前清理旧版本
         if cleanup_before_deploy {
+            if advanced.backup_before_install && !plan.cleanup_targets.is_empty() {
+                let archive_path = create_undo_archive(&self.game_root, &plan.cleanup_targets)?;
+                self.ui.uninstall_undo_archive_created(&archive_path)?;
+            }
+
             self.ui.install_start_cleanup()?;
-            let (success, failed) = Self::execute_install_cleanup(&self.game_root, self.ui)?;
+            let (success, failed, results) =
+                Self::execute_install_cleanup(&plan.cleanup_targets, self.ui)?;
+            if verbose() {
+                self.ui.install_display_cleanup_detail(&results)?;
+            }
             self.ui.install_cleanup_result(success, failed)?;
             report_event(
                 "Install.Cleanup",
@@ -299,16 +585,148 @@ impl<'a> Installer<'a> {
         }
 
         // 6. 安装文件
-        self.ui.install_display_step(4, "安装文件")?;
+        self.display_step(&progress, 4, "安装文件")?;
+
+        // 安装 BepInEx
+        let mut loader_check_deadline = Instant::now();
+        if let Some((ref bepinex_dir, bepinex_from_primary)) = bepinex_download {
+            self.deploy_bepinex(
+                bepinex_dir,
+                bepinex_from_primary,
+                show_bepinex_console,
+                advanced.bepinex_log_levels.as_deref(),
+            )?;
+            loader_check_deadline += LOADER_CHECK_DELAY;
+        }
 
-        // 检查 BepInEx 是否存在（用于决定是否跳过 plugins）
-        let bepinex_dir = self.game_root.join("BepInEx");
-        let bepinex_exists = bepinex_dir.exists();
+        // 安装 MetaMystia DLL
+        if let Some(ref path) = dll_path {
+            Extractor::deploy_metamystia(path, &self.game_root)?;
+        }
+
+        // 安装 ResourceExample ZIP
+        if let (Some(path), Some(version)) = (&resourceex_path, &resourceex_version) {
+            Extractor::deploy_resourceex(path, &self.game_root, version)?;
+        }
 
-        // 安装 BepInEx（如果之前存在则保留 plugins 目录）
-        Extractor::deploy_bepinex(&bepinex_path, &self.game_root, bepinex_exists)?;
+        // 7. 检查加载器是否被杀毒软件隔离（部署 DLL 与 ResourceEx 的时间已计入等待）
+        let loader_path = self.game_root.join(BEPINEX_LOADER_DLL);
+        if Self::wait_for_loader_removal(&loader_path, loader_check_deadline) {
+            let detection = query_defender_detection(&loader_path);
+            self.ui
+                .install_warn_loader_quarantined(&loader_path, detection.as_deref())?;
+            report_event("Install.LoaderQuarantined", detection.as_deref());
+        }
 
-        // 写入默认配置（如果不存在）
+        let mut summary = OperationSummary::default();
+        if bepinex_download.is_some() {
+            summary.push(
+                "BepInEx",
+                SummaryAction::Installed,
+                Some(&version_info.bep_in_ex),
+                Some(self.game_root.join("BepInEx")),
+            );
+        } else {
+            summary.push("BepInEx", SummaryAction::Kept, None, None);
+        }
+        match &dll_version {
+            Some(version) => summary.push(
+                "MetaMystia DLL",
+                SummaryAction::Installed,
+                Some(version),
+                Some(
+                    self.game_root
+                        .join("BepInEx")
+                        .join("plugins")
+                        .join(VersionInfo::metamystia_filename(version)),
+                ),
+            ),
+            None => summary.push("MetaMystia DLL", SummaryAction::Kept, None, None),
+        }
+        if let Some(ref version) = resourceex_version {
+            summary.push(
+                "ResourceExample ZIP",
+                SummaryAction::Installed,
+                Some(version),
+                Some(
+                    self.game_root
+                        .join("ResourceEx")
+                        .join(VersionInfo::resourceex_filename(version)),
+                ),
+            );
+        } else if !selection.resourceex {
+            summary.push("ResourceExample ZIP", SummaryAction::Kept, None, None);
+        }
+
+        self.ui.display_download_summary(&download_reports)?;
+        self.ui.display_operation_summary(&summary)?;
+        self.ui
+            .install_finished(show_bepinex_console, first_install && selection.bepinex)?;
+        if let Some((_, false)) = bepinex_download {
+            self.ui.warn_bepinex_il2cpp_download(UNITY_LIBRARY_SOURCE)?;
+        }
+        report_event("Install.Finished", None);
+
+        Ok(())
+    }
+
+    /// 在截止时间前轮询加载器是否仍然存在，加载器不存在时立即返回 true
+    fn wait_for_loader_removal(loader_path: &Path, deadline: Instant) -> bool {
+        loop {
+            if !loader_path.exists() {
+                return true;
+            }
+            if Instant::now() >= deadline {
+                return false;
+            }
+            sleep(LOADER_CHECK_INTERVAL);
+        }
+    }
+
+    /// 显示安装步骤并记录进度
+    fn display_step(&self, progress: &ProgressScope, step: usize, description: &str) -> Result<()> {
+        progress.step(step, description);
+        self.ui.install_display_step(step, description)
+    }
+
+    /// 在可用版本中查找命令行指定的版本，找不到时列出可用版本并返回错误
+    fn resolve_requested_version(
+        &self,
+        component: &str,
+        versions: &[String],
+        requested: &str,
+    ) -> Result<String> {
+        match find_version_index(versions, requested) {
+            Some(idx) => Ok(versions[idx].clone()),
+            None => {
+                self.ui
+                    .select_version_not_available(component, requested, versions)?;
+                Err(ManagerError::Other(format!(
+                    "Specified {} version \"{}\" is not available (available: {})",
+                    component,
+                    requested,
+                    versions.join(", ")
+                )))
+            }
+        }
+    }
+
+    /// 部署 BepInEx 并写入默认配置
+    fn deploy_bepinex(
+        &self,
+        bepinex_dir: &Path,
+        bepinex_from_primary: bool,
+        show_bepinex_console: bool,
+        log_levels: Option<&[String]>,
+    ) -> Result<()> {
+        // 必须在清理之后检查：清理会删除 BepInEx 下除保留目录外的内容，
+        // 只有 plugins 目录实际仍然存在时才需要跳过压缩包中的 plugins
+        let plugins_exist = self.game_root.join("BepInEx").join("plugins").is_dir();
+
+        // 安装 BepInEx（如果 plugins 目录仍存在则保留）
+        Extractor::deploy_bepinex(bepinex_dir, &self.game_root, plugins_exist)?;
+
+        // 写入默认配置（必须在解压之后，避免被压缩包中的 BepInEx.cfg 覆盖）
         let bepinex_config_dir = self.game_root.join("BepInEx").join("config");
         if !bepinex_config_dir.exists() {
             std::fs::create_dir_all(&bepinex_config_dir).map_err(|e| {
@@ -331,17 +749,20 @@ impl<'a> Installer<'a> {
 # Default value: true
 Enabled = false
 "#;
-        let bepinex_cfg_il2cpp = r#"[IL2CPP]
+        let bepinex_cfg_il2cpp = format!(
+            r#"[IL2CPP]
 
 ## URL to a ZIP file with managed Unity base libraries. They are used by Il2CppInterop to generate interop assemblies.
-## The URL can include {VERSION} template which will be replaced with the game's Unity engine version.
+## The URL can include {{VERSION}} template which will be replaced with the game's Unity engine version.
 ## If a .zip file with the same filename as the URL (after template replacement) already exists in unity-libs, it will be used instead of downloading a new copy.
 ## If you want to ensure BepInEx doesn't try to connect to the internet, set this to only the .zip filename (without a URL) and manually place the file in the unity-libs directory.
 ##
 # Setting type: String
-# Default value: https://unity.bepinex.dev/libraries/{VERSION}.zip
-UnityBaseLibrariesSource = https://url.izakaya.cc/unity-library
-"#;
+# Default value: https://unity.bepinex.dev/libraries/{{VERSION}}.zip
+UnityBaseLibrariesSource = {}
+"#,
+            UNITY_LIBRARY_SOURCE
+        );
 
         let mut bepinex_cfg = String::new();
         if !show_bepinex_console {
@@ -351,48 +772,236 @@ UnityBaseLibrariesSource = https://url.izakaya.cc/unity-library
             if !bepinex_cfg.is_empty() {
                 bepinex_cfg.push('\n');
             }
-            bepinex_cfg.push_str(bepinex_cfg_il2cpp);
+            bepinex_cfg.push_str(&bepinex_cfg_il2cpp);
+        }
+        if show_bepinex_console {
+            // 压缩包中可能自带关闭控制台的 BepInEx.cfg，显式写入用户的选择
+            bepinex_cfg = set_ini_value(&bepinex_cfg, "Logging.Console", "Enabled", "true");
+        }
+        if let Some(levels) = log_levels {
+            bepinex_cfg = set_ini_value(
+                &bepinex_cfg,
+                "Logging.Console",
+                "LogLevels",
+                &levels.join(", "),
+            );
         }
         if !bepinex_cfg.is_empty() {
-            let bepinex_tmp_cfg = bepinex_cfg_path.with_extension("cfg.tmp");
+            write_file_atomic(&bepinex_cfg_path, bepinex_cfg.as_bytes())?;
+        }
 
-            std::fs::write(&bepinex_tmp_cfg, bepinex_cfg.as_bytes()).map_err(|e| {
-                ManagerError::from(std::io::Error::new(
-                    e.kind(),
-                    format!(
-                        "写入 BepInEx 临时配置文件 {} 失败：{}",
-                        bepinex_tmp_cfg.display(),
-                        e
-                    ),
-                ))
-            })?;
+        Ok(())
+    }
+}
 
-            match atomic_rename_or_copy(&bepinex_tmp_cfg, &bepinex_cfg_path) {
-                Ok(_) => {
-                    let _ = std::fs::remove_file(&bepinex_tmp_cfg);
-                }
-                Err(e) => {
-                    let _ = std::fs::remove_file(&bepinex_tmp_cfg);
-                    return Err(ManagerError::from(std::io::Error::other(format!(
-                        "写入 BepInEx 配置文件 {} 失败：{}",
-                        bepinex_cfg_path.display(),
-                        e
-                    ))));
-                }
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bepinex_config::bepinex_console_enabled;
+    use crate::test_utils::{ScratchDir, TestUI};
+
+    /// 构造一个已安装 BepInEx、MetaMystia 与其他插件的游戏目录
+    fn populated_game(name: &str) -> ScratchDir {
+        let game = ScratchDir::new(name);
+        game.write("BepInEx/core/BepInEx.Core.dll", b"core");
+        game.write("BepInEx/cache/chainloader_cache.dat", b"cache");
+        game.write("BepInEx/LogOutput.log", b"log");
+        game.write("BepInEx/plugins/MetaMystia-v1.0.0.dll", b"dll");
+        game.write("BepInEx/plugins/OtherPlugin.dll", b"other");
+        game.write("BepInEx/config/BepInEx.cfg", b"[Logging.Console]");
+        game.write("BepInEx/config/other.plugin.cfg", b"setting = 1");
+        game.write("BepInEx/patchers/OtherPatcher.dll", b"patcher");
+        game.write("BepInEx/GeneratedMMHookDLLs/MMHOOK_Assembly.dll", b"hook");
+        game.write(BEPINEX_LOADER_DLL, b"loader");
+        game.write("ResourceEx/ResourceExample-v1.0.0.zip", b"zip");
+        game
+    }
+
+    #[test]
+    fn loader_check_does_not_wait_when_loader_is_missing_or_time_is_up() {
+        let dir = ScratchDir::new("loader-check");
+        let loader = dir.path().join(BEPINEX_LOADER_DLL);
+        let start = Instant::now();
+
+        assert!(Installer::wait_for_loader_removal(
+            &loader,
+            start + LOADER_CHECK_DELAY
+        ));
+
+        dir.write(BEPINEX_LOADER_DLL, b"");
+        assert!(!Installer::wait_for_loader_removal(&loader, start));
+        assert!(start.elapsed() < LOADER_CHECK_DELAY);
+    }
+
+    #[test]
+    fn loader_check_waits_until_deadline_for_present_loader() {
+        let dir = ScratchDir::new("loader-wait");
+        let loader = dir.write(BEPINEX_LOADER_DLL, b"");
+        let deadline = Instant::now() + LOADER_CHECK_INTERVAL;
+
+        assert!(!Installer::wait_for_loader_removal(&loader, deadline));
+        assert!(Instant::now() >= deadline);
+    }
+
+    #[test]
+    fn install_cleanup_keeps_preserved_bepinex_dirs() {
+        let game = populated_game("install-cleanup");
+        let root = game.path();
+        let ui = TestUI::new();
+
+        let targets = Installer::plan_install_cleanup(root, OverwriteSelection::all()).unwrap();
+        let (_, failed, _) = Installer::execute_install_cleanup(&targets, &ui).unwrap();
+        assert_eq!(failed, 0);
+
+        for kept in [
+            "BepInEx/plugins/OtherPlugin.dll",
+            "BepInEx/config/other.plugin.cfg",
+            "BepInEx/patchers/OtherPatcher.dll",
+            "BepInEx/GeneratedMMHookDLLs/MMHOOK_Assembly.dll",
+        ] {
+            assert!(root.join(kept).is_file(), "{} 应被保留", kept);
         }
 
-        // 安装 MetaMystia DLL
-        Extractor::deploy_metamystia(&dll_path, &self.game_root)?;
+        for removed in [
+            "BepInEx/core",
+            "BepInEx/cache",
+            "BepInEx/LogOutput.log",
+            "BepInEx/plugins/MetaMystia-v1.0.0.dll",
+            "BepInEx/config/BepInEx.cfg",
+            "ResourceEx/ResourceExample-v1.0.0.zip",
+            BEPINEX_LOADER_DLL,
+        ] {
+            assert!(!root.join(removed).exists(), "{} 应被删除", removed);
+        }
+    }
 
-        // 安装 ResourceExample ZIP
-        if let Some(ref path) = resourceex_path {
-            Extractor::deploy_resourceex(path, &self.game_root)?;
+    /// 构造 BepInEx 压缩包解压后的暂存目录，其中自带关闭控制台的配置
+    fn staged_bepinex(name: &str) -> ScratchDir {
+        let staging = ScratchDir::new(name);
+        staging.write("BepInEx/core/BepInEx.Core.dll", b"new core");
+        staging.write("BepInEx/plugins/Bundled.dll", b"bundled");
+        staging.write(
+            "BepInEx/config/BepInEx.cfg",
+            b"[Logging.Console]\nEnabled = false\n",
+        );
+        staging.write(BEPINEX_LOADER_DLL, b"new loader");
+        staging
+    }
+
+    #[test]
+    fn reinstall_over_existing_keeps_plugins_and_applies_console_choice() {
+        let game = populated_game("reinstall-existing");
+        let staging = staged_bepinex("reinstall-staging");
+        let ui = TestUI::new();
+        let installer = Installer::new(game.path().to_path_buf(), &ui).unwrap();
+
+        let targets =
+            Installer::plan_install_cleanup(game.path(), OverwriteSelection::all()).unwrap();
+        Installer::execute_install_cleanup(&targets, &ui).unwrap();
+        installer
+            .deploy_bepinex(staging.path(), true, false, None)
+            .unwrap();
+
+        let plugins = game.path().join("BepInEx").join("plugins");
+        assert!(plugins.join("OtherPlugin.dll").is_file());
+        assert!(!plugins.join("Bundled.dll").exists());
+        assert!(installer.check_bepinex_installed());
+        assert!(!bepinex_console_enabled(game.path()).unwrap());
+    }
+
+    #[test]
+    fn fresh_install_deploys_plugins_and_applies_console_choice() {
+        let game = ScratchDir::new("fresh-install");
+        let staging = staged_bepinex("fresh-staging");
+        let ui = TestUI::new();
+        let installer = Installer::new(game.path().to_path_buf(), &ui).unwrap();
+
+        installer
+            .deploy_bepinex(staging.path(), true, true, None)
+            .unwrap();
+
+        let plugins = game.path().join("BepInEx").join("plugins");
+        assert!(plugins.join("Bundled.dll").is_file());
+        assert!(game.path().join(BEPINEX_LOADER_DLL).is_file());
+        assert!(bepinex_console_enabled(game.path()).unwrap());
+    }
+
+    #[test]
+    fn cleanup_plan_excludes_other_plugins_and_preserved_dirs() {
+        let game = populated_game("cleanup-plan");
+        let root = game.path();
+        let bepinex = root.join("BepInEx");
+
+        let targets = Installer::plan_install_cleanup(root, OverwriteSelection::all()).unwrap();
+
+        assert!(targets.contains(&bepinex.join("plugins").join("MetaMystia-v1.0.0.dll")));
+        assert!(targets.contains(&bepinex.join("config").join("BepInEx.cfg")));
+        assert!(targets.contains(&bepinex.join("core")));
+        for dir in PRESERVED_BEPINEX_DIRS {
+            assert!(!targets.contains(&bepinex.join(dir)), "{} 不应被清理", dir);
         }
+        assert!(
+            targets
+                .iter()
+                .all(|t| !t.ends_with("OtherPlugin.dll") && !t.ends_with("other.plugin.cfg"))
+        );
+    }
 
-        self.ui.install_finished(show_bepinex_console)?;
-        report_event("Install.Finished", None);
+    #[test]
+    fn cleanup_plan_skips_kept_components() {
+        let game = populated_game("cleanup-plan-kept");
+        let root = game.path();
+        let selection = OverwriteSelection {
+            bepinex: false,
+            metamystia: true,
+            resourceex: false,
+        };
 
-        Ok(())
+        let targets = Installer::plan_install_cleanup(root, selection).unwrap();
+
+        assert_eq!(
+            targets,
+            vec![
+                root.join("BepInEx")
+                    .join("plugins")
+                    .join("MetaMystia-v1.0.0.dll")
+            ]
+        );
+    }
+
+    #[test]
+    fn misplaced_resourceex_is_only_moved_when_migrated() {
+        let game = ScratchDir::new("misplaced-resourceex");
+        let root = game.path();
+        game.write("BepInEx/plugins/ResourceExample-v1.0.0.zip", b"zip");
+        game.write("BepInEx/config/ResourceExample-v1.1.0.zip", b"zip");
+        let ui = TestUI::new();
+
+        let misplaced = find_misplaced_resourceex(root, &ui).unwrap();
+
+        assert_eq!(misplaced.len(), 2);
+        assert!(misplaced.iter().all(|p| p.exists()));
+        assert!(!root.join("ResourceEx").exists());
+
+        assert_eq!(migrate_resourceex_files(root, &misplaced, &ui).unwrap(), 2);
+        assert!(misplaced.iter().all(|p| !p.exists()));
+        assert!(root.join("ResourceEx/ResourceExample-v1.0.0.zip").exists());
+        assert!(root.join("ResourceEx/ResourceExample-v1.1.0.zip").exists());
+    }
+
+    #[test]
+    fn declined_migration_plans_no_moves() {
+        let game = ScratchDir::new("misplaced-resourceex-declined");
+        game.write("BepInEx/plugins/ResourceExample-v1.0.0.zip", b"zip");
+        let ui = TestUI::with_confirm(false);
+
+        let misplaced = find_misplaced_resourceex(game.path(), &ui).unwrap();
+
+        assert!(misplaced.is_empty());
+        assert!(
+            game.path()
+                .join("BepInEx/plugins/ResourceExample-v1.0.0.zip")
+                .exists()
+        );
     }
 }
@@ -0,0 +1,57 @@
+use crate::config::{GAME_EXECUTABLE, GAME_STEAM_APP_ID};
+use crate::env_check::PathSource;
+use crate::error::{ManagerError, Result};
+use crate::metrics::report_event;
+
+use std::os::windows::process::CommandExt;
+use std::path::Path;
+use std::process::Command;
+use windows::Win32::System::Threading::CREATE_NO_WINDOW;
+
+/// 启动游戏：通过 Steam 检测到的安装使用 steam:// 协议启动，否则直接运行游戏可执行文件
+pub fn launch_game(game_root: &Path, source: &PathSource) -> Result<()> {
+    let res = match source {
+        PathSource::Steam(_) => Command::new("cmd")
+            .args([
+                "/C",
+                "start",
+                "",
+                &format!("steam://rungameid/{}", GAME_STEAM_APP_ID),
+            ])
+            .creation_flags(CREATE_NO_WINDOW.0)
+            .spawn(),
+        PathSource::CurrentDirectory | PathSource::Specified => {
+            Command::new(game_root.join(GAME_EXECUTABLE))
+                .current_dir(game_root)
+                .spawn()
+        }
+    };
+
+    match res {
+        Ok(_) => {
+            report_event("Launch.Success", Some(source.as_str()));
+            Ok(())
+        }
+        Err(e) => {
+            report_event(
+                "Launch.Failed",
+                Some(&format!("{};err={}", source.as_str(), e)),
+            );
+            // 由 UI 统一添加“启动游戏失败”前缀
+            Err(e.into())
+        }
+    }
+}
+
+/// 在默认浏览器中打开链接（不等待浏览器启动完成）
+pub fn open_url(url: &str) -> Result<()> {
+    Command::new("cmd")
+        .args(["/C", "start", "", url])
+        .creation_flags(CREATE_NO_WINDOW.0)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| {
+            report_event("OpenUrl.Failed", Some(&format!("{};err={}", url, e)));
+            ManagerError::from(e)
+        })
+}
@@ -0,0 +1,1613 @@
+use crate::config::{OperationMode, UninstallMode};
+use crate::downloader::{DownloadReport, DownloadSizeEstimate, PrefetchResult};
+use crate::env_check::{GameProcess, PathSource};
+use crate::error::{Result, SourceFailure};
+use crate::file_ops::DeletionResult;
+use crate::model::VersionInfo;
+use crate::net::format_utc_time;
+use crate::prefs::data_dir;
+use crate::progress::ProgressSnapshot;
+use crate::ui::{
+    InstallPlan, OperationSummary, OverwriteSelection, PendingUpgrade, Ui, WaitOutcome,
+};
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+const LOG_FILE_NAME: &str = "manager.log";
+const ROTATED_LOG_FILE_NAME: &str = "manager.log.1";
+/// 日志文件默认超过此大小时轮换
+const LOG_ROTATE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// 默认日志目录（%APPDATA%\meta-mystia-manager\logs）
+pub fn default_log_dir() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("logs"))
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum LogLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+/// 一条日志记录（按行写入 JSON）
+#[derive(Debug, Deserialize, Serialize)]
+struct LogEntry<'a> {
+    timestamp: String,
+    level: LogLevel,
+    category: &'a str,
+    message: String,
+}
+
+struct LogFile {
+    file: File,
+    size: u64,
+}
+
+/// 将每次 UI 调用记录到日志文件，显示仍交给内部的 UI 实现
+pub struct LoggingUi<T: Ui> {
+    inner: T,
+    log_dir: PathBuf,
+    /// 日志文件超过此大小时轮换
+    rotate_bytes: u64,
+    file: Mutex<Option<LogFile>>,
+}
+
+impl<T: Ui> LoggingUi<T> {
+    /// 以追加模式打开日志文件；无法打开时不记录日志，仅转发 UI 调用
+    pub fn new(inner: T, log_dir: PathBuf) -> Self {
+        let file = Self::open(&log_dir);
+        Self {
+            inner,
+            log_dir,
+            rotate_bytes: LOG_ROTATE_BYTES,
+            file: Mutex::new(file),
+        }
+    }
+
+    #[cfg(test)]
+    fn with_rotate_bytes(mut self, bytes: u64) -> Self {
+        self.rotate_bytes = bytes;
+        self
+    }
+
+    fn open(log_dir: &Path) -> Option<LogFile> {
+        std::fs::create_dir_all(log_dir).ok()?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log_dir.join(LOG_FILE_NAME))
+            .ok()?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+        Some(LogFile { file, size })
+    }
+
+    /// 将当前日志重命名为 manager.log.1 并创建新文件
+    fn rotate(&self) -> Option<LogFile> {
+        let _ = std::fs::rename(
+            self.log_dir.join(LOG_FILE_NAME),
+            self.log_dir.join(ROTATED_LOG_FILE_NAME),
+        );
+        Self::open(&self.log_dir)
+    }
+
+    fn log(&self, level: LogLevel, category: &str, message: String) {
+        let entry = LogEntry {
+            timestamp: format_utc_time(SystemTime::now()),
+            level,
+            category,
+            message,
+        };
+        let Ok(mut line) = serde_json::to_string(&entry) else {
+            return;
+        };
+        line.push('\n');
+
+        let mut guard = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if guard.as_ref().is_some_and(|f| f.size >= self.rotate_bytes) {
+            *guard = None;
+            *guard = self.rotate();
+        }
+
+        if let Some(log) = guard.as_mut()
+            && log.file.write_all(line.as_bytes()).is_ok()
+        {
+            log.size += line.len() as u64;
+        }
+    }
+
+    /// 记录询问类调用的返回结果（用户的回答或错误）
+    fn log_answer<A: Debug>(&self, category: &str, method: &str, result: &Result<A>) {
+        let message = match result {
+            Ok(answer) => format!("{} -> {:?}", method, answer),
+            Err(e) => format!("{} -> error: {}", method, e),
+        };
+        self.log(LogLevel::Info, category, message);
+    }
+}
+
+impl<T: Ui> Ui for LoggingUi<T> {
+    fn display_welcome(&self, is_first_run: bool) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "display",
+            format!("display_welcome: is_first_run={:?}", is_first_run),
+        );
+        self.inner.display_welcome(is_first_run)
+    }
+
+    fn ask_telemetry_consent(&self) -> Result<bool> {
+        self.log(LogLevel::Info, "ask", "ask_telemetry_consent".to_string());
+        let result = self.inner.ask_telemetry_consent();
+        self.log_answer("ask", "ask_telemetry_consent", &result);
+        result
+    }
+
+    fn display_version(&self, manager_version: Option<&str>) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "display",
+            format!("display_version: manager_version={:?}", manager_version),
+        );
+        self.inner.display_version(manager_version)
+    }
+
+    fn display_update_channel_info(&self, channel: &str) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "display",
+            format!("display_update_channel_info: channel={:?}", channel),
+        );
+        self.inner.display_update_channel_info(channel)
+    }
+
+    fn warn_old_manager_version(
+        &self,
+        current: &str,
+        latest: &str,
+        behind_versions: usize,
+    ) -> Result<()> {
+        self.log(
+            LogLevel::Warn,
+            "warn",
+            format!(
+                "warn_old_manager_version: current={:?}, latest={:?}, behind_versions={:?}",
+                current, latest, behind_versions
+            ),
+        );
+        self.inner
+            .warn_old_manager_version(current, latest, behind_versions)
+    }
+
+    fn display_game_running_warning(&self, processes: &[GameProcess]) -> Result<()> {
+        self.log(
+            LogLevel::Warn,
+            "display",
+            format!("display_game_running_warning: processes={:?}", processes),
+        );
+        self.inner.display_game_running_warning(processes)
+    }
+
+    fn display_available_updates(
+        &self,
+        dll_available: bool,
+        resourceex_available: bool,
+    ) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "display",
+            format!(
+                "display_available_updates: dll_available={:?}, resourceex_available={:?}",
+                dll_available, resourceex_available
+            ),
+        );
+        self.inner
+            .display_available_updates(dll_available, resourceex_available)
+    }
+
+    fn display_upgrade_check_failed(&self, err: &str) -> Result<()> {
+        self.log(
+            LogLevel::Error,
+            "display",
+            format!("display_upgrade_check_failed: err={:?}", err),
+        );
+        self.inner.display_upgrade_check_failed(err)
+    }
+
+    fn display_progress_cancelled(&self, snapshot: &ProgressSnapshot) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "display",
+            format!("display_progress_cancelled: snapshot={:?}", snapshot),
+        );
+        self.inner.display_progress_cancelled(snapshot)
+    }
+
+    fn select_operation_mode(&self) -> Result<OperationMode> {
+        self.log(
+            LogLevel::Info,
+            "select",
+            "select_operation_mode".to_string(),
+        );
+        let result = self.inner.select_operation_mode();
+        self.log_answer("select", "select_operation_mode", &result);
+        result
+    }
+
+    fn blank_line(&self) -> Result<()> {
+        self.inner.blank_line()
+    }
+
+    fn wait_for_key(&self) -> Result<()> {
+        self.log(LogLevel::Info, "wait", "wait_for_key".to_string());
+        self.inner.wait_for_key()
+    }
+
+    fn message(&self, text: &str) -> Result<()> {
+        self.log(LogLevel::Info, "general", text.to_string());
+        self.inner.message(text)
+    }
+
+    fn warn(&self, text: &str) -> Result<()> {
+        self.log(LogLevel::Warn, "general", text.to_string());
+        self.inner.warn(text)
+    }
+
+    fn error(&self, text: &str) -> Result<()> {
+        self.log(LogLevel::Error, "general", text.to_string());
+        self.inner.error(text)
+    }
+
+    fn display_operation_summary(&self, summary: &OperationSummary) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "display",
+            format!("display_operation_summary: summary={:?}", summary),
+        );
+        self.inner.display_operation_summary(summary)
+    }
+
+    fn display_controlled_folder_access_blocked(&self, app_path: &Path) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "display",
+            format!(
+                "display_controlled_folder_access_blocked: app_path={:?}",
+                app_path
+            ),
+        );
+        self.inner
+            .display_controlled_folder_access_blocked(app_path)
+    }
+
+    fn path_display_steam_found(&self, app_id: u32, name: Option<&str>, path: &Path) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "path",
+            format!(
+                "path_display_steam_found: app_id={:?}, name={:?}, path={:?}",
+                app_id, name, path
+            ),
+        );
+        self.inner.path_display_steam_found(app_id, name, path)
+    }
+
+    fn path_display_steam_stale(&self, app_id: u32, name: Option<&str>, path: &Path) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "path",
+            format!(
+                "path_display_steam_stale: app_id={:?}, name={:?}, path={:?}",
+                app_id, name, path
+            ),
+        );
+        self.inner.path_display_steam_stale(app_id, name, path)
+    }
+
+    fn path_confirm_use_steam_found(&self) -> Result<bool> {
+        self.log(
+            LogLevel::Info,
+            "path",
+            "path_confirm_use_steam_found".to_string(),
+        );
+        let result = self.inner.path_confirm_use_steam_found();
+        self.log_answer("path", "path_confirm_use_steam_found", &result);
+        result
+    }
+
+    fn select_game_path_from_candidates(
+        &self,
+        candidates: &[(PathBuf, PathSource)],
+    ) -> Result<PathBuf> {
+        self.log(
+            LogLevel::Info,
+            "select",
+            format!(
+                "select_game_path_from_candidates: candidates={:?}",
+                candidates
+            ),
+        );
+        let result = self.inner.select_game_path_from_candidates(candidates);
+        self.log_answer("select", "select_game_path_from_candidates", &result);
+        result
+    }
+
+    fn path_ask_manual_entry(&self) -> Result<Option<PathBuf>> {
+        self.log(LogLevel::Info, "path", "path_ask_manual_entry".to_string());
+        let result = self.inner.path_ask_manual_entry();
+        self.log_answer("path", "path_ask_manual_entry", &result);
+        result
+    }
+
+    fn path_manual_entry_not_found(&self, input: &Path) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "path",
+            format!("path_manual_entry_not_found: input={:?}", input),
+        );
+        self.inner.path_manual_entry_not_found(input)
+    }
+
+    fn warn_conflicting_game_exe(&self, extras: &[PathBuf]) -> Result<bool> {
+        self.log(
+            LogLevel::Warn,
+            "warn",
+            format!("warn_conflicting_game_exe: extras={:?}", extras),
+        );
+        let result = self.inner.warn_conflicting_game_exe(extras);
+        self.log_answer("warn", "warn_conflicting_game_exe", &result);
+        result
+    }
+
+    fn warn_cloud_sync_directory(&self, provider: &str) -> Result<()> {
+        self.log(
+            LogLevel::Warn,
+            "warn",
+            format!("warn_cloud_sync_directory: provider={:?}", provider),
+        );
+        self.inner.warn_cloud_sync_directory(provider)
+    }
+
+    fn install_display_step(&self, step: usize, description: &str) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "install",
+            format!(
+                "install_display_step: step={:?}, description={:?}",
+                step, description
+            ),
+        );
+        self.inner.install_display_step(step, description)
+    }
+
+    fn install_display_version_info(&self, version_info: &VersionInfo) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "install",
+            format!(
+                "install_display_version_info: version_info={:?}",
+                version_info
+            ),
+        );
+        self.inner.install_display_version_info(version_info)
+    }
+
+    fn install_display_estimated_download_size(
+        &self,
+        estimate: &DownloadSizeEstimate,
+    ) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "install",
+            format!(
+                "install_display_estimated_download_size: estimate={:?}",
+                estimate
+            ),
+        );
+        self.inner.install_display_estimated_download_size(estimate)
+    }
+
+    fn install_display_estimated_duration(&self, estimated_secs: u64) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "install",
+            format!(
+                "install_display_estimated_duration: estimated_secs={:?}",
+                estimated_secs
+            ),
+        );
+        self.inner
+            .install_display_estimated_duration(estimated_secs)
+    }
+
+    fn install_display_download_sources(&self, result: &PrefetchResult) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "install",
+            format!("install_display_download_sources: result={:?}", result),
+        );
+        self.inner.install_display_download_sources(result)
+    }
+
+    fn install_warn_slow_storage(&self, estimated_extra_mins: u64) -> Result<()> {
+        self.log(
+            LogLevel::Warn,
+            "install",
+            format!(
+                "install_warn_slow_storage: estimated_extra_mins={:?}",
+                estimated_extra_mins
+            ),
+        );
+        self.inner.install_warn_slow_storage(estimated_extra_mins)
+    }
+
+    fn install_warn_existing(
+        &self,
+        bepinex_installed: bool,
+        metamystia_installed: bool,
+        resourceex_installed: bool,
+        cleanup_plan: &[PathBuf],
+    ) -> Result<()> {
+        self.log(LogLevel::Warn, "install", format!("install_warn_existing: bepinex_installed={:?}, metamystia_installed={:?}, resourceex_installed={:?}, cleanup_plan={:?}", bepinex_installed, metamystia_installed, resourceex_installed, cleanup_plan));
+        self.inner.install_warn_existing(
+            bepinex_installed,
+            metamystia_installed,
+            resourceex_installed,
+            cleanup_plan,
+        )
+    }
+
+    fn install_confirm_overwrite(&self) -> Result<bool> {
+        self.log(
+            LogLevel::Info,
+            "install",
+            "install_confirm_overwrite".to_string(),
+        );
+        let result = self.inner.install_confirm_overwrite();
+        self.log_answer("install", "install_confirm_overwrite", &result);
+        result
+    }
+
+    fn install_select_overwrite(
+        &self,
+        bepinex_installed: bool,
+        metamystia_installed: bool,
+        resourceex_installed: bool,
+    ) -> Result<OverwriteSelection> {
+        self.log(LogLevel::Info, "install", format!("install_select_overwrite: bepinex_installed={:?}, metamystia_installed={:?}, resourceex_installed={:?}", bepinex_installed, metamystia_installed, resourceex_installed));
+        let result = self.inner.install_select_overwrite(
+            bepinex_installed,
+            metamystia_installed,
+            resourceex_installed,
+        );
+        self.log_answer("install", "install_select_overwrite", &result);
+        result
+    }
+
+    fn install_ask_install_resourceex(&self) -> Result<bool> {
+        self.log(
+            LogLevel::Info,
+            "install",
+            "install_ask_install_resourceex".to_string(),
+        );
+        let result = self.inner.install_ask_install_resourceex();
+        self.log_answer("install", "install_ask_install_resourceex", &result);
+        result
+    }
+
+    fn install_ask_show_bepinex_console(&self) -> Result<bool> {
+        self.log(
+            LogLevel::Info,
+            "install",
+            "install_ask_show_bepinex_console".to_string(),
+        );
+        let result = self.inner.install_ask_show_bepinex_console();
+        self.log_answer("install", "install_ask_show_bepinex_console", &result);
+        result
+    }
+
+    fn install_ask_configure_advanced_options(&self) -> Result<bool> {
+        self.log(
+            LogLevel::Info,
+            "install",
+            "install_ask_configure_advanced_options".to_string(),
+        );
+        let result = self.inner.install_ask_configure_advanced_options();
+        self.log_answer("install", "install_ask_configure_advanced_options", &result);
+        result
+    }
+
+    fn install_select_bepinex_log_levels(&self) -> Result<Vec<String>> {
+        self.log(
+            LogLevel::Info,
+            "install",
+            "install_select_bepinex_log_levels".to_string(),
+        );
+        let result = self.inner.install_select_bepinex_log_levels();
+        self.log_answer("install", "install_select_bepinex_log_levels", &result);
+        result
+    }
+
+    fn install_ask_backup_before_install(&self) -> Result<bool> {
+        self.log(
+            LogLevel::Info,
+            "install",
+            "install_ask_backup_before_install".to_string(),
+        );
+        let result = self.inner.install_ask_backup_before_install();
+        self.log_answer("install", "install_ask_backup_before_install", &result);
+        result
+    }
+
+    fn install_display_plan(&self, plan: &InstallPlan) -> Result<bool> {
+        self.log(
+            LogLevel::Info,
+            "install",
+            format!("install_display_plan: plan={:?}", plan),
+        );
+        let result = self.inner.install_display_plan(plan);
+        self.log_answer("install", "install_display_plan", &result);
+        result
+    }
+
+    fn install_downloads_completed(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "install",
+            "install_downloads_completed".to_string(),
+        );
+        self.inner.install_downloads_completed()
+    }
+
+    fn install_start_cleanup(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "install",
+            "install_start_cleanup".to_string(),
+        );
+        self.inner.install_start_cleanup()
+    }
+
+    fn install_display_cleanup_detail(&self, items: &[DeletionResult]) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "install",
+            format!("install_display_cleanup_detail: items={:?}", items),
+        );
+        self.inner.install_display_cleanup_detail(items)
+    }
+
+    fn install_cleanup_result(&self, success_count: usize, failed_count: usize) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "install",
+            format!(
+                "install_cleanup_result: success_count={:?}, failed_count={:?}",
+                success_count, failed_count
+            ),
+        );
+        self.inner
+            .install_cleanup_result(success_count, failed_count)
+    }
+
+    fn install_finished(&self, show_bepinex_console: bool, first_install: bool) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "install",
+            format!(
+                "install_finished: show_bepinex_console={:?}, first_install={:?}",
+                show_bepinex_console, first_install
+            ),
+        );
+        self.inner
+            .install_finished(show_bepinex_console, first_install)
+    }
+
+    fn display_first_time_bepinex_info(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "display",
+            "display_first_time_bepinex_info".to_string(),
+        );
+        self.inner.display_first_time_bepinex_info()
+    }
+
+    fn warn_bepinex_il2cpp_download(&self, source_url: &str) -> Result<()> {
+        self.log(
+            LogLevel::Warn,
+            "warn",
+            format!("warn_bepinex_il2cpp_download: source_url={:?}", source_url),
+        );
+        self.inner.warn_bepinex_il2cpp_download(source_url)
+    }
+
+    fn install_warn_loader_quarantined(&self, path: &Path, detection: Option<&str>) -> Result<()> {
+        self.log(
+            LogLevel::Warn,
+            "install",
+            format!(
+                "install_warn_loader_quarantined: path={:?}, detection={:?}",
+                path, detection
+            ),
+        );
+        self.inner.install_warn_loader_quarantined(path, detection)
+    }
+
+    fn resourceex_display_misplaced(&self, files: &[PathBuf]) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "resourceex",
+            format!("resourceex_display_misplaced: files={:?}", files),
+        );
+        self.inner.resourceex_display_misplaced(files)
+    }
+
+    fn resourceex_confirm_migrate(&self) -> Result<bool> {
+        self.log(
+            LogLevel::Info,
+            "resourceex",
+            "resourceex_confirm_migrate".to_string(),
+        );
+        let result = self.inner.resourceex_confirm_migrate();
+        self.log_answer("resourceex", "resourceex_confirm_migrate", &result);
+        result
+    }
+
+    fn resourceex_migrate_failed(&self, path: &Path, err: &str) -> Result<()> {
+        self.log(
+            LogLevel::Error,
+            "resourceex",
+            format!("resourceex_migrate_failed: path={:?}, err={:?}", path, err),
+        );
+        self.inner.resourceex_migrate_failed(path, err)
+    }
+
+    fn upgrade_warn_unparse_version(&self, filename: &str) -> Result<()> {
+        self.log(
+            LogLevel::Warn,
+            "upgrade",
+            format!("upgrade_warn_unparse_version: filename={:?}", filename),
+        );
+        self.inner.upgrade_warn_unparse_version(filename)
+    }
+
+    fn upgrade_found_duplicate(&self, path: &Path) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "upgrade",
+            format!("upgrade_found_duplicate: path={:?}", path),
+        );
+        self.inner.upgrade_found_duplicate(path)
+    }
+
+    fn upgrade_backup_failed(&self, err: &str) -> Result<()> {
+        self.log(
+            LogLevel::Error,
+            "upgrade",
+            format!("upgrade_backup_failed: err={:?}", err),
+        );
+        self.inner.upgrade_backup_failed(err)
+    }
+
+    fn upgrade_deleted(&self, path: &Path) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "upgrade",
+            format!("upgrade_deleted: path={:?}", path),
+        );
+        self.inner.upgrade_deleted(path)
+    }
+
+    fn upgrade_delete_failed(&self, path: &Path, err: &str) -> Result<()> {
+        self.log(
+            LogLevel::Error,
+            "upgrade",
+            format!("upgrade_delete_failed: path={:?}, err={:?}", path, err),
+        );
+        self.inner.upgrade_delete_failed(path, err)
+    }
+
+    fn upgrade_checking_installed_version(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "upgrade",
+            "upgrade_checking_installed_version".to_string(),
+        );
+        self.inner.upgrade_checking_installed_version()
+    }
+
+    fn upgrade_detected_resourceex(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "upgrade",
+            "upgrade_detected_resourceex".to_string(),
+        );
+        self.inner.upgrade_detected_resourceex()
+    }
+
+    fn upgrade_display_current_and_latest_dll(&self, current: &str, latest: &str) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "upgrade",
+            format!(
+                "upgrade_display_current_and_latest_dll: current={:?}, latest={:?}",
+                current, latest
+            ),
+        );
+        self.inner
+            .upgrade_display_current_and_latest_dll(current, latest)
+    }
+
+    fn upgrade_display_current_and_latest_resourceex(
+        &self,
+        current: &str,
+        latest: &str,
+    ) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "upgrade",
+            format!(
+                "upgrade_display_current_and_latest_resourceex: current={:?}, latest={:?}",
+                current, latest
+            ),
+        );
+        self.inner
+            .upgrade_display_current_and_latest_resourceex(current, latest)
+    }
+
+    fn upgrade_no_update_needed(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "upgrade",
+            "upgrade_no_update_needed".to_string(),
+        );
+        self.inner.upgrade_no_update_needed()
+    }
+
+    fn upgrade_detected_new_dll(&self, current: &str, new: &str) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "upgrade",
+            format!(
+                "upgrade_detected_new_dll: current={:?}, new={:?}",
+                current, new
+            ),
+        );
+        self.inner.upgrade_detected_new_dll(current, new)
+    }
+
+    fn upgrade_dll_already_latest(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "upgrade",
+            "upgrade_dll_already_latest".to_string(),
+        );
+        self.inner.upgrade_dll_already_latest()
+    }
+
+    fn upgrade_resourceex_needs_upgrade(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "upgrade",
+            "upgrade_resourceex_needs_upgrade".to_string(),
+        );
+        self.inner.upgrade_resourceex_needs_upgrade()
+    }
+
+    fn confirm_batch_upgrade(&self, updates: &[PendingUpgrade]) -> Result<bool> {
+        self.log(
+            LogLevel::Info,
+            "confirm",
+            format!("confirm_batch_upgrade: updates={:?}", updates),
+        );
+        let result = self.inner.confirm_batch_upgrade(updates);
+        self.log_answer("confirm", "confirm_batch_upgrade", &result);
+        result
+    }
+
+    fn upgrade_downloading_dll(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "upgrade",
+            "upgrade_downloading_dll".to_string(),
+        );
+        self.inner.upgrade_downloading_dll()
+    }
+
+    fn upgrade_downloading_resourceex(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "upgrade",
+            "upgrade_downloading_resourceex".to_string(),
+        );
+        self.inner.upgrade_downloading_resourceex()
+    }
+
+    fn upgrade_installing_dll(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "upgrade",
+            "upgrade_installing_dll".to_string(),
+        );
+        self.inner.upgrade_installing_dll()
+    }
+
+    fn upgrade_installing_resourceex(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "upgrade",
+            "upgrade_installing_resourceex".to_string(),
+        );
+        self.inner.upgrade_installing_resourceex()
+    }
+
+    fn upgrade_install_success(&self, path: &Path) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "upgrade",
+            format!("upgrade_install_success: path={:?}", path),
+        );
+        self.inner.upgrade_install_success(path)
+    }
+
+    fn upgrade_cleanup_start(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "upgrade",
+            "upgrade_cleanup_start".to_string(),
+        );
+        self.inner.upgrade_cleanup_start()
+    }
+
+    fn upgrade_done(&self) -> Result<()> {
+        self.log(LogLevel::Info, "upgrade", "upgrade_done".to_string());
+        self.inner.upgrade_done()
+    }
+
+    fn ask_launch_game(&self) -> Result<bool> {
+        self.log(LogLevel::Info, "ask", "ask_launch_game".to_string());
+        let result = self.inner.ask_launch_game();
+        self.log_answer("ask", "ask_launch_game", &result);
+        result
+    }
+
+    fn launch_game_failed(&self, err: &str) -> Result<()> {
+        self.log(
+            LogLevel::Error,
+            "launch",
+            format!("launch_game_failed: err={:?}", err),
+        );
+        self.inner.launch_game_failed(err)
+    }
+
+    fn ask_open_docs(&self) -> Result<bool> {
+        self.log(LogLevel::Info, "ask", "ask_open_docs".to_string());
+        let result = self.inner.ask_open_docs();
+        self.log_answer("ask", "ask_open_docs", &result);
+        result
+    }
+
+    fn open_docs_failed(&self, url: &str) -> Result<()> {
+        self.log(
+            LogLevel::Error,
+            "open",
+            format!("open_docs_failed: url={:?}", url),
+        );
+        self.inner.open_docs_failed(url)
+    }
+
+    fn configure_ask_bepinex_console(&self, current: bool) -> Result<bool> {
+        self.log(
+            LogLevel::Info,
+            "configure",
+            format!("configure_ask_bepinex_console: current={:?}", current),
+        );
+        let result = self.inner.configure_ask_bepinex_console(current);
+        self.log_answer("configure", "configure_ask_bepinex_console", &result);
+        result
+    }
+
+    fn configure_bepinex_console_changed(&self, previous: bool, enabled: bool) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "configure",
+            format!(
+                "configure_bepinex_console_changed: previous={:?}, enabled={:?}",
+                previous, enabled
+            ),
+        );
+        self.inner
+            .configure_bepinex_console_changed(previous, enabled)
+    }
+
+    fn uninstall_select_mode(&self) -> Result<UninstallMode> {
+        self.log(
+            LogLevel::Info,
+            "uninstall",
+            "uninstall_select_mode".to_string(),
+        );
+        let result = self.inner.uninstall_select_mode();
+        self.log_answer("uninstall", "uninstall_select_mode", &result);
+        result
+    }
+
+    fn uninstall_no_files_found(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "uninstall",
+            "uninstall_no_files_found".to_string(),
+        );
+        self.inner.uninstall_no_files_found()
+    }
+
+    fn uninstall_display_target_files(&self, files: &[PathBuf]) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "uninstall",
+            format!("uninstall_display_target_files: files={:?}", files),
+        );
+        self.inner.uninstall_display_target_files(files)
+    }
+
+    fn uninstall_confirm_deletion(&self) -> Result<bool> {
+        self.log(
+            LogLevel::Info,
+            "uninstall",
+            "uninstall_confirm_deletion".to_string(),
+        );
+        let result = self.inner.uninstall_confirm_deletion();
+        self.log_answer("uninstall", "uninstall_confirm_deletion", &result);
+        result
+    }
+
+    fn uninstall_ask_create_undo_archive(&self, estimated_size: u64) -> Result<bool> {
+        self.log(
+            LogLevel::Info,
+            "uninstall",
+            format!(
+                "uninstall_ask_create_undo_archive: estimated_size={:?}",
+                estimated_size
+            ),
+        );
+        let result = self.inner.uninstall_ask_create_undo_archive(estimated_size);
+        self.log_answer("uninstall", "uninstall_ask_create_undo_archive", &result);
+        result
+    }
+
+    fn uninstall_undo_archive_insufficient_space(
+        &self,
+        required: u64,
+        available: u64,
+    ) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "uninstall",
+            format!(
+                "uninstall_undo_archive_insufficient_space: required={:?}, available={:?}",
+                required, available
+            ),
+        );
+        self.inner
+            .uninstall_undo_archive_insufficient_space(required, available)
+    }
+
+    fn uninstall_undo_archive_created(&self, path: &Path) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "uninstall",
+            format!("uninstall_undo_archive_created: path={:?}", path),
+        );
+        self.inner.uninstall_undo_archive_created(path)
+    }
+
+    fn restore_undo_finished(&self, archive: &Path, count: usize) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "restore",
+            format!(
+                "restore_undo_finished: archive={:?}, count={:?}",
+                archive, count
+            ),
+        );
+        self.inner.restore_undo_finished(archive, count)
+    }
+
+    fn uninstall_files_in_use_warning(&self) -> Result<()> {
+        self.log(
+            LogLevel::Warn,
+            "uninstall",
+            "uninstall_files_in_use_warning".to_string(),
+        );
+        self.inner.uninstall_files_in_use_warning()
+    }
+
+    fn uninstall_wait_before_retry(
+        &self,
+        delay_secs: u64,
+        attempt: usize,
+        attempts: usize,
+    ) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "uninstall",
+            format!(
+                "uninstall_wait_before_retry: delay_secs={:?}, attempt={:?}, attempts={:?}",
+                delay_secs, attempt, attempts
+            ),
+        );
+        self.inner
+            .uninstall_wait_before_retry(delay_secs, attempt, attempts)
+    }
+
+    fn uninstall_wait_or_skip(&self, delay_secs: u64) -> Result<WaitOutcome> {
+        self.log(
+            LogLevel::Info,
+            "uninstall",
+            format!("uninstall_wait_or_skip: delay_secs={:?}", delay_secs),
+        );
+        let result = self.inner.uninstall_wait_or_skip(delay_secs);
+        self.log_answer("uninstall", "uninstall_wait_or_skip", &result);
+        result
+    }
+
+    fn uninstall_ask_elevate_permission(&self) -> Result<bool> {
+        self.log(
+            LogLevel::Info,
+            "uninstall",
+            "uninstall_ask_elevate_permission".to_string(),
+        );
+        let result = self.inner.uninstall_ask_elevate_permission();
+        self.log_answer("uninstall", "uninstall_ask_elevate_permission", &result);
+        result
+    }
+
+    fn uninstall_restarting_elevated(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "uninstall",
+            "uninstall_restarting_elevated".to_string(),
+        );
+        self.inner.uninstall_restarting_elevated()
+    }
+
+    fn uninstall_ask_retry_failures(&self) -> Result<bool> {
+        self.log(
+            LogLevel::Info,
+            "uninstall",
+            "uninstall_ask_retry_failures".to_string(),
+        );
+        let result = self.inner.uninstall_ask_retry_failures();
+        self.log_answer("uninstall", "uninstall_ask_retry_failures", &result);
+        result
+    }
+
+    fn uninstall_retrying_failed_items(&self) -> Result<()> {
+        self.log(
+            LogLevel::Error,
+            "uninstall",
+            "uninstall_retrying_failed_items".to_string(),
+        );
+        self.inner.uninstall_retrying_failed_items()
+    }
+
+    fn uninstall_display_disk_space_freed(&self, bytes_freed: u64) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "uninstall",
+            format!(
+                "uninstall_display_disk_space_freed: bytes_freed={:?}",
+                bytes_freed
+            ),
+        );
+        self.inner.uninstall_display_disk_space_freed(bytes_freed)
+    }
+
+    fn uninstall_confirm_take_ownership(&self, paths: &[PathBuf]) -> Result<bool> {
+        self.log(
+            LogLevel::Info,
+            "uninstall",
+            format!("uninstall_confirm_take_ownership: paths={:?}", paths),
+        );
+        let result = self.inner.uninstall_confirm_take_ownership(paths);
+        self.log_answer("uninstall", "uninstall_confirm_take_ownership", &result);
+        result
+    }
+
+    fn uninstall_display_acl_fix_commands(&self, commands: &[String]) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "uninstall",
+            format!(
+                "uninstall_display_acl_fix_commands: commands={:?}",
+                commands
+            ),
+        );
+        self.inner.uninstall_display_acl_fix_commands(commands)
+    }
+
+    fn uninstall_display_failure_breakdown(
+        &self,
+        locked: usize,
+        acl_denied: usize,
+        other: usize,
+    ) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "uninstall",
+            format!(
+                "uninstall_display_failure_breakdown: locked={:?}, acl_denied={:?}, other={:?}",
+                locked, acl_denied, other
+            ),
+        );
+        self.inner
+            .uninstall_display_failure_breakdown(locked, acl_denied, other)
+    }
+
+    fn deletion_start(&self, total: usize) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "deletion",
+            format!("deletion_start: total={:?}", total),
+        );
+        self.inner.deletion_start(total)
+    }
+
+    fn deletion_display_progress(&self, current: usize, total: usize, path: &str) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "deletion",
+            format!(
+                "deletion_display_progress: current={:?}, total={:?}, path={:?}",
+                current, total, path
+            ),
+        );
+        self.inner.deletion_display_progress(current, total, path)
+    }
+
+    fn deletion_display_success(&self, path: &str) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "deletion",
+            format!("deletion_display_success: path={:?}", path),
+        );
+        self.inner.deletion_display_success(path)
+    }
+
+    fn deletion_display_failure(&self, path: &str, error: &str) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "deletion",
+            format!(
+                "deletion_display_failure: path={:?}, error={:?}",
+                path, error
+            ),
+        );
+        self.inner.deletion_display_failure(path, error)
+    }
+
+    fn deletion_display_skipped(&self, path: &str) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "deletion",
+            format!("deletion_display_skipped: path={:?}", path),
+        );
+        self.inner.deletion_display_skipped(path)
+    }
+
+    fn deletion_display_summary(
+        &self,
+        success_count: usize,
+        failed_count: usize,
+        skipped_count: usize,
+    ) -> Result<()> {
+        self.log(LogLevel::Info, "deletion", format!("deletion_display_summary: success_count={:?}, failed_count={:?}, skipped_count={:?}", success_count, failed_count, skipped_count));
+        self.inner
+            .deletion_display_summary(success_count, failed_count, skipped_count)
+    }
+
+    fn temp_dir_warn_cross_volume(&self, temp_dir: &Path, game_root: &Path) -> Result<()> {
+        self.log(
+            LogLevel::Warn,
+            "temp",
+            format!(
+                "temp_dir_warn_cross_volume: temp_dir={:?}, game_root={:?}",
+                temp_dir, game_root
+            ),
+        );
+        self.inner.temp_dir_warn_cross_volume(temp_dir, game_root)
+    }
+
+    fn download_start(&self, filename: &str, total: Option<u64>) -> Result<usize> {
+        self.log(
+            LogLevel::Info,
+            "download",
+            format!("download_start: filename={:?}, total={:?}", filename, total),
+        );
+        self.inner.download_start(filename, total)
+    }
+
+    fn download_update(&self, id: usize, downloaded: u64) -> Result<()> {
+        self.inner.download_update(id, downloaded)
+    }
+
+    fn download_finish(&self, id: usize, message: &str) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "download",
+            format!("download_finish: id={:?}, message={:?}", id, message),
+        );
+        self.inner.download_finish(id, message)
+    }
+
+    fn download_version_info_start(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "download",
+            "download_version_info_start".to_string(),
+        );
+        self.inner.download_version_info_start()
+    }
+
+    fn download_version_info_failed(&self, err: &str) -> Result<()> {
+        self.log(
+            LogLevel::Error,
+            "download",
+            format!("download_version_info_failed: err={:?}", err),
+        );
+        self.inner.download_version_info_failed(err)
+    }
+
+    fn download_version_info_success(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "download",
+            "download_version_info_success".to_string(),
+        );
+        self.inner.download_version_info_success()
+    }
+
+    fn download_version_info_parse_failed(&self, err: &str, snippet: &str) -> Result<()> {
+        self.log(
+            LogLevel::Error,
+            "download",
+            format!(
+                "download_version_info_parse_failed: err={:?}, snippet={:?}",
+                err, snippet
+            ),
+        );
+        self.inner.download_version_info_parse_failed(err, snippet)
+    }
+
+    fn download_share_code_start(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "download",
+            "download_share_code_start".to_string(),
+        );
+        self.inner.download_share_code_start()
+    }
+
+    fn download_share_code_failed(&self, err: &str) -> Result<()> {
+        self.log(
+            LogLevel::Error,
+            "download",
+            format!("download_share_code_failed: err={:?}", err),
+        );
+        self.inner.download_share_code_failed(err)
+    }
+
+    fn download_share_code_success(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "download",
+            "download_share_code_success".to_string(),
+        );
+        self.inner.download_share_code_success()
+    }
+
+    fn download_attempt_github_dll(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "download",
+            "download_attempt_github_dll".to_string(),
+        );
+        self.inner.download_attempt_github_dll()
+    }
+
+    fn download_found_github_asset(&self, name: &str) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "download",
+            format!("download_found_github_asset: name={:?}", name),
+        );
+        self.inner.download_found_github_asset(name)
+    }
+
+    fn download_github_dll_not_found(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "download",
+            "download_github_dll_not_found".to_string(),
+        );
+        self.inner.download_github_dll_not_found()
+    }
+
+    fn download_github_auth_required(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "download",
+            "download_github_auth_required".to_string(),
+        );
+        self.inner.download_github_auth_required()
+    }
+
+    fn download_display_github_release_notes(
+        &self,
+        tag: &str,
+        name: &str,
+        body: &str,
+    ) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "download",
+            format!(
+                "download_display_github_release_notes: tag={:?}, name={:?}, body={:?}",
+                tag, name, body
+            ),
+        );
+        self.inner
+            .download_display_github_release_notes(tag, name, body)
+    }
+
+    fn download_ask_continue_after_release_notes(&self) -> Result<bool> {
+        self.log(
+            LogLevel::Info,
+            "download",
+            "download_ask_continue_after_release_notes".to_string(),
+        );
+        let result = self.inner.download_ask_continue_after_release_notes();
+        self.log_answer(
+            "download",
+            "download_ask_continue_after_release_notes",
+            &result,
+        );
+        result
+    }
+
+    fn download_switch_to_fallback(&self, reason: &str) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "download",
+            format!("download_switch_to_fallback: reason={:?}", reason),
+        );
+        self.inner.download_switch_to_fallback(reason)
+    }
+
+    fn download_try_fallback_metamystia(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "download",
+            "download_try_fallback_metamystia".to_string(),
+        );
+        self.inner.download_try_fallback_metamystia()
+    }
+
+    fn download_bepinex_attempt_primary(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "download",
+            "download_bepinex_attempt_primary".to_string(),
+        );
+        self.inner.download_bepinex_attempt_primary()
+    }
+
+    fn download_bepinex_primary_failed(&self, err: &str) -> Result<()> {
+        self.log(
+            LogLevel::Error,
+            "download",
+            format!("download_bepinex_primary_failed: err={:?}", err),
+        );
+        self.inner.download_bepinex_primary_failed(err)
+    }
+
+    fn download_all_sources_failed(&self, failures: &[SourceFailure]) -> Result<()> {
+        self.log(
+            LogLevel::Error,
+            "download",
+            format!("download_all_sources_failed: failures={:?}", failures),
+        );
+        self.inner.download_all_sources_failed(failures)
+    }
+
+    fn display_download_summary(&self, reports: &[DownloadReport]) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "display",
+            format!("display_download_summary: reports={:?}", reports),
+        );
+        self.inner.display_download_summary(reports)
+    }
+
+    fn network_retrying(
+        &self,
+        op_desc: &str,
+        delay_secs: u64,
+        attempt: usize,
+        attempts: usize,
+        err: &str,
+    ) -> Result<()> {
+        self.log(LogLevel::Info, "network", format!("network_retrying: op_desc={:?}, delay_secs={:?}, attempt={:?}, attempts={:?}, err={:?}", op_desc, delay_secs, attempt, attempts, err));
+        self.inner
+            .network_retrying(op_desc, delay_secs, attempt, attempts, err)
+    }
+
+    fn network_rate_limited(&self, secs: u64) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "network",
+            format!("network_rate_limited: secs={:?}", secs),
+        );
+        self.inner.network_rate_limited(secs)
+    }
+
+    fn display_network_diagnostics(&self, endpoint: &str, last_error: &str) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "display",
+            format!(
+                "display_network_diagnostics: endpoint={:?}, last_error={:?}",
+                endpoint, last_error
+            ),
+        );
+        self.inner.display_network_diagnostics(endpoint, last_error)
+    }
+
+    fn manager_ask_self_update(&self, current_version: &str, latest_version: &str) -> Result<bool> {
+        self.log(
+            LogLevel::Info,
+            "manager",
+            format!(
+                "manager_ask_self_update: current_version={:?}, latest_version={:?}",
+                current_version, latest_version
+            ),
+        );
+        let result = self
+            .inner
+            .manager_ask_self_update(current_version, latest_version);
+        self.log_answer("manager", "manager_ask_self_update", &result);
+        result
+    }
+
+    fn display_self_update_downloaded(&self, path: &Path, version: &str) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "display",
+            format!(
+                "display_self_update_downloaded: path={:?}, version={:?}",
+                path, version
+            ),
+        );
+        self.inner.display_self_update_downloaded(path, version)
+    }
+
+    fn manager_update_starting(&self) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "manager",
+            "manager_update_starting".to_string(),
+        );
+        self.inner.manager_update_starting()
+    }
+
+    fn manager_update_failed(&self, err: &str) -> Result<()> {
+        self.log(
+            LogLevel::Error,
+            "manager",
+            format!("manager_update_failed: err={:?}", err),
+        );
+        self.inner.manager_update_failed(err)
+    }
+
+    fn manager_prompt_manual_update(&self, reason: &str) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "manager",
+            format!("manager_prompt_manual_update: reason={:?}", reason),
+        );
+        self.inner.manager_prompt_manual_update(reason)
+    }
+
+    fn select_version_ask_select(&self, component: &str) -> Result<bool> {
+        self.log(
+            LogLevel::Info,
+            "select",
+            format!("select_version_ask_select: component={:?}", component),
+        );
+        let result = self.inner.select_version_ask_select(component);
+        self.log_answer("select", "select_version_ask_select", &result);
+        result
+    }
+
+    fn select_version_from_list(&self, component: &str, versions: &[String]) -> Result<usize> {
+        self.log(
+            LogLevel::Info,
+            "select",
+            format!(
+                "select_version_from_list: component={:?}, versions={:?}",
+                component, versions
+            ),
+        );
+        let result = self.inner.select_version_from_list(component, versions);
+        self.log_answer("select", "select_version_from_list", &result);
+        result
+    }
+
+    fn select_version_not_available(
+        &self,
+        component: &str,
+        version: &str,
+        available: &[String],
+    ) -> Result<()> {
+        self.log(
+            LogLevel::Info,
+            "select",
+            format!(
+                "select_version_not_available: component={:?}, version={:?}, available={:?}",
+                component, version, available
+            ),
+        );
+        self.inner
+            .select_version_not_available(component, version, available)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{ScratchDir, TestUI};
+
+    fn read_entries(path: &Path) -> Vec<(String, String)> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let entry: LogEntry = serde_json::from_str(line).unwrap();
+                (entry.category.to_string(), entry.message)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn logs_rotate_and_record_answers() {
+        let dir = ScratchDir::new("logging-rotate");
+        let ui = LoggingUi::new(TestUI::with_confirm(false), dir.path().to_path_buf())
+            .with_rotate_bytes(512);
+
+        for i in 0..10 {
+            ui.message(&format!("第 {} 条消息", i)).unwrap();
+        }
+        assert!(!ui.ask_telemetry_consent().unwrap());
+
+        let rotated = read_entries(&dir.path().join(ROTATED_LOG_FILE_NAME));
+        let current = read_entries(&dir.path().join(LOG_FILE_NAME));
+        assert!(!rotated.is_empty());
+        assert_eq!(
+            current[current.len() - 2..],
+            [
+                ("ask".to_string(), "ask_telemetry_consent".to_string()),
+                (
+                    "ask".to_string(),
+                    "ask_telemetry_consent -> false".to_string()
+                ),
+            ]
+        );
+    }
+}
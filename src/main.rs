@@ -1,47 +1,97 @@
+mod bepinex_config;
 mod cli;
 mod cli_ui;
 mod config;
 mod console_ui;
+mod crash;
 mod downloader;
 mod env_check;
 mod error;
 mod extractor;
 mod file_ops;
 mod installer;
+mod launcher;
+mod logging;
 mod metrics;
 mod model;
 mod net;
 mod permission;
+mod prefs;
+mod progress;
 mod shutdown;
 mod temp_dir;
+#[cfg(test)]
+mod test_utils;
 mod ui;
+mod undo;
 mod uninstaller;
 mod updater;
 mod upgrader;
+mod win_event_log;
 
-use crate::cli::{Cli, CliConfig, CliOperation, InstallConfig};
+use crate::bepinex_config::{bepinex_console_enabled, set_bepinex_console};
+use crate::cli::{Cli, CliConfig, CliOperation, ConfigureSetting, InstallConfig};
 use crate::cli_ui::CliUI;
-use crate::config::{GAME_EXECUTABLE, OperationMode, UninstallMode};
-use crate::console_ui::ConsoleUI;
-use crate::downloader::Downloader;
-use crate::env_check::{check_game_directory, check_game_running};
+use crate::config::{DOC_URL, GAME_EXECUTABLE, OperationMode, UninstallMode};
+use crate::console_ui::{ConsoleUI, configure_plain_output, configure_prompt_timeout};
+use crate::crash::install_panic_hook;
+use crate::downloader::{Downloader, configure_rate_limit, configure_refuse_fallback_version};
+use crate::env_check::{
+    PathSource, check_game_directory, check_game_running, confirm_game_executables,
+    environment_report, locate_game_directory, warn_cloud_sync,
+};
 use crate::error::{ManagerError, Result};
+use crate::file_ops::count_results;
 use crate::installer::Installer;
-use crate::metrics::report_event;
-use crate::shutdown::run_shutdown;
-use crate::ui::Ui;
+use crate::launcher::{launch_game, open_url};
+use crate::logging::{LoggingUi, default_log_dir};
+use crate::metrics::{report_event, reset_user_id, set_telemetry_enabled};
+use crate::model::VersionInfo;
+use crate::net::{configure_client_cert, configure_retry_overrides};
+use crate::permission::{is_access_denied, is_controlled_folder_access_enabled};
+use crate::prefs::Preferences;
+use crate::progress::progress_snapshot;
+use crate::shutdown::{
+    CleanupHandle, PRIORITY_PROGRESS_CANCELLED, register_cleanup_with_priority, run_shutdown,
+};
+use crate::temp_dir::{configure_slow_storage_check, configure_temp_base};
+use crate::ui::{OverwriteSelection, Ui, set_verbose};
+use crate::undo::restore_undo_archive;
 use crate::uninstaller::Uninstaller;
-use crate::updater::perform_self_update;
+use crate::updater::{
+    configure_update_channel, perform_self_uninstall, perform_self_update, run_dir_write_problem,
+    self_uninstall_targets, update_channel,
+};
 use crate::upgrader::Upgrader;
+use crate::win_event_log::{
+    EVENT_ID_INSTALL, EVENT_ID_UNINSTALL, EVENT_ID_UPGRADE, record_operation, register_event_source,
+};
 
 use clap::Parser;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
+use std::sync::{Arc, Weak};
 
 fn main() -> ExitCode {
+    install_panic_hook();
+
     let cli_args = Cli::parse();
+    if cli_args.health_check {
+        println!("{}", env!("CARGO_PKG_VERSION"));
+        return ExitCode::SUCCESS;
+    }
     let cli_config = cli_args.to_config();
 
+    configure_client_cert(cli_args.client_cert.clone(), cli_args.client_key.clone());
+    configure_rate_limit(cli_args.rate_limit);
+    configure_update_channel(cli_args.channel.map(Into::into));
+    configure_refuse_fallback_version(cli_args.refuse_fallback_version);
+    configure_slow_storage_check(cli_args.skip_slow_storage_check);
+    set_verbose(cli_args.verbose);
+    configure_plain_output(cli_args.plain);
+    configure_prompt_timeout(cli_args.prompt_timeout);
+    configure_retry_overrides(cli_args.retries.map(|r| r as usize), cli_args.retry_delay);
+
     if !cfg!(windows) {
         if let Some(ref config) = cli_config {
             let cli_ui = CliUI::new(config.quiet);
@@ -55,19 +105,134 @@ fn main() -> ExitCode {
         }
     }
 
+    // 交互模式下首次运行时，在用户同意前不发送统计事件
+    let telemetry_pref = Preferences::load().telemetry_enabled;
+    let ask_telemetry_consent =
+        cli_config.is_none() && !cli_args.no_telemetry && telemetry_pref.is_none();
+    set_telemetry_enabled(!cli_args.no_telemetry && telemetry_pref.unwrap_or(cli_config.is_some()));
+
+    if cli_args.reset_user_id {
+        let res = match reset_user_id() {
+            Ok(id) => {
+                println!("New anonymous user ID: {}", id);
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                ExitCode::from(1)
+            }
+        };
+        run_shutdown();
+        return res;
+    }
+
+    if cli_args.register_event_source {
+        let res = match register_event_source() {
+            Ok(()) => {
+                println!("Event source registered.");
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                ExitCode::from(1)
+            }
+        };
+        run_shutdown();
+        return res;
+    }
+
+    if cli_args.status {
+        let res = match run_status(cli_args.path.as_deref(), cli_args.json) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                if cli_args.json {
+                    print_json_error(&e);
+                }
+                eprintln!("Error: {}", e);
+                ExitCode::from(1)
+            }
+        };
+        run_shutdown();
+        return res;
+    }
+
+    if cli_args.get_manager_url {
+        let res = match run_get_manager_url(cli_args.json) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                if cli_args.json {
+                    print_json_error(&e);
+                }
+                eprintln!("Error: {}", e);
+                ExitCode::from(1)
+            }
+        };
+        run_shutdown();
+        return res;
+    }
+
+    if let Err(e) = configure_temp_base(cli_args.temp_dir.clone()) {
+        if let Some(ref config) = cli_config {
+            let _ = CliUI::new(config.quiet).error(&format!("{}", e));
+        } else {
+            let console_ui = ConsoleUI::new();
+            let _ = console_ui.error(&format!("错误：{}", e));
+            console_ui.wait_for_key().ok();
+        }
+        return ExitCode::from(1);
+    }
+
+    if cli_args.self_uninstall {
+        let res = match run_self_uninstall(cli_args.yes, cli_args.quiet) {
+            Ok(code) => ExitCode::from(code),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                ExitCode::from(1)
+            }
+        };
+        run_shutdown();
+        return res;
+    }
+
+    let log_dir = cli_args
+        .log_dir
+        .clone()
+        .or_else(default_log_dir)
+        .unwrap_or_else(|| std::env::temp_dir().join("meta-mystia-manager-logs"));
+
     let res = if let Some(ref config) = cli_config {
-        let cli_ui = CliUI::new(config.quiet);
-        match run_with_cli(&cli_ui, config) {
+        let cli_ui = Arc::new(LoggingUi::new(
+            CliUI::new(config.quiet)
+                .with_json(config.json)
+                .with_interactive_upgrade(config.interactive_upgrade)
+                .with_confirm_install(config.confirm_install),
+            log_dir,
+        ));
+        let _cancel_notice = register_cancel_notice(Arc::downgrade(&cli_ui) as Weak<dyn Ui>);
+        match run_with_cli(cli_ui.as_ref(), config) {
             Ok(exit_code) => ExitCode::from(exit_code),
             Err(e) => {
+                if config.json {
+                    print_json_error(&e);
+                }
                 eprintln!("Error: {}", e);
-                ExitCode::from(1)
+                if matches!(e, ManagerError::PartialFailure { .. }) {
+                    ExitCode::from(2)
+                } else {
+                    ExitCode::from(1)
+                }
             }
         }
     } else {
-        let console_ui = ConsoleUI::new();
-        match run(&console_ui) {
+        let console_ui = Arc::new(LoggingUi::new(ConsoleUI::new(), log_dir));
+        let _cancel_notice = register_cancel_notice(Arc::downgrade(&console_ui) as Weak<dyn Ui>);
+        match run(console_ui.as_ref(), ask_telemetry_consent) {
             Ok(()) => ExitCode::SUCCESS,
+            Err(ManagerError::UserCancelled) => {
+                report_event("Run.Cancelled", None);
+                let _ = console_ui.message("已取消操作。");
+                ExitCode::SUCCESS
+            }
             Err(e) => {
                 let _ = console_ui.error(&format!("错误：{}", e));
                 console_ui.wait_for_key().ok();
@@ -82,11 +247,42 @@ fn main() -> ExitCode {
     res
 }
 
-fn run(ui: &dyn Ui) -> Result<()> {
-    report_event("Run", Some(env!("CARGO_PKG_VERSION")));
+/// 操作被 Ctrl+C 等中断时，通过 UI 说明已完成的进度
+fn register_cancel_notice(ui: Weak<dyn Ui>) -> CleanupHandle {
+    let snapshot = progress_snapshot();
+    register_cleanup_with_priority(PRIORITY_PROGRESS_CANCELLED, move || {
+        let snapshot = snapshot.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        if snapshot.is_active()
+            && let Some(ui) = ui.upgrade()
+        {
+            report_event("Run.Interrupted", Some(&snapshot.operation));
+            let _ = ui.display_progress_cancelled(&snapshot);
+        }
+    })
+}
 
+fn run(ui: &dyn Ui, ask_telemetry_consent: bool) -> Result<()> {
     // 1. 显示欢迎信息
-    ui.display_welcome()?;
+    let mut prefs = Preferences::load();
+    let is_first_run = !prefs.welcome_shown;
+    ui.display_welcome(is_first_run)?;
+    if is_first_run {
+        prefs.welcome_shown = true;
+        let _ = prefs.save();
+    }
+
+    if ask_telemetry_consent {
+        let consent = ui.ask_telemetry_consent()?;
+        set_telemetry_enabled(consent);
+
+        let mut prefs = Preferences::load();
+        prefs.telemetry_enabled = Some(consent);
+        let _ = prefs.save();
+
+        report_event("Telemetry.Consent", None);
+    }
+
+    report_event("Run", Some(env!("CARGO_PKG_VERSION")));
 
     let mut version_info = None;
     let downloader = match Downloader::new(ui) {
@@ -96,47 +292,58 @@ fn run(ui: &dyn Ui) -> Result<()> {
                 Some(dl)
             }
             Err(e) => {
-                let _ = ui.message(&format!("无法获取版本信息：{}", e));
+                report_event("Run.UpgradeCheckFailed", Some(&format!("{}", e)));
+                ui.display_upgrade_check_failed(&format!("{}", e))?;
                 None
             }
         },
         _ => None,
     };
 
-    ui.display_version(version_info.as_ref().map(|vi| vi.manager.as_str()))?;
+    let channel = update_channel();
+    let latest_manager = version_info.as_ref().map(|vi| vi.latest_manager(channel));
+    ui.display_update_channel_info(channel.name())?;
+    ui.display_version(latest_manager)?;
 
     // 自升级提示
-    if let (Some(downloader), Some(vi)) = (&downloader, &version_info) {
+    if let (Some(downloader), Some(latest)) = (&downloader, latest_manager) {
         let current_version = env!("CARGO_PKG_VERSION");
-        if current_version != vi.manager
-            && ui.manager_ask_self_update(current_version, &vi.manager)?
-        {
-            match perform_self_update(&std::env::current_dir()?, ui, downloader, vi, true) {
-                Ok(_) => {
-                    run_shutdown();
-                    std::process::exit(0);
+        if current_version != latest {
+            // 运行目录不可写时无法原地升级，直接提示手动升级
+            if let Some(reason) = run_dir_write_problem() {
+                ui.manager_prompt_manual_update(&reason)?;
+            } else if ui.manager_ask_self_update(current_version, latest)? {
+                match perform_self_update(&std::env::current_dir()?, ui, downloader, latest, true) {
+                    Ok(_) => {
+                        run_shutdown();
+                        std::process::exit(0);
+                    }
+                    Err(e) => ui.manager_update_failed(&format!("{}", e))?,
                 }
-                Err(e) => ui.manager_update_failed(&format!("{}", e))?,
             }
         }
     }
 
     // 2. 目录环境检查
-    let game_root = match check_game_directory(ui) {
-        Ok(path) => path,
+    let (game_root, path_source) = match check_game_directory(ui) {
+        Ok(found) => found,
         Err(e) => {
             ui.message(&format!("当前目录：{}", std::env::current_dir()?.display()))?;
             ui.message(&format!(
                 "请在游戏根目录（包含 {} 的文件夹）下运行本程序。",
                 GAME_EXECUTABLE
             ))?;
+            if ui.ask_open_docs()? {
+                open_docs(ui, "game_not_found")?;
+            }
             return Err(e);
         }
     };
 
     // 3. 游戏进程检查
-    if check_game_running()? {
-        ui.display_game_running_warning()?;
+    let running = check_game_running()?;
+    if !running.is_empty() {
+        ui.display_game_running_warning(&running)?;
         return Err(ManagerError::GameRunning);
     }
 
@@ -148,19 +355,32 @@ fn run(ui: &dyn Ui) -> Result<()> {
         ui.display_available_updates(dll_needs, res_needs)?;
     }
 
-    // 5. 选择操作模式
-    let operation = ui.select_operation_mode()?;
-    match operation {
-        OperationMode::Install => run_install(game_root.clone(), ui, None),
-        OperationMode::Upgrade => run_upgrade(game_root.clone(), ui),
-        OperationMode::Uninstall => run_uninstall(game_root.clone(), ui, None),
+    // 5. 选择操作模式（查看文档后返回菜单）
+    loop {
+        match ui.select_operation_mode()? {
+            OperationMode::Install => {
+                return run_install(game_root.clone(), path_source, ui, None, None);
+            }
+            OperationMode::Upgrade => return run_upgrade(game_root.clone(), path_source, ui, None),
+            OperationMode::Uninstall => {
+                return run_uninstall(game_root.clone(), ui, None, None, version_info.as_ref());
+            }
+            OperationMode::Configure => return run_configure(&game_root, ui, None),
+            OperationMode::OpenDocs => {
+                open_docs(ui, "menu")?;
+                ui.blank_line()?;
+            }
+        }
     }
 }
 
 fn run_with_cli(ui: &dyn Ui, config: &CliConfig) -> Result<u8> {
     report_event("Run.CLI", Some(env!("CARGO_PKG_VERSION")));
 
-    let skip_network = matches!(config.operation, CliOperation::Uninstall(_));
+    let skip_network = matches!(
+        config.operation,
+        CliOperation::Uninstall(_) | CliOperation::RestoreUndo(_) | CliOperation::Configure(_)
+    );
 
     let mut version_info = None;
     let downloader = if !skip_network {
@@ -172,28 +392,36 @@ fn run_with_cli(ui: &dyn Ui, config: &CliConfig) -> Result<u8> {
         None
     };
 
-    ui.display_version(version_info.as_ref().map(|vi| vi.manager.as_str()))?;
+    let channel = update_channel();
+    let latest_manager = version_info.as_ref().map(|vi| vi.latest_manager(channel));
+    ui.display_update_channel_info(channel.name())?;
+    ui.display_version(latest_manager)?;
 
     // 执行自更新
     if !skip_network
         && !config.skip_self_update
-        && let (Some(downloader), Some(vi)) = (&downloader, &version_info)
+        && let (Some(downloader), Some(latest)) = (&downloader, latest_manager)
     {
         let current_version = env!("CARGO_PKG_VERSION");
-        if current_version != vi.manager {
-            match perform_self_update(&std::env::current_dir()?, ui, downloader, vi, false) {
-                Ok(filename) => {
-                    ui.message(&filename)?;
-                    run_shutdown();
-                    return Ok(100);
+        if current_version != latest {
+            if let Some(reason) = run_dir_write_problem() {
+                ui.manager_prompt_manual_update(&reason)?;
+            } else {
+                match perform_self_update(&std::env::current_dir()?, ui, downloader, latest, false)
+                {
+                    Ok(filename) => {
+                        ui.message(&filename)?;
+                        run_shutdown();
+                        return Ok(100);
+                    }
+                    Err(e) => ui.manager_update_failed(&format!("{}", e))?,
                 }
-                Err(e) => ui.manager_update_failed(&format!("{}", e))?,
             }
         }
     }
 
     // 1. 目录环境检查
-    let game_root = if let Some(path) = &config.game_path {
+    let (game_root, path_source) = if let Some(path) = &config.game_path {
         if !path.exists() {
             return Err(ManagerError::Other(format!(
                 "Path does not exist: {}",
@@ -207,10 +435,12 @@ fn run_with_cli(ui: &dyn Ui, config: &CliConfig) -> Result<u8> {
                 path.display()
             )));
         }
-        path.clone()
+        confirm_game_executables(ui, path)?;
+        warn_cloud_sync(ui, path)?;
+        (path.clone(), PathSource::Specified)
     } else {
         match check_game_directory(ui) {
-            Ok(path) => path,
+            Ok(found) => found,
             Err(e) => {
                 ui.message(&format!(
                     "Current directory: {}",
@@ -226,30 +456,162 @@ fn run_with_cli(ui: &dyn Ui, config: &CliConfig) -> Result<u8> {
     };
 
     // 2. 游戏进程检查
-    if check_game_running()? {
-        ui.display_game_running_warning()?;
+    let running = check_game_running()?;
+    if !running.is_empty() {
+        ui.display_game_running_warning(&running)?;
         return Err(ManagerError::GameRunning);
     }
 
     // 3. 执行操作
     match &config.operation {
         CliOperation::Install(install_config) => {
-            run_install(game_root, ui, Some(install_config))?;
+            run_install(
+                game_root,
+                path_source,
+                ui,
+                Some(install_config),
+                Some(config.launch_game),
+            )?;
         }
         CliOperation::Upgrade => {
-            run_upgrade(game_root, ui)?;
+            run_upgrade(game_root, path_source, ui, Some(config.launch_game))?;
         }
         CliOperation::Uninstall(mode) => {
-            run_uninstall(game_root, ui, Some(*mode))?;
+            run_uninstall(
+                game_root,
+                ui,
+                Some(*mode),
+                Some(config.create_undo_archive),
+                version_info.as_ref(),
+            )?;
+        }
+        CliOperation::RestoreUndo(archive) => {
+            let count = restore_undo_archive(archive, &game_root)?;
+            ui.restore_undo_finished(archive, count)?;
+        }
+        CliOperation::Configure(setting) => {
+            run_configure(&game_root, ui, Some(*setting))?;
         }
     }
 
     Ok(0)
 }
 
-fn run_install(game_root: PathBuf, ui: &dyn Ui, config: Option<&InstallConfig>) -> Result<()> {
+/// 以 JSON 输出失败原因，便于脚本按错误类型处理
+fn print_json_error(e: &ManagerError) {
+    if let Ok(content) = serde_json::to_string_pretty(&serde_json::json!({ "error": e.to_json() }))
+    {
+        println!("{}", content);
+    }
+}
+
+/// 输出运行环境信息
+fn run_status(path: Option<&Path>, json: bool) -> Result<()> {
+    let game_root = match path {
+        Some(path) => Some(path.to_path_buf()),
+        // 仅查询状态，不做会写入偏好设置的检查（如云同步提示）
+        None => locate_game_directory(&CliUI::new(true))
+            .ok()
+            .map(|(root, _)| root),
+    };
+    let report = environment_report(game_root.as_deref());
+    report_event("Run.Status", None);
+
+    if json {
+        let content = serde_json::to_string_pretty(&report)
+            .map_err(|e| ManagerError::Other(format!("序列化环境信息失败：{}", e)))?;
+        println!("{}", content);
+        return Ok(());
+    }
+
+    let yes_no = |b: bool| if b { "yes" } else { "no" };
+    println!("Version: {}", env!("CARGO_PKG_VERSION"));
+    println!("Elevated: {}", yes_no(report.elevated));
+    match &report.game_root {
+        Some(root) => {
+            println!("Game root: {}", root.display());
+            println!("Protected location: {}", yes_no(report.protected_path));
+            println!(
+                "Filesystem: {}",
+                report.filesystem.as_deref().unwrap_or("unknown")
+            );
+            println!(
+                "Cloud sync: {}",
+                report.cloud_sync.as_deref().unwrap_or("none")
+            );
+        }
+        None => println!("Game root: not found"),
+    }
+    match &report.run_dir_write_problem {
+        None => println!("Manager directory writable: yes"),
+        Some(reason) => println!("Manager directory writable: no ({})", reason),
+    }
+    match &report.preferences_path {
+        Some(path) => println!("Preferences: {}", path.display()),
+        None => println!("Preferences: unavailable"),
+    }
+    println!("User ID source: {}", report.user_id_source.name());
+
+    Ok(())
+}
+
+/// 输出管理工具最新版本的下载链接
+fn run_get_manager_url(json: bool) -> Result<()> {
+    let ui = CliUI::new(true);
+    let downloader = Downloader::new(&ui)?;
+    let version_info = downloader.get_version_info()?;
+    let url = downloader.get_manager_download_url(&version_info)?;
+    let version = version_info.latest_manager(update_channel());
+    report_event("Run.GetManagerUrl", Some(version));
+
+    if json {
+        let content = serde_json::to_string_pretty(&serde_json::json!({
+            "url": url,
+            "version": version,
+            "filename": VersionInfo::manager_filename(version),
+        }))
+        .map_err(|e| ManagerError::Other(format!("序列化下载链接失败：{}", e)))?;
+        println!("{}", content);
+    } else {
+        println!("{}", url);
+    }
+
+    Ok(())
+}
+
+/// 删除管理工具自身的文件（不涉及游戏文件）
+fn run_self_uninstall(yes: bool, quiet: bool) -> Result<u8> {
+    let targets = self_uninstall_targets()?;
+    let exe_path = std::env::current_exe()?;
+
+    println!("The following will be removed:");
+    for path in &targets {
+        println!("  {}", path.display());
+    }
+    println!("  {} (after exit)", exe_path.display());
+
+    if !yes && !CliUI::read_yes_no("Remove the manager and its files?")? {
+        report_event("SelfUninstall.Declined", None);
+        return Ok(0);
+    }
+
+    let ui = CliUI::new(quiet);
+    let results = perform_self_uninstall(&ui, &targets)?;
+    let (success, failed, skipped) = count_results(&results);
+    ui.deletion_display_summary(success, failed, skipped)?;
+
+    Ok(if failed > 0 { 2 } else { 0 })
+}
+
+fn run_install(
+    game_root: PathBuf,
+    path_source: PathSource,
+    ui: &dyn Ui,
+    config: Option<&InstallConfig>,
+    launch: Option<bool>,
+) -> Result<()> {
     // 创建安装器
-    let installer = Installer::new(game_root, ui)?;
+    let installer = Installer::new(game_root.clone(), ui)?;
 
     // 检查是否已安装组件
     let bepinex_installed = installer.check_bepinex_installed();
@@ -258,10 +620,13 @@ fn run_install(game_root: PathBuf, ui: &dyn Ui, config: Option<&InstallConfig>)
     let has_installed = bepinex_installed || metamystia_installed || resourceex_installed;
 
     if has_installed {
+        // 此时尚未选择保留哪些组件，按全部覆盖列出最多会清理的项目
+        let cleanup_plan = Installer::plan_install_cleanup(&game_root, OverwriteSelection::all())?;
         ui.install_warn_existing(
             bepinex_installed,
             metamystia_installed,
             resourceex_installed,
+            &cleanup_plan,
         )?;
 
         let confirmed = ui.install_confirm_overwrite()?;
@@ -270,31 +635,124 @@ fn run_install(game_root: PathBuf, ui: &dyn Ui, config: Option<&InstallConfig>)
         }
     }
 
+    // 选择需要覆盖的组件（未安装的组件总是安装）
+    let selection = match config {
+        Some(cfg) => OverwriteSelection {
+            bepinex: !(bepinex_installed && cfg.keep_bepinex),
+            metamystia: true,
+            resourceex: !(resourceex_installed && cfg.keep_resourceex),
+        },
+        None if has_installed => ui.install_select_overwrite(
+            bepinex_installed,
+            metamystia_installed,
+            resourceex_installed,
+        )?,
+        None => OverwriteSelection::all(),
+    };
+
     // 执行安装
-    installer.install(has_installed, config)?;
+    let result = installer
+        .install(has_installed, config, selection)
+        .map_err(|e| explain_access_denied(ui, e));
+    record_operation(EVENT_ID_INSTALL, "安装", &game_root, &result);
+    result?;
+
+    launch_game_after_success(&game_root, path_source, ui, launch)?;
 
     ui.wait_for_key()?;
     Ok(())
 }
 
-fn run_upgrade(game_root: PathBuf, ui: &dyn Ui) -> Result<()> {
+fn run_upgrade(
+    game_root: PathBuf,
+    path_source: PathSource,
+    ui: &dyn Ui,
+    launch: Option<bool>,
+) -> Result<()> {
     // 创建升级器
-    let upgrader = Upgrader::new(game_root, ui)?;
+    let upgrader = Upgrader::new(game_root.clone(), ui)?;
 
     // 执行升级
-    upgrader.upgrade()?;
+    let result = upgrader.upgrade().map_err(|e| explain_access_denied(ui, e));
+    record_operation(EVENT_ID_UPGRADE, "升级", &game_root, &result);
+    if result? {
+        launch_game_after_success(&game_root, path_source, ui, launch)?;
+    }
 
     ui.wait_for_key()?;
     Ok(())
 }
 
-fn run_uninstall(game_root: PathBuf, ui: &dyn Ui, mode: Option<UninstallMode>) -> Result<()> {
-    // 创建卸载器
-    let uninstaller = Uninstaller::new(game_root, ui)?;
+fn run_uninstall(
+    game_root: PathBuf,
+    ui: &dyn Ui,
+    mode: Option<UninstallMode>,
+    undo_archive: Option<bool>,
+    version_info: Option<&VersionInfo>,
+) -> Result<()> {
+    // 创建卸载器（无版本信息时仅使用内置卸载目标）
+    let remote_targets = version_info.map_or(&[][..], |vi| &vi.uninstall_targets);
+    let uninstaller = Uninstaller::new(game_root.clone(), ui)?.with_remote_targets(remote_targets);
 
     // 执行卸载
-    uninstaller.uninstall(mode)?;
+    let result = uninstaller.uninstall(mode, undo_archive);
+    record_operation(EVENT_ID_UNINSTALL, "卸载", &game_root, &result);
+    result?;
+
+    ui.wait_for_key()?;
+    Ok(())
+}
+
+/// 修改已安装 Mod 的设置（setting 为 None 时交互式询问）
+fn run_configure(game_root: &Path, ui: &dyn Ui, setting: Option<ConfigureSetting>) -> Result<()> {
+    let enabled = match setting {
+        Some(ConfigureSetting::BepInExConsole(enabled)) => enabled,
+        None => ui.configure_ask_bepinex_console(bepinex_console_enabled(game_root)?)?,
+    };
+
+    let previous = set_bepinex_console(game_root, enabled)?;
+    ui.configure_bepinex_console_changed(previous, enabled)?;
 
     ui.wait_for_key()?;
     Ok(())
 }
+
+/// 在默认浏览器中打开使用文档，失败时显示链接
+fn open_docs(ui: &dyn Ui, from: &str) -> Result<()> {
+    report_event("Docs.Open", Some(from));
+    if open_url(DOC_URL).is_err() {
+        ui.open_docs_failed(DOC_URL)?;
+    }
+    Ok(())
+}
+
+/// 操作成功后按需启动游戏（launch 为 None 时询问用户；启动失败仅提示，不影响操作结果）
+fn launch_game_after_success(
+    game_root: &Path,
+    path_source: PathSource,
+    ui: &dyn Ui,
+    launch: Option<bool>,
+) -> Result<()> {
+    let launch = match launch {
+        Some(launch) => launch,
+        None => ui.ask_launch_game()?,
+    };
+
+    if launch && let Err(e) = launch_game(game_root, &path_source) {
+        ui.launch_game_failed(&format!("{}", e))?;
+    }
+
+    Ok(())
+}
+
+/// 写入被拒绝时检查是否由“受控文件夹访问”导致，如是则给出针对性提示
+fn explain_access_denied(ui: &dyn Ui, err: ManagerError) -> ManagerError {
+    if is_access_denied(&err) && is_controlled_folder_access_enabled() {
+        report_event("Permission.ControlledFolderAccess", None);
+        if let Ok(exe) = std::env::current_exe() {
+            let _ = ui.display_controlled_folder_access_blocked(&exe);
+        }
+    }
+
+    err
+}
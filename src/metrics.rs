@@ -1,20 +1,31 @@
 use crate::error::{ManagerError, Result};
-use crate::shutdown::SHUTDOWN_TIMEOUT;
+use crate::prefs::Preferences;
+use crate::shutdown::{PRIORITY_METRICS, SHUTDOWN_TIMEOUT, register_cleanup_with_priority};
 
 use percent_encoding::{NON_ALPHANUMERIC, percent_encode};
 use reqwest::blocking::Client;
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{BuildHasher, Hasher};
 use std::process::Command;
-use std::sync::mpsc::{RecvTimeoutError, Sender, channel};
-use std::sync::{Mutex, OnceLock};
-use std::thread::{JoinHandle, spawn};
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{Sender, channel};
+use std::sync::{Condvar, Mutex, OnceLock};
+use std::thread::spawn;
+use std::time::{Duration, Instant};
 
 const ID_SITE: &str = "13";
 const TRACKING_ENDPOINT: &str = "https://track.izakaya.cc/api.php";
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
 
-fn build_tracking_url(user_id: &str, params: &HashMap<&str, String>) -> String {
+/// 单次批量请求最多包含的事件数
+const BATCH_MAX_EVENTS: usize = 20;
+/// 批次中第一个事件最多等待多久后发送
+const BATCH_WINDOW: Duration = Duration::from_millis(500);
+
+/// 构造单个事件的查询字符串（以 ? 开头，可直接用于批量请求）
+fn build_tracking_query(user_id: &str, params: &HashMap<&str, String>) -> String {
     let mut base = vec![
         ("idsite".to_string(), ID_SITE.to_string()),
         ("rec".to_string(), "1".to_string()),
@@ -32,7 +43,7 @@ fn build_tracking_url(user_id: &str, params: &HashMap<&str, String>) -> String {
         .collect::<Vec<_>>()
         .join("&");
 
-    format!("{}?{}", TRACKING_ENDPOINT, q)
+    format!("?{}", q)
 }
 
 fn read_machine_guid() -> Option<String> {
@@ -67,24 +78,91 @@ fn md5_hex(input: &str) -> String {
     format!("{:x}", md5::compute(input))
 }
 
-static CACHED_USER_ID: OnceLock<String> = OnceLock::new();
+fn random_uuid() -> String {
+    let a = RandomState::new().build_hasher().finish();
+    let b = RandomState::new().build_hasher().finish();
+
+    format!(
+        "{:08x}-{:04x}-4{:03x}-{:04x}-{:012x}",
+        a >> 32,
+        (a >> 16) & 0xffff,
+        a & 0x0fff,
+        ((b >> 48) & 0x3fff) | 0x8000,
+        b & 0xffff_ffff_ffff
+    )
+}
+
+/// 匿名用户 ID 的来源
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UserIdSource {
+    /// 通过 --reset-user-id 生成并保存在偏好设置中的 ID
+    Override,
+    /// 由设备的 MachineGuid 派生
+    MachineGuid,
+    /// 无法读取 MachineGuid 时由计算机名与用户名派生
+    ComputerName,
+}
+
+impl UserIdSource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            UserIdSource::Override => "override",
+            UserIdSource::MachineGuid => "machine_guid",
+            UserIdSource::ComputerName => "computer_name",
+        }
+    }
+}
+
+static CACHED_USER_ID: Mutex<Option<(String, UserIdSource)>> = Mutex::new(None);
+
+/// 当前使用的匿名用户 ID 及其来源
+fn user_id_with_source() -> (String, UserIdSource) {
+    let mut guard = CACHED_USER_ID.lock().unwrap_or_else(|e| e.into_inner());
+
+    guard
+        .get_or_insert_with(|| {
+            if let Some(id) = Preferences::load().user_id {
+                return (id, UserIdSource::Override);
+            }
 
-pub fn get_user_id() -> String {
-    CACHED_USER_ID
-        .get_or_init(|| {
             if let Some(guid) = read_machine_guid() {
-                return md5_hex(&guid);
+                return (md5_hex(&guid), UserIdSource::MachineGuid);
             }
 
             let hostname = std::env::var("COMPUTERNAME").unwrap_or_default();
             let username = std::env::var("USERNAME").unwrap_or_default();
             let combined = format!("{}|{}", hostname, username);
 
-            md5_hex(&combined)
+            (md5_hex(&combined), UserIdSource::ComputerName)
         })
         .clone()
 }
 
+pub fn get_user_id() -> String {
+    user_id_with_source().0
+}
+
+/// 当前匿名用户 ID 的来源
+pub fn user_id_source() -> UserIdSource {
+    user_id_with_source().1
+}
+
+/// 重置匿名用户 ID：先以旧 ID 报告一次轮换事件，再切换为随机生成的新 ID 并保存
+pub fn reset_user_id() -> Result<String> {
+    report_event("UserId.Rotated", None);
+
+    let new_id = random_uuid();
+    let mut prefs = Preferences::load();
+    prefs.user_id = Some(new_id.clone());
+    prefs.save()?;
+
+    *CACHED_USER_ID.lock().unwrap_or_else(|e| e.into_inner()) =
+        Some((new_id.clone(), UserIdSource::Override));
+
+    Ok(new_id)
+}
+
 static CACHED_CLIENT: OnceLock<Client> = OnceLock::new();
 
 fn get_client() -> Result<&'static Client> {
@@ -92,104 +170,225 @@ fn get_client() -> Result<&'static Client> {
         return Ok(c);
     }
 
-    let client = Client::builder()
+    let mut builder = Client::builder()
         .timeout(DEFAULT_TIMEOUT)
-        .user_agent(crate::config::USER_AGENT)
+        .user_agent(crate::config::USER_AGENT);
+
+    if let Some(identity) = crate::net::load_client_identity()? {
+        builder = builder.identity(identity);
+    }
+
+    let client = builder
         .build()
         .map_err(|e| ManagerError::NetworkError(format!("创建 metrics HTTP 客户端失败：{}", e)))?;
 
     Ok(CACHED_CLIENT.get_or_init(|| client))
 }
 
-fn send_with_client(url: String) {
-    if let Ok(client) = get_client() {
-        let _ = client.get(&url).send();
+fn send_with_client(query: &str) -> bool {
+    let Ok(client) = get_client() else {
+        return false;
+    };
+
+    client
+        .get(format!("{}{}", TRACKING_ENDPOINT, query))
+        .send()
+        .is_ok_and(|resp| resp.status().is_success())
+}
+
+/// 通过批量接口一次发送多个事件，失败时逐个发送；返回是否有事件发送成功
+fn send_batch(batch: Vec<String>) -> bool {
+    if batch.len() <= 1 {
+        return batch.first().is_some_and(|query| send_with_client(query));
+    }
+
+    let Ok(client) = get_client() else {
+        return false;
+    };
+
+    let body = serde_json::json!({ "requests": batch }).to_string();
+    let sent = client
+        .post(TRACKING_ENDPOINT)
+        .header("Content-Type", "application/json")
+        .body(body)
+        .send()
+        .is_ok_and(|resp| resp.status().is_success());
+
+    if sent {
+        return true;
     }
+
+    let mut any_sent = false;
+    for query in &batch {
+        any_sent |= send_with_client(query);
+    }
+    any_sent
 }
 
-struct TrackingWorker {
-    sender: Sender<String>,
-    handle: JoinHandle<()>,
+enum TrackingMessage {
+    Event(String),
+    /// 发送当前批次及此前排队的所有事件后回复
+    Flush(Sender<()>),
 }
 
-static TRACKING_WORKER: OnceLock<Mutex<Option<TrackingWorker>>> = OnceLock::new();
+/// 排队等待发送的事件上限，超出时丢弃最早的事件
+const QUEUE_CAPACITY: usize = 256;
 
-fn start_tracking_worker() -> Sender<String> {
-    if let Some(m) = TRACKING_WORKER.get()
-        && let Ok(guard) = m.lock()
-        && let Some(w) = guard.as_ref()
-    {
-        return w.sender.clone();
+/// 有界的发送队列（统计服务不可达时避免事件无限堆积）
+struct TrackingQueue {
+    items: Mutex<VecDeque<TrackingMessage>>,
+    ready: Condvar,
+}
+
+impl TrackingQueue {
+    fn new() -> Self {
+        Self {
+            items: Mutex::new(VecDeque::new()),
+            ready: Condvar::new(),
+        }
     }
 
-    let (tx, rx) = channel::<String>();
+    fn push(&self, msg: TrackingMessage) {
+        let mut items = self.items.lock().unwrap_or_else(|e| e.into_inner());
+
+        if matches!(msg, TrackingMessage::Event(_))
+            && items
+                .iter()
+                .filter(|m| matches!(m, TrackingMessage::Event(_)))
+                .count()
+                >= QUEUE_CAPACITY
+            && let Some(pos) = items
+                .iter()
+                .position(|m| matches!(m, TrackingMessage::Event(_)))
+        {
+            items.remove(pos);
+            DROPPED_EVENTS.fetch_add(1, Ordering::SeqCst);
+        }
+
+        items.push_back(msg);
+        self.ready.notify_one();
+    }
 
-    let handle = spawn(move || {
-        for url in rx {
-            send_with_client(url);
+    /// 取出下一条消息；指定 timeout 时超时返回 None
+    fn pop(&self, timeout: Option<Duration>) -> Option<TrackingMessage> {
+        let mut items = self.items.lock().unwrap_or_else(|e| e.into_inner());
+
+        match timeout {
+            None => {
+                while items.is_empty() {
+                    items = self.ready.wait(items).unwrap_or_else(|e| e.into_inner());
+                }
+            }
+            Some(timeout) => {
+                items = self
+                    .ready
+                    .wait_timeout_while(items, timeout, |items| items.is_empty())
+                    .unwrap_or_else(|e| e.into_inner())
+                    .0;
+            }
         }
-    });
-    let worker = TrackingWorker {
-        sender: tx.clone(),
-        handle,
-    };
 
-    let m = TRACKING_WORKER.get_or_init(|| Mutex::new(None));
-    let mut guard = match m.lock() {
-        Ok(g) => g,
-        Err(e) => e.into_inner(),
-    };
+        items.pop_front()
+    }
 
-    if guard.is_none() {
-        *guard = Some(worker);
+    /// 放弃所有尚未发送的事件
+    fn clear_events(&self) {
+        self.items
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|m| !matches!(m, TrackingMessage::Event(_)));
     }
+}
+
+/// 因队列已满而丢弃的事件数（恢复连接后以一条汇总事件报告）
+static DROPPED_EVENTS: AtomicUsize = AtomicUsize::new(0);
 
-    guard.as_ref().map(|w| w.sender.clone()).unwrap_or(tx)
+/// 后台发送线程在进程生命周期内一直运行，队列仅初始化一次
+static TRACKING_QUEUE: OnceLock<TrackingQueue> = OnceLock::new();
+
+fn send_and_report_dropped(batch: Vec<String>) {
+    if send_batch(batch) {
+        let dropped = DROPPED_EVENTS.swap(0, Ordering::SeqCst);
+        if dropped > 0 {
+            report_event("Metrics.Dropped", Some(&dropped.to_string()));
+        }
+    }
 }
 
-fn send_tracking_request(url: String) {
-    let sender = start_tracking_worker();
-    if let Err(e) = sender.send(url) {
-        spawn(move || send_with_client(e.0));
+/// 收集事件，达到数量上限或等待时间后通过 send 批量发送
+fn run_tracking_worker(queue: &TrackingQueue, mut send: impl FnMut(Vec<String>)) {
+    let mut batch = Vec::new();
+    let mut deadline = Instant::now();
+
+    loop {
+        let timeout =
+            (!batch.is_empty()).then(|| deadline.saturating_duration_since(Instant::now()));
+
+        match queue.pop(timeout) {
+            Some(TrackingMessage::Event(query)) => {
+                if batch.is_empty() {
+                    deadline = Instant::now() + BATCH_WINDOW;
+                }
+                batch.push(query);
+                if batch.len() >= BATCH_MAX_EVENTS {
+                    send(std::mem::take(&mut batch));
+                }
+            }
+            Some(TrackingMessage::Flush(done)) => {
+                if !batch.is_empty() {
+                    send(std::mem::take(&mut batch));
+                }
+                let _ = done.send(());
+            }
+            None => send(std::mem::take(&mut batch)),
+        }
     }
 }
 
-fn join_handle_with_timeout(h: JoinHandle<()>, timeout: Duration) -> bool {
-    let (tx, rx) = channel::<()>();
+fn tracking_queue() -> &'static TrackingQueue {
+    TRACKING_QUEUE.get_or_init(|| {
+        spawn(|| run_tracking_worker(tracking_queue(), send_and_report_dropped));
 
-    spawn(move || {
-        let _ = h.join();
-        let _ = tx.send(());
-    });
+        register_cleanup_with_priority(PRIORITY_METRICS, || {
+            let _ = shutdown(None);
+        })
+        .keep();
 
-    match rx.recv_timeout(timeout) {
-        Ok(_) => true,
-        Err(RecvTimeoutError::Timeout) => false,
-        Err(_) => true,
-    }
+        TrackingQueue::new()
+    })
+}
+
+fn send_tracking_request(query: String) {
+    tracking_queue().push(TrackingMessage::Event(query));
 }
 
+/// 等待已排队的事件发送完成（最长等待 timeout，超时后放弃剩余事件）
 pub fn shutdown(timeout: Option<Duration>) -> Result<()> {
-    let Some(m) = TRACKING_WORKER.get() else {
+    let Some(queue) = TRACKING_QUEUE.get() else {
         return Ok(());
     };
 
-    let to = timeout.unwrap_or(SHUTDOWN_TIMEOUT);
-    let mut guard = match m.lock() {
-        Ok(g) => g,
-        Err(e) => e.into_inner(),
-    };
-
-    if let Some(worker) = guard.take() {
-        drop(guard);
-        let _ = join_handle_with_timeout(worker.handle, to);
+    let (done_tx, done_rx) = channel::<()>();
+    queue.push(TrackingMessage::Flush(done_tx));
+    if done_rx
+        .recv_timeout(timeout.unwrap_or(SHUTDOWN_TIMEOUT))
+        .is_err()
+    {
+        queue.clear_events();
     }
 
     Ok(())
 }
 
+static TELEMETRY_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// 设置是否发送统计事件
+pub fn set_telemetry_enabled(enabled: bool) {
+    TELEMETRY_ENABLED.store(enabled, Ordering::SeqCst);
+}
+
 pub fn report_event(action: &str, name: Option<&str>) {
-    if cfg!(debug_assertions) {
+    if cfg!(debug_assertions) || !TELEMETRY_ENABLED.load(Ordering::SeqCst) {
         return;
     }
 
@@ -203,6 +402,141 @@ pub fn report_event(action: &str, name: Option<&str>) {
         params.insert("e_n", n.to_string());
     }
 
-    let url = build_tracking_url(&user_id, &params);
-    send_tracking_request(url);
+    let query = build_tracking_query(&user_id, &params);
+    send_tracking_request(query);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::mpsc::{Receiver, sync_channel};
+
+    /// 在后台运行发送线程，发送的批次转交给返回的接收端
+    fn spawn_worker() -> (&'static TrackingQueue, Receiver<Vec<String>>) {
+        let queue: &'static TrackingQueue = Box::leak(Box::new(TrackingQueue::new()));
+        let (tx, rx) = channel();
+        spawn(move || {
+            run_tracking_worker(queue, |batch| {
+                let _ = tx.send(batch);
+            })
+        });
+        (queue, rx)
+    }
+
+    fn push_events(queue: &TrackingQueue, prefix: &str, count: usize) {
+        for i in 0..count {
+            queue.push(TrackingMessage::Event(format!("{}{}", prefix, i)));
+        }
+    }
+
+    fn queued_events(queue: &TrackingQueue) -> Vec<String> {
+        queue
+            .items
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|m| match m {
+                TrackingMessage::Event(q) => Some(q.clone()),
+                TrackingMessage::Flush(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn full_batches_are_sent_without_waiting_for_window() {
+        let (queue, sent) = spawn_worker();
+        let start = Instant::now();
+
+        push_events(queue, "e", BATCH_MAX_EVENTS * 2 + 5);
+
+        let first = sent.recv_timeout(Duration::from_secs(5)).unwrap();
+        let second = sent.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(first.len(), BATCH_MAX_EVENTS);
+        assert_eq!(second.len(), BATCH_MAX_EVENTS);
+        assert_eq!(first[0], "e0");
+        assert_eq!(second[0], format!("e{}", BATCH_MAX_EVENTS));
+
+        // 不足一批的剩余事件在等待时间结束后发送
+        let rest = sent.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(rest.len(), 5);
+        assert!(start.elapsed() >= BATCH_WINDOW);
+    }
+
+    #[test]
+    fn flush_sends_partial_batch_immediately() {
+        let (queue, sent) = spawn_worker();
+
+        push_events(queue, "e", 3);
+        let (done_tx, done_rx) = channel();
+        queue.push(TrackingMessage::Flush(done_tx));
+
+        done_rx.recv_timeout(BATCH_WINDOW / 2).unwrap();
+        assert_eq!(sent.try_recv().unwrap(), vec!["e0", "e1", "e2"]);
+
+        // 空批次不会发送
+        let (done_tx, done_rx) = channel();
+        queue.push(TrackingMessage::Flush(done_tx));
+        done_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(sent.try_recv().is_err());
+    }
+
+    #[test]
+    fn blocked_sender_drops_oldest_events_instead_of_accumulating() {
+        let queue: &'static TrackingQueue = Box::leak(Box::new(TrackingQueue::new()));
+        let (started_tx, started_rx) = channel();
+        let (release_tx, release_rx) = sync_channel::<()>(0);
+        spawn(move || {
+            run_tracking_worker(queue, |_batch| {
+                // 模拟统计服务不可达：发送一直阻塞直到测试放行
+                let _ = started_tx.send(());
+                let _ = release_rx.recv();
+            })
+        });
+
+        push_events(queue, "first", BATCH_MAX_EVENTS);
+        started_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        let dropped_before = DROPPED_EVENTS.load(Ordering::SeqCst);
+        push_events(queue, "e", QUEUE_CAPACITY + 44);
+
+        let queued = queued_events(queue);
+        assert_eq!(queued.len(), QUEUE_CAPACITY);
+        assert_eq!(queued[0], "e44");
+        assert_eq!(
+            queued.last().map(String::as_str),
+            Some(format!("e{}", QUEUE_CAPACITY + 43).as_str())
+        );
+        assert!(DROPPED_EVENTS.load(Ordering::SeqCst) >= dropped_before + 44);
+
+        // 刷新请求不会被丢弃
+        let (done_tx, _done_rx) = channel();
+        queue.push(TrackingMessage::Flush(done_tx));
+        push_events(queue, "late", 1);
+        assert_eq!(queued_events(queue).len(), QUEUE_CAPACITY);
+        assert!(
+            queue
+                .items
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|m| matches!(m, TrackingMessage::Flush(_)))
+        );
+
+        drop(release_tx);
+    }
+
+    #[test]
+    fn user_id_source_names_match_json() {
+        for source in [
+            UserIdSource::Override,
+            UserIdSource::MachineGuid,
+            UserIdSource::ComputerName,
+        ] {
+            assert_eq!(
+                serde_json::to_value(source).unwrap(),
+                serde_json::Value::from(source.name())
+            );
+        }
+    }
 }
@@ -1,15 +1,64 @@
 use crate::error::{ManagerError, Result};
 use crate::metrics::report_event;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct VersionInfo {
     #[serde(rename = "bepInEx")]
     pub bep_in_ex: String,
     pub manager: String,
+    /// 测试版管理工具的最新版本（未发布测试版时为 None）
+    #[serde(default, rename = "managerBeta")]
+    pub manager_beta: Option<String>,
+    /// 版本信息所属的发布通道（如 stable、beta）
+    #[serde(default)]
+    pub channel: Option<String>,
     pub dlls: Vec<String>,
     pub zips: Vec<String>,
+    /// 远程下发的额外卸载目标（BepInEx 文件布局变化时无需发布新版管理工具）
+    #[serde(default, rename = "uninstallTargets")]
+    pub uninstall_targets: Vec<UninstallTarget>,
+    /// 是否为管理工具内置的版本信息（可能落后于实际版本，只能用于安装）
+    #[serde(skip)]
+    pub bundled: bool,
+}
+
+/// 远程下发的卸载目标
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct UninstallTarget {
+    /// 相对游戏根目录的路径（可含通配符）
+    pub pattern: String,
+    #[serde(rename = "isDir")]
+    pub is_dir: bool,
+    /// 所属卸载模式（light、full、bepInExOnly）
+    pub mode: String,
+}
+
+/// 管理工具的更新通道
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    Stable,
+    Beta,
+}
+
+impl UpdateChannel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            UpdateChannel::Stable => "stable",
+            UpdateChannel::Beta => "beta",
+        }
+    }
+
+    /// 根据版本号判断所属通道（带预发布标识的版本属于测试版）
+    pub fn of_version(version: &str) -> Self {
+        match semver::Version::parse(version.trim()) {
+            Ok(v) if !v.pre.is_empty() => UpdateChannel::Beta,
+            _ => UpdateChannel::Stable,
+        }
+    }
 }
 
 impl VersionInfo {
@@ -36,6 +85,16 @@ impl VersionInfo {
         &self.zips[0]
     }
 
+    /// 获取指定位置的 DLL 版本（0 为最新版本），越界时返回 None
+    pub fn dll_at(&self, index: usize) -> Option<&str> {
+        self.dlls.get(index).map(String::as_str)
+    }
+
+    /// 获取指定位置的 ResourceEx 版本（0 为最新版本），越界时返回 None
+    pub fn zip_at(&self, index: usize) -> Option<&str> {
+        self.zips.get(index).map(String::as_str)
+    }
+
     /// 解析 BepInEx 的文件名
     pub fn bepinex_filename(&self) -> Result<&str> {
         self.bep_in_ex
@@ -70,9 +129,69 @@ impl VersionInfo {
         format!("ResourceExample-v{}.zip", version.trim())
     }
 
+    /// 指定更新通道上管理工具的最新版本（测试版通道没有测试版时使用稳定版）
+    pub fn latest_manager(&self, channel: UpdateChannel) -> &str {
+        match (channel, &self.manager_beta) {
+            (UpdateChannel::Beta, Some(beta)) if !beta.trim().is_empty() => beta.trim(),
+            _ => self.manager.trim(),
+        }
+    }
+
     /// MetaMystia Manager 可执行文件名
-    pub fn manager_filename(&self) -> String {
-        format!("meta-mystia-manager-v{}.exe", self.manager.trim())
+    pub fn manager_filename(version: &str) -> String {
+        format!("meta-mystia-manager-v{}.exe", version.trim())
+    }
+}
+
+/// 判断 latest 是否比 current 新：两者均为语义化版本时按版本号比较，否则视为不同即较新
+pub fn is_newer_version(current: &str, latest: &str) -> bool {
+    let parse = |v: &str| semver::Version::parse(v.trim().trim_start_matches('v')).ok();
+    match (parse(current), parse(latest)) {
+        (Some(current), Some(latest)) => latest > current,
+        _ => current.trim() != latest.trim(),
+    }
+}
+
+/// 在版本列表中查找指定版本（忽略首尾空白与前缀 `v`）
+pub fn find_version_index(versions: &[String], requested: &str) -> Option<usize> {
+    let normalize = |v: &str| {
+        let v = v.trim();
+        v.strip_prefix(['v', 'V']).unwrap_or(v).to_string()
+    };
+    let requested = normalize(requested);
+
+    versions.iter().position(|v| normalize(v) == requested)
+}
+
+#[cfg(test)]
+impl VersionInfo {
+    /// 构造仅包含单个版本的测试用版本信息（bepinex 格式同接口：`<版本>#<文件名>`）
+    pub fn for_testing(dll_version: &str, zip_version: &str, bepinex: &str, manager: &str) -> Self {
+        Self::for_testing_with_history(
+            vec![dll_version.to_string()],
+            vec![zip_version.to_string()],
+            bepinex,
+            manager,
+        )
+    }
+
+    /// 构造包含历史版本列表的测试用版本信息（列表首项为最新版本）
+    pub fn for_testing_with_history(
+        dlls: Vec<String>,
+        zips: Vec<String>,
+        bepinex: &str,
+        manager: &str,
+    ) -> Self {
+        Self {
+            bep_in_ex: bepinex.to_string(),
+            manager: manager.to_string(),
+            manager_beta: None,
+            channel: None,
+            dlls,
+            zips,
+            uninstall_targets: Vec::new(),
+            bundled: false,
+        }
     }
 }
 
@@ -84,6 +203,105 @@ impl std::fmt::Display for VersionInfo {
             self.bep_in_ex.trim(),
             self.dlls.first().map(|s| s.trim()).unwrap_or(""),
             self.zips.first().map(|s| s.trim()).unwrap_or("")
-        )
+        )?;
+        if let Some(channel) = &self.channel {
+            write!(f, ", channel: {}", channel.trim())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newer_version_compares_semver() {
+        assert!(is_newer_version("0.14.0", "0.15.0"));
+        assert!(is_newer_version("0.9.0", "0.10.0"));
+        assert!(is_newer_version("v1.0.0", "1.0.1"));
+        assert!(!is_newer_version("0.15.0", "0.14.0"));
+        assert!(!is_newer_version("0.15.0", " 0.15.0 "));
+    }
+
+    #[test]
+    fn newer_version_falls_back_to_inequality() {
+        assert!(is_newer_version("custom", "0.15.0"));
+        assert!(!is_newer_version("custom", "custom"));
+    }
+
+    #[test]
+    fn version_lists_are_indexed_from_latest() {
+        let info = VersionInfo::for_testing_with_history(
+            vec!["1.2.0".into(), "1.1.0".into(), "1.0.0".into()],
+            vec!["0.3.0".into(), "0.2.0".into()],
+            "6.0.0-be.733#BepInEx.zip",
+            "1.7.0",
+        );
+
+        assert!(info.validate().is_ok());
+        assert_eq!(info.latest_dll(), "1.2.0");
+        assert_eq!(info.dll_at(2), Some("1.0.0"));
+        assert_eq!(info.dll_at(3), None);
+        assert_eq!(info.latest_resourceex(), "0.3.0");
+        assert_eq!(info.zip_at(1), Some("0.2.0"));
+        assert_eq!(info.zip_at(2), None);
+    }
+
+    #[test]
+    fn find_version_index_ignores_prefix_and_whitespace() {
+        let info = VersionInfo::for_testing_with_history(
+            vec!["1.2.0".into(), " 1.1.0 ".into()],
+            vec!["0.3.0".into()],
+            "6.0.0#BepInEx.zip",
+            "1.7.0",
+        );
+
+        assert_eq!(find_version_index(&info.dlls, "1.2.0"), Some(0));
+        assert_eq!(find_version_index(&info.dlls, "v1.1.0"), Some(1));
+        assert_eq!(find_version_index(&info.dlls, " V1.1.0"), Some(1));
+        assert_eq!(find_version_index(&info.dlls, "1.0.0"), None);
+    }
+
+    #[test]
+    fn bepinex_field_splits_version_and_filename() {
+        let info = VersionInfo::for_testing(
+            "1.2.0",
+            "0.3.0",
+            "6.0.0-be.733 # BepInEx-Unity.IL2CPP-win-x64.zip",
+            "1.7.0",
+        );
+
+        assert_eq!(info.bepinex_version().unwrap(), "6.0.0-be.733");
+        assert_eq!(
+            info.bepinex_filename().unwrap(),
+            "BepInEx-Unity.IL2CPP-win-x64.zip"
+        );
+
+        let broken = VersionInfo::for_testing("1.2.0", "0.3.0", "6.0.0", "1.7.0");
+        assert!(broken.bepinex_filename().is_err());
+    }
+
+    #[test]
+    fn empty_version_lists_are_invalid() {
+        let info = VersionInfo::for_testing_with_history(Vec::new(), vec!["0.3.0".into()], "", "");
+        assert!(matches!(
+            info.validate(),
+            Err(ManagerError::InvalidVersionInfo)
+        ));
+    }
+
+    #[test]
+    fn beta_channel_falls_back_to_stable_manager() {
+        let mut info = VersionInfo::for_testing("1.2.0", "0.3.0", "6.0.0#BepInEx.zip", " 1.7.0 ");
+        assert_eq!(info.latest_manager(UpdateChannel::Beta), "1.7.0");
+
+        info.manager_beta = Some("1.8.0-beta.1".to_string());
+        assert_eq!(info.latest_manager(UpdateChannel::Beta), "1.8.0-beta.1");
+        assert_eq!(info.latest_manager(UpdateChannel::Stable), "1.7.0");
+        assert_eq!(
+            UpdateChannel::of_version("1.8.0-beta.1"),
+            UpdateChannel::Beta
+        );
     }
 }
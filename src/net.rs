@@ -1,24 +1,279 @@
-use crate::config::RetryConfig;
+use crate::config::{RetryConfig, RetryProfile};
 use crate::error::{ManagerError, Result};
 use crate::metrics::report_event;
 use crate::ui::Ui;
 
+use reqwest::Identity;
 use reqwest::blocking::{Client, Response};
 use reqwest::header::{HeaderValue, RETRY_AFTER};
 use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-pub fn with_retry<F, T>(ui: &dyn Ui, op_desc: &str, mut f: F) -> Result<T>
+/// 同一操作连续出现连接失败达到该次数后重建 HTTP 客户端
+const CLIENT_REBUILD_THRESHOLD: usize = 2;
+
+/// 命令行指定的重试次数与基础延迟（以下载配置为基准，其他配置按比例缩放）
+static RETRY_OVERRIDE: OnceLock<(Option<usize>, Option<u64>)> = OnceLock::new();
+
+/// 设置命令行指定的重试参数
+pub fn configure_retry_overrides(retries: Option<usize>, retry_delay_secs: Option<u64>) {
+    if retries.is_some() || retry_delay_secs.is_some() {
+        let _ = RETRY_OVERRIDE.set((retries, retry_delay_secs));
+    }
+}
+
+/// 获取指定类别的重试配置（应用命令行覆盖）
+fn retry_config(profile: RetryProfile) -> RetryConfig {
+    match RETRY_OVERRIDE.get() {
+        Some(&(retries, delay)) => scaled_retry_config(profile, retries, delay),
+        None => RetryConfig::network(profile),
+    }
+}
+
+/// 按命令行指定的重试参数相对下载配置的比例缩放指定类别的配置
+fn scaled_retry_config(
+    profile: RetryProfile,
+    retries: Option<usize>,
+    delay: Option<u64>,
+) -> RetryConfig {
+    let cfg = RetryConfig::network(profile);
+    let reference = RetryConfig::network(RetryProfile::Download);
+    let attempts_factor = retries
+        .map(|r| r.max(1) as f64 / reference.attempts as f64)
+        .unwrap_or(1.0);
+    let delay_factor = delay
+        .map(|d| d as f64 / reference.base_delay_secs as f64)
+        .unwrap_or(1.0);
+
+    cfg.scaled(attempts_factor, delay_factor)
+}
+
+/// 网络诊断信息每次运行只显示一次
+static DIAGNOSTICS_SHOWN: AtomicBool = AtomicBool::new(false);
+
+const CLIENT_CERT_ENV_VAR: &str = "METAMYSTIA_CLIENT_CERT_PATH";
+const CLIENT_KEY_ENV_VAR: &str = "METAMYSTIA_CLIENT_CERT_KEY_PATH";
+
+/// 命令行指定的客户端证书与私钥路径（优先于环境变量）
+static CLIENT_CERT_OVERRIDE: OnceLock<(PathBuf, PathBuf)> = OnceLock::new();
+
+/// 设置命令行指定的客户端证书（需在创建任何 HTTP 客户端之前调用）
+pub fn configure_client_cert(cert: Option<PathBuf>, key: Option<PathBuf>) {
+    if let (Some(cert), Some(key)) = (cert, key) {
+        let _ = CLIENT_CERT_OVERRIDE.set((cert, key));
+    }
+}
+
+fn client_cert_paths() -> Option<(PathBuf, PathBuf)> {
+    if let Some(paths) = CLIENT_CERT_OVERRIDE.get() {
+        return Some(paths.clone());
+    }
+
+    let var = |name| {
+        std::env::var_os(name)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+
+    Some((var(CLIENT_CERT_ENV_VAR)?, var(CLIENT_KEY_ENV_VAR)?))
+}
+
+/// 读取配置的客户端证书（用于双向 TLS），未配置时返回 None
+pub fn load_client_identity() -> Result<Option<Identity>> {
+    let Some((cert_path, key_path)) = client_cert_paths() else {
+        return Ok(None);
+    };
+
+    let load = || -> std::result::Result<Identity, String> {
+        let mut pem = std::fs::read(&cert_path)
+            .map_err(|e| format!("读取 {} 失败：{}", cert_path.display(), e))?;
+        pem.push(b'\n');
+        pem.extend(
+            std::fs::read(&key_path)
+                .map_err(|e| format!("读取 {} 失败：{}", key_path.display(), e))?,
+        );
+
+        Identity::from_pem(&pem).map_err(|e| e.to_string())
+    };
+
+    load().map(Some).map_err(|e| {
+        report_event("Network.ClientCertFailed", Some(&e));
+        ManagerError::NetworkError(format!("加载客户端证书失败：{}", e))
+    })
+}
+
+/// 可重建的 HTTP 客户端
+///
+/// 网络环境变化（如切换 Wi-Fi、连接 VPN）后，旧客户端持有的连接池与 DNS 结果可能已失效，
+/// 重建时使用同一构建函数，保留代理、超时与 UA 等配置。
+pub struct HttpClient {
+    inner: Mutex<Client>,
+    build: Option<fn() -> Result<Client>>,
+}
+
+impl HttpClient {
+    pub fn new(build: fn() -> Result<Client>) -> Result<Self> {
+        Ok(Self {
+            inner: Mutex::new(build()?),
+            build: Some(build),
+        })
+    }
+
+    /// 使用外部传入的客户端（不支持重建，用于测试）
+    #[cfg(test)]
+    pub fn fixed(client: Client) -> Self {
+        Self {
+            inner: Mutex::new(client),
+            build: None,
+        }
+    }
+
+    /// 获取当前客户端（内部为引用计数，克隆开销很小）
+    pub fn get(&self) -> Client {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 丢弃旧客户端并重新构建，返回是否实际进行了重建
+    pub fn rebuild(&self) -> Result<bool> {
+        let Some(build) = self.build else {
+            return Ok(false);
+        };
+
+        let client = build()?;
+        *self.inner.lock().unwrap_or_else(|e| e.into_inner()) = client;
+        Ok(true)
+    }
+}
+
+/// 请求去重器：同一键的请求同时只由一个线程发出，其余线程等待其结果
+///
+/// 成功的结果在去重器的生命周期内复用；请求失败时不保存结果，由下一个等待的线程重新请求。
+pub struct RequestDeduplicator<V> {
+    slots: Mutex<HashMap<String, Arc<Mutex<Option<V>>>>>,
+}
+
+impl<V: Clone> RequestDeduplicator<V> {
+    pub fn new() -> Self {
+        Self {
+            slots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 获取键对应的结果，尚无结果时执行 fetch
+    pub fn get_or_fetch<F>(&self, key: &str, fetch: F) -> Result<V>
+    where
+        F: FnOnce() -> Result<V>,
+    {
+        let slot = self
+            .slots
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(key.to_string())
+            .or_default()
+            .clone();
+
+        // 持有该键的锁直到请求结束，同时到达的线程在此等待
+        let mut guard = slot.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(value) = guard.as_ref() {
+            return Ok(value.clone());
+        }
+
+        let value = fetch()?;
+        *guard = Some(value.clone());
+        Ok(value)
+    }
+}
+
+/// 转换请求发送阶段的错误，连接类错误单独归类以便触发客户端重建
+pub fn map_send_error(e: &reqwest::Error, msg: String) -> ManagerError {
+    if e.is_connect() {
+        ManagerError::ConnectFailed(msg)
+    } else if e.is_timeout() {
+        ManagerError::Timeout(msg)
+    } else {
+        ManagerError::NetworkError(msg)
+    }
+}
+
+/// 当前系统时间（UTC），用于排查时钟偏差导致的 TLS 错误
+pub fn current_utc_time() -> String {
+    format!("{} UTC", format_utc_time(SystemTime::now()))
+}
+
+/// 将时间格式化为 UTC 的 `YYYY-MM-DD HH:MM:SS`
+pub fn format_utc_time(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let (days, rem) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+
+    // 由天数推算公历日期
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+fn display_diagnostics_once(ui: &dyn Ui, endpoint: &str, last_error: &str) -> Result<()> {
+    report_event("Network.Exhausted", Some(endpoint));
+
+    if DIAGNOSTICS_SHOWN.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+
+    ui.display_network_diagnostics(endpoint, last_error)
+}
+
+pub fn with_retry<F, T>(
+    ui: &dyn Ui,
+    client: Option<&HttpClient>,
+    profile: RetryProfile,
+    endpoint: &str,
+    op_desc: &str,
+    mut f: F,
+) -> Result<T>
 where
     F: FnMut() -> Result<T>,
 {
-    let cfg = RetryConfig::network();
+    let cfg = retry_config(profile);
+    let mut connect_failures = 0;
 
     for attempt in 0..cfg.attempts {
         match f() {
             Ok(v) => return Ok(v),
             Err(e) => {
+                // 未认证的配额需要等待较长时间才会恢复，重试没有意义
+                if matches!(e, ManagerError::AuthRequired(_)) {
+                    return Err(e);
+                }
+
+                if matches!(e, ManagerError::ConnectFailed(_)) {
+                    connect_failures += 1;
+                } else {
+                    connect_failures = 0;
+                }
+
                 let raw = (cfg.base_delay_secs as f64) * cfg.multiplier.powi(attempt as i32);
                 let delay_secs = raw.min(cfg.max_delay_secs as f64).ceil() as u64;
 
@@ -41,8 +296,23 @@ where
 
                 if attempt < cfg.attempts - 1 {
                     sleep(Duration::from_secs(delay_secs));
+
+                    if connect_failures >= CLIENT_REBUILD_THRESHOLD
+                        && let Some(c) = client
+                    {
+                        connect_failures = 0;
+                        match c.rebuild() {
+                            Ok(true) => report_event("Network.ClientRebuilt", Some(op_desc)),
+                            Ok(false) => {}
+                            Err(err) => report_event(
+                                "Network.ClientRebuildFailed",
+                                Some(&format!("{};err={}", op_desc, err)),
+                            ),
+                        }
+                    }
                 } else {
                     report_event("Network.RetryFailed", Some(op_desc));
+                    display_diagnostics_once(ui, endpoint, &format!("{}", e))?;
                     return Err(e);
                 }
             }
@@ -78,35 +348,54 @@ fn check_response_status(resp: &Response, ui: &dyn Ui, op_desc: &str) -> Result<
         return Err(ManagerError::RateLimited(op_desc.to_string()));
     }
 
+    // GitHub API 未认证请求超出每小时配额
+    if resp.status().as_u16() == 403
+        && resp
+            .headers()
+            .get("X-RateLimit-Remaining")
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.trim() == "0")
+    {
+        report_event("Network.AuthRequired", Some(op_desc));
+        return Err(ManagerError::AuthRequired(format!(
+            "{}超出 GitHub API 未认证请求配额（每小时 60 次），请稍后重试或设置 GITHUB_TOKEN 环境变量",
+            op_desc
+        )));
+    }
+
     report_event(
         "Network.HttpError",
         Some(&format!("{};status={}", op_desc, resp.status())),
     );
 
-    Err(ManagerError::NetworkError(format!(
-        "{}返回错误：HTTP {}",
-        op_desc,
-        resp.status()
-    )))
+    Err(ManagerError::HttpStatus {
+        status: resp.status().as_u16(),
+        message: format!("{}返回错误：HTTP {}", op_desc, resp.status()),
+    })
 }
 
 /// 使用重试机制获取并解析 JSON 数据
 pub fn get_json_with_retry<T: DeserializeOwned>(
-    client: &Client,
+    client: &HttpClient,
     ui: &dyn Ui,
     url: &str,
     accept_header: Option<&str>,
+    bearer_token: Option<&str>,
+    profile: RetryProfile,
     op_desc: &str,
 ) -> Result<T> {
-    with_retry(ui, op_desc, || {
-        let mut req = client.get(url);
+    with_retry(ui, Some(client), profile, url, op_desc, || {
+        let mut req = client.get().get(url);
         if let Some(h) = accept_header {
             req = req.header("Accept", h);
         }
+        if let Some(token) = bearer_token {
+            req = req.bearer_auth(token);
+        }
 
         let resp = req
             .send()
-            .map_err(|e| ManagerError::NetworkError(format!("请求失败：{}", e)))?;
+            .map_err(|e| map_send_error(&e, format!("请求失败：{}", e)))?;
 
         check_response_status(&resp, ui, op_desc)?;
 
@@ -128,21 +417,157 @@ pub fn get_json_with_retry<T: DeserializeOwned>(
     })
 }
 
-/// 使用重试机制获取响应
-pub fn get_response_with_retry(
-    client: &Client,
-    ui: &dyn Ui,
-    url: &str,
-    op_desc: &str,
-) -> Result<Response> {
-    with_retry(ui, op_desc, || {
-        let resp = client
-            .get(url)
-            .send()
-            .map_err(|e| ManagerError::NetworkError(format!("请求失败：{}", e)))?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TestUI, UiCallKind};
 
-        check_response_status(&resp, ui, op_desc)?;
+    use std::sync::atomic::AtomicUsize;
 
-        Ok(resp)
-    })
+    static CONNECT_BUILDS: AtomicUsize = AtomicUsize::new(0);
+    static HTTP_ERROR_BUILDS: AtomicUsize = AtomicUsize::new(0);
+
+    fn build_for_connect_test() -> Result<Client> {
+        CONNECT_BUILDS.fetch_add(1, Ordering::SeqCst);
+        Ok(Client::new())
+    }
+
+    fn build_for_http_error_test() -> Result<Client> {
+        HTTP_ERROR_BUILDS.fetch_add(1, Ordering::SeqCst);
+        Ok(Client::new())
+    }
+
+    /// 测试中不等待重试间隔
+    fn disable_retry_delay() {
+        configure_retry_overrides(None, Some(0));
+    }
+
+    #[test]
+    fn connect_failures_rebuild_client() {
+        disable_retry_delay();
+        let ui = TestUI::new();
+        let client = HttpClient::new(build_for_connect_test).unwrap();
+        assert_eq!(CONNECT_BUILDS.load(Ordering::SeqCst), 1);
+
+        let mut calls = 0;
+        let result: Result<()> = with_retry(
+            &ui,
+            Some(&client),
+            RetryProfile::Download,
+            "https://example.invalid",
+            "测试",
+            || {
+                calls += 1;
+                Err(ManagerError::ConnectFailed("refused".to_string()))
+            },
+        );
+
+        assert!(matches!(result, Err(ManagerError::ConnectFailed(_))));
+        assert_eq!(calls, 4);
+        // 第 2 次失败后重建一次，最后一次失败不再重建
+        assert_eq!(CONNECT_BUILDS.load(Ordering::SeqCst), 2);
+        assert!(ui.assert_called(UiCallKind::NetworkRetrying));
+    }
+
+    #[test]
+    fn other_failures_do_not_rebuild_client() {
+        disable_retry_delay();
+        let ui = TestUI::new();
+        let client = HttpClient::new(build_for_http_error_test).unwrap();
+
+        let mut calls = 0;
+        let result: Result<()> = with_retry(
+            &ui,
+            Some(&client),
+            RetryProfile::Download,
+            "https://example.invalid",
+            "测试",
+            || {
+                calls += 1;
+                if calls % 2 == 0 {
+                    Err(ManagerError::HttpStatus {
+                        status: 500,
+                        message: "HTTP 500".to_string(),
+                    })
+                } else {
+                    Err(ManagerError::ConnectFailed("refused".to_string()))
+                }
+            },
+        );
+
+        assert!(result.is_err());
+        assert_eq!(HTTP_ERROR_BUILDS.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn fixed_client_is_not_rebuilt() {
+        let client = HttpClient::fixed(Client::new());
+        assert!(!client.rebuild().unwrap());
+    }
+
+    fn utc_at(secs: u64) -> String {
+        format_utc_time(UNIX_EPOCH + Duration::from_secs(secs))
+    }
+
+    #[test]
+    fn format_utc_time_handles_epoch_and_day_boundaries() {
+        assert_eq!(utc_at(0), "1970-01-01 00:00:00");
+        assert_eq!(utc_at(86399), "1970-01-01 23:59:59");
+        assert_eq!(utc_at(86400), "1970-01-02 00:00:00");
+        // 早于纪元的时间按纪元显示
+        assert_eq!(
+            format_utc_time(UNIX_EPOCH - Duration::from_secs(1)),
+            "1970-01-01 00:00:00"
+        );
+    }
+
+    #[test]
+    fn format_utc_time_handles_leap_years_and_month_ends() {
+        // 2024 年是闰年
+        assert_eq!(utc_at(1_709_164_800), "2024-02-29 00:00:00");
+        assert_eq!(utc_at(1_709_251_199), "2024-02-29 23:59:59");
+        assert_eq!(utc_at(1_709_251_200), "2024-03-01 00:00:00");
+        // 2023 年不是闰年
+        assert_eq!(utc_at(1_677_628_799), "2023-02-28 23:59:59");
+        assert_eq!(utc_at(1_677_628_800), "2023-03-01 00:00:00");
+        // 2000 年是闰年（能被 400 整除），2100 年不是
+        assert_eq!(utc_at(951_782_400), "2000-02-29 00:00:00");
+        assert_eq!(utc_at(4_107_542_399), "2100-02-28 23:59:59");
+        assert_eq!(utc_at(4_107_542_400), "2100-03-01 00:00:00");
+        // 月末与年末
+        assert_eq!(utc_at(1_714_521_599), "2024-04-30 23:59:59");
+        assert_eq!(utc_at(1_735_689_599), "2024-12-31 23:59:59");
+        assert_eq!(utc_at(1_735_689_600), "2025-01-01 00:00:00");
+    }
+
+    #[test]
+    fn retry_overrides_scale_all_profiles_proportionally() {
+        // 下载配置为基准：4 次 / 5 秒，指定 8 次 / 10 秒即放大两倍
+        let download = scaled_retry_config(RetryProfile::Download, Some(8), Some(10));
+        assert_eq!(download.attempts, 8);
+        assert_eq!(download.base_delay_secs, 10);
+        assert_eq!(download.max_delay_secs, 60);
+
+        let metadata = scaled_retry_config(RetryProfile::Metadata, Some(8), Some(10));
+        assert_eq!(metadata.attempts, 4);
+        assert_eq!(metadata.base_delay_secs, 4);
+        assert_eq!(metadata.max_delay_secs, 8);
+
+        let github = scaled_retry_config(RetryProfile::GitHubApi, Some(8), Some(10));
+        assert_eq!(github.attempts, 6);
+        assert_eq!(github.base_delay_secs, 6);
+        assert_eq!(github.max_delay_secs, 20);
+    }
+
+    #[test]
+    fn retry_overrides_keep_at_least_one_attempt_and_unset_values() {
+        let cfg = scaled_retry_config(RetryProfile::Metadata, Some(0), None);
+        assert_eq!(cfg.attempts, 1);
+        assert_eq!(cfg.base_delay_secs, 2);
+
+        let cfg = scaled_retry_config(RetryProfile::GitHubApi, None, Some(0));
+        assert_eq!(cfg.attempts, 3);
+        assert_eq!(cfg.base_delay_secs, 0);
+        assert_eq!(cfg.max_delay_secs, 0);
+    }
 }
@@ -1,11 +1,27 @@
 use crate::error::{ManagerError, Result};
+use crate::file_ops::write_file_atomic;
 use crate::metrics::report_event;
 
+use std::os::windows::ffi::OsStrExt;
 use std::os::windows::process::CommandExt;
+use std::path::Path;
 use std::process::Command;
-use windows::Win32::Foundation::{CloseHandle, HANDLE};
-use windows::Win32::Security::{GetTokenInformation, TOKEN_ELEVATION, TOKEN_QUERY, TokenElevation};
+use std::sync::OnceLock;
+use windows::Win32::Foundation::{CloseHandle, ERROR_SUCCESS, HANDLE, HLOCAL, LUID, LocalFree};
+use windows::Win32::Security::Authorization::{
+    EXPLICIT_ACCESS_W, GRANT_ACCESS, SE_FILE_OBJECT, SetEntriesInAclW, SetNamedSecurityInfoW,
+    TRUSTEE_IS_SID, TRUSTEE_IS_WELL_KNOWN_GROUP, TRUSTEE_W,
+};
+use windows::Win32::Security::{
+    ACL, AdjustTokenPrivileges, CreateWellKnownSid, DACL_SECURITY_INFORMATION, GetTokenInformation,
+    LUID_AND_ATTRIBUTES, LookupPrivilegeValueW, NO_INHERITANCE, OWNER_SECURITY_INFORMATION, PSID,
+    SE_PRIVILEGE_ENABLED, SE_RESTORE_NAME, SE_TAKE_OWNERSHIP_NAME, SECURITY_MAX_SID_SIZE,
+    SUB_CONTAINERS_AND_OBJECTS_INHERIT, TOKEN_ADJUST_PRIVILEGES, TOKEN_ELEVATION, TOKEN_PRIVILEGES,
+    TOKEN_QUERY, TokenElevation, WinBuiltinAdministratorsSid,
+};
+use windows::Win32::Storage::FileSystem::FILE_ALL_ACCESS;
 use windows::Win32::System::Threading::{CREATE_NO_WINDOW, GetCurrentProcess, OpenProcessToken};
+use windows::core::{PCWSTR, PWSTR};
 
 struct TokenHandle(HANDLE);
 
@@ -41,13 +57,20 @@ impl Drop for TempScript {
     }
 }
 
+/// 当前进程的管理员权限状态（进程生命周期内不会改变）
+static ELEVATED: OnceLock<bool> = OnceLock::new();
+
 /// 检查当前进程是否具有管理员权限
 pub fn is_elevated() -> Result<bool> {
+    Ok(*ELEVATED.get_or_init(query_elevated))
+}
+
+fn query_elevated() -> bool {
     unsafe {
         let mut token = HANDLE::default();
 
         if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token).is_err() {
-            return Ok(false);
+            return false;
         }
 
         let token_handle = TokenHandle::new(token);
@@ -63,12 +86,179 @@ pub fn is_elevated() -> Result<bool> {
             &mut return_length,
         );
 
-        if result.is_ok() {
-            Ok(elevation.TokenIsElevated != 0)
-        } else {
-            Ok(false)
+        result.is_ok() && elevation.TokenIsElevated != 0
+    }
+}
+
+/// 检查 Windows Defender 的“受控文件夹访问”是否已启用
+pub fn is_controlled_folder_access_enabled() -> bool {
+    let Ok(out) = Command::new("reg")
+        .args([
+            "query",
+            r"HKLM\SOFTWARE\Microsoft\Windows Defender\Windows Defender Exploit Guard\Controlled Folder Access",
+            "/v",
+            "EnableControlledFolderAccess",
+        ])
+        .creation_flags(CREATE_NO_WINDOW.0)
+        .output()
+    else {
+        return false;
+    };
+    if !out.status.success() {
+        return false;
+    }
+
+    // 1 = 阻止，2 = 审核模式（仅记录不阻止）
+    let s = String::from_utf8_lossy(&out.stdout);
+    s.lines()
+        .map(str::trim)
+        .filter(|t| t.starts_with("EnableControlledFolderAccess"))
+        .filter_map(|t| t.split_whitespace().last())
+        .any(|v| v.eq_ignore_ascii_case("0x1"))
+}
+
+/// 判断错误是否为访问被拒绝
+pub fn is_access_denied(err: &ManagerError) -> bool {
+    match err {
+        ManagerError::PermissionDenied(_) => true,
+        ManagerError::Io(e) => e.kind() == std::io::ErrorKind::PermissionDenied,
+        _ => false,
+    }
+}
+
+/// 为当前进程启用指定的特权
+fn enable_privilege(name: PCWSTR) -> Result<()> {
+    unsafe {
+        let mut token = HANDLE::default();
+        OpenProcessToken(
+            GetCurrentProcess(),
+            TOKEN_ADJUST_PRIVILEGES | TOKEN_QUERY,
+            &mut token,
+        )
+        .map_err(|e| ManagerError::PermissionDenied(format!("打开进程令牌失败：{}", e)))?;
+        let token = TokenHandle::new(token);
+
+        let mut luid = LUID::default();
+        LookupPrivilegeValueW(PCWSTR::null(), name, &mut luid)
+            .map_err(|e| ManagerError::PermissionDenied(format!("查询特权失败：{}", e)))?;
+
+        let privileges = TOKEN_PRIVILEGES {
+            PrivilegeCount: 1,
+            Privileges: [LUID_AND_ATTRIBUTES {
+                Luid: luid,
+                Attributes: SE_PRIVILEGE_ENABLED,
+            }],
+        };
+        AdjustTokenPrivileges(token.raw(), false, Some(&privileges), 0, None, None)
+            .map_err(|e| ManagerError::PermissionDenied(format!("启用特权失败：{}", e)))
+    }
+}
+
+/// 将文件或目录的所有者改为 Administrators 组，并授予该组完全控制权限（需要管理员权限）
+pub fn take_ownership(path: &Path) -> Result<()> {
+    enable_privilege(SE_TAKE_OWNERSHIP_NAME)?;
+    // 设置为其他所有者需要还原特权，获取失败时仍可尝试
+    let _ = enable_privilege(SE_RESTORE_NAME);
+
+    let wide_path: Vec<u16> = path
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+
+    unsafe {
+        let mut sid_buf = [0u8; SECURITY_MAX_SID_SIZE as usize];
+        let mut sid_len = SECURITY_MAX_SID_SIZE;
+        let admins = PSID(sid_buf.as_mut_ptr().cast());
+        CreateWellKnownSid(
+            WinBuiltinAdministratorsSid,
+            None,
+            Some(admins),
+            &mut sid_len,
+        )
+        .map_err(|e| ManagerError::Other(format!("创建 Administrators SID 失败：{}", e)))?;
+
+        let err = SetNamedSecurityInfoW(
+            PCWSTR(wide_path.as_ptr()),
+            SE_FILE_OBJECT,
+            OWNER_SECURITY_INFORMATION,
+            Some(admins),
+            None,
+            None,
+            None,
+        );
+        if err != ERROR_SUCCESS {
+            return Err(ManagerError::PermissionDenied(format!(
+                "无法获取 {} 的所有权（错误码 {}）",
+                path.display(),
+                err.0
+            )));
+        }
+
+        let access = EXPLICIT_ACCESS_W {
+            grfAccessPermissions: FILE_ALL_ACCESS.0,
+            grfAccessMode: GRANT_ACCESS,
+            grfInheritance: if path.is_dir() {
+                SUB_CONTAINERS_AND_OBJECTS_INHERIT
+            } else {
+                NO_INHERITANCE
+            },
+            Trustee: TRUSTEE_W {
+                TrusteeForm: TRUSTEE_IS_SID,
+                TrusteeType: TRUSTEE_IS_WELL_KNOWN_GROUP,
+                ptstrName: PWSTR(admins.0.cast()),
+                ..Default::default()
+            },
+        };
+        let mut acl: *mut ACL = std::ptr::null_mut();
+        let err = SetEntriesInAclW(Some(&[access]), None, &mut acl);
+        if err != ERROR_SUCCESS {
+            return Err(ManagerError::PermissionDenied(format!(
+                "构建 {} 的访问控制列表失败（错误码 {}）",
+                path.display(),
+                err.0
+            )));
+        }
+
+        let err = SetNamedSecurityInfoW(
+            PCWSTR(wide_path.as_ptr()),
+            SE_FILE_OBJECT,
+            DACL_SECURITY_INFORMATION,
+            None,
+            None,
+            Some(acl),
+            None,
+        );
+        let _ = LocalFree(Some(HLOCAL(acl.cast())));
+        if err != ERROR_SUCCESS {
+            return Err(ManagerError::PermissionDenied(format!(
+                "无法修改 {} 的访问权限（错误码 {}）",
+                path.display(),
+                err.0
+            )));
         }
     }
+
+    report_event("Permission.TakeOwnership", None);
+    Ok(())
+}
+
+/// 手动获取所有权并授予 Administrators 完全控制权限的命令
+pub fn acl_fix_commands(path: &Path) -> Vec<String> {
+    // 使用 SID 指定 Administrators 组，避免系统语言导致组名不同
+    let (takeown_recursive, icacls_recursive) = if path.is_dir() {
+        (" /r", " /t")
+    } else {
+        ("", "")
+    };
+    vec![
+        format!("takeown /f \"{}\" /a{}", path.display(), takeown_recursive),
+        format!(
+            "icacls \"{}\" /grant *S-1-5-32-544:F{} /c",
+            path.display(),
+            icacls_recursive
+        ),
+    ]
 }
 
 /// 以管理员权限重新启动程序
@@ -89,12 +279,7 @@ pub fn elevate_and_restart() -> Result<()> {
     let mut script_path = std::env::temp_dir();
     script_path.push(format!("meta_mystia_elevate_{}.ps1", std::process::id()));
 
-    std::fs::write(&script_path, script.as_bytes()).map_err(|e| {
-        ManagerError::from(std::io::Error::new(
-            e.kind(),
-            format!("写入提升脚本 {} 失败：{}", script_path.display(), e),
-        ))
-    })?;
+    write_file_atomic(&script_path, script.as_bytes())?;
 
     let _temp_script = TempScript::new(script_path.clone());
 
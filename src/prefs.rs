@@ -0,0 +1,199 @@
+use crate::error::{ManagerError, Result};
+use crate::file_ops::write_file_atomic;
+use crate::metrics::report_event;
+use crate::model::UpdateChannel;
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+const PREFS_DIR_NAME: &str = "meta-mystia-manager";
+const PREFS_FILE_NAME: &str = "prefs.toml";
+/// 旧版本使用的 JSON 偏好设置文件（首次读取时迁移）
+const LEGACY_PREFS_FILE_NAME: &str = "preferences.json";
+
+/// 用户偏好设置（保存在 %APPDATA% 下）
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Preferences {
+    /// 是否允许发送匿名使用统计（None 表示尚未询问）
+    #[serde(default)]
+    pub telemetry_enabled: Option<bool>,
+
+    /// 用户重置后的匿名用户 ID（优先于由设备标识派生的 ID）
+    #[serde(default)]
+    pub user_id: Option<String>,
+
+    /// 是否已显示过首次运行的欢迎介绍
+    #[serde(default)]
+    pub welcome_shown: bool,
+
+    /// 历史平均下载速度（KB/s），用于估算安装耗时
+    #[serde(default)]
+    pub avg_download_speed_kbps: Option<u64>,
+
+    /// 是否跳过慢速存储检测
+    #[serde(default)]
+    pub skip_slow_storage_check: bool,
+
+    /// 用户手动指定过的游戏目录
+    #[serde(default)]
+    pub game_path: Option<PathBuf>,
+
+    /// 是否已提示过游戏目录位于云同步目录中
+    #[serde(default)]
+    pub cloud_sync_warned: bool,
+
+    /// 管理工具的更新通道（None 表示根据当前版本号判断）
+    #[serde(default)]
+    pub update_channel: Option<UpdateChannel>,
+
+    /// 确认提示的等待时限（秒），超时后采用默认选项（None 或 0 表示一直等待）
+    #[serde(default)]
+    pub prompt_timeout_secs: Option<u64>,
+}
+
+/// 管理器数据目录（%APPDATA%\meta-mystia-manager）
+pub fn data_dir() -> Option<PathBuf> {
+    std::env::var_os("APPDATA")
+        .filter(|v| !v.is_empty())
+        .map(|dir| PathBuf::from(dir).join(PREFS_DIR_NAME))
+}
+
+impl Preferences {
+    /// 偏好设置文件路径
+    pub fn path() -> Option<PathBuf> {
+        data_dir().map(|dir| dir.join(PREFS_FILE_NAME))
+    }
+
+    /// 读取偏好设置，文件不存在时返回默认值；无法解析的文件改名为 prefs.toml.corrupt 后返回默认值
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => return Self::migrate_legacy().unwrap_or_default(),
+        };
+
+        match toml::from_str(&content) {
+            Ok(prefs) => prefs,
+            Err(e) => {
+                let _ = std::fs::rename(&path, path.with_extension("toml.corrupt"));
+                report_event("Prefs.Corrupt", Some(&format!("{}", e)));
+                Self::default()
+            }
+        }
+    }
+
+    /// 将旧版 preferences.json 转存为 prefs.toml
+    fn migrate_legacy() -> Option<Self> {
+        let legacy = data_dir()?.join(LEGACY_PREFS_FILE_NAME);
+        let prefs: Self = serde_json::from_str(&std::fs::read_to_string(&legacy).ok()?).ok()?;
+
+        if prefs.save().is_ok() {
+            let _ = std::fs::remove_file(&legacy);
+            report_event("Prefs.Migrated", None);
+        }
+
+        Some(prefs)
+    }
+
+    /// 保存偏好设置
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()
+            .ok_or_else(|| ManagerError::Other("无法确定偏好设置文件位置".to_string()))?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                ManagerError::from(std::io::Error::new(
+                    e.kind(),
+                    format!("创建目录 {} 失败：{}", parent.display(), e),
+                ))
+            })?;
+        }
+
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| ManagerError::Other(format!("序列化偏好设置失败：{}", e)))?;
+
+        write_file_atomic(&path, content.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::rerun_with_scratch_appdata;
+
+    fn prefs_dir() -> PathBuf {
+        data_dir().unwrap()
+    }
+
+    #[test]
+    fn saved_preferences_are_loaded_back() {
+        if rerun_with_scratch_appdata("prefs::tests::saved_preferences_are_loaded_back") {
+            return;
+        }
+
+        let prefs = Preferences {
+            telemetry_enabled: Some(false),
+            user_id: Some("reset-id".to_string()),
+            game_path: Some(PathBuf::from("D:\\Games\\Touhou Mystia Izakaya")),
+            prompt_timeout_secs: Some(30),
+            cloud_sync_warned: true,
+            ..Preferences::default()
+        };
+        prefs.save().unwrap();
+
+        let loaded = Preferences::load();
+        assert_eq!(loaded.telemetry_enabled, Some(false));
+        assert_eq!(loaded.user_id.as_deref(), Some("reset-id"));
+        assert_eq!(loaded.game_path, prefs.game_path);
+        assert_eq!(loaded.prompt_timeout_secs, Some(30));
+        assert!(loaded.cloud_sync_warned);
+        assert!(!loaded.welcome_shown);
+    }
+
+    #[test]
+    fn corrupt_preferences_are_set_aside() {
+        if rerun_with_scratch_appdata("prefs::tests::corrupt_preferences_are_set_aside") {
+            return;
+        }
+
+        let path = Preferences::path().unwrap();
+        std::fs::create_dir_all(prefs_dir()).unwrap();
+        std::fs::write(&path, "welcome_shown = [").unwrap();
+
+        let loaded = Preferences::load();
+
+        assert!(!loaded.welcome_shown);
+        assert!(loaded.telemetry_enabled.is_none());
+        assert!(!path.exists());
+        assert_eq!(
+            std::fs::read_to_string(prefs_dir().join("prefs.toml.corrupt")).unwrap(),
+            "welcome_shown = ["
+        );
+    }
+
+    #[test]
+    fn legacy_json_preferences_are_migrated() {
+        if rerun_with_scratch_appdata("prefs::tests::legacy_json_preferences_are_migrated") {
+            return;
+        }
+
+        let legacy = prefs_dir().join(LEGACY_PREFS_FILE_NAME);
+        std::fs::create_dir_all(prefs_dir()).unwrap();
+        std::fs::write(
+            &legacy,
+            r#"{"telemetry_enabled": true, "welcome_shown": true}"#,
+        )
+        .unwrap();
+
+        let loaded = Preferences::load();
+
+        assert_eq!(loaded.telemetry_enabled, Some(true));
+        assert!(loaded.welcome_shown);
+        assert!(!legacy.exists());
+        assert!(Preferences::path().unwrap().exists());
+        assert!(Preferences::load().welcome_shown);
+    }
+}
@@ -0,0 +1,61 @@
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// 正在进行的操作的最新进度，供中断时向用户说明已完成的部分
+#[derive(Clone, Debug, Default)]
+pub struct ProgressSnapshot {
+    /// 操作名称（如“安装”），为空表示当前没有进行中的操作
+    pub operation: String,
+    pub completed_steps: usize,
+    pub total_steps: usize,
+    /// 当前正在执行的步骤
+    pub current: Option<String>,
+}
+
+impl ProgressSnapshot {
+    /// 是否有进行中的操作
+    pub fn is_active(&self) -> bool {
+        !self.operation.is_empty()
+    }
+}
+
+static PROGRESS: OnceLock<Arc<Mutex<ProgressSnapshot>>> = OnceLock::new();
+
+/// 操作与中断处理共享的进度记录
+pub fn progress_snapshot() -> Arc<Mutex<ProgressSnapshot>> {
+    PROGRESS
+        .get_or_init(|| Arc::new(Mutex::new(ProgressSnapshot::default())))
+        .clone()
+}
+
+/// 进度记录的作用域，drop 时清空记录（操作正常结束或出错返回后不再视为被中断）
+#[must_use = "丢弃作用域会立即清空进度记录"]
+pub struct ProgressScope {
+    snapshot: Arc<Mutex<ProgressSnapshot>>,
+}
+
+impl ProgressScope {
+    /// 开始记录一个分步骤的操作
+    pub fn begin(operation: &str, total_steps: usize) -> Self {
+        let snapshot = progress_snapshot();
+        *snapshot.lock().unwrap_or_else(|e| e.into_inner()) = ProgressSnapshot {
+            operation: operation.to_string(),
+            completed_steps: 0,
+            total_steps,
+            current: None,
+        };
+        Self { snapshot }
+    }
+
+    /// 记录开始执行第 step 步（之前的步骤视为已完成）
+    pub fn step(&self, step: usize, description: &str) {
+        let mut guard = self.snapshot.lock().unwrap_or_else(|e| e.into_inner());
+        guard.completed_steps = step.saturating_sub(1);
+        guard.current = Some(description.to_string());
+    }
+}
+
+impl Drop for ProgressScope {
+    fn drop(&mut self) {
+        *self.snapshot.lock().unwrap_or_else(|e| e.into_inner()) = ProgressSnapshot::default();
+    }
+}
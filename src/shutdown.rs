@@ -1,6 +1,6 @@
 use crate::metrics;
 
-use std::mem::take;
+use std::cmp::Reverse;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{RecvTimeoutError, channel};
 use std::sync::{Mutex, Once, OnceLock};
@@ -9,8 +9,21 @@ use std::time::{Duration, Instant};
 
 pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
 
+/// 清除控制台进度条的优先级（最先执行，避免后续输出错位）
+pub const PRIORITY_PROGRESS: u8 = 250;
+/// 说明操作中断时进度的优先级（在清除进度条之后、删除临时目录之前）
+pub const PRIORITY_PROGRESS_CANCELLED: u8 = 240;
+/// 临时目录清理的优先级（100-255 保留给内部使用）
+pub const PRIORITY_TEMP_DIR: u8 = 200;
+/// 统计数据发送的优先级
+pub const PRIORITY_METRICS: u8 = 100;
+/// 普通清理回调的默认优先级（0-99 供一般回调使用）
+#[allow(dead_code)]
+pub const DEFAULT_PRIORITY: u8 = 50;
+
 type CleanupCallback = Box<dyn Fn() + Send + 'static>;
-static CALLBACKS: OnceLock<Mutex<Vec<Option<CleanupCallback>>>> = OnceLock::new();
+type PrioritizedCallback = (u8, CleanupCallback);
+static CALLBACKS: OnceLock<Mutex<Vec<Option<PrioritizedCallback>>>> = OnceLock::new();
 static SET_HANDLER: Once = Once::new();
 static SHUTDOWN_STARTED: AtomicBool = AtomicBool::new(false);
 
@@ -56,8 +69,36 @@ fn ensure_handlers() {
     });
 }
 
-/// 注册一个清理回调函数
-pub fn register_cleanup<F>(f: F) -> usize
+/// 清理回调的注册句柄，drop 时注销对应的回调
+#[must_use = "丢弃句柄会立即注销回调，需要保留到进程退出时请调用 keep()"]
+pub struct CleanupHandle {
+    id: usize,
+}
+
+impl CleanupHandle {
+    /// 保留回调直到进程退出，不再随句柄注销
+    pub fn keep(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for CleanupHandle {
+    fn drop(&mut self) {
+        unregister_cleanup(self.id);
+    }
+}
+
+/// 注册一个清理回调函数（默认优先级）
+#[allow(dead_code)]
+pub fn register_cleanup<F>(f: F) -> CleanupHandle
+where
+    F: Fn() + Send + 'static,
+{
+    register_cleanup_with_priority(DEFAULT_PRIORITY, f)
+}
+
+/// 注册一个带优先级的清理回调函数，优先级高的回调先执行
+pub fn register_cleanup_with_priority<F>(priority: u8, f: F) -> CleanupHandle
 where
     F: Fn() + Send + 'static,
 {
@@ -69,9 +110,26 @@ where
         Err(poisoned) => poisoned.into_inner(),
     };
 
-    guard.push(Some(Box::new(f)));
+    guard.push(Some((priority, Box::new(f))));
 
-    guard.len() - 1
+    CleanupHandle {
+        id: guard.len() - 1,
+    }
+}
+
+/// 注销清理回调（仅将对应位置置空，其他回调的 ID 保持不变）
+pub fn unregister_cleanup(id: usize) {
+    let Some(m) = CALLBACKS.get() else {
+        return;
+    };
+    let mut guard = match m.lock() {
+        Ok(g) => g,
+        Err(e) => e.into_inner(),
+    };
+
+    if let Some(slot) = guard.get_mut(id) {
+        *slot = None;
+    }
 }
 
 /// 执行所有注册的清理回调函数
@@ -83,28 +141,52 @@ pub fn run_shutdown() {
     metrics::report_event("Shutdown", None);
 
     let to = SHUTDOWN_TIMEOUT;
-    let callbacks: Vec<CleanupCallback> = if let Some(m) = CALLBACKS.get() {
+    let mut callbacks: Vec<PrioritizedCallback> = if let Some(m) = CALLBACKS.get() {
         let mut guard = match m.lock() {
             Ok(g) => g,
             Err(e) => e.into_inner(),
         };
-        take(&mut *guard).into_iter().flatten().collect()
+        // 保留空位，使此后注销旧 ID 不会影响新注册的回调
+        guard.iter_mut().filter_map(Option::take).collect()
     } else {
         Vec::new()
     };
 
-    if callbacks.is_empty() {
-        let _ = metrics::shutdown(Some(to));
-        return;
+    // 按优先级从高到低分组执行，同组内并发，组间等待完成
+    callbacks.sort_by_key(|(priority, _)| Reverse(*priority));
+
+    let start = Instant::now();
+    let deadline = start + to;
+
+    let mut callbacks = callbacks.into_iter().peekable();
+    while let Some((priority, first)) = callbacks.next() {
+        let mut group = vec![first];
+        while let Some((_, cb)) = callbacks.next_if(|(p, _)| *p == priority) {
+            group.push(cb);
+        }
+
+        if !run_callback_group(group, deadline) {
+            break;
+        }
     }
 
+    let elapsed = start.elapsed();
+    let remaining = if elapsed >= to {
+        Duration::from_secs(0)
+    } else {
+        to - elapsed
+    };
+
+    let _ = metrics::shutdown(Some(remaining));
+}
+
+/// 并发执行一组回调并等待其完成，超过截止时间返回 false
+fn run_callback_group(group: Vec<CleanupCallback>, deadline: Instant) -> bool {
     let (tx, rx) = channel::<usize>();
 
-    let total = callbacks.len();
-    let start = Instant::now();
-    let deadline = start + to;
+    let total = group.len();
 
-    for (i, cb) in callbacks.into_iter().enumerate() {
+    for (i, cb) in group.into_iter().enumerate() {
         let tx = tx.clone();
         spawn(move || {
             let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(cb));
@@ -120,7 +202,7 @@ pub fn run_shutdown() {
     while completed < total {
         let now = Instant::now();
         if now >= deadline {
-            break;
+            return false;
         }
 
         let remaining = deadline - now;
@@ -132,7 +214,7 @@ pub fn run_shutdown() {
                 }
             }
             Err(RecvTimeoutError::Timeout) => {
-                break;
+                return false;
             }
             Err(_) => {
                 break;
@@ -140,12 +222,55 @@ pub fn run_shutdown() {
         }
     }
 
-    let elapsed = start.elapsed();
-    let remaining = if elapsed >= to {
-        Duration::from_secs(0)
-    } else {
-        to - elapsed
-    };
+    true
+}
 
-    let _ = metrics::shutdown(Some(remaining));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::ScratchDir;
+
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    /// 设置该环境变量时，测试在子进程中注册回调并执行退出清理
+    const CHILD_ENV: &str = "META_MYSTIA_SHUTDOWN_TEST_DIR";
+
+    #[test]
+    fn dropped_handle_is_not_invoked_on_shutdown() {
+        if let Some(dir) = std::env::var_os(CHILD_ENV).map(PathBuf::from) {
+            let dropped_marker = dir.join("dropped");
+            let dropped = register_cleanup(move || {
+                let _ = std::fs::write(&dropped_marker, b"ran");
+            });
+            drop(dropped);
+
+            let kept_marker = dir.join("kept");
+            let _kept = register_cleanup(move || {
+                let _ = std::fs::write(&kept_marker, b"ran");
+            });
+
+            run_shutdown();
+            return;
+        }
+
+        let dir = ScratchDir::new("shutdown");
+        let output = Command::new(std::env::current_exe().unwrap())
+            .args([
+                "--exact",
+                "shutdown::tests::dropped_handle_is_not_invoked_on_shutdown",
+                "--test-threads=1",
+            ])
+            .env(CHILD_ENV, dir.path())
+            .output()
+            .unwrap();
+
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(dir.path().join("kept").exists(), "保留的回调未执行");
+        assert!(!dir.path().join("dropped").exists(), "已注销的回调仍被执行");
+    }
 }
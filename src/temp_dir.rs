@@ -1,11 +1,26 @@
+use crate::env_check::is_process_alive;
+use crate::error::{ManagerError, Result};
 use crate::metrics::report_event;
-use crate::shutdown::register_cleanup;
+use crate::prefs::Preferences;
+use crate::shutdown::{CleanupHandle, PRIORITY_TEMP_DIR, register_cleanup_with_priority};
 
-use std::path::{Path, PathBuf};
+use std::collections::hash_map::RandomState;
+use std::fs::File;
+use std::hash::{BuildHasher, Hasher};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
+
+const TEMP_DIR_NAME: &str = ".meta-mystia-tmp";
+const TEMP_ENV_VAR: &str = "META_MYSTIA_TEMP";
+
+static TEMP_BASE_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
 
 type RefCounter = Arc<Mutex<usize>>;
-type PathRegistry = Vec<(PathBuf, RefCounter)>;
+/// 已注册的目录、引用计数与对应的退出清理回调（移除条目时注销回调）
+type PathRegistry = Vec<(PathBuf, RefCounter, CleanupHandle)>;
 
 /// 在 Guard 被 drop 时删除目录
 pub struct DirGuard {
@@ -23,7 +38,7 @@ impl DirGuard {
             Err(e) => e.into_inner(),
         };
 
-        if let Some((_, counter)) = guard.iter().find(|(p, _)| p == &path) {
+        if let Some((_, counter, _)) = guard.iter().find(|(p, _, _)| p == &path) {
             let counter = counter.clone();
             if let Ok(mut count) = counter.lock() {
                 *count += 1;
@@ -34,13 +49,14 @@ impl DirGuard {
         let counter = Arc::new(Mutex::new(1));
         let path_clone = path.clone();
 
-        register_cleanup(move || {
+        let handle = register_cleanup_with_priority(PRIORITY_TEMP_DIR, move || {
             if path_clone.exists() {
                 let _ = std::fs::remove_dir_all(&path_clone);
             }
+            remove_parent_if_empty(&path_clone);
         });
 
-        guard.push((path.clone(), counter.clone()));
+        guard.push((path.clone(), counter.clone(), handle));
 
         Self { path, counter }
     }
@@ -56,35 +72,209 @@ impl Drop for DirGuard {
             Err(_) => true,
         };
 
-        if should_delete && self.path.exists() {
-            let _ = std::fs::remove_dir_all(&self.path);
+        if should_delete {
+            if self.path.exists() {
+                let _ = std::fs::remove_dir_all(&self.path);
+                remove_parent_if_empty(&self.path);
+            }
+            // 目录已删除，同时注销退出时的清理回调
             if let Some(m) = REGISTERED_PATHS.get()
                 && let Ok(mut guard) = m.lock()
             {
-                guard.retain(|(p, _)| p != &self.path);
+                guard.retain(|(p, _, _)| p != &self.path);
             }
         }
     }
 }
 
-pub fn create_temp_dir_with_guard(base: &Path) -> std::io::Result<(PathBuf, DirGuard)> {
-    let temp_dir = base.join(".meta-mystia-tmp");
+/// 配置临时目录所在位置（命令行参数优先，其次为环境变量 META_MYSTIA_TEMP），并验证其可用
+pub fn configure_temp_base(cli_path: Option<PathBuf>) -> Result<()> {
+    let Some(path) = cli_path.or_else(|| {
+        std::env::var_os(TEMP_ENV_VAR)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    }) else {
+        return Ok(());
+    };
 
-    if let Some(m) = REGISTERED_PATHS.get()
-        && let Ok(guard) = m.lock()
-        && guard.iter().any(|(p, _)| p == &temp_dir)
-    {
-        return Ok((temp_dir.clone(), DirGuard::new(temp_dir)));
+    if !path.exists() {
+        return Err(ManagerError::Other(format!(
+            "临时目录 {} 不存在",
+            path.display()
+        )));
+    }
+    if !path.is_dir() {
+        return Err(ManagerError::Other(format!(
+            "临时目录 {} 不是文件夹",
+            path.display()
+        )));
     }
 
-    if temp_dir.exists()
-        && let Err(e) = std::fs::remove_dir_all(&temp_dir)
-    {
+    let probe = path.join(format!(".meta-mystia-write-test-{}", std::process::id()));
+    if let Err(e) = std::fs::write(&probe, b"") {
         report_event(
-            "TempDir.CleanupFailed",
-            Some(&format!("{};err={}", temp_dir.display(), e)),
+            "TempDir.Override.NotWritable",
+            Some(&format!("{};err={}", path.display(), e)),
         );
+        return Err(ManagerError::PermissionDenied(format!(
+            "临时目录 {} 不可写：{}",
+            path.display(),
+            e
+        )));
+    }
+    let _ = std::fs::remove_file(&probe);
+
+    report_event("TempDir.Override", Some(&path.display().to_string()));
+    let _ = TEMP_BASE_OVERRIDE.set(path);
+
+    Ok(())
+}
+
+/// 可能残留临时目录的位置：命令行或环境变量指定的位置，以及偏好设置中记录的游戏目录
+pub fn known_temp_dirs() -> Vec<PathBuf> {
+    let env_base = std::env::var_os(TEMP_ENV_VAR)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from);
+
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for base in TEMP_BASE_OVERRIDE
+        .get()
+        .cloned()
+        .into_iter()
+        .chain(env_base)
+        .chain(Preferences::load().game_path)
+    {
+        let dir = base.join(TEMP_DIR_NAME);
+        if dir.is_dir() && !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+
+    dirs
+}
+
+/// 写入速度探测文件大小
+const SPEED_PROBE_SIZE: usize = 1024 * 1024;
+
+/// 命令行指定跳过慢速存储检测
+static SKIP_SLOW_STORAGE_CHECK: AtomicBool = AtomicBool::new(false);
+
+/// 设置是否跳过慢速存储检测
+pub fn configure_slow_storage_check(skip: bool) {
+    SKIP_SLOW_STORAGE_CHECK.store(skip, Ordering::SeqCst);
+}
+
+/// 是否需要进行慢速存储检测（命令行参数或偏好设置均可关闭）
+pub fn slow_storage_check_enabled() -> bool {
+    !SKIP_SLOW_STORAGE_CHECK.load(Ordering::SeqCst) && !Preferences::load().skip_slow_storage_check
+}
+
+/// 写入 1 MB 测试文件并测量写入速度（MB/s），探测文件写入后立即删除
+pub fn probe_write_speed(dir: &Path) -> Option<f64> {
+    let probe = dir.join(format!(".meta-mystia-speed-test-{}", std::process::id()));
+    let data = vec![0u8; SPEED_PROBE_SIZE];
+
+    let start = Instant::now();
+    let result = (|| -> std::io::Result<()> {
+        let mut file = File::create(&probe)?;
+        file.write_all(&data)?;
+        file.sync_all()
+    })();
+    let elapsed = start.elapsed().as_secs_f64();
+    let _ = std::fs::remove_file(&probe);
+
+    result.ok()?;
+    if elapsed <= 0.0 {
+        return None;
+    }
+
+    Some(SPEED_PROBE_SIZE as f64 / (1024.0 * 1024.0) / elapsed)
+}
+
+fn volume_of(path: &Path) -> Option<String> {
+    let path = path.canonicalize().ok()?;
+    match path.components().next()? {
+        Component::Prefix(prefix) => Some(prefix.as_os_str().to_string_lossy().to_lowercase()),
+        _ => None,
+    }
+}
+
+/// 判断两个路径是否位于不同卷（不同卷之间只能复制，无法原子重命名）
+pub fn is_cross_volume(a: &Path, b: &Path) -> bool {
+    match (volume_of(a), volume_of(b)) {
+        (Some(va), Some(vb)) => va != vb,
+        _ => false,
+    }
+}
+
+/// 父目录为空时将其删除（非空时 remove_dir 会失败，直接忽略）
+fn remove_parent_if_empty(path: &Path) {
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::remove_dir(parent);
+    }
+}
+
+/// 生成本次运行专用的子目录名：`<pid>-<random>`
+fn unique_subdir_name() -> String {
+    let random = RandomState::new().build_hasher().finish();
+    format!("{}-{:08x}", std::process::id(), random as u32)
+}
+
+/// 从子目录名中解析创建者 PID
+fn parse_owner_pid(name: &str) -> Option<u32> {
+    name.split_once('-').and_then(|(pid, _)| pid.parse().ok())
+}
+
+/// 清理已退出进程遗留的子目录，仍在运行的进程所属的子目录保持不动
+fn cleanup_stale_subdirs(parent: &Path) {
+    let Ok(entries) = std::fs::read_dir(parent) else {
+        return;
+    };
+
+    let current_pid = std::process::id();
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+
+        let stale = match parse_owner_pid(&name) {
+            Some(pid) if path.is_dir() => {
+                // 无法获取进程列表时保守处理，不删除
+                pid != current_pid && matches!(is_process_alive(pid), Ok(false))
+            }
+            // 旧版本直接写入父目录的遗留文件
+            _ => true,
+        };
+        if !stale {
+            continue;
+        }
+
+        let res = if path.is_dir() {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+        if let Err(e) = res {
+            report_event(
+                "TempDir.CleanupFailed",
+                Some(&format!("{};err={}", path.display(), e)),
+            );
+        }
     }
+}
+
+pub fn create_temp_dir_with_guard(base: &Path) -> std::io::Result<(PathBuf, DirGuard)> {
+    let base = TEMP_BASE_OVERRIDE
+        .get()
+        .map(PathBuf::as_path)
+        .unwrap_or(base);
+    let parent = base.join(TEMP_DIR_NAME);
+
+    if parent.is_dir() {
+        cleanup_stale_subdirs(&parent);
+    }
+
+    let temp_dir = parent.join(unique_subdir_name());
 
     if let Err(e) = std::fs::create_dir_all(&temp_dir) {
         report_event(
@@ -0,0 +1,2012 @@
+#![allow(dead_code)]
+
+use crate::config::{BEPINEX_DEFAULT_LOG_LEVELS, OperationMode, UninstallMode};
+use crate::downloader::{DownloadReport, DownloadSizeEstimate, DownloadSource, PrefetchResult};
+use crate::env_check::{GameProcess, PathSource};
+use crate::error::{Result, SourceFailure};
+use crate::file_ops::{DeletionResult, DeletionStatus};
+use crate::model::VersionInfo;
+use crate::progress::ProgressSnapshot;
+use crate::ui::{
+    InstallPlan, OperationSummary, OverwriteSelection, PendingUpgrade, SummaryAction, Ui,
+    WaitOutcome,
+};
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// 一次 UI 调用及其参数
+#[derive(Debug, Clone)]
+pub enum UiCall {
+    DisplayWelcome(bool),
+    AskTelemetryConsent,
+    DisplayVersion(Option<String>),
+    DisplayUpdateChannelInfo(String),
+    WarnOldManagerVersion(String, String, usize),
+    DisplayGameRunningWarning(Vec<GameProcess>),
+    DisplayAvailableUpdates(bool, bool),
+    DisplayUpgradeCheckFailed(String),
+    DisplayProgressCancelled(ProgressSnapshot),
+    SelectOperationMode,
+    BlankLine,
+    WaitForKey,
+    Message(String),
+    Warn(String),
+    Error(String),
+    DisplayOperationSummary(OperationSummary),
+    DisplayControlledFolderAccessBlocked(PathBuf),
+    PathDisplaySteamFound(u32, Option<String>, PathBuf),
+    PathDisplaySteamStale(u32, Option<String>, PathBuf),
+    PathConfirmUseSteamFound,
+    SelectGamePathFromCandidates(Vec<(PathBuf, PathSource)>),
+    PathAskManualEntry,
+    PathManualEntryNotFound(PathBuf),
+    WarnConflictingGameExe(Vec<PathBuf>),
+    WarnCloudSyncDirectory(String),
+    InstallDisplayStep(usize, String),
+    InstallDisplayVersionInfo(VersionInfo),
+    InstallDisplayEstimatedDownloadSize(DownloadSizeEstimate),
+    InstallDisplayEstimatedDuration(u64),
+    InstallDisplayDownloadSources(PrefetchResult),
+    InstallWarnSlowStorage(u64),
+    InstallWarnExisting(bool, bool, bool, Vec<PathBuf>),
+    InstallConfirmOverwrite,
+    InstallSelectOverwrite(bool, bool, bool),
+    InstallAskInstallResourceex,
+    InstallAskShowBepinexConsole,
+    InstallAskConfigureAdvancedOptions,
+    InstallSelectBepinexLogLevels,
+    InstallAskBackupBeforeInstall,
+    InstallDisplayPlan(InstallPlan),
+    InstallDownloadsCompleted,
+    InstallStartCleanup,
+    InstallDisplayCleanupDetail(Vec<DeletionResult>),
+    InstallCleanupResult(usize, usize),
+    InstallFinished(bool, bool),
+    DisplayFirstTimeBepinexInfo,
+    WarnBepinexIl2cppDownload(String),
+    InstallWarnLoaderQuarantined(PathBuf, Option<String>),
+    ResourceexDisplayMisplaced(Vec<PathBuf>),
+    ResourceexConfirmMigrate,
+    ResourceexMigrateFailed(PathBuf, String),
+    UpgradeWarnUnparseVersion(String),
+    UpgradeFoundDuplicate(PathBuf),
+    UpgradeBackupFailed(String),
+    UpgradeDeleted(PathBuf),
+    UpgradeDeleteFailed(PathBuf, String),
+    UpgradeCheckingInstalledVersion,
+    UpgradeDetectedResourceex,
+    UpgradeDisplayCurrentAndLatestDll(String, String),
+    UpgradeDisplayCurrentAndLatestResourceex(String, String),
+    UpgradeNoUpdateNeeded,
+    UpgradeDetectedNewDll(String, String),
+    UpgradeDllAlreadyLatest,
+    UpgradeResourceexNeedsUpgrade,
+    ConfirmBatchUpgrade(Vec<PendingUpgrade>),
+    UpgradeDownloadingDll,
+    UpgradeDownloadingResourceex,
+    UpgradeInstallingDll,
+    UpgradeInstallingResourceex,
+    UpgradeInstallSuccess(PathBuf),
+    UpgradeCleanupStart,
+    UpgradeDone,
+    AskLaunchGame,
+    LaunchGameFailed(String),
+    AskOpenDocs,
+    OpenDocsFailed(String),
+    ConfigureAskBepinexConsole(bool),
+    ConfigureBepinexConsoleChanged(bool, bool),
+    UninstallSelectMode,
+    UninstallNoFilesFound,
+    UninstallDisplayTargetFiles(Vec<PathBuf>),
+    UninstallConfirmDeletion,
+    UninstallAskCreateUndoArchive(u64),
+    UninstallUndoArchiveInsufficientSpace(u64, u64),
+    UninstallUndoArchiveCreated(PathBuf),
+    RestoreUndoFinished(PathBuf, usize),
+    UninstallFilesInUseWarning,
+    UninstallWaitBeforeRetry(u64, usize, usize),
+    UninstallWaitOrSkip(u64),
+    UninstallAskElevatePermission,
+    UninstallRestartingElevated,
+    UninstallAskRetryFailures,
+    UninstallRetryingFailedItems,
+    UninstallDisplayDiskSpaceFreed(u64),
+    UninstallConfirmTakeOwnership(Vec<PathBuf>),
+    UninstallDisplayAclFixCommands(Vec<String>),
+    UninstallDisplayFailureBreakdown(usize, usize, usize),
+    DeletionStart(usize),
+    DeletionDisplayProgress(usize, usize, String),
+    DeletionDisplaySuccess(String),
+    DeletionDisplayFailure(String, String),
+    DeletionDisplaySkipped(String),
+    DeletionDisplaySummary(usize, usize, usize),
+    TempDirWarnCrossVolume(PathBuf, PathBuf),
+    DownloadStart(String, Option<u64>),
+    DownloadUpdate(usize, u64),
+    DownloadFinish(usize, String),
+    DownloadVersionInfoStart,
+    DownloadVersionInfoFailed(String),
+    DownloadVersionInfoSuccess,
+    DownloadVersionInfoParseFailed(String, String),
+    DownloadShareCodeStart,
+    DownloadShareCodeFailed(String),
+    DownloadShareCodeSuccess,
+    DownloadAttemptGithubDll,
+    DownloadFoundGithubAsset(String),
+    DownloadGithubDllNotFound,
+    DownloadGithubAuthRequired,
+    DownloadDisplayGithubReleaseNotes(String, String, String),
+    DownloadAskContinueAfterReleaseNotes,
+    DownloadSwitchToFallback(String),
+    DownloadTryFallbackMetamystia,
+    DownloadBepinexAttemptPrimary,
+    DownloadBepinexPrimaryFailed(String),
+    DownloadAllSourcesFailed(Vec<SourceFailure>),
+    DisplayDownloadSummary(Vec<DownloadReport>),
+    NetworkRetrying(String, u64, usize, usize, String),
+    NetworkRateLimited(u64),
+    DisplayNetworkDiagnostics(String, String),
+    ManagerAskSelfUpdate(String, String),
+    DisplaySelfUpdateDownloaded(PathBuf, String),
+    ManagerUpdateStarting,
+    ManagerUpdateFailed(String),
+    ManagerPromptManualUpdate(String),
+    SelectVersionAskSelect(String),
+    SelectVersionFromList(String, Vec<String>),
+    SelectVersionNotAvailable(String, String, Vec<String>),
+}
+
+/// UI 调用类型（不含参数）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UiCallKind {
+    DisplayWelcome,
+    AskTelemetryConsent,
+    DisplayVersion,
+    DisplayUpdateChannelInfo,
+    WarnOldManagerVersion,
+    DisplayGameRunningWarning,
+    DisplayAvailableUpdates,
+    DisplayUpgradeCheckFailed,
+    DisplayProgressCancelled,
+    SelectOperationMode,
+    BlankLine,
+    WaitForKey,
+    Message,
+    Warn,
+    Error,
+    DisplayOperationSummary,
+    DisplayControlledFolderAccessBlocked,
+    PathDisplaySteamFound,
+    PathDisplaySteamStale,
+    PathConfirmUseSteamFound,
+    SelectGamePathFromCandidates,
+    PathAskManualEntry,
+    PathManualEntryNotFound,
+    WarnConflictingGameExe,
+    WarnCloudSyncDirectory,
+    InstallDisplayStep,
+    InstallDisplayVersionInfo,
+    InstallDisplayEstimatedDownloadSize,
+    InstallDisplayEstimatedDuration,
+    InstallDisplayDownloadSources,
+    InstallWarnSlowStorage,
+    InstallWarnExisting,
+    InstallConfirmOverwrite,
+    InstallSelectOverwrite,
+    InstallAskInstallResourceex,
+    InstallAskShowBepinexConsole,
+    InstallAskConfigureAdvancedOptions,
+    InstallSelectBepinexLogLevels,
+    InstallAskBackupBeforeInstall,
+    InstallDisplayPlan,
+    InstallDownloadsCompleted,
+    InstallStartCleanup,
+    InstallDisplayCleanupDetail,
+    InstallCleanupResult,
+    InstallFinished,
+    DisplayFirstTimeBepinexInfo,
+    WarnBepinexIl2cppDownload,
+    InstallWarnLoaderQuarantined,
+    ResourceexDisplayMisplaced,
+    ResourceexConfirmMigrate,
+    ResourceexMigrateFailed,
+    UpgradeWarnUnparseVersion,
+    UpgradeFoundDuplicate,
+    UpgradeBackupFailed,
+    UpgradeDeleted,
+    UpgradeDeleteFailed,
+    UpgradeCheckingInstalledVersion,
+    UpgradeDetectedResourceex,
+    UpgradeDisplayCurrentAndLatestDll,
+    UpgradeDisplayCurrentAndLatestResourceex,
+    UpgradeNoUpdateNeeded,
+    UpgradeDetectedNewDll,
+    UpgradeDllAlreadyLatest,
+    UpgradeResourceexNeedsUpgrade,
+    ConfirmBatchUpgrade,
+    UpgradeDownloadingDll,
+    UpgradeDownloadingResourceex,
+    UpgradeInstallingDll,
+    UpgradeInstallingResourceex,
+    UpgradeInstallSuccess,
+    UpgradeCleanupStart,
+    UpgradeDone,
+    AskLaunchGame,
+    LaunchGameFailed,
+    AskOpenDocs,
+    OpenDocsFailed,
+    ConfigureAskBepinexConsole,
+    ConfigureBepinexConsoleChanged,
+    UninstallSelectMode,
+    UninstallNoFilesFound,
+    UninstallDisplayTargetFiles,
+    UninstallConfirmDeletion,
+    UninstallAskCreateUndoArchive,
+    UninstallUndoArchiveInsufficientSpace,
+    UninstallUndoArchiveCreated,
+    RestoreUndoFinished,
+    UninstallFilesInUseWarning,
+    UninstallWaitBeforeRetry,
+    UninstallWaitOrSkip,
+    UninstallAskElevatePermission,
+    UninstallRestartingElevated,
+    UninstallAskRetryFailures,
+    UninstallRetryingFailedItems,
+    UninstallDisplayDiskSpaceFreed,
+    UninstallConfirmTakeOwnership,
+    UninstallDisplayAclFixCommands,
+    UninstallDisplayFailureBreakdown,
+    DeletionStart,
+    DeletionDisplayProgress,
+    DeletionDisplaySuccess,
+    DeletionDisplayFailure,
+    DeletionDisplaySkipped,
+    DeletionDisplaySummary,
+    TempDirWarnCrossVolume,
+    DownloadStart,
+    DownloadUpdate,
+    DownloadFinish,
+    DownloadVersionInfoStart,
+    DownloadVersionInfoFailed,
+    DownloadVersionInfoSuccess,
+    DownloadVersionInfoParseFailed,
+    DownloadShareCodeStart,
+    DownloadShareCodeFailed,
+    DownloadShareCodeSuccess,
+    DownloadAttemptGithubDll,
+    DownloadFoundGithubAsset,
+    DownloadGithubDllNotFound,
+    DownloadGithubAuthRequired,
+    DownloadDisplayGithubReleaseNotes,
+    DownloadAskContinueAfterReleaseNotes,
+    DownloadSwitchToFallback,
+    DownloadTryFallbackMetamystia,
+    DownloadBepinexAttemptPrimary,
+    DownloadBepinexPrimaryFailed,
+    DownloadAllSourcesFailed,
+    DisplayDownloadSummary,
+    NetworkRetrying,
+    NetworkRateLimited,
+    DisplayNetworkDiagnostics,
+    ManagerAskSelfUpdate,
+    DisplaySelfUpdateDownloaded,
+    ManagerUpdateStarting,
+    ManagerUpdateFailed,
+    ManagerPromptManualUpdate,
+    SelectVersionAskSelect,
+    SelectVersionFromList,
+    SelectVersionNotAvailable,
+}
+
+impl UiCall {
+    pub fn kind(&self) -> UiCallKind {
+        match self {
+            UiCall::DisplayWelcome(..) => UiCallKind::DisplayWelcome,
+            UiCall::AskTelemetryConsent => UiCallKind::AskTelemetryConsent,
+            UiCall::DisplayVersion(..) => UiCallKind::DisplayVersion,
+            UiCall::DisplayUpdateChannelInfo(..) => UiCallKind::DisplayUpdateChannelInfo,
+            UiCall::WarnOldManagerVersion(..) => UiCallKind::WarnOldManagerVersion,
+            UiCall::DisplayGameRunningWarning(..) => UiCallKind::DisplayGameRunningWarning,
+            UiCall::DisplayAvailableUpdates(..) => UiCallKind::DisplayAvailableUpdates,
+            UiCall::DisplayUpgradeCheckFailed(..) => UiCallKind::DisplayUpgradeCheckFailed,
+            UiCall::DisplayProgressCancelled(..) => UiCallKind::DisplayProgressCancelled,
+            UiCall::SelectOperationMode => UiCallKind::SelectOperationMode,
+            UiCall::BlankLine => UiCallKind::BlankLine,
+            UiCall::WaitForKey => UiCallKind::WaitForKey,
+            UiCall::Message(..) => UiCallKind::Message,
+            UiCall::Warn(..) => UiCallKind::Warn,
+            UiCall::Error(..) => UiCallKind::Error,
+            UiCall::DisplayOperationSummary(..) => UiCallKind::DisplayOperationSummary,
+            UiCall::DisplayControlledFolderAccessBlocked(..) => {
+                UiCallKind::DisplayControlledFolderAccessBlocked
+            }
+            UiCall::PathDisplaySteamFound(..) => UiCallKind::PathDisplaySteamFound,
+            UiCall::PathDisplaySteamStale(..) => UiCallKind::PathDisplaySteamStale,
+            UiCall::PathConfirmUseSteamFound => UiCallKind::PathConfirmUseSteamFound,
+            UiCall::SelectGamePathFromCandidates(..) => UiCallKind::SelectGamePathFromCandidates,
+            UiCall::PathAskManualEntry => UiCallKind::PathAskManualEntry,
+            UiCall::PathManualEntryNotFound(..) => UiCallKind::PathManualEntryNotFound,
+            UiCall::WarnConflictingGameExe(..) => UiCallKind::WarnConflictingGameExe,
+            UiCall::WarnCloudSyncDirectory(..) => UiCallKind::WarnCloudSyncDirectory,
+            UiCall::InstallDisplayStep(..) => UiCallKind::InstallDisplayStep,
+            UiCall::InstallDisplayVersionInfo(..) => UiCallKind::InstallDisplayVersionInfo,
+            UiCall::InstallDisplayEstimatedDownloadSize(..) => {
+                UiCallKind::InstallDisplayEstimatedDownloadSize
+            }
+            UiCall::InstallDisplayEstimatedDuration(..) => {
+                UiCallKind::InstallDisplayEstimatedDuration
+            }
+            UiCall::InstallDisplayDownloadSources(..) => UiCallKind::InstallDisplayDownloadSources,
+            UiCall::InstallWarnSlowStorage(..) => UiCallKind::InstallWarnSlowStorage,
+            UiCall::InstallWarnExisting(..) => UiCallKind::InstallWarnExisting,
+            UiCall::InstallConfirmOverwrite => UiCallKind::InstallConfirmOverwrite,
+            UiCall::InstallSelectOverwrite(..) => UiCallKind::InstallSelectOverwrite,
+            UiCall::InstallAskInstallResourceex => UiCallKind::InstallAskInstallResourceex,
+            UiCall::InstallAskShowBepinexConsole => UiCallKind::InstallAskShowBepinexConsole,
+            UiCall::InstallAskConfigureAdvancedOptions => {
+                UiCallKind::InstallAskConfigureAdvancedOptions
+            }
+            UiCall::InstallSelectBepinexLogLevels => UiCallKind::InstallSelectBepinexLogLevels,
+            UiCall::InstallAskBackupBeforeInstall => UiCallKind::InstallAskBackupBeforeInstall,
+            UiCall::InstallDisplayPlan(..) => UiCallKind::InstallDisplayPlan,
+            UiCall::InstallDownloadsCompleted => UiCallKind::InstallDownloadsCompleted,
+            UiCall::InstallStartCleanup => UiCallKind::InstallStartCleanup,
+            UiCall::InstallDisplayCleanupDetail(..) => UiCallKind::InstallDisplayCleanupDetail,
+            UiCall::InstallCleanupResult(..) => UiCallKind::InstallCleanupResult,
+            UiCall::InstallFinished(..) => UiCallKind::InstallFinished,
+            UiCall::DisplayFirstTimeBepinexInfo => UiCallKind::DisplayFirstTimeBepinexInfo,
+            UiCall::WarnBepinexIl2cppDownload(..) => UiCallKind::WarnBepinexIl2cppDownload,
+            UiCall::InstallWarnLoaderQuarantined(..) => UiCallKind::InstallWarnLoaderQuarantined,
+            UiCall::ResourceexDisplayMisplaced(..) => UiCallKind::ResourceexDisplayMisplaced,
+            UiCall::ResourceexConfirmMigrate => UiCallKind::ResourceexConfirmMigrate,
+            UiCall::ResourceexMigrateFailed(..) => UiCallKind::ResourceexMigrateFailed,
+            UiCall::UpgradeWarnUnparseVersion(..) => UiCallKind::UpgradeWarnUnparseVersion,
+            UiCall::UpgradeFoundDuplicate(..) => UiCallKind::UpgradeFoundDuplicate,
+            UiCall::UpgradeBackupFailed(..) => UiCallKind::UpgradeBackupFailed,
+            UiCall::UpgradeDeleted(..) => UiCallKind::UpgradeDeleted,
+            UiCall::UpgradeDeleteFailed(..) => UiCallKind::UpgradeDeleteFailed,
+            UiCall::UpgradeCheckingInstalledVersion => UiCallKind::UpgradeCheckingInstalledVersion,
+            UiCall::UpgradeDetectedResourceex => UiCallKind::UpgradeDetectedResourceex,
+            UiCall::UpgradeDisplayCurrentAndLatestDll(..) => {
+                UiCallKind::UpgradeDisplayCurrentAndLatestDll
+            }
+            UiCall::UpgradeDisplayCurrentAndLatestResourceex(..) => {
+                UiCallKind::UpgradeDisplayCurrentAndLatestResourceex
+            }
+            UiCall::UpgradeNoUpdateNeeded => UiCallKind::UpgradeNoUpdateNeeded,
+            UiCall::UpgradeDetectedNewDll(..) => UiCallKind::UpgradeDetectedNewDll,
+            UiCall::UpgradeDllAlreadyLatest => UiCallKind::UpgradeDllAlreadyLatest,
+            UiCall::UpgradeResourceexNeedsUpgrade => UiCallKind::UpgradeResourceexNeedsUpgrade,
+            UiCall::ConfirmBatchUpgrade(..) => UiCallKind::ConfirmBatchUpgrade,
+            UiCall::UpgradeDownloadingDll => UiCallKind::UpgradeDownloadingDll,
+            UiCall::UpgradeDownloadingResourceex => UiCallKind::UpgradeDownloadingResourceex,
+            UiCall::UpgradeInstallingDll => UiCallKind::UpgradeInstallingDll,
+            UiCall::UpgradeInstallingResourceex => UiCallKind::UpgradeInstallingResourceex,
+            UiCall::UpgradeInstallSuccess(..) => UiCallKind::UpgradeInstallSuccess,
+            UiCall::UpgradeCleanupStart => UiCallKind::UpgradeCleanupStart,
+            UiCall::UpgradeDone => UiCallKind::UpgradeDone,
+            UiCall::AskLaunchGame => UiCallKind::AskLaunchGame,
+            UiCall::LaunchGameFailed(..) => UiCallKind::LaunchGameFailed,
+            UiCall::AskOpenDocs => UiCallKind::AskOpenDocs,
+            UiCall::OpenDocsFailed(..) => UiCallKind::OpenDocsFailed,
+            UiCall::ConfigureAskBepinexConsole(..) => UiCallKind::ConfigureAskBepinexConsole,
+            UiCall::ConfigureBepinexConsoleChanged(..) => {
+                UiCallKind::ConfigureBepinexConsoleChanged
+            }
+            UiCall::UninstallSelectMode => UiCallKind::UninstallSelectMode,
+            UiCall::UninstallNoFilesFound => UiCallKind::UninstallNoFilesFound,
+            UiCall::UninstallDisplayTargetFiles(..) => UiCallKind::UninstallDisplayTargetFiles,
+            UiCall::UninstallConfirmDeletion => UiCallKind::UninstallConfirmDeletion,
+            UiCall::UninstallAskCreateUndoArchive(..) => UiCallKind::UninstallAskCreateUndoArchive,
+            UiCall::UninstallUndoArchiveInsufficientSpace(..) => {
+                UiCallKind::UninstallUndoArchiveInsufficientSpace
+            }
+            UiCall::UninstallUndoArchiveCreated(..) => UiCallKind::UninstallUndoArchiveCreated,
+            UiCall::RestoreUndoFinished(..) => UiCallKind::RestoreUndoFinished,
+            UiCall::UninstallFilesInUseWarning => UiCallKind::UninstallFilesInUseWarning,
+            UiCall::UninstallWaitBeforeRetry(..) => UiCallKind::UninstallWaitBeforeRetry,
+            UiCall::UninstallWaitOrSkip(..) => UiCallKind::UninstallWaitOrSkip,
+            UiCall::UninstallAskElevatePermission => UiCallKind::UninstallAskElevatePermission,
+            UiCall::UninstallRestartingElevated => UiCallKind::UninstallRestartingElevated,
+            UiCall::UninstallAskRetryFailures => UiCallKind::UninstallAskRetryFailures,
+            UiCall::UninstallRetryingFailedItems => UiCallKind::UninstallRetryingFailedItems,
+            UiCall::UninstallDisplayDiskSpaceFreed(..) => {
+                UiCallKind::UninstallDisplayDiskSpaceFreed
+            }
+            UiCall::UninstallConfirmTakeOwnership(..) => UiCallKind::UninstallConfirmTakeOwnership,
+            UiCall::UninstallDisplayAclFixCommands(..) => {
+                UiCallKind::UninstallDisplayAclFixCommands
+            }
+            UiCall::UninstallDisplayFailureBreakdown(..) => {
+                UiCallKind::UninstallDisplayFailureBreakdown
+            }
+            UiCall::DeletionStart(..) => UiCallKind::DeletionStart,
+            UiCall::DeletionDisplayProgress(..) => UiCallKind::DeletionDisplayProgress,
+            UiCall::DeletionDisplaySuccess(..) => UiCallKind::DeletionDisplaySuccess,
+            UiCall::DeletionDisplayFailure(..) => UiCallKind::DeletionDisplayFailure,
+            UiCall::DeletionDisplaySkipped(..) => UiCallKind::DeletionDisplaySkipped,
+            UiCall::DeletionDisplaySummary(..) => UiCallKind::DeletionDisplaySummary,
+            UiCall::TempDirWarnCrossVolume(..) => UiCallKind::TempDirWarnCrossVolume,
+            UiCall::DownloadStart(..) => UiCallKind::DownloadStart,
+            UiCall::DownloadUpdate(..) => UiCallKind::DownloadUpdate,
+            UiCall::DownloadFinish(..) => UiCallKind::DownloadFinish,
+            UiCall::DownloadVersionInfoStart => UiCallKind::DownloadVersionInfoStart,
+            UiCall::DownloadVersionInfoFailed(..) => UiCallKind::DownloadVersionInfoFailed,
+            UiCall::DownloadVersionInfoSuccess => UiCallKind::DownloadVersionInfoSuccess,
+            UiCall::DownloadVersionInfoParseFailed(..) => {
+                UiCallKind::DownloadVersionInfoParseFailed
+            }
+            UiCall::DownloadShareCodeStart => UiCallKind::DownloadShareCodeStart,
+            UiCall::DownloadShareCodeFailed(..) => UiCallKind::DownloadShareCodeFailed,
+            UiCall::DownloadShareCodeSuccess => UiCallKind::DownloadShareCodeSuccess,
+            UiCall::DownloadAttemptGithubDll => UiCallKind::DownloadAttemptGithubDll,
+            UiCall::DownloadFoundGithubAsset(..) => UiCallKind::DownloadFoundGithubAsset,
+            UiCall::DownloadGithubDllNotFound => UiCallKind::DownloadGithubDllNotFound,
+            UiCall::DownloadGithubAuthRequired => UiCallKind::DownloadGithubAuthRequired,
+            UiCall::DownloadDisplayGithubReleaseNotes(..) => {
+                UiCallKind::DownloadDisplayGithubReleaseNotes
+            }
+            UiCall::DownloadAskContinueAfterReleaseNotes => {
+                UiCallKind::DownloadAskContinueAfterReleaseNotes
+            }
+            UiCall::DownloadSwitchToFallback(..) => UiCallKind::DownloadSwitchToFallback,
+            UiCall::DownloadTryFallbackMetamystia => UiCallKind::DownloadTryFallbackMetamystia,
+            UiCall::DownloadBepinexAttemptPrimary => UiCallKind::DownloadBepinexAttemptPrimary,
+            UiCall::DownloadBepinexPrimaryFailed(..) => UiCallKind::DownloadBepinexPrimaryFailed,
+            UiCall::DownloadAllSourcesFailed(..) => UiCallKind::DownloadAllSourcesFailed,
+            UiCall::DisplayDownloadSummary(..) => UiCallKind::DisplayDownloadSummary,
+            UiCall::NetworkRetrying(..) => UiCallKind::NetworkRetrying,
+            UiCall::NetworkRateLimited(..) => UiCallKind::NetworkRateLimited,
+            UiCall::DisplayNetworkDiagnostics(..) => UiCallKind::DisplayNetworkDiagnostics,
+            UiCall::ManagerAskSelfUpdate(..) => UiCallKind::ManagerAskSelfUpdate,
+            UiCall::DisplaySelfUpdateDownloaded(..) => UiCallKind::DisplaySelfUpdateDownloaded,
+            UiCall::ManagerUpdateStarting => UiCallKind::ManagerUpdateStarting,
+            UiCall::ManagerUpdateFailed(..) => UiCallKind::ManagerUpdateFailed,
+            UiCall::ManagerPromptManualUpdate(..) => UiCallKind::ManagerPromptManualUpdate,
+            UiCall::SelectVersionAskSelect(..) => UiCallKind::SelectVersionAskSelect,
+            UiCall::SelectVersionFromList(..) => UiCallKind::SelectVersionFromList,
+            UiCall::SelectVersionNotAvailable(..) => UiCallKind::SelectVersionNotAvailable,
+        }
+    }
+}
+
+/// 记录所有调用的测试 UI
+pub struct TestUI {
+    calls: Mutex<Vec<UiCall>>,
+    confirm: bool,
+    next_download_id: AtomicUsize,
+}
+
+impl Default for TestUI {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestUI {
+    /// 创建测试 UI，所有确认类询问默认回答“是”
+    pub fn new() -> Self {
+        Self::with_confirm(true)
+    }
+
+    /// 创建测试 UI，并指定确认类询问的统一回答
+    pub fn with_confirm(confirm: bool) -> Self {
+        Self {
+            calls: Mutex::new(Vec::new()),
+            confirm,
+            next_download_id: AtomicUsize::new(0),
+        }
+    }
+
+    /// 获取已记录调用的副本
+    pub fn calls(&self) -> Vec<UiCall> {
+        self.calls.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 获取已记录调用的类型序列
+    pub fn kinds(&self) -> Vec<UiCallKind> {
+        self.calls().iter().map(UiCall::kind).collect()
+    }
+
+    /// 检查是否发生过指定类型的调用
+    pub fn assert_called(&self, kind: UiCallKind) -> bool {
+        self.calls().iter().any(|c| c.kind() == kind)
+    }
+
+    fn record(&self, call: UiCall) {
+        self.calls
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(call);
+    }
+}
+
+impl Ui for TestUI {
+    fn display_welcome(&self, is_first_run: bool) -> Result<()> {
+        self.record(UiCall::DisplayWelcome(is_first_run));
+        Ok(())
+    }
+
+    fn ask_telemetry_consent(&self) -> Result<bool> {
+        self.record(UiCall::AskTelemetryConsent);
+        Ok(self.confirm)
+    }
+
+    fn display_version(&self, manager_version: Option<&str>) -> Result<()> {
+        self.record(UiCall::DisplayVersion(manager_version.map(str::to_string)));
+        Ok(())
+    }
+
+    fn display_update_channel_info(&self, channel: &str) -> Result<()> {
+        self.record(UiCall::DisplayUpdateChannelInfo(channel.to_string()));
+        Ok(())
+    }
+
+    fn warn_old_manager_version(
+        &self,
+        current: &str,
+        latest: &str,
+        behind_versions: usize,
+    ) -> Result<()> {
+        self.record(UiCall::WarnOldManagerVersion(
+            current.to_string(),
+            latest.to_string(),
+            behind_versions,
+        ));
+        Ok(())
+    }
+
+    fn display_game_running_warning(&self, processes: &[GameProcess]) -> Result<()> {
+        self.record(UiCall::DisplayGameRunningWarning(processes.to_vec()));
+        Ok(())
+    }
+
+    fn display_available_updates(
+        &self,
+        dll_available: bool,
+        resourceex_available: bool,
+    ) -> Result<()> {
+        self.record(UiCall::DisplayAvailableUpdates(
+            dll_available,
+            resourceex_available,
+        ));
+        Ok(())
+    }
+
+    fn display_upgrade_check_failed(&self, err: &str) -> Result<()> {
+        self.record(UiCall::DisplayUpgradeCheckFailed(err.to_string()));
+        Ok(())
+    }
+
+    fn display_progress_cancelled(&self, snapshot: &ProgressSnapshot) -> Result<()> {
+        self.record(UiCall::DisplayProgressCancelled(snapshot.clone()));
+        Ok(())
+    }
+
+    fn select_operation_mode(&self) -> Result<OperationMode> {
+        self.record(UiCall::SelectOperationMode);
+        Ok(OperationMode::Install)
+    }
+
+    fn blank_line(&self) -> Result<()> {
+        self.record(UiCall::BlankLine);
+        Ok(())
+    }
+
+    fn wait_for_key(&self) -> Result<()> {
+        self.record(UiCall::WaitForKey);
+        Ok(())
+    }
+
+    fn message(&self, text: &str) -> Result<()> {
+        self.record(UiCall::Message(text.to_string()));
+        Ok(())
+    }
+
+    fn warn(&self, text: &str) -> Result<()> {
+        self.record(UiCall::Warn(text.to_string()));
+        Ok(())
+    }
+
+    fn error(&self, text: &str) -> Result<()> {
+        self.record(UiCall::Error(text.to_string()));
+        Ok(())
+    }
+
+    fn display_operation_summary(&self, summary: &OperationSummary) -> Result<()> {
+        self.record(UiCall::DisplayOperationSummary(summary.clone()));
+        Ok(())
+    }
+
+    fn display_controlled_folder_access_blocked(&self, app_path: &Path) -> Result<()> {
+        self.record(UiCall::DisplayControlledFolderAccessBlocked(
+            app_path.to_path_buf(),
+        ));
+        Ok(())
+    }
+
+    fn path_display_steam_found(&self, app_id: u32, name: Option<&str>, path: &Path) -> Result<()> {
+        self.record(UiCall::PathDisplaySteamFound(
+            app_id,
+            name.map(str::to_string),
+            path.to_path_buf(),
+        ));
+        Ok(())
+    }
+
+    fn path_display_steam_stale(&self, app_id: u32, name: Option<&str>, path: &Path) -> Result<()> {
+        self.record(UiCall::PathDisplaySteamStale(
+            app_id,
+            name.map(str::to_string),
+            path.to_path_buf(),
+        ));
+        Ok(())
+    }
+
+    fn path_confirm_use_steam_found(&self) -> Result<bool> {
+        self.record(UiCall::PathConfirmUseSteamFound);
+        Ok(self.confirm)
+    }
+
+    fn select_game_path_from_candidates(
+        &self,
+        candidates: &[(PathBuf, PathSource)],
+    ) -> Result<PathBuf> {
+        self.record(UiCall::SelectGamePathFromCandidates(candidates.to_vec()));
+        Ok(candidates[0].0.clone())
+    }
+
+    fn path_ask_manual_entry(&self) -> Result<Option<PathBuf>> {
+        self.record(UiCall::PathAskManualEntry);
+        Ok(None)
+    }
+
+    fn path_manual_entry_not_found(&self, input: &Path) -> Result<()> {
+        self.record(UiCall::PathManualEntryNotFound(input.to_path_buf()));
+        Ok(())
+    }
+
+    fn warn_conflicting_game_exe(&self, extras: &[PathBuf]) -> Result<bool> {
+        self.record(UiCall::WarnConflictingGameExe(extras.to_vec()));
+        Ok(self.confirm)
+    }
+
+    fn warn_cloud_sync_directory(&self, provider: &str) -> Result<()> {
+        self.record(UiCall::WarnCloudSyncDirectory(provider.to_string()));
+        Ok(())
+    }
+
+    fn install_display_step(&self, step: usize, description: &str) -> Result<()> {
+        self.record(UiCall::InstallDisplayStep(step, description.to_string()));
+        Ok(())
+    }
+
+    fn install_display_version_info(&self, version_info: &VersionInfo) -> Result<()> {
+        self.record(UiCall::InstallDisplayVersionInfo(version_info.clone()));
+        Ok(())
+    }
+
+    fn install_display_estimated_download_size(
+        &self,
+        estimate: &DownloadSizeEstimate,
+    ) -> Result<()> {
+        self.record(UiCall::InstallDisplayEstimatedDownloadSize(*estimate));
+        Ok(())
+    }
+
+    fn install_display_estimated_duration(&self, estimated_secs: u64) -> Result<()> {
+        self.record(UiCall::InstallDisplayEstimatedDuration(estimated_secs));
+        Ok(())
+    }
+
+    fn install_display_download_sources(&self, result: &PrefetchResult) -> Result<()> {
+        self.record(UiCall::InstallDisplayDownloadSources(result.clone()));
+        Ok(())
+    }
+
+    fn install_warn_slow_storage(&self, estimated_extra_mins: u64) -> Result<()> {
+        self.record(UiCall::InstallWarnSlowStorage(estimated_extra_mins));
+        Ok(())
+    }
+
+    fn install_warn_existing(
+        &self,
+        bepinex_installed: bool,
+        metamystia_installed: bool,
+        resourceex_installed: bool,
+        cleanup_plan: &[PathBuf],
+    ) -> Result<()> {
+        self.record(UiCall::InstallWarnExisting(
+            bepinex_installed,
+            metamystia_installed,
+            resourceex_installed,
+            cleanup_plan.to_vec(),
+        ));
+        Ok(())
+    }
+
+    fn install_confirm_overwrite(&self) -> Result<bool> {
+        self.record(UiCall::InstallConfirmOverwrite);
+        Ok(self.confirm)
+    }
+
+    fn install_select_overwrite(
+        &self,
+        bepinex_installed: bool,
+        metamystia_installed: bool,
+        resourceex_installed: bool,
+    ) -> Result<OverwriteSelection> {
+        self.record(UiCall::InstallSelectOverwrite(
+            bepinex_installed,
+            metamystia_installed,
+            resourceex_installed,
+        ));
+        Ok(OverwriteSelection::all())
+    }
+
+    fn install_ask_install_resourceex(&self) -> Result<bool> {
+        self.record(UiCall::InstallAskInstallResourceex);
+        Ok(self.confirm)
+    }
+
+    fn install_ask_show_bepinex_console(&self) -> Result<bool> {
+        self.record(UiCall::InstallAskShowBepinexConsole);
+        Ok(self.confirm)
+    }
+
+    fn install_ask_configure_advanced_options(&self) -> Result<bool> {
+        self.record(UiCall::InstallAskConfigureAdvancedOptions);
+        Ok(self.confirm)
+    }
+
+    fn install_select_bepinex_log_levels(&self) -> Result<Vec<String>> {
+        self.record(UiCall::InstallSelectBepinexLogLevels);
+        Ok(BEPINEX_DEFAULT_LOG_LEVELS
+            .iter()
+            .map(|l| l.to_string())
+            .collect())
+    }
+
+    fn install_ask_backup_before_install(&self) -> Result<bool> {
+        self.record(UiCall::InstallAskBackupBeforeInstall);
+        Ok(self.confirm)
+    }
+
+    fn install_display_plan(&self, plan: &InstallPlan) -> Result<bool> {
+        self.record(UiCall::InstallDisplayPlan(plan.clone()));
+        Ok(self.confirm)
+    }
+
+    fn install_downloads_completed(&self) -> Result<()> {
+        self.record(UiCall::InstallDownloadsCompleted);
+        Ok(())
+    }
+
+    fn install_start_cleanup(&self) -> Result<()> {
+        self.record(UiCall::InstallStartCleanup);
+        Ok(())
+    }
+
+    fn install_display_cleanup_detail(&self, items: &[DeletionResult]) -> Result<()> {
+        self.record(UiCall::InstallDisplayCleanupDetail(items.to_vec()));
+        Ok(())
+    }
+
+    fn install_cleanup_result(&self, success_count: usize, failed_count: usize) -> Result<()> {
+        self.record(UiCall::InstallCleanupResult(success_count, failed_count));
+        Ok(())
+    }
+
+    fn install_finished(&self, show_bepinex_console: bool, first_install: bool) -> Result<()> {
+        self.record(UiCall::InstallFinished(show_bepinex_console, first_install));
+        Ok(())
+    }
+
+    fn display_first_time_bepinex_info(&self) -> Result<()> {
+        self.record(UiCall::DisplayFirstTimeBepinexInfo);
+        Ok(())
+    }
+
+    fn warn_bepinex_il2cpp_download(&self, source_url: &str) -> Result<()> {
+        self.record(UiCall::WarnBepinexIl2cppDownload(source_url.to_string()));
+        Ok(())
+    }
+
+    fn install_warn_loader_quarantined(&self, path: &Path, detection: Option<&str>) -> Result<()> {
+        self.record(UiCall::InstallWarnLoaderQuarantined(
+            path.to_path_buf(),
+            detection.map(str::to_string),
+        ));
+        Ok(())
+    }
+
+    fn resourceex_display_misplaced(&self, files: &[PathBuf]) -> Result<()> {
+        self.record(UiCall::ResourceexDisplayMisplaced(files.to_vec()));
+        Ok(())
+    }
+
+    fn resourceex_confirm_migrate(&self) -> Result<bool> {
+        self.record(UiCall::ResourceexConfirmMigrate);
+        Ok(self.confirm)
+    }
+
+    fn resourceex_migrate_failed(&self, path: &Path, err: &str) -> Result<()> {
+        self.record(UiCall::ResourceexMigrateFailed(
+            path.to_path_buf(),
+            err.to_string(),
+        ));
+        Ok(())
+    }
+
+    fn upgrade_warn_unparse_version(&self, filename: &str) -> Result<()> {
+        self.record(UiCall::UpgradeWarnUnparseVersion(filename.to_string()));
+        Ok(())
+    }
+
+    fn upgrade_found_duplicate(&self, path: &Path) -> Result<()> {
+        self.record(UiCall::UpgradeFoundDuplicate(path.to_path_buf()));
+        Ok(())
+    }
+
+    fn upgrade_backup_failed(&self, err: &str) -> Result<()> {
+        self.record(UiCall::UpgradeBackupFailed(err.to_string()));
+        Ok(())
+    }
+
+    fn upgrade_deleted(&self, path: &Path) -> Result<()> {
+        self.record(UiCall::UpgradeDeleted(path.to_path_buf()));
+        Ok(())
+    }
+
+    fn upgrade_delete_failed(&self, path: &Path, err: &str) -> Result<()> {
+        self.record(UiCall::UpgradeDeleteFailed(
+            path.to_path_buf(),
+            err.to_string(),
+        ));
+        Ok(())
+    }
+
+    fn upgrade_checking_installed_version(&self) -> Result<()> {
+        self.record(UiCall::UpgradeCheckingInstalledVersion);
+        Ok(())
+    }
+
+    fn upgrade_detected_resourceex(&self) -> Result<()> {
+        self.record(UiCall::UpgradeDetectedResourceex);
+        Ok(())
+    }
+
+    fn upgrade_display_current_and_latest_dll(&self, current: &str, latest: &str) -> Result<()> {
+        self.record(UiCall::UpgradeDisplayCurrentAndLatestDll(
+            current.to_string(),
+            latest.to_string(),
+        ));
+        Ok(())
+    }
+
+    fn upgrade_display_current_and_latest_resourceex(
+        &self,
+        current: &str,
+        latest: &str,
+    ) -> Result<()> {
+        self.record(UiCall::UpgradeDisplayCurrentAndLatestResourceex(
+            current.to_string(),
+            latest.to_string(),
+        ));
+        Ok(())
+    }
+
+    fn upgrade_no_update_needed(&self) -> Result<()> {
+        self.record(UiCall::UpgradeNoUpdateNeeded);
+        Ok(())
+    }
+
+    fn upgrade_detected_new_dll(&self, current: &str, new: &str) -> Result<()> {
+        self.record(UiCall::UpgradeDetectedNewDll(
+            current.to_string(),
+            new.to_string(),
+        ));
+        Ok(())
+    }
+
+    fn upgrade_dll_already_latest(&self) -> Result<()> {
+        self.record(UiCall::UpgradeDllAlreadyLatest);
+        Ok(())
+    }
+
+    fn upgrade_resourceex_needs_upgrade(&self) -> Result<()> {
+        self.record(UiCall::UpgradeResourceexNeedsUpgrade);
+        Ok(())
+    }
+
+    fn confirm_batch_upgrade(&self, updates: &[PendingUpgrade]) -> Result<bool> {
+        self.record(UiCall::ConfirmBatchUpgrade(updates.to_vec()));
+        Ok(self.confirm)
+    }
+
+    fn upgrade_downloading_dll(&self) -> Result<()> {
+        self.record(UiCall::UpgradeDownloadingDll);
+        Ok(())
+    }
+
+    fn upgrade_downloading_resourceex(&self) -> Result<()> {
+        self.record(UiCall::UpgradeDownloadingResourceex);
+        Ok(())
+    }
+
+    fn upgrade_installing_dll(&self) -> Result<()> {
+        self.record(UiCall::UpgradeInstallingDll);
+        Ok(())
+    }
+
+    fn upgrade_installing_resourceex(&self) -> Result<()> {
+        self.record(UiCall::UpgradeInstallingResourceex);
+        Ok(())
+    }
+
+    fn upgrade_install_success(&self, path: &Path) -> Result<()> {
+        self.record(UiCall::UpgradeInstallSuccess(path.to_path_buf()));
+        Ok(())
+    }
+
+    fn upgrade_cleanup_start(&self) -> Result<()> {
+        self.record(UiCall::UpgradeCleanupStart);
+        Ok(())
+    }
+
+    fn upgrade_done(&self) -> Result<()> {
+        self.record(UiCall::UpgradeDone);
+        Ok(())
+    }
+
+    fn ask_launch_game(&self) -> Result<bool> {
+        self.record(UiCall::AskLaunchGame);
+        Ok(self.confirm)
+    }
+
+    fn launch_game_failed(&self, err: &str) -> Result<()> {
+        self.record(UiCall::LaunchGameFailed(err.to_string()));
+        Ok(())
+    }
+
+    fn ask_open_docs(&self) -> Result<bool> {
+        self.record(UiCall::AskOpenDocs);
+        Ok(self.confirm)
+    }
+
+    fn open_docs_failed(&self, url: &str) -> Result<()> {
+        self.record(UiCall::OpenDocsFailed(url.to_string()));
+        Ok(())
+    }
+
+    fn configure_ask_bepinex_console(&self, current: bool) -> Result<bool> {
+        self.record(UiCall::ConfigureAskBepinexConsole(current));
+        Ok(self.confirm)
+    }
+
+    fn configure_bepinex_console_changed(&self, previous: bool, enabled: bool) -> Result<()> {
+        self.record(UiCall::ConfigureBepinexConsoleChanged(previous, enabled));
+        Ok(())
+    }
+
+    fn uninstall_select_mode(&self) -> Result<UninstallMode> {
+        self.record(UiCall::UninstallSelectMode);
+        Ok(UninstallMode::Light)
+    }
+
+    fn uninstall_no_files_found(&self) -> Result<()> {
+        self.record(UiCall::UninstallNoFilesFound);
+        Ok(())
+    }
+
+    fn uninstall_display_target_files(&self, files: &[PathBuf]) -> Result<()> {
+        self.record(UiCall::UninstallDisplayTargetFiles(files.to_vec()));
+        Ok(())
+    }
+
+    fn uninstall_confirm_deletion(&self) -> Result<bool> {
+        self.record(UiCall::UninstallConfirmDeletion);
+        Ok(self.confirm)
+    }
+
+    fn uninstall_ask_create_undo_archive(&self, estimated_size: u64) -> Result<bool> {
+        self.record(UiCall::UninstallAskCreateUndoArchive(estimated_size));
+        Ok(self.confirm)
+    }
+
+    fn uninstall_undo_archive_insufficient_space(
+        &self,
+        required: u64,
+        available: u64,
+    ) -> Result<()> {
+        self.record(UiCall::UninstallUndoArchiveInsufficientSpace(
+            required, available,
+        ));
+        Ok(())
+    }
+
+    fn uninstall_undo_archive_created(&self, path: &Path) -> Result<()> {
+        self.record(UiCall::UninstallUndoArchiveCreated(path.to_path_buf()));
+        Ok(())
+    }
+
+    fn restore_undo_finished(&self, archive: &Path, count: usize) -> Result<()> {
+        self.record(UiCall::RestoreUndoFinished(archive.to_path_buf(), count));
+        Ok(())
+    }
+
+    fn uninstall_files_in_use_warning(&self) -> Result<()> {
+        self.record(UiCall::UninstallFilesInUseWarning);
+        Ok(())
+    }
+
+    fn uninstall_wait_before_retry(
+        &self,
+        delay_secs: u64,
+        attempt: usize,
+        attempts: usize,
+    ) -> Result<()> {
+        self.record(UiCall::UninstallWaitBeforeRetry(
+            delay_secs, attempt, attempts,
+        ));
+        Ok(())
+    }
+
+    fn uninstall_wait_or_skip(&self, delay_secs: u64) -> Result<WaitOutcome> {
+        self.record(UiCall::UninstallWaitOrSkip(delay_secs));
+        Ok(WaitOutcome::Elapsed)
+    }
+
+    fn uninstall_ask_elevate_permission(&self) -> Result<bool> {
+        self.record(UiCall::UninstallAskElevatePermission);
+        Ok(self.confirm)
+    }
+
+    fn uninstall_restarting_elevated(&self) -> Result<()> {
+        self.record(UiCall::UninstallRestartingElevated);
+        Ok(())
+    }
+
+    fn uninstall_ask_retry_failures(&self) -> Result<bool> {
+        self.record(UiCall::UninstallAskRetryFailures);
+        Ok(self.confirm)
+    }
+
+    fn uninstall_retrying_failed_items(&self) -> Result<()> {
+        self.record(UiCall::UninstallRetryingFailedItems);
+        Ok(())
+    }
+
+    fn uninstall_display_disk_space_freed(&self, bytes_freed: u64) -> Result<()> {
+        self.record(UiCall::UninstallDisplayDiskSpaceFreed(bytes_freed));
+        Ok(())
+    }
+
+    fn uninstall_confirm_take_ownership(&self, paths: &[PathBuf]) -> Result<bool> {
+        self.record(UiCall::UninstallConfirmTakeOwnership(paths.to_vec()));
+        Ok(self.confirm)
+    }
+
+    fn uninstall_display_acl_fix_commands(&self, commands: &[String]) -> Result<()> {
+        self.record(UiCall::UninstallDisplayAclFixCommands(commands.to_vec()));
+        Ok(())
+    }
+
+    fn uninstall_display_failure_breakdown(
+        &self,
+        locked: usize,
+        acl_denied: usize,
+        other: usize,
+    ) -> Result<()> {
+        self.record(UiCall::UninstallDisplayFailureBreakdown(
+            locked, acl_denied, other,
+        ));
+        Ok(())
+    }
+
+    fn deletion_start(&self, total: usize) -> Result<()> {
+        self.record(UiCall::DeletionStart(total));
+        Ok(())
+    }
+
+    fn deletion_display_progress(&self, current: usize, total: usize, path: &str) -> Result<()> {
+        self.record(UiCall::DeletionDisplayProgress(
+            current,
+            total,
+            path.to_string(),
+        ));
+        Ok(())
+    }
+
+    fn deletion_display_success(&self, path: &str) -> Result<()> {
+        self.record(UiCall::DeletionDisplaySuccess(path.to_string()));
+        Ok(())
+    }
+
+    fn deletion_display_failure(&self, path: &str, error: &str) -> Result<()> {
+        self.record(UiCall::DeletionDisplayFailure(
+            path.to_string(),
+            error.to_string(),
+        ));
+        Ok(())
+    }
+
+    fn deletion_display_skipped(&self, path: &str) -> Result<()> {
+        self.record(UiCall::DeletionDisplaySkipped(path.to_string()));
+        Ok(())
+    }
+
+    fn deletion_display_summary(
+        &self,
+        success_count: usize,
+        failed_count: usize,
+        skipped_count: usize,
+    ) -> Result<()> {
+        self.record(UiCall::DeletionDisplaySummary(
+            success_count,
+            failed_count,
+            skipped_count,
+        ));
+        Ok(())
+    }
+
+    fn temp_dir_warn_cross_volume(&self, temp_dir: &Path, game_root: &Path) -> Result<()> {
+        self.record(UiCall::TempDirWarnCrossVolume(
+            temp_dir.to_path_buf(),
+            game_root.to_path_buf(),
+        ));
+        Ok(())
+    }
+
+    fn download_start(&self, filename: &str, total: Option<u64>) -> Result<usize> {
+        self.record(UiCall::DownloadStart(filename.to_string(), total));
+        Ok(self.next_download_id.fetch_add(1, Ordering::SeqCst))
+    }
+
+    fn download_update(&self, id: usize, downloaded: u64) -> Result<()> {
+        self.record(UiCall::DownloadUpdate(id, downloaded));
+        Ok(())
+    }
+
+    fn download_finish(&self, id: usize, message: &str) -> Result<()> {
+        self.record(UiCall::DownloadFinish(id, message.to_string()));
+        Ok(())
+    }
+
+    fn download_version_info_start(&self) -> Result<()> {
+        self.record(UiCall::DownloadVersionInfoStart);
+        Ok(())
+    }
+
+    fn download_version_info_failed(&self, err: &str) -> Result<()> {
+        self.record(UiCall::DownloadVersionInfoFailed(err.to_string()));
+        Ok(())
+    }
+
+    fn download_version_info_success(&self) -> Result<()> {
+        self.record(UiCall::DownloadVersionInfoSuccess);
+        Ok(())
+    }
+
+    fn download_version_info_parse_failed(&self, err: &str, snippet: &str) -> Result<()> {
+        self.record(UiCall::DownloadVersionInfoParseFailed(
+            err.to_string(),
+            snippet.to_string(),
+        ));
+        Ok(())
+    }
+
+    fn download_share_code_start(&self) -> Result<()> {
+        self.record(UiCall::DownloadShareCodeStart);
+        Ok(())
+    }
+
+    fn download_share_code_failed(&self, err: &str) -> Result<()> {
+        self.record(UiCall::DownloadShareCodeFailed(err.to_string()));
+        Ok(())
+    }
+
+    fn download_share_code_success(&self) -> Result<()> {
+        self.record(UiCall::DownloadShareCodeSuccess);
+        Ok(())
+    }
+
+    fn download_attempt_github_dll(&self) -> Result<()> {
+        self.record(UiCall::DownloadAttemptGithubDll);
+        Ok(())
+    }
+
+    fn download_found_github_asset(&self, name: &str) -> Result<()> {
+        self.record(UiCall::DownloadFoundGithubAsset(name.to_string()));
+        Ok(())
+    }
+
+    fn download_github_dll_not_found(&self) -> Result<()> {
+        self.record(UiCall::DownloadGithubDllNotFound);
+        Ok(())
+    }
+
+    fn download_github_auth_required(&self) -> Result<()> {
+        self.record(UiCall::DownloadGithubAuthRequired);
+        Ok(())
+    }
+
+    fn download_display_github_release_notes(
+        &self,
+        tag: &str,
+        name: &str,
+        body: &str,
+    ) -> Result<()> {
+        self.record(UiCall::DownloadDisplayGithubReleaseNotes(
+            tag.to_string(),
+            name.to_string(),
+            body.to_string(),
+        ));
+        Ok(())
+    }
+
+    fn download_ask_continue_after_release_notes(&self) -> Result<bool> {
+        self.record(UiCall::DownloadAskContinueAfterReleaseNotes);
+        Ok(self.confirm)
+    }
+
+    fn download_switch_to_fallback(&self, reason: &str) -> Result<()> {
+        self.record(UiCall::DownloadSwitchToFallback(reason.to_string()));
+        Ok(())
+    }
+
+    fn download_try_fallback_metamystia(&self) -> Result<()> {
+        self.record(UiCall::DownloadTryFallbackMetamystia);
+        Ok(())
+    }
+
+    fn download_bepinex_attempt_primary(&self) -> Result<()> {
+        self.record(UiCall::DownloadBepinexAttemptPrimary);
+        Ok(())
+    }
+
+    fn download_bepinex_primary_failed(&self, err: &str) -> Result<()> {
+        self.record(UiCall::DownloadBepinexPrimaryFailed(err.to_string()));
+        Ok(())
+    }
+
+    fn download_all_sources_failed(&self, failures: &[SourceFailure]) -> Result<()> {
+        self.record(UiCall::DownloadAllSourcesFailed(failures.to_vec()));
+        Ok(())
+    }
+
+    fn display_download_summary(&self, reports: &[DownloadReport]) -> Result<()> {
+        self.record(UiCall::DisplayDownloadSummary(reports.to_vec()));
+        Ok(())
+    }
+
+    fn network_retrying(
+        &self,
+        op_desc: &str,
+        delay_secs: u64,
+        attempt: usize,
+        attempts: usize,
+        err: &str,
+    ) -> Result<()> {
+        self.record(UiCall::NetworkRetrying(
+            op_desc.to_string(),
+            delay_secs,
+            attempt,
+            attempts,
+            err.to_string(),
+        ));
+        Ok(())
+    }
+
+    fn network_rate_limited(&self, secs: u64) -> Result<()> {
+        self.record(UiCall::NetworkRateLimited(secs));
+        Ok(())
+    }
+
+    fn display_network_diagnostics(&self, endpoint: &str, last_error: &str) -> Result<()> {
+        self.record(UiCall::DisplayNetworkDiagnostics(
+            endpoint.to_string(),
+            last_error.to_string(),
+        ));
+        Ok(())
+    }
+
+    fn manager_ask_self_update(&self, current_version: &str, latest_version: &str) -> Result<bool> {
+        self.record(UiCall::ManagerAskSelfUpdate(
+            current_version.to_string(),
+            latest_version.to_string(),
+        ));
+        Ok(self.confirm)
+    }
+
+    fn display_self_update_downloaded(&self, path: &Path, version: &str) -> Result<()> {
+        self.record(UiCall::DisplaySelfUpdateDownloaded(
+            path.to_path_buf(),
+            version.to_string(),
+        ));
+        Ok(())
+    }
+
+    fn manager_update_starting(&self) -> Result<()> {
+        self.record(UiCall::ManagerUpdateStarting);
+        Ok(())
+    }
+
+    fn manager_update_failed(&self, err: &str) -> Result<()> {
+        self.record(UiCall::ManagerUpdateFailed(err.to_string()));
+        Ok(())
+    }
+
+    fn manager_prompt_manual_update(&self, reason: &str) -> Result<()> {
+        self.record(UiCall::ManagerPromptManualUpdate(reason.to_string()));
+        Ok(())
+    }
+
+    fn select_version_ask_select(&self, component: &str) -> Result<bool> {
+        self.record(UiCall::SelectVersionAskSelect(component.to_string()));
+        Ok(self.confirm)
+    }
+
+    fn select_version_from_list(&self, component: &str, versions: &[String]) -> Result<usize> {
+        self.record(UiCall::SelectVersionFromList(
+            component.to_string(),
+            versions.to_vec(),
+        ));
+        Ok(0)
+    }
+
+    fn select_version_not_available(
+        &self,
+        component: &str,
+        version: &str,
+        available: &[String],
+    ) -> Result<()> {
+        self.record(UiCall::SelectVersionNotAvailable(
+            component.to_string(),
+            version.to_string(),
+            available.to_vec(),
+        ));
+        Ok(())
+    }
+}
+
+static SCRATCH_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// 测试用临时目录，离开作用域时自动删除
+pub struct ScratchDir {
+    path: PathBuf,
+}
+
+impl ScratchDir {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "meta-mystia-test-{}-{}-{}",
+            name,
+            std::process::id(),
+            SCRATCH_COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).expect("创建测试目录失败");
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 在目录中写入文件（自动创建父目录），返回文件的完整路径
+    pub fn write(&self, relative: &str, content: &[u8]) -> PathBuf {
+        let path = self.path.join(relative);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).expect("创建测试目录失败");
+        }
+        std::fs::write(&path, content).expect("写入测试文件失败");
+        path
+    }
+
+    /// 在目录中创建子目录，返回其完整路径
+    pub fn mkdir(&self, relative: &str) -> PathBuf {
+        let path = self.path.join(relative);
+        std::fs::create_dir_all(&path).expect("创建测试目录失败");
+        path
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+/// 设置该环境变量时，测试运行在使用独立 %APPDATA% 的子进程中
+const SCRATCH_APPDATA_ENV: &str = "META_MYSTIA_TEST_SCRATCH_APPDATA";
+
+/// 以指向临时目录的 %APPDATA% 在子进程中重新运行指定测试，避免读写真实的数据目录。
+/// 返回 true 表示子进程已运行完毕；已在子进程中时返回 false，调用方继续执行测试内容
+pub fn rerun_with_scratch_appdata(test: &str) -> bool {
+    if std::env::var_os(SCRATCH_APPDATA_ENV).is_some() {
+        return false;
+    }
+
+    let appdata = ScratchDir::new("appdata");
+    let output = std::process::Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", test, "--test-threads=1"])
+        .env("APPDATA", appdata.path())
+        .env(SCRATCH_APPDATA_ENV, "1")
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success() && stdout.contains("1 passed"),
+        "{}\n{}",
+        stdout,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    true
+}
+
+/// 以代表性参数调用 Ui 的每个方法，返回调用失败的方法名
+///
+/// 方法列表与 TestUI 一同由 Ui trait 生成；任何方法 panic 都会使调用方的测试失败。
+pub fn test_ui_coverage(ui: &dyn Ui) -> Vec<&'static str> {
+    let path = PathBuf::from("C:\\Games\\Touhou Mystia Izakaya");
+    let paths = vec![path.join("winhttp.dll"), path.join("BepInEx")];
+    let versions = vec!["1.1.0".to_string(), "1.0.0".to_string()];
+    let version_info = VersionInfo::for_testing("1.1.0", "1.0.0", "6.0.0#BepInEx.zip", "1.7.0");
+    let plan = InstallPlan {
+        bepinex_version: Some("6.0.0".to_string()),
+        dll_version: Some("1.1.0".to_string()),
+        resourceex_version: None,
+        overwrite: vec!["BepInEx".to_string()],
+        cleanup_targets: paths.clone(),
+        misplaced_resourceex: paths.clone(),
+        show_bepinex_console: false,
+    };
+    let mut summary = OperationSummary::default();
+    summary.push("MetaMystia", SummaryAction::Installed, Some("1.1.0"), None);
+    let prefetch = PrefetchResult {
+        available: vec![("https://example.invalid/a.zip".to_string(), Some(1024))],
+        unavailable: vec![(
+            "https://example.invalid/b.zip".to_string(),
+            "HTTP 404".to_string(),
+        )],
+    };
+    let candidates = vec![
+        (path.clone(), PathSource::Steam("D:\\Steam".to_string())),
+        (PathBuf::from("."), PathSource::CurrentDirectory),
+    ];
+    let deletions = vec![DeletionResult {
+        path: paths[0].clone(),
+        status: DeletionStatus::Success,
+        bytes_freed: Some(1024),
+    }];
+    let reports = vec![DownloadReport {
+        component: "MetaMystia".to_string(),
+        source: DownloadSource::Mirror,
+        bytes: 1024,
+        duration: Duration::from_secs(1),
+    }];
+    let processes = vec![GameProcess {
+        pid: 1,
+        name: "Touhou Mystia Izakaya.exe".to_string(),
+        started_at: None,
+    }];
+    let pending = vec![PendingUpgrade {
+        component: "MetaMystia".to_string(),
+        from_version: "1.0.0".to_string(),
+        to_version: "1.1.0".to_string(),
+    }];
+    let failures = vec![SourceFailure {
+        source: "GitHub".to_string(),
+        url: "https://example.invalid".to_string(),
+        kind: "network",
+        reason: "HTTP 500".to_string(),
+    }];
+
+    let mut failed = Vec::new();
+    let mut check = |name: &'static str, ok: bool| {
+        if !ok {
+            failed.push(name);
+        }
+    };
+
+    check("display_welcome", ui.display_welcome(true).is_ok());
+    check("ask_telemetry_consent", ui.ask_telemetry_consent().is_ok());
+    check("display_version", ui.display_version(Some("1.0.0")).is_ok());
+    check(
+        "display_update_channel_info",
+        ui.display_update_channel_info("1.0.0").is_ok(),
+    );
+    check(
+        "warn_old_manager_version",
+        ui.warn_old_manager_version("1.0.0", "1.0.0", 1).is_ok(),
+    );
+    check(
+        "display_game_running_warning",
+        ui.display_game_running_warning(&processes).is_ok(),
+    );
+    check(
+        "display_available_updates",
+        ui.display_available_updates(true, true).is_ok(),
+    );
+    check(
+        "display_upgrade_check_failed",
+        ui.display_upgrade_check_failed("1.0.0").is_ok(),
+    );
+    check(
+        "display_progress_cancelled",
+        ui.display_progress_cancelled(&ProgressSnapshot::default())
+            .is_ok(),
+    );
+    check("select_operation_mode", ui.select_operation_mode().is_ok());
+    check("blank_line", ui.blank_line().is_ok());
+    check("wait_for_key", ui.wait_for_key().is_ok());
+    check("message", ui.message("1.0.0").is_ok());
+    check("warn", ui.warn("1.0.0").is_ok());
+    check("error", ui.error("1.0.0").is_ok());
+    check(
+        "display_operation_summary",
+        ui.display_operation_summary(&summary).is_ok(),
+    );
+    check(
+        "display_controlled_folder_access_blocked",
+        ui.display_controlled_folder_access_blocked(&path).is_ok(),
+    );
+    check(
+        "path_display_steam_found",
+        ui.path_display_steam_found(1, Some("1.0.0"), &path).is_ok(),
+    );
+    check(
+        "path_display_steam_stale",
+        ui.path_display_steam_stale(1, Some("1.0.0"), &path).is_ok(),
+    );
+    check(
+        "path_confirm_use_steam_found",
+        ui.path_confirm_use_steam_found().is_ok(),
+    );
+    check(
+        "select_game_path_from_candidates",
+        ui.select_game_path_from_candidates(&candidates).is_ok(),
+    );
+    check("path_ask_manual_entry", ui.path_ask_manual_entry().is_ok());
+    check(
+        "path_manual_entry_not_found",
+        ui.path_manual_entry_not_found(&path).is_ok(),
+    );
+    check(
+        "warn_conflicting_game_exe",
+        ui.warn_conflicting_game_exe(&paths).is_ok(),
+    );
+    check(
+        "warn_cloud_sync_directory",
+        ui.warn_cloud_sync_directory("1.0.0").is_ok(),
+    );
+    check(
+        "install_display_step",
+        ui.install_display_step(1, "1.0.0").is_ok(),
+    );
+    check(
+        "install_display_version_info",
+        ui.install_display_version_info(&version_info).is_ok(),
+    );
+    check(
+        "install_display_estimated_download_size",
+        ui.install_display_estimated_download_size(&DownloadSizeEstimate::default())
+            .is_ok(),
+    );
+    check(
+        "install_display_estimated_duration",
+        ui.install_display_estimated_duration(0).is_ok(),
+    );
+    check(
+        "install_display_download_sources",
+        ui.install_display_download_sources(&prefetch).is_ok(),
+    );
+    check(
+        "install_warn_slow_storage",
+        ui.install_warn_slow_storage(0).is_ok(),
+    );
+    check(
+        "install_warn_existing",
+        ui.install_warn_existing(true, true, true, &paths).is_ok(),
+    );
+    check(
+        "install_confirm_overwrite",
+        ui.install_confirm_overwrite().is_ok(),
+    );
+    check(
+        "install_select_overwrite",
+        ui.install_select_overwrite(true, true, true).is_ok(),
+    );
+    check(
+        "install_ask_install_resourceex",
+        ui.install_ask_install_resourceex().is_ok(),
+    );
+    check(
+        "install_ask_show_bepinex_console",
+        ui.install_ask_show_bepinex_console().is_ok(),
+    );
+    check(
+        "install_ask_configure_advanced_options",
+        ui.install_ask_configure_advanced_options().is_ok(),
+    );
+    check(
+        "install_select_bepinex_log_levels",
+        ui.install_select_bepinex_log_levels().is_ok(),
+    );
+    check(
+        "install_ask_backup_before_install",
+        ui.install_ask_backup_before_install().is_ok(),
+    );
+    check(
+        "install_display_plan",
+        ui.install_display_plan(&plan).is_ok(),
+    );
+    check(
+        "install_downloads_completed",
+        ui.install_downloads_completed().is_ok(),
+    );
+    check("install_start_cleanup", ui.install_start_cleanup().is_ok());
+    check(
+        "install_display_cleanup_detail",
+        ui.install_display_cleanup_detail(&deletions).is_ok(),
+    );
+    check(
+        "install_cleanup_result",
+        ui.install_cleanup_result(1, 1).is_ok(),
+    );
+    check("install_finished", ui.install_finished(true, true).is_ok());
+    check(
+        "display_first_time_bepinex_info",
+        ui.display_first_time_bepinex_info().is_ok(),
+    );
+    check(
+        "warn_bepinex_il2cpp_download",
+        ui.warn_bepinex_il2cpp_download("1.0.0").is_ok(),
+    );
+    check(
+        "install_warn_loader_quarantined",
+        ui.install_warn_loader_quarantined(&path, Some("1.0.0"))
+            .is_ok(),
+    );
+    check(
+        "resourceex_display_misplaced",
+        ui.resourceex_display_misplaced(&paths).is_ok(),
+    );
+    check(
+        "resourceex_confirm_migrate",
+        ui.resourceex_confirm_migrate().is_ok(),
+    );
+    check(
+        "resourceex_migrate_failed",
+        ui.resourceex_migrate_failed(&path, "1.0.0").is_ok(),
+    );
+    check(
+        "upgrade_warn_unparse_version",
+        ui.upgrade_warn_unparse_version("1.0.0").is_ok(),
+    );
+    check(
+        "upgrade_found_duplicate",
+        ui.upgrade_found_duplicate(&path).is_ok(),
+    );
+    check(
+        "upgrade_backup_failed",
+        ui.upgrade_backup_failed("1.0.0").is_ok(),
+    );
+    check("upgrade_deleted", ui.upgrade_deleted(&path).is_ok());
+    check(
+        "upgrade_delete_failed",
+        ui.upgrade_delete_failed(&path, "1.0.0").is_ok(),
+    );
+    check(
+        "upgrade_checking_installed_version",
+        ui.upgrade_checking_installed_version().is_ok(),
+    );
+    check(
+        "upgrade_detected_resourceex",
+        ui.upgrade_detected_resourceex().is_ok(),
+    );
+    check(
+        "upgrade_display_current_and_latest_dll",
+        ui.upgrade_display_current_and_latest_dll("1.0.0", "1.0.0")
+            .is_ok(),
+    );
+    check(
+        "upgrade_display_current_and_latest_resourceex",
+        ui.upgrade_display_current_and_latest_resourceex("1.0.0", "1.0.0")
+            .is_ok(),
+    );
+    check(
+        "upgrade_no_update_needed",
+        ui.upgrade_no_update_needed().is_ok(),
+    );
+    check(
+        "upgrade_detected_new_dll",
+        ui.upgrade_detected_new_dll("1.0.0", "1.0.0").is_ok(),
+    );
+    check(
+        "upgrade_dll_already_latest",
+        ui.upgrade_dll_already_latest().is_ok(),
+    );
+    check(
+        "upgrade_resourceex_needs_upgrade",
+        ui.upgrade_resourceex_needs_upgrade().is_ok(),
+    );
+    check(
+        "confirm_batch_upgrade",
+        ui.confirm_batch_upgrade(&pending).is_ok(),
+    );
+    check(
+        "upgrade_downloading_dll",
+        ui.upgrade_downloading_dll().is_ok(),
+    );
+    check(
+        "upgrade_downloading_resourceex",
+        ui.upgrade_downloading_resourceex().is_ok(),
+    );
+    check(
+        "upgrade_installing_dll",
+        ui.upgrade_installing_dll().is_ok(),
+    );
+    check(
+        "upgrade_installing_resourceex",
+        ui.upgrade_installing_resourceex().is_ok(),
+    );
+    check(
+        "upgrade_install_success",
+        ui.upgrade_install_success(&path).is_ok(),
+    );
+    check("upgrade_cleanup_start", ui.upgrade_cleanup_start().is_ok());
+    check("upgrade_done", ui.upgrade_done().is_ok());
+    check("ask_launch_game", ui.ask_launch_game().is_ok());
+    check("launch_game_failed", ui.launch_game_failed("1.0.0").is_ok());
+    check("ask_open_docs", ui.ask_open_docs().is_ok());
+    check("open_docs_failed", ui.open_docs_failed("1.0.0").is_ok());
+    check(
+        "configure_ask_bepinex_console",
+        ui.configure_ask_bepinex_console(true).is_ok(),
+    );
+    check(
+        "configure_bepinex_console_changed",
+        ui.configure_bepinex_console_changed(true, true).is_ok(),
+    );
+    check("uninstall_select_mode", ui.uninstall_select_mode().is_ok());
+    check(
+        "uninstall_no_files_found",
+        ui.uninstall_no_files_found().is_ok(),
+    );
+    check(
+        "uninstall_display_target_files",
+        ui.uninstall_display_target_files(&paths).is_ok(),
+    );
+    check(
+        "uninstall_confirm_deletion",
+        ui.uninstall_confirm_deletion().is_ok(),
+    );
+    check(
+        "uninstall_ask_create_undo_archive",
+        ui.uninstall_ask_create_undo_archive(0).is_ok(),
+    );
+    check(
+        "uninstall_undo_archive_insufficient_space",
+        ui.uninstall_undo_archive_insufficient_space(0, 0).is_ok(),
+    );
+    check(
+        "uninstall_undo_archive_created",
+        ui.uninstall_undo_archive_created(&path).is_ok(),
+    );
+    check(
+        "restore_undo_finished",
+        ui.restore_undo_finished(&path, 1).is_ok(),
+    );
+    check(
+        "uninstall_files_in_use_warning",
+        ui.uninstall_files_in_use_warning().is_ok(),
+    );
+    check(
+        "uninstall_wait_before_retry",
+        ui.uninstall_wait_before_retry(0, 1, 1).is_ok(),
+    );
+    check(
+        "uninstall_wait_or_skip",
+        ui.uninstall_wait_or_skip(0).is_ok(),
+    );
+    check(
+        "uninstall_ask_elevate_permission",
+        ui.uninstall_ask_elevate_permission().is_ok(),
+    );
+    check(
+        "uninstall_restarting_elevated",
+        ui.uninstall_restarting_elevated().is_ok(),
+    );
+    check(
+        "uninstall_ask_retry_failures",
+        ui.uninstall_ask_retry_failures().is_ok(),
+    );
+    check(
+        "uninstall_retrying_failed_items",
+        ui.uninstall_retrying_failed_items().is_ok(),
+    );
+    check(
+        "uninstall_display_disk_space_freed",
+        ui.uninstall_display_disk_space_freed(0).is_ok(),
+    );
+    check(
+        "uninstall_confirm_take_ownership",
+        ui.uninstall_confirm_take_ownership(&paths).is_ok(),
+    );
+    check(
+        "uninstall_display_acl_fix_commands",
+        ui.uninstall_display_acl_fix_commands(&versions).is_ok(),
+    );
+    check(
+        "uninstall_display_failure_breakdown",
+        ui.uninstall_display_failure_breakdown(1, 1, 1).is_ok(),
+    );
+    check("deletion_start", ui.deletion_start(1).is_ok());
+    check(
+        "deletion_display_progress",
+        ui.deletion_display_progress(1, 1, "1.0.0").is_ok(),
+    );
+    check(
+        "deletion_display_success",
+        ui.deletion_display_success("1.0.0").is_ok(),
+    );
+    check(
+        "deletion_display_failure",
+        ui.deletion_display_failure("1.0.0", "1.0.0").is_ok(),
+    );
+    check(
+        "deletion_display_skipped",
+        ui.deletion_display_skipped("1.0.0").is_ok(),
+    );
+    check(
+        "deletion_display_summary",
+        ui.deletion_display_summary(1, 1, 1).is_ok(),
+    );
+    check(
+        "temp_dir_warn_cross_volume",
+        ui.temp_dir_warn_cross_volume(&path, &path).is_ok(),
+    );
+    let download_id = ui.download_start("MetaMystia.dll", Some(1024));
+    check("download_start", download_id.is_ok());
+    let download_id = download_id.unwrap_or(0);
+    check(
+        "download_update",
+        ui.download_update(download_id, 0).is_ok(),
+    );
+    check(
+        "download_finish",
+        ui.download_finish(download_id, "1.0.0").is_ok(),
+    );
+    check(
+        "download_version_info_start",
+        ui.download_version_info_start().is_ok(),
+    );
+    check(
+        "download_version_info_failed",
+        ui.download_version_info_failed("1.0.0").is_ok(),
+    );
+    check(
+        "download_version_info_success",
+        ui.download_version_info_success().is_ok(),
+    );
+    check(
+        "download_version_info_cached",
+        ui.download_version_info_cached("1.0.0").is_ok(),
+    );
+    check(
+        "download_version_info_parse_failed",
+        ui.download_version_info_parse_failed("1.0.0", "1.0.0")
+            .is_ok(),
+    );
+    check(
+        "download_share_code_start",
+        ui.download_share_code_start().is_ok(),
+    );
+    check(
+        "download_share_code_failed",
+        ui.download_share_code_failed("1.0.0").is_ok(),
+    );
+    check(
+        "download_share_code_success",
+        ui.download_share_code_success().is_ok(),
+    );
+    check(
+        "download_attempt_github_dll",
+        ui.download_attempt_github_dll().is_ok(),
+    );
+    check(
+        "download_found_github_asset",
+        ui.download_found_github_asset("1.0.0").is_ok(),
+    );
+    check(
+        "download_github_dll_not_found",
+        ui.download_github_dll_not_found().is_ok(),
+    );
+    check(
+        "download_github_auth_required",
+        ui.download_github_auth_required().is_ok(),
+    );
+    check(
+        "download_display_github_release_notes",
+        ui.download_display_github_release_notes("1.0.0", "1.0.0", "1.0.0")
+            .is_ok(),
+    );
+    check(
+        "download_ask_continue_after_release_notes",
+        ui.download_ask_continue_after_release_notes().is_ok(),
+    );
+    check(
+        "download_switch_to_fallback",
+        ui.download_switch_to_fallback("1.0.0").is_ok(),
+    );
+    check(
+        "download_try_fallback_metamystia",
+        ui.download_try_fallback_metamystia().is_ok(),
+    );
+    check(
+        "download_bepinex_attempt_primary",
+        ui.download_bepinex_attempt_primary().is_ok(),
+    );
+    check(
+        "download_bepinex_primary_failed",
+        ui.download_bepinex_primary_failed("1.0.0").is_ok(),
+    );
+    check(
+        "download_all_sources_failed",
+        ui.download_all_sources_failed(&failures).is_ok(),
+    );
+    check(
+        "display_download_summary",
+        ui.display_download_summary(&reports).is_ok(),
+    );
+    check(
+        "network_retrying",
+        ui.network_retrying("1.0.0", 0, 1, 1, "1.0.0").is_ok(),
+    );
+    check("network_rate_limited", ui.network_rate_limited(0).is_ok());
+    check(
+        "display_network_diagnostics",
+        ui.display_network_diagnostics("1.0.0", "1.0.0").is_ok(),
+    );
+    check(
+        "manager_ask_self_update",
+        ui.manager_ask_self_update("1.0.0", "1.0.0").is_ok(),
+    );
+    check(
+        "display_self_update_downloaded",
+        ui.display_self_update_downloaded(&path, "1.0.0").is_ok(),
+    );
+    check(
+        "manager_update_starting",
+        ui.manager_update_starting().is_ok(),
+    );
+    check(
+        "manager_update_failed",
+        ui.manager_update_failed("1.0.0").is_ok(),
+    );
+    check(
+        "manager_prompt_manual_update",
+        ui.manager_prompt_manual_update("1.0.0").is_ok(),
+    );
+    check(
+        "select_version_ask_select",
+        ui.select_version_ask_select("1.0.0").is_ok(),
+    );
+    check(
+        "select_version_from_list",
+        ui.select_version_from_list("1.0.0", &versions).is_ok(),
+    );
+    check(
+        "select_version_not_available",
+        ui.select_version_not_available("1.0.0", "1.0.0", &versions)
+            .is_ok(),
+    );
+
+    failed
+}
@@ -1,54 +1,241 @@
 use crate::config::{OperationMode, UninstallMode};
-use crate::error::Result;
+use crate::downloader::{DownloadReport, DownloadSizeEstimate, PrefetchResult};
+use crate::env_check::{GameProcess, PathSource};
+use crate::error::{Result, SourceFailure};
+use crate::file_ops::DeletionResult;
 use crate::model::VersionInfo;
+use crate::progress::ProgressSnapshot;
 
+use serde::Serialize;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+/// 设置是否输出详细信息（--verbose）
+pub fn set_verbose(enabled: bool) {
+    VERBOSE.store(enabled, Ordering::SeqCst);
+}
+
+/// 是否输出详细信息
+pub fn verbose() -> bool {
+    VERBOSE.load(Ordering::SeqCst)
+}
+
+/// 等待结果
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WaitOutcome {
+    /// 等待时间已到
+    Elapsed,
+    /// 用户跳过等待，立即继续
+    Skipped,
+    /// 用户放弃后续重试
+    Aborted,
+}
+
+/// 待执行的组件升级
+#[derive(Clone, Debug)]
+pub struct PendingUpgrade {
+    pub component: String,
+    pub from_version: String,
+    pub to_version: String,
+}
+
+/// 重新安装时各组件是否覆盖（false 表示保留已有版本）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OverwriteSelection {
+    pub bepinex: bool,
+    pub metamystia: bool,
+    pub resourceex: bool,
+}
+
+impl OverwriteSelection {
+    /// 覆盖全部组件
+    pub fn all() -> Self {
+        Self {
+            bepinex: true,
+            metamystia: true,
+            resourceex: true,
+        }
+    }
+}
+
+/// 安装时的高级选项
+#[derive(Clone, Debug, Default)]
+pub struct AdvancedInstallOptions {
+    /// BepInEx 控制台日志级别（None 表示使用 BepInEx 默认值）
+    pub bepinex_log_levels: Option<Vec<String>>,
+    /// 清理前是否将待清理的文件打包为撤销存档
+    pub backup_before_install: bool,
+}
+
+/// 安装开始前汇总展示的安装计划
+#[derive(Clone, Debug)]
+pub struct InstallPlan {
+    /// 将安装的 BepInEx 版本（None 表示保留已有版本）
+    pub bepinex_version: Option<String>,
+    /// 将安装的 MetaMystia DLL 版本（None 表示保留已有版本）
+    pub dll_version: Option<String>,
+    /// 将安装的 ResourceExample ZIP 版本（None 表示不安装或保留已有版本）
+    pub resourceex_version: Option<String>,
+    /// 将被覆盖的已安装组件
+    pub overwrite: Vec<String>,
+    /// 安装前将被清理的文件与目录
+    pub cleanup_targets: Vec<PathBuf>,
+    /// 确认计划后将移动到 ResourceEx 目录的 ResourceExample ZIP
+    pub misplaced_resourceex: Vec<PathBuf>,
+    pub show_bepinex_console: bool,
+}
+
+/// 操作摘要中的变更类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SummaryAction {
+    Installed,
+    Upgraded,
+    Kept,
+    Removed,
+}
+
+/// 操作摘要中的一项变更
+#[derive(Clone, Debug, Serialize)]
+pub struct SummaryEntry {
+    pub component: String,
+    pub action: SummaryAction,
+    pub version: Option<String>,
+    pub path: Option<PathBuf>,
+}
+
+/// 安装、升级或卸载结束时展示的变更摘要
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct OperationSummary {
+    pub entries: Vec<SummaryEntry>,
+}
+
+impl OperationSummary {
+    /// 记录一项变更
+    pub fn push(
+        &mut self,
+        component: &str,
+        action: SummaryAction,
+        version: Option<&str>,
+        path: Option<PathBuf>,
+    ) {
+        self.entries.push(SummaryEntry {
+            component: component.to_string(),
+            action,
+            version: version.map(str::to_string),
+            path,
+        });
+    }
+}
 
 /// UI 抽象接口
 pub trait Ui: Send + Sync {
-    fn display_welcome(&self) -> Result<()>;
+    fn display_welcome(&self, is_first_run: bool) -> Result<()>;
+    fn ask_telemetry_consent(&self) -> Result<bool>;
     fn display_version(&self, manager_version: Option<&str>) -> Result<()>;
-    fn display_game_running_warning(&self) -> Result<()>;
+    /// 显示当前的更新通道（稳定版通道不显示）
+    fn display_update_channel_info(&self, channel: &str) -> Result<()>;
+    fn warn_old_manager_version(
+        &self,
+        current: &str,
+        latest: &str,
+        behind_versions: usize,
+    ) -> Result<()>;
+    fn display_game_running_warning(&self, processes: &[GameProcess]) -> Result<()>;
     fn display_available_updates(
         &self,
         dll_available: bool,
         resourceex_available: bool,
     ) -> Result<()>;
+    /// 无法获取版本信息时说明影响（仍可继续选择操作）
+    fn display_upgrade_check_failed(&self, err: &str) -> Result<()>;
+    fn display_progress_cancelled(&self, snapshot: &ProgressSnapshot) -> Result<()>;
     fn select_operation_mode(&self) -> Result<OperationMode>;
 
     fn blank_line(&self) -> Result<()>;
     fn wait_for_key(&self) -> Result<()>;
 
     // 通用输出
+    /// 一般提示（命令行 --quiet 时不显示）
     fn message(&self, text: &str) -> Result<()>;
-    #[allow(dead_code)]
+    /// 需要用户留意的问题（命令行 --quiet 时仍会显示）
     fn warn(&self, text: &str) -> Result<()>;
-    #[allow(dead_code)]
+    /// 错误（命令行 --quiet 时仍会显示）
     fn error(&self, text: &str) -> Result<()>;
 
+    /// 操作结束时汇总展示所有变更
+    fn display_operation_summary(&self, summary: &OperationSummary) -> Result<()>;
+
+    // 权限相关
+    fn display_controlled_folder_access_blocked(&self, app_path: &Path) -> Result<()>;
+
     // 目录相关
     fn path_display_steam_found(&self, app_id: u32, name: Option<&str>, path: &Path) -> Result<()>;
+    fn path_display_steam_stale(&self, app_id: u32, name: Option<&str>, path: &Path) -> Result<()>;
     fn path_confirm_use_steam_found(&self) -> Result<bool>;
+    fn select_game_path_from_candidates(
+        &self,
+        candidates: &[(PathBuf, PathSource)],
+    ) -> Result<PathBuf>;
+    fn path_ask_manual_entry(&self) -> Result<Option<PathBuf>>;
+    fn path_manual_entry_not_found(&self, input: &Path) -> Result<()>;
+    fn warn_conflicting_game_exe(&self, extras: &[PathBuf]) -> Result<bool>;
+    fn warn_cloud_sync_directory(&self, provider: &str) -> Result<()>;
 
     // 安装相关
     fn install_display_step(&self, step: usize, description: &str) -> Result<()>;
     fn install_display_version_info(&self, version_info: &VersionInfo) -> Result<()>;
+    fn install_display_estimated_download_size(
+        &self,
+        estimate: &DownloadSizeEstimate,
+    ) -> Result<()>;
+    fn install_display_estimated_duration(&self, estimated_secs: u64) -> Result<()>;
+    fn install_display_download_sources(&self, result: &PrefetchResult) -> Result<()>;
+    fn install_warn_slow_storage(&self, estimated_extra_mins: u64) -> Result<()>;
     fn install_warn_existing(
         &self,
         bepinex_installed: bool,
         metamystia_installed: bool,
         resourceex_installed: bool,
+        cleanup_plan: &[PathBuf],
     ) -> Result<()>;
     fn install_confirm_overwrite(&self) -> Result<bool>;
+    fn install_select_overwrite(
+        &self,
+        bepinex_installed: bool,
+        metamystia_installed: bool,
+        resourceex_installed: bool,
+    ) -> Result<OverwriteSelection>;
     fn install_ask_install_resourceex(&self) -> Result<bool>;
     fn install_ask_show_bepinex_console(&self) -> Result<bool>;
+    fn install_ask_configure_advanced_options(&self) -> Result<bool>;
+    fn install_select_bepinex_log_levels(&self) -> Result<Vec<String>>;
+    fn install_ask_backup_before_install(&self) -> Result<bool>;
+    /// 展示安装计划并做最终确认（返回 false 时不做任何下载或删除）
+    fn install_display_plan(&self, plan: &InstallPlan) -> Result<bool>;
     fn install_downloads_completed(&self) -> Result<()>;
     fn install_start_cleanup(&self) -> Result<()>;
+    /// 逐项列出清理结果（仅在 --verbose 时调用）
+    fn install_display_cleanup_detail(&self, items: &[DeletionResult]) -> Result<()>;
     fn install_cleanup_result(&self, success_count: usize, failed_count: usize) -> Result<()>;
-    fn install_finished(&self, show_bepinex_console: bool) -> Result<()>;
+    /// 安装完成提示（first_install 为 true 时还会调用 display_first_time_bepinex_info）
+    fn install_finished(&self, show_bepinex_console: bool, first_install: bool) -> Result<()>;
+    /// 详细说明 BepInEx 首次启动耗时较长的原因
+    fn display_first_time_bepinex_info(&self) -> Result<()>;
+    /// 说明 BepInEx 首次启动时会从配置的地址额外下载 Unity 基础库
+    fn warn_bepinex_il2cpp_download(&self, source_url: &str) -> Result<()>;
+    fn install_warn_loader_quarantined(&self, path: &Path, detection: Option<&str>) -> Result<()>;
+
+    // ResourceEx 迁移相关
+    fn resourceex_display_misplaced(&self, files: &[PathBuf]) -> Result<()>;
+    fn resourceex_confirm_migrate(&self) -> Result<bool>;
+    fn resourceex_migrate_failed(&self, path: &Path, err: &str) -> Result<()>;
 
     // 升级相关
     fn upgrade_warn_unparse_version(&self, filename: &str) -> Result<()>;
+    fn upgrade_found_duplicate(&self, path: &Path) -> Result<()>;
     fn upgrade_backup_failed(&self, err: &str) -> Result<()>;
     fn upgrade_deleted(&self, path: &Path) -> Result<()>;
     fn upgrade_delete_failed(&self, path: &Path, err: &str) -> Result<()>;
@@ -64,6 +251,8 @@ pub trait Ui: Send + Sync {
     fn upgrade_detected_new_dll(&self, current: &str, new: &str) -> Result<()>;
     fn upgrade_dll_already_latest(&self) -> Result<()>;
     fn upgrade_resourceex_needs_upgrade(&self) -> Result<()>;
+    /// 下载开始前一次性展示所有待升级组件并确认
+    fn confirm_batch_upgrade(&self, updates: &[PendingUpgrade]) -> Result<bool>;
     fn upgrade_downloading_dll(&self) -> Result<()>;
     fn upgrade_downloading_resourceex(&self) -> Result<()>;
     fn upgrade_installing_dll(&self) -> Result<()>;
@@ -72,11 +261,31 @@ pub trait Ui: Send + Sync {
     fn upgrade_cleanup_start(&self) -> Result<()>;
     fn upgrade_done(&self) -> Result<()>;
 
+    // 启动游戏相关
+    fn ask_launch_game(&self) -> Result<bool>;
+    fn launch_game_failed(&self, err: &str) -> Result<()>;
+
+    // 使用文档相关
+    fn ask_open_docs(&self) -> Result<bool>;
+    fn open_docs_failed(&self, url: &str) -> Result<()>;
+
+    // 设置相关
+    fn configure_ask_bepinex_console(&self, current: bool) -> Result<bool>;
+    fn configure_bepinex_console_changed(&self, previous: bool, enabled: bool) -> Result<()>;
+
     // 卸载相关
     fn uninstall_select_mode(&self) -> Result<UninstallMode>;
     fn uninstall_no_files_found(&self) -> Result<()>;
     fn uninstall_display_target_files(&self, files: &[PathBuf]) -> Result<()>;
     fn uninstall_confirm_deletion(&self) -> Result<bool>;
+    fn uninstall_ask_create_undo_archive(&self, estimated_size: u64) -> Result<bool>;
+    fn uninstall_undo_archive_insufficient_space(
+        &self,
+        required: u64,
+        available: u64,
+    ) -> Result<()>;
+    fn uninstall_undo_archive_created(&self, path: &Path) -> Result<()>;
+    fn restore_undo_finished(&self, archive: &Path, count: usize) -> Result<()>;
     fn uninstall_files_in_use_warning(&self) -> Result<()>;
     fn uninstall_wait_before_retry(
         &self,
@@ -84,13 +293,24 @@ pub trait Ui: Send + Sync {
         attempt: usize,
         attempts: usize,
     ) -> Result<()>;
+    /// 等待指定秒数，期间允许用户跳过或放弃
+    fn uninstall_wait_or_skip(&self, delay_secs: u64) -> Result<WaitOutcome>;
     fn uninstall_ask_elevate_permission(&self) -> Result<bool>;
     fn uninstall_restarting_elevated(&self) -> Result<()>;
     fn uninstall_ask_retry_failures(&self) -> Result<bool>;
     fn uninstall_retrying_failed_items(&self) -> Result<()>;
+    fn uninstall_display_disk_space_freed(&self, bytes_freed: u64) -> Result<()>;
+    fn uninstall_confirm_take_ownership(&self, paths: &[PathBuf]) -> Result<bool>;
+    fn uninstall_display_acl_fix_commands(&self, commands: &[String]) -> Result<()>;
+    fn uninstall_display_failure_breakdown(
+        &self,
+        locked: usize,
+        acl_denied: usize,
+        other: usize,
+    ) -> Result<()>;
 
     // 删除相关
-    fn deletion_start(&self) -> Result<()>;
+    fn deletion_start(&self, total: usize) -> Result<()>;
     fn deletion_display_progress(&self, current: usize, total: usize, path: &str) -> Result<()>;
     fn deletion_display_success(&self, path: &str) -> Result<()>;
     fn deletion_display_failure(&self, path: &str, error: &str) -> Result<()>;
@@ -102,6 +322,9 @@ pub trait Ui: Send + Sync {
         skipped_count: usize,
     ) -> Result<()>;
 
+    // 临时目录相关
+    fn temp_dir_warn_cross_volume(&self, temp_dir: &Path, game_root: &Path) -> Result<()>;
+
     // 下载相关
     /// 开始一个下载任务，返回一个用于后续更新的 id
     fn download_start(&self, filename: &str, total: Option<u64>) -> Result<usize>;
@@ -112,6 +335,10 @@ pub trait Ui: Send + Sync {
     fn download_version_info_start(&self) -> Result<()>;
     fn download_version_info_failed(&self, err: &str) -> Result<()>;
     fn download_version_info_success(&self) -> Result<()>;
+    /// 网络获取失败、改用磁盘缓存的版本信息时提示（cached_at 为缓存更新时间）
+    fn download_version_info_cached(&self, _cached_at: &str) -> Result<()> {
+        Ok(())
+    }
     fn download_version_info_parse_failed(&self, err: &str, snippet: &str) -> Result<()>;
     fn download_share_code_start(&self) -> Result<()>;
     fn download_share_code_failed(&self, err: &str) -> Result<()>;
@@ -119,6 +346,7 @@ pub trait Ui: Send + Sync {
     fn download_attempt_github_dll(&self) -> Result<()>;
     fn download_found_github_asset(&self, name: &str) -> Result<()>;
     fn download_github_dll_not_found(&self) -> Result<()>;
+    fn download_github_auth_required(&self) -> Result<()>;
     fn download_display_github_release_notes(
         &self,
         tag: &str,
@@ -130,6 +358,8 @@ pub trait Ui: Send + Sync {
     fn download_try_fallback_metamystia(&self) -> Result<()>;
     fn download_bepinex_attempt_primary(&self) -> Result<()>;
     fn download_bepinex_primary_failed(&self, err: &str) -> Result<()>;
+    fn download_all_sources_failed(&self, failures: &[SourceFailure]) -> Result<()>;
+    fn display_download_summary(&self, reports: &[DownloadReport]) -> Result<()>;
 
     // 网络相关
     fn network_retrying(
@@ -141,12 +371,14 @@ pub trait Ui: Send + Sync {
         err: &str,
     ) -> Result<()>;
     fn network_rate_limited(&self, secs: u64) -> Result<()>;
+    fn display_network_diagnostics(&self, endpoint: &str, last_error: &str) -> Result<()>;
 
     // 自升级相关
     fn manager_ask_self_update(&self, current_version: &str, latest_version: &str) -> Result<bool>;
+    fn display_self_update_downloaded(&self, path: &Path, version: &str) -> Result<()>;
     fn manager_update_starting(&self) -> Result<()>;
     fn manager_update_failed(&self, err: &str) -> Result<()>;
-    fn manager_prompt_manual_update(&self) -> Result<()>;
+    fn manager_prompt_manual_update(&self, reason: &str) -> Result<()>;
 
     // 版本选择相关
     fn select_version_ask_select(&self, component: &str) -> Result<bool>;
@@ -158,3 +390,52 @@ pub trait Ui: Send + Sync {
         available: &[String],
     ) -> Result<()>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Ui;
+    use crate::cli_ui::CliUI;
+    use crate::console_ui::ConsoleUI;
+    use crate::logging::LoggingUi;
+    use crate::test_utils::{TestUI, test_ui_coverage};
+
+    /// 仅约束类型实现了 Ui，签名与 trait 不一致时无法编译
+    fn assert_ui_impl<T: Ui>() {}
+
+    #[test]
+    fn test_all_impls() {
+        assert_ui_impl::<ConsoleUI>();
+        assert_ui_impl::<CliUI>();
+        assert_ui_impl::<LoggingUi<CliUI>>();
+        assert_ui_impl::<TestUI>();
+    }
+
+    /// CliUI 中仅交互模式支持、应返回错误的方法
+    const CLI_INTERACTIVE_ONLY: &[&str] = &[
+        "select_operation_mode",
+        "install_ask_install_resourceex",
+        "install_ask_show_bepinex_console",
+        "install_select_bepinex_log_levels",
+        "install_ask_backup_before_install",
+        "ask_launch_game",
+        "configure_ask_bepinex_console",
+        "uninstall_select_mode",
+    ];
+
+    #[test]
+    fn test_ui_handles_every_method() {
+        assert!(test_ui_coverage(&TestUI::new()).is_empty());
+        assert!(test_ui_coverage(&TestUI::with_confirm(false)).is_empty());
+    }
+
+    #[test]
+    fn cli_ui_handles_every_method() {
+        let ui = CliUI::new(true).with_output(std::io::sink(), std::io::sink());
+        let mut failed = test_ui_coverage(&ui);
+        failed.sort_unstable();
+        let mut expected = CLI_INTERACTIVE_ONLY.to_vec();
+        expected.sort_unstable();
+
+        assert_eq!(failed, expected);
+    }
+}
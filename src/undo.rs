@@ -0,0 +1,247 @@
+use crate::error::{ManagerError, Result};
+use crate::extractor::Extractor;
+use crate::file_ops::write_file_atomic;
+use crate::metrics::report_event;
+use crate::prefs::data_dir;
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+const UNDO_ARCHIVE_PREFIX: &str = "meta-mystia-undo-";
+/// 撤销存档保存在数据目录下的子目录
+const UNDO_DIR_NAME: &str = "undo";
+/// 提权重启前记录本次卸载已创建的存档
+const UNDO_SESSION_FILE_NAME: &str = "undo-session.json";
+/// 超过该时间的记录视为已过期（如用户拒绝了提权）
+const UNDO_SESSION_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// 提权重启前后属于同一次卸载的撤销存档
+#[derive(Deserialize, Serialize)]
+struct UndoSession {
+    game_root: PathBuf,
+    archive: PathBuf,
+    created_at: u64,
+}
+
+/// 递归收集路径下的所有文件与目录（目录在其内容之前）
+fn collect_entries(path: &Path, files: &mut Vec<PathBuf>, dirs: &mut Vec<PathBuf>) {
+    if path.is_dir() {
+        dirs.push(path.to_path_buf());
+        if let Ok(entries) = std::fs::read_dir(path) {
+            for entry in entries.flatten() {
+                collect_entries(&entry.path(), files, dirs);
+            }
+        }
+    } else if path.is_file() {
+        files.push(path.to_path_buf());
+    }
+}
+
+/// 估算将要删除的文件总大小（字节）
+pub fn estimate_size(paths: &[PathBuf]) -> u64 {
+    let mut files = Vec::new();
+    let mut dirs = Vec::new();
+    for p in paths {
+        collect_entries(p, &mut files, &mut dirs);
+    }
+
+    files
+        .iter()
+        .filter_map(|f| std::fs::metadata(f).ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 撤销存档所在目录（数据目录不可用时使用游戏根目录）
+pub fn undo_archive_dir(game_root: &Path) -> PathBuf {
+    data_dir()
+        .map(|dir| dir.join(UNDO_DIR_NAME))
+        .unwrap_or_else(|| game_root.to_path_buf())
+}
+
+fn undo_archive_path(game_root: &Path) -> PathBuf {
+    undo_archive_dir(game_root).join(format!("{}{}.zip", UNDO_ARCHIVE_PREFIX, now_secs()))
+}
+
+fn undo_session_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(UNDO_SESSION_FILE_NAME))
+}
+
+/// 提权重启前记录本次已创建的撤销存档，重启后的卸载不再重复创建
+pub fn remember_session_archive(game_root: &Path, archive: &Path) {
+    let Some(path) = undo_session_path() else {
+        return;
+    };
+    let session = UndoSession {
+        game_root: game_root.to_path_buf(),
+        archive: archive.to_path_buf(),
+        created_at: now_secs(),
+    };
+
+    if let Ok(content) = serde_json::to_vec(&session) {
+        let _ = write_file_atomic(&path, &content);
+    }
+}
+
+/// 取出提权重启前为同一游戏目录创建的撤销存档（记录只使用一次）
+pub fn take_session_archive(game_root: &Path) -> Option<PathBuf> {
+    let path = undo_session_path()?;
+    let content = std::fs::read(&path).ok()?;
+    let _ = std::fs::remove_file(&path);
+
+    let session: UndoSession = serde_json::from_slice(&content).ok()?;
+    let fresh = now_secs().saturating_sub(session.created_at) < UNDO_SESSION_TTL.as_secs();
+
+    (fresh && session.game_root == game_root && session.archive.is_file())
+        .then_some(session.archive)
+}
+
+/// 条目名称使用相对于游戏根目录的路径，统一为 `/` 分隔
+fn entry_name(game_root: &Path, path: &Path) -> Result<String> {
+    let relative = path
+        .strip_prefix(game_root)
+        .map_err(|_| ManagerError::Other(format!("{} 不在游戏目录内", path.display())))?;
+
+    Ok(relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
+fn write_archive(archive_path: &Path, game_root: &Path, paths: &[PathBuf]) -> Result<usize> {
+    if let Some(dir) = archive_path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let file = File::create(archive_path).map_err(|e| {
+        ManagerError::from(std::io::Error::new(
+            e.kind(),
+            format!("创建撤销存档 {} 失败：{}", archive_path.display(), e),
+        ))
+    })?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut files = Vec::new();
+    let mut dirs = Vec::new();
+    for p in paths {
+        collect_entries(p, &mut files, &mut dirs);
+    }
+
+    for dir in &dirs {
+        zip.add_directory(format!("{}/", entry_name(game_root, dir)?), options)
+            .map_err(|e| ManagerError::Other(format!("写入撤销存档失败：{}", e)))?;
+    }
+
+    for path in &files {
+        let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        zip.start_file(
+            entry_name(game_root, path)?,
+            options.large_file(size >= u32::MAX as u64),
+        )
+        .map_err(|e| ManagerError::Other(format!("写入撤销存档失败：{}", e)))?;
+
+        let mut src = File::open(path).map_err(|e| {
+            ManagerError::from(std::io::Error::new(
+                e.kind(),
+                format!("读取文件 {} 失败：{}", path.display(), e),
+            ))
+        })?;
+        std::io::copy(&mut src, &mut zip)?;
+    }
+
+    zip.finish()
+        .map_err(|e| ManagerError::Other(format!("写入撤销存档失败：{}", e)))?;
+
+    Ok(files.len())
+}
+
+/// 将即将删除的文件打包为撤销存档，返回存档路径
+pub fn create_undo_archive(game_root: &Path, paths: &[PathBuf]) -> Result<PathBuf> {
+    let archive_path = undo_archive_path(game_root);
+
+    match write_archive(&archive_path, game_root, paths) {
+        Ok(count) => {
+            report_event("Undo.Created", Some(&format!("count:{}", count)));
+            Ok(archive_path)
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&archive_path);
+            report_event("Undo.CreateFailed", Some(&format!("{}", e)));
+            Err(e)
+        }
+    }
+}
+
+/// 将撤销存档解压回游戏根目录，返回恢复的文件数量
+pub fn restore_undo_archive(archive_path: &Path, game_root: &Path) -> Result<usize> {
+    if !archive_path.is_file() {
+        return Err(ManagerError::Other(format!(
+            "撤销存档不存在：{}",
+            archive_path.display()
+        )));
+    }
+
+    let restored = Extractor::extract_zip_safe(archive_path, game_root)?;
+    report_event("Undo.Restored", Some(&format!("count:{}", restored.len())));
+
+    Ok(restored.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{ScratchDir, rerun_with_scratch_appdata};
+
+    #[test]
+    fn undo_archive_restores_deleted_files() {
+        if rerun_with_scratch_appdata("undo::tests::undo_archive_restores_deleted_files") {
+            return;
+        }
+
+        let game = ScratchDir::new("undo-round-trip");
+        let files = [
+            ("winhttp.dll", b"doorstop".as_slice()),
+            ("BepInEx/plugins/MetaMystia-v1.0.0.dll", b"dll"),
+            (
+                "BepInEx/config/MetaMystia/nested/settings.cfg",
+                b"[General]",
+            ),
+        ];
+        for (path, content) in files {
+            game.write(path, content);
+        }
+        game.mkdir("BepInEx/cache/empty");
+
+        let targets = [game.path().join("winhttp.dll"), game.path().join("BepInEx")];
+        let archive = create_undo_archive(game.path(), &targets).unwrap();
+        assert!(archive.starts_with(data_dir().unwrap()));
+
+        std::fs::remove_file(&targets[0]).unwrap();
+        std::fs::remove_dir_all(&targets[1]).unwrap();
+
+        let restored = restore_undo_archive(&archive, game.path()).unwrap();
+        assert_eq!(restored, files.len());
+        for (path, content) in files {
+            assert_eq!(
+                std::fs::read(game.path().join(path)).unwrap(),
+                content,
+                "{}",
+                path
+            );
+        }
+        assert!(game.path().join("BepInEx/cache/empty").is_dir());
+    }
+}
@@ -1,33 +1,152 @@
 use crate::config::{RetryConfig, UninstallMode};
 use crate::error::{ManagerError, Result};
 use crate::file_ops::{
-    DeletionStatus, count_results, execute_deletion, extract_failed_files, scan_existing_files,
+    DeletionResult, DeletionStatus, FailureKind, available_space, count_failures, count_results,
+    execute_deletion, extract_failed_files, scan_existing_files, total_bytes_freed,
 };
 use crate::metrics::report_event;
-use crate::permission::{elevate_and_restart, is_elevated};
+use crate::model::UninstallTarget;
+use crate::permission::{
+    acl_fix_commands, elevate_and_restart, is_controlled_folder_access_enabled, is_elevated,
+    take_ownership,
+};
 use crate::shutdown::run_shutdown;
-use crate::ui::Ui;
+use crate::ui::{OperationSummary, SummaryAction, Ui, WaitOutcome};
+use crate::undo::{
+    create_undo_archive, estimate_size, remember_session_archive, take_session_archive,
+    undo_archive_dir,
+};
 
 use std::collections::HashSet;
 use std::path::PathBuf;
-use std::thread::sleep;
-use std::time::Duration;
 
 /// 卸载管理器
 pub struct Uninstaller<'a> {
     game_root: PathBuf,
     ui: &'a dyn Ui,
+    /// 版本信息中下发的额外卸载目标（未获取版本信息时为空）
+    remote_targets: Vec<UninstallTarget>,
 }
 
 impl<'a> Uninstaller<'a> {
     pub fn new(game_root: PathBuf, ui: &'a dyn Ui) -> Result<Self> {
-        Ok(Self { game_root, ui })
+        Ok(Self {
+            game_root,
+            ui,
+            remote_targets: Vec::new(),
+        })
+    }
+
+    /// 合并版本信息中下发的卸载目标
+    pub fn with_remote_targets(mut self, targets: &[UninstallTarget]) -> Self {
+        self.remote_targets = targets.to_vec();
+        self
+    }
+
+    /// 删除前将目标文件打包为撤销存档（undo_archive 为 None 时询问用户），返回存档路径
+    fn create_undo_archive(
+        &self,
+        files: &[PathBuf],
+        undo_archive: Option<bool>,
+    ) -> Result<Option<PathBuf>> {
+        // 提权重启前已为本次卸载创建过存档，其中包含了此时仍残留的文件
+        if let Some(archive_path) = take_session_archive(&self.game_root) {
+            report_event("Undo.Reused", None);
+            self.ui.uninstall_undo_archive_created(&archive_path)?;
+            return Ok(Some(archive_path));
+        }
+
+        let estimated_size = estimate_size(files);
+
+        let create = match undo_archive {
+            Some(create) => create,
+            None => self.ui.uninstall_ask_create_undo_archive(estimated_size)?,
+        };
+        if !create {
+            return Ok(None);
+        }
+
+        if let Some(available) = available_space(&undo_archive_dir(&self.game_root))
+            && available < estimated_size
+        {
+            self.ui
+                .uninstall_undo_archive_insufficient_space(estimated_size, available)?;
+            report_event(
+                "Undo.InsufficientSpace",
+                Some(&format!(
+                    "required:{};available:{}",
+                    estimated_size, available
+                )),
+            );
+            return Err(ManagerError::Other(
+                "磁盘空间不足，无法创建撤销存档".to_string(),
+            ));
+        }
+
+        let archive_path = create_undo_archive(&self.game_root, files)?;
+        self.ui.uninstall_undo_archive_created(&archive_path)?;
+
+        Ok(Some(archive_path))
+    }
+
+    /// 处理管理员权限下仍被拒绝访问的项：经用户确认后获取所有权并重试，仍失败的项给出手动命令
+    fn handle_acl_denied(&self, all_results: &mut Vec<DeletionResult>) -> Result<()> {
+        let denied = |results: &[DeletionResult]| -> Vec<PathBuf> {
+            results
+                .iter()
+                .filter(|r| r.failure_kind() == Some(FailureKind::AclDenied))
+                .map(|r| r.path.clone())
+                .collect()
+        };
+
+        let targets = denied(all_results);
+        report_event("Uninstall.AclDenied", Some(&targets.len().to_string()));
+
+        if self.ui.uninstall_confirm_take_ownership(&targets)? {
+            let owned: Vec<PathBuf> = targets
+                .iter()
+                .filter(|p| match take_ownership(p) {
+                    Ok(()) => true,
+                    Err(e) => {
+                        report_event("Uninstall.TakeOwnership.Failed", Some(&format!("{}", e)));
+                        false
+                    }
+                })
+                .cloned()
+                .collect();
+
+            if !owned.is_empty() {
+                let retry_results = execute_deletion(&owned, self.ui);
+                all_results.retain(|r| !owned.contains(&r.path));
+                all_results.extend(retry_results);
+            }
+        }
+
+        let remaining = denied(all_results);
+        report_event(
+            "Uninstall.AclDenied.Result",
+            Some(&format!(
+                "fixed:{};remaining:{}",
+                targets.len() - remaining.len(),
+                remaining.len()
+            )),
+        );
+        if !remaining.is_empty() {
+            let commands: Vec<String> =
+                remaining.iter().flat_map(|p| acl_fix_commands(p)).collect();
+            self.ui.uninstall_display_acl_fix_commands(&commands)?;
+        }
+
+        Ok(())
     }
 
     /// 执行卸载流程
-    pub fn uninstall(&self, mode: Option<UninstallMode>) -> Result<()> {
+    pub fn uninstall(&self, mode: Option<UninstallMode>, undo_archive: Option<bool>) -> Result<()> {
         report_event("Uninstall.Start", None);
 
+        // 通过命令行指定模式时为无人值守运行，残留失败项需要以错误返回
+        let unattended = mode.is_some();
+
         // 1. 选择卸载模式（如果 mode 存在则使用，否则询问用户）
         let mode = if let Some(m) = mode {
             m
@@ -38,7 +157,8 @@ impl<'a> Uninstaller<'a> {
         report_event("Uninstall.ModeSelected", Some(&mode_desc));
 
         // 2. 扫描实际存在的文件（相对于游戏目录）
-        let existing_files = scan_existing_files(&self.game_root, mode);
+        let targets = mode.merged_targets(&self.remote_targets);
+        let existing_files = scan_existing_files(&self.game_root, &targets);
 
         if existing_files.is_empty() {
             self.ui.uninstall_no_files_found()?;
@@ -56,13 +176,18 @@ impl<'a> Uninstaller<'a> {
         }
         report_event("Uninstall.Confirmed", Some(&mode_desc));
 
-        // 5. 检查当前权限状态
+        // 5. 按需创建撤销存档
+        let archive_path = self.create_undo_archive(&existing_files, undo_archive)?;
+
+        // 6. 检查当前权限状态
         let is_elevated = is_elevated()?;
 
-        // 6. 执行删除操作
+        // 7. 执行删除操作
         let mut all_results = execute_deletion(&existing_files, self.ui);
+        // 已具有管理员权限时是否已重试过被拒绝访问的项
+        let mut elevated_retried = false;
 
-        // 7. 处理失败项
+        // 8. 处理失败项
         loop {
             let failed_files = extract_failed_files(&all_results);
             if failed_files.is_empty() {
@@ -105,7 +230,10 @@ impl<'a> Uninstaller<'a> {
                     self.ui
                         .uninstall_wait_before_retry(delay_secs, attempt + 1, cfg.attempts)?;
 
-                    sleep(Duration::from_secs(delay_secs));
+                    if self.ui.uninstall_wait_or_skip(delay_secs)? == WaitOutcome::Aborted {
+                        report_event("Uninstall.RetryAborted", None);
+                        break;
+                    }
 
                     let retry_results = execute_deletion(&still_in_use, self.ui);
 
@@ -140,8 +268,26 @@ impl<'a> Uninstaller<'a> {
                 _ => false,
             });
 
-            if has_permission_issue && !is_elevated && self.ui.uninstall_ask_elevate_permission()? {
-                elevate_and_restart()?;
+            // 受控文件夹访问会阻止所有未加入允许列表的程序写入，提升权限也无济于事
+            if has_permission_issue && is_controlled_folder_access_enabled() {
+                report_event("Permission.ControlledFolderAccess", Some("uninstall"));
+                self.ui
+                    .display_controlled_folder_access_blocked(&std::env::current_exe()?)?;
+            } else if has_permission_issue && is_elevated && elevated_retried {
+                // 管理员权限下重试后仍被拒绝，再次重试不会有变化
+                self.handle_acl_denied(&mut all_results)?;
+                break;
+            } else if has_permission_issue
+                && !is_elevated
+                && self.ui.uninstall_ask_elevate_permission()?
+            {
+                if let Some(ref archive_path) = archive_path {
+                    remember_session_archive(&self.game_root, archive_path);
+                }
+                if let Err(e) = elevate_and_restart() {
+                    take_session_archive(&self.game_root);
+                    return Err(e);
+                }
                 self.ui.uninstall_restarting_elevated()?;
                 run_shutdown();
                 std::process::exit(0);
@@ -150,6 +296,7 @@ impl<'a> Uninstaller<'a> {
             if !self.ui.uninstall_ask_retry_failures()? {
                 break;
             }
+            elevated_retried = is_elevated && has_permission_issue;
 
             self.ui.uninstall_retrying_failed_items()?;
 
@@ -177,17 +324,56 @@ impl<'a> Uninstaller<'a> {
             }
         }
 
-        // 8. 显示操作摘要
+        // 9. 显示操作摘要
         let (success, failed, skipped) = count_results(&all_results);
         self.ui.deletion_display_summary(success, failed, skipped)?;
+        let (locked, acl_denied, other) = count_failures(&all_results);
+        if failed > 0 {
+            self.ui
+                .uninstall_display_failure_breakdown(locked, acl_denied, other)?;
+        }
+        let bytes_freed = total_bytes_freed(&all_results);
+        if bytes_freed > 0 {
+            self.ui.uninstall_display_disk_space_freed(bytes_freed)?;
+        }
+
+        let mut summary = OperationSummary::default();
+        for r in &all_results {
+            if matches!(r.status, DeletionStatus::Success) {
+                let component = r
+                    .path
+                    .strip_prefix(&self.game_root)
+                    .unwrap_or(&r.path)
+                    .display()
+                    .to_string();
+                summary.push(
+                    &component,
+                    SummaryAction::Removed,
+                    None,
+                    Some(r.path.clone()),
+                );
+            }
+        }
+        self.ui.display_operation_summary(&summary)?;
         report_event(
             "Uninstall.Finished",
             Some(&format!(
-                "success:{};failed:{};skipped:{}",
-                success, failed, skipped
+                "success:{};failed:{};skipped:{};locked:{};acl_denied:{};other:{}",
+                success, failed, skipped, locked, acl_denied, other
             )),
         );
 
+        if unattended && failed > 0 {
+            let total = all_results.len();
+            return Err(ManagerError::PartialFailure {
+                failed: all_results
+                    .into_iter()
+                    .filter(|r| matches!(r.status, DeletionStatus::Failed(_)))
+                    .collect(),
+                total,
+            });
+        }
+
         Ok(())
     }
 }
@@ -1,30 +1,125 @@
 use crate::downloader::Downloader;
 use crate::error::{ManagerError, Result};
+use crate::file_ops::{DeletionResult, execute_deletion, glob_matches, write_file_atomic};
 use crate::metrics::report_event;
-use crate::model::VersionInfo;
-use crate::temp_dir::create_temp_dir_with_guard;
+use crate::model::{UpdateChannel, VersionInfo};
+use crate::prefs::{Preferences, data_dir};
+use crate::temp_dir::{create_temp_dir_with_guard, known_temp_dirs};
 use crate::ui::Ui;
 
+use semver::Version;
 use std::os::windows::process::CommandExt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::OnceLock;
 use windows::Win32::System::Threading::CREATE_NO_WINDOW;
 
+/// 落后超过该数量的主/次版本时显示醒目警告
+pub const OUTDATED_WARN_THRESHOLD: usize = 2;
+
+/// 计算当前版本落后最新版本多少个主/次版本，无法解析时返回 None
+///
+/// 主版本不同时，按主版本差加上最新版本的次版本号计算。
+pub fn versions_behind(current: &str, latest: &str) -> Option<usize> {
+    let current = Version::parse(current).ok()?;
+    let latest = Version::parse(latest).ok()?;
+
+    let behind = if latest.major > current.major {
+        (latest.major - current.major) + latest.minor
+    } else if latest.major == current.major {
+        latest.minor.saturating_sub(current.minor)
+    } else {
+        0
+    };
+
+    Some(behind as usize)
+}
+
+/// 命令行指定的更新通道
+static UPDATE_CHANNEL_OVERRIDE: OnceLock<UpdateChannel> = OnceLock::new();
+
+/// 设置命令行指定的更新通道，并保存到偏好设置供之后使用
+pub fn configure_update_channel(channel: Option<UpdateChannel>) {
+    let Some(channel) = channel else {
+        return;
+    };
+    let _ = UPDATE_CHANNEL_OVERRIDE.set(channel);
+
+    let mut prefs = Preferences::load();
+    if prefs.update_channel != Some(channel) {
+        prefs.update_channel = Some(channel);
+        let _ = prefs.save();
+        report_event("SelfUpdate.Channel.Changed", Some(channel.name()));
+    }
+}
+
+/// 当前使用的更新通道：命令行参数优先，其次为偏好设置，最后根据当前版本号判断
+pub fn update_channel() -> UpdateChannel {
+    UPDATE_CHANNEL_OVERRIDE
+        .get()
+        .copied()
+        .or(Preferences::load().update_channel)
+        .unwrap_or_else(|| UpdateChannel::of_version(env!("CARGO_PKG_VERSION")))
+}
+
+/// 写入保护（只读介质）的 Windows 错误码 ERROR_WRITE_PROTECT
+const ERROR_WRITE_PROTECT: i32 = 19;
+
+/// 在运行目录中创建并删除探测文件，无法写入时返回原因
+///
+/// 从光盘/镜像、压缩包预览或被同步软件锁定的目录运行时，升级会在复制新版本时才失败。
+pub fn run_dir_write_problem() -> Option<String> {
+    let exe_path = std::env::current_exe().ok()?;
+    let run_dir = exe_path.parent()?;
+    let probe = run_dir.join(format!(".meta-mystia-write-test-{}", std::process::id()));
+
+    let err = match std::fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            return None;
+        }
+        Err(e) => e,
+    };
+
+    let (kind, reason) = if err.raw_os_error() == Some(ERROR_WRITE_PROTECT)
+        || err.kind() == std::io::ErrorKind::ReadOnlyFilesystem
+    {
+        (
+            "read_only_media",
+            "管理工具位于只读位置（如光盘、磁盘镜像或压缩包预览）".to_string(),
+        )
+    } else if err.kind() == std::io::ErrorKind::PermissionDenied {
+        (
+            "permission",
+            "没有写入管理工具所在目录的权限（该目录可能被同步软件锁定）".to_string(),
+        )
+    } else {
+        ("other", format!("无法写入管理工具所在目录：{}", err))
+    };
+
+    report_event(
+        "SelfUpdate.RunDirNotWritable",
+        Some(&format!("{};err={}", kind, err)),
+    );
+
+    Some(reason)
+}
+
 pub fn perform_self_update(
     game_root: &Path,
     ui: &dyn Ui,
     downloader: &Downloader,
-    version_info: &VersionInfo,
+    version: &str,
     auto_launch: bool,
 ) -> Result<String> {
-    report_event("SelfUpdate.Start", Some(&version_info.manager));
+    report_event("SelfUpdate.Start", Some(version));
 
     // 1. 准备临时目录并下载
     let (temp_dir, _guard) = create_temp_dir_with_guard(game_root)?;
-    let filename = version_info.manager_filename();
+    let filename = VersionInfo::manager_filename(version);
     let temp_path = temp_dir.join(&filename);
 
-    if let Err(e) = downloader.download_manager(version_info, &temp_path) {
+    if let Err(e) = downloader.download_manager(version, &temp_path) {
         ui.manager_update_failed(&format!("下载失败：{}", e))?;
         report_event("SelfUpdate.Failed.Download", Some(&format!("{}", e)));
         return Err(e);
@@ -38,9 +133,9 @@ pub fn perform_self_update(
     let target_path = run_dir.join(&filename);
 
     match std::fs::copy(&temp_path, &target_path) {
-        Ok(_) => {}
+        Ok(_) => ui.display_self_update_downloaded(&target_path, version)?,
         Err(e) => {
-            ui.manager_prompt_manual_update()?;
+            ui.manager_prompt_manual_update("无法向当前运行目录写入文件")?;
             report_event("SelfUpdate.Failed.Copy", Some(&format!("{}", e)));
             return Err(ManagerError::from(std::io::Error::new(
                 e.kind(),
@@ -64,12 +159,8 @@ pub fn perform_self_update(
         auto_launch,
     );
 
-    std::fs::write(&script_path, script.as_bytes()).map_err(|e| {
+    write_file_atomic(&script_path, script.as_bytes()).inspect_err(|e| {
         report_event("SelfUpdate.Failed.ScriptWrite", Some(&format!("{}", e)));
-        ManagerError::from(std::io::Error::new(
-            e.kind(),
-            format!("写入升级脚本 {} 失败：{}", script_path.display(), e),
-        ))
     })?;
 
     if !script_path.exists() {
@@ -85,30 +176,128 @@ pub fn perform_self_update(
     }
 
     // 4. 启动脚本
+    if spawn_powershell_script(&script_path) {
+        report_event("SelfUpdate.Scheduled", Some(version));
+        ui.manager_update_starting()?;
+        return Ok(filename);
+    }
+
+    ui.manager_update_failed("无法执行升级脚本")?;
+    Err(ManagerError::Other("无法启动 PowerShell".to_string()))
+}
+
+/// 在后台启动 PowerShell 脚本（优先 pwsh），返回是否启动成功
+fn spawn_powershell_script(script_path: &Path) -> bool {
     let shells = ["pwsh.exe", "powershell.exe"];
 
-    for shell in &shells {
-        let res = Command::new(shell)
+    shells.iter().any(|shell| {
+        Command::new(shell)
             .arg("-NoProfile")
             .arg("-ExecutionPolicy")
             .arg("Bypass")
             .arg("-File")
-            .arg(&script_path)
+            .arg(script_path)
             .creation_flags(CREATE_NO_WINDOW.0)
-            .spawn();
+            .spawn()
+            .is_ok()
+    })
+}
+
+/// 收集管理工具自身产生的文件：运行目录中的其他版本、备份与崩溃日志，
+/// 数据目录（偏好设置与版本缓存）、升级脚本以及已知位置的临时目录。不包含任何游戏文件
+pub fn self_uninstall_targets() -> Result<Vec<PathBuf>> {
+    let exe_path = std::env::current_exe()?;
+    let run_dir = exe_path
+        .parent()
+        .ok_or_else(|| ManagerError::Other("无法确定运行目录".to_string()))?;
 
-        if res.is_ok() {
-            report_event("SelfUpdate.Scheduled", Some(&version_info.manager));
-            ui.manager_update_starting()?;
-            return Ok(filename);
+    let mut targets = Vec::new();
+    for pattern in [
+        "meta-mystia-manager-v*.exe",
+        "meta-mystia-manager*.exe.old.*",
+        "meta-mystia-crash-*.log",
+    ] {
+        for path in glob_matches(&run_dir.join(pattern)) {
+            if path != exe_path && !targets.contains(&path) {
+                targets.push(path);
+            }
         }
     }
 
-    ui.manager_update_failed("无法执行升级脚本")?;
-    Err(ManagerError::Other("无法启动 PowerShell".to_string()))
+    if let Some(dir) = data_dir()
+        && dir.is_dir()
+    {
+        targets.push(dir);
+    }
+
+    let script_pattern = std::env::temp_dir().join(format!("{}-*.ps1", env!("CARGO_PKG_NAME")));
+    targets.extend(glob_matches(&script_pattern));
+
+    targets.extend(known_temp_dirs());
+
+    Ok(targets)
 }
 
+/// 删除管理工具自身的文件，并在进程退出后通过脚本删除正在运行的 exe
+pub fn perform_self_uninstall(ui: &dyn Ui, targets: &[PathBuf]) -> Result<Vec<DeletionResult>> {
+    report_event("SelfUninstall.Start", Some(&targets.len().to_string()));
+
+    let results = execute_deletion(targets, ui);
+
+    let exe_path = std::env::current_exe()?;
+    let script_path = std::env::temp_dir().join(format!(
+        "{}-uninstall_{}.ps1",
+        env!("CARGO_PKG_NAME"),
+        std::process::id()
+    ));
+    let script = generate_self_delete_script(&exe_path.to_string_lossy(), std::process::id());
+    write_file_atomic(&script_path, script.as_bytes())?;
+
+    if !spawn_powershell_script(&script_path) {
+        report_event("SelfUninstall.Failed.Script", None);
+        return Err(ManagerError::Other(format!(
+            "无法启动 PowerShell，请手动删除 {}",
+            exe_path.display()
+        )));
+    }
+
+    report_event("SelfUninstall.Scheduled", None);
+    Ok(results)
+}
+
+fn generate_self_delete_script(exe: &str, pid: u32) -> String {
+    format!(
+        r#"param(
+    [string]$Exe = '{exe}',
+    [int]$OldPid = {pid}
+)
+
+# 等待进程退出
+$start = Get-Date
+while ((Get-Date) -lt $start.AddSeconds(30)) {{
+    $p = Get-Process -Id $OldPid -ErrorAction SilentlyContinue
+    if ($null -eq $p) {{ break }}
+    Start-Sleep -Seconds 1
+}}
+
+for ($i = 0; $i -lt 5 -and (Test-Path $Exe); $i++) {{
+    try {{ Remove-Item -Path $Exe -Force -ErrorAction Stop }} catch {{ Start-Sleep -Seconds 1 }}
+}}
+
+# 删除脚本自身
+Remove-Item -Path $MyInvocation.MyCommand.Path -Force -ErrorAction SilentlyContinue
+
+exit 0
+"#,
+        exe = exe.replace('\'', "''")
+    )
+}
+
+/// 升级脚本等待新 exe 完成 --health-check 的最长时间
+const HEALTH_CHECK_TIMEOUT_MS: u64 = 15_000;
+
 fn generate_powershell_script(target: &str, new_exe: &str, pid: u32, auto_launch: bool) -> String {
+    let health_check_timeout_ms = HEALTH_CHECK_TIMEOUT_MS;
     let launch_script = if auto_launch {
         r#"
 # 启动新 exe
@@ -165,6 +354,28 @@ if (Test-Path $Old) {{
         $bak = $null
     }}
 }}
+
+# 先以 --health-check 运行新 exe，确认可以正常启动
+$healthy = $false
+try {{
+    $hc = Start-Process -FilePath $New -ArgumentList '--health-check' -WindowStyle Hidden -PassThru -ErrorAction Stop
+    $null = $hc.Handle
+    if ($hc.WaitForExit({health_check_timeout_ms}) -and $hc.ExitCode -eq 0) {{
+        $healthy = $true
+    }} else {{
+        try {{ $hc.Kill() }} catch {{}}
+    }}
+}} catch {{}}
+
+# 新 exe 无法运行时恢复旧 exe
+if (-not $healthy) {{
+    Write-Output "Health check of $New failed, restoring $Old"
+    if ($bak -ne $null -and (Test-Path $bak)) {{
+        try {{ Move-Item -Path $bak -Destination $Old -Force -ErrorAction Stop }} catch {{}}
+    }}
+    try {{ Remove-Item -Path $New -Force -ErrorAction SilentlyContinue }} catch {{}}
+    exit 2
+}}
 {launch_script}
 # 清理
 Start-Sleep -Seconds 1
@@ -173,6 +384,87 @@ if ($bak -ne $null -and (Test-Path $bak)) {{
 }}
 
 exit 0
-"#
+"#,
+        target = target.replace('\'', "''"),
+        new_exe = new_exe.replace('\'', "''"),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_behind_counts_minor_versions() {
+        assert_eq!(versions_behind("1.7.0", "1.7.3"), Some(0));
+        assert_eq!(versions_behind("1.5.2", "1.7.0"), Some(2));
+        assert_eq!(versions_behind("1.8.0", "1.7.0"), Some(0));
+        assert_eq!(versions_behind("2.0.0", "1.9.0"), Some(0));
+    }
+
+    #[test]
+    fn versions_behind_adds_latest_minor_across_major_versions() {
+        assert_eq!(versions_behind("1.9.0", "2.0.0"), Some(1));
+        assert_eq!(versions_behind("1.2.0", "3.4.0"), Some(6));
+    }
+
+    #[test]
+    fn versions_behind_rejects_unparsable_versions() {
+        assert_eq!(versions_behind("1.7", "1.8.0"), None);
+        assert_eq!(versions_behind("1.7.0", "latest"), None);
+    }
+
+    #[test]
+    fn update_script_health_checks_before_launch_and_cleanup() {
+        let script = generate_powershell_script(
+            r"C:\Games\manager.exe",
+            r"C:\Games\manager.new.exe",
+            4242,
+            true,
+        );
+
+        assert!(script.contains(r"[string]$Old = 'C:\Games\manager.exe'"));
+        assert!(script.contains(r"[string]$New = 'C:\Games\manager.new.exe'"));
+        assert!(script.contains("[int]$OldPid = 4242"));
+        assert!(script.contains("-ArgumentList '--health-check'"));
+        assert!(script.contains(&format!("WaitForExit({})", HEALTH_CHECK_TIMEOUT_MS)));
+
+        let health_check = script.find("--health-check").unwrap();
+        let restore = script
+            .find("Move-Item -Path $bak -Destination $Old")
+            .unwrap();
+        let launch = script
+            .find("Start-Process -FilePath $New -WorkingDirectory")
+            .unwrap();
+        let cleanup = script.find("Remove-Item -Path $bak").unwrap();
+        assert!(health_check < restore);
+        assert!(health_check < launch);
+        assert!(launch < cleanup);
+        assert!(script.contains("exit 2"));
+    }
+
+    #[test]
+    fn update_script_without_auto_launch_still_health_checks() {
+        let script = generate_powershell_script("old.exe", "new.exe", 1, false);
+
+        assert!(script.contains("-ArgumentList '--health-check'"));
+        assert!(!script.contains("Start-Process -FilePath $New -WorkingDirectory"));
+    }
+
+    #[test]
+    fn scripts_escape_single_quotes_in_paths() {
+        let update = generate_powershell_script(
+            r"C:\Users\O'Brien\manager.exe",
+            r"C:\Users\O'Brien\new.exe",
+            1,
+            true,
+        );
+        assert!(update.contains(r"'C:\Users\O''Brien\manager.exe'"));
+        assert!(update.contains(r"'C:\Users\O''Brien\new.exe'"));
+
+        let delete = generate_self_delete_script(r"C:\Users\O'Brien\manager.exe", 99);
+        assert!(delete.contains(r"[string]$Exe = 'C:\Users\O''Brien\manager.exe'"));
+        assert!(delete.contains("[int]$OldPid = 99"));
+        assert!(delete.contains("Remove-Item -Path $Exe -Force"));
+    }
+}
@@ -1,12 +1,16 @@
-use crate::downloader::Downloader;
+use crate::config::RESOURCEEX_MARKER;
+use crate::downloader::{Downloader, record_download_speed};
 use crate::error::{ManagerError, Result};
+use crate::extractor::Extractor;
 use crate::file_ops::{
-    atomic_rename_or_copy, backup_paths_with_index, glob_matches, remove_glob_files,
+    atomic_rename_or_copy, backup_paths_with_index, clear_glob_cache, glob_matches,
+    remove_glob_files,
 };
+use crate::installer::migrate_misplaced_resourceex;
 use crate::metrics::report_event;
-use crate::model::VersionInfo;
-use crate::temp_dir::create_temp_dir_with_guard;
-use crate::ui::Ui;
+use crate::model::{VersionInfo, is_newer_version};
+use crate::temp_dir::{create_temp_dir_with_guard, is_cross_volume};
+use crate::ui::{OperationSummary, PendingUpgrade, SummaryAction, Ui};
 
 use semver::Version;
 use std::path::{Path, PathBuf};
@@ -38,8 +42,56 @@ impl<'a> Upgrader<'a> {
         None
     }
 
+    /// 将文件名包含 MetaMystia 但非规范命名的 DLL（副本、大小写不同等）移至 .old，
+    /// 避免与规范命名的 DLL 同时被加载
+    fn set_aside_duplicate_dlls(&self, plugins_dir: &Path) -> Result<()> {
+        let Ok(entries) = std::fs::read_dir(plugins_dir) else {
+            return Ok(());
+        };
+
+        let (canonical, duplicates): (Vec<PathBuf>, Vec<PathBuf>) = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| {
+                p.is_file()
+                    && p.file_name().and_then(|n| n.to_str()).is_some_and(|n| {
+                        let lower = n.to_lowercase();
+                        lower.contains("metamystia") && lower.ends_with(".dll")
+                    })
+            })
+            .partition(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .and_then(|n| Self::parse_version(n, "MetaMystia-v", ".dll"))
+                    .is_some()
+            });
+
+        // 没有规范命名的 DLL 时无法判断哪个是重复项，保持原样
+        if canonical.is_empty() || duplicates.is_empty() {
+            return Ok(());
+        }
+
+        report_event(
+            "Upgrade.DuplicateDll",
+            Some(&format!("count:{}", duplicates.len())),
+        );
+
+        for path in &duplicates {
+            self.ui.upgrade_found_duplicate(path)?;
+        }
+
+        for res in backup_paths_with_index(&duplicates, "dll.old") {
+            if let Err(e) = res {
+                self.ui.upgrade_backup_failed(&format!("{}", e))?;
+            }
+        }
+
+        Ok(())
+    }
+
     fn consolidate_installed_dlls(&self) -> Result<Option<(String, PathBuf)>> {
         let plugins_dir = self.game_root.join("BepInEx").join("plugins");
+        self.set_aside_duplicate_dlls(&plugins_dir)?;
         self.consolidate_installed_by_pattern(
             &plugins_dir,
             "MetaMystia-*.dll",
@@ -51,13 +103,19 @@ impl<'a> Upgrader<'a> {
 
     fn consolidate_installed_resourceex(&self) -> Result<Option<(String, PathBuf)>> {
         let resourceex_dir = self.game_root.join("ResourceEx");
-        self.consolidate_installed_by_pattern(
+        let from_zip = self.consolidate_installed_by_pattern(
             &resourceex_dir,
             "ResourceExample-*.zip",
             "ResourceExample-v",
             ".zip",
             "zip.old",
-        )
+        )?;
+
+        // 优先使用安装标记（用户可能已解压并删除压缩包），旧版本安装没有标记时使用压缩包文件名
+        match Extractor::read_resourceex_marker(&resourceex_dir) {
+            Some(version) => Ok(Some((version, resourceex_dir.join(RESOURCEEX_MARKER)))),
+            None => Ok(from_zip),
+        }
     }
 
     fn consolidate_installed_by_pattern(
@@ -138,7 +196,11 @@ impl<'a> Upgrader<'a> {
         Ok(Some((latest_version_str, latest)))
     }
 
-    fn cleanup_old_files(&self) -> Result<()> {
+    /// 删除升级过程中留下的 .old 备份，返回已删除的文件
+    fn cleanup_old_files(&self) -> Result<Vec<PathBuf>> {
+        clear_glob_cache();
+        let mut removed_files = Vec::new();
+
         let plugins_dir = self.game_root.join("BepInEx").join("plugins");
         if plugins_dir.exists() {
             let pattern = plugins_dir.join("MetaMystia-*.dll.old*");
@@ -146,6 +208,7 @@ impl<'a> Upgrader<'a> {
             for removed in result.removed.iter() {
                 self.ui.upgrade_deleted(removed)?;
             }
+            removed_files.extend(result.removed);
             for (path, err) in result.failed.into_iter() {
                 self.ui.upgrade_delete_failed(&path, &format!("{}", err))?;
             }
@@ -158,12 +221,13 @@ impl<'a> Upgrader<'a> {
             for removed in result.removed.iter() {
                 self.ui.upgrade_deleted(removed)?;
             }
+            removed_files.extend(result.removed);
             for (path, err) in result.failed.into_iter() {
                 self.ui.upgrade_delete_failed(&path, &format!("{}", err))?;
             }
         }
 
-        Ok(())
+        Ok(removed_files)
     }
 
     fn get_installed_versions(&self) -> Result<(Option<String>, Option<String>)> {
@@ -179,20 +243,22 @@ impl<'a> Upgrader<'a> {
 
         let dll_needs = dll_opt
             .as_ref()
-            .map(|cur| cur != version_info.latest_dll())
+            .map(|cur| is_newer_version(cur, version_info.latest_dll()))
             .unwrap_or(false);
         let res_needs = res_opt
             .as_ref()
-            .map(|cur| cur != version_info.latest_resourceex())
+            .map(|cur| is_newer_version(cur, version_info.latest_resourceex()))
             .unwrap_or(false);
 
         Ok((dll_needs, res_needs))
     }
 
-    /// 执行升级
-    pub fn upgrade(&self) -> Result<()> {
+    /// 执行升级，返回是否实际进行了升级
+    pub fn upgrade(&self) -> Result<bool> {
         report_event("Upgrade.Start", None);
 
+        migrate_misplaced_resourceex(&self.game_root, self.ui)?;
+
         // 1. 查找当前安装的版本
         self.ui.upgrade_checking_installed_version()?;
 
@@ -226,16 +292,24 @@ impl<'a> Upgrader<'a> {
         let version_info = self.downloader.get_version_info()?;
         report_event("Upgrade.VersionInfo", Some(&version_info.to_string()));
 
+        // 内置版本信息可能比已安装的版本旧，不能作为升级依据
+        if version_info.bundled {
+            report_event("Upgrade.Refused.BundledVersionInfo", None);
+            return Err(ManagerError::NetworkError(
+                "无法获取最新版本信息（内置版本信息仅可用于安装），请检查网络后重试".to_string(),
+            ));
+        }
+
         // 检查 MetaMystia DLL 是否需要升级
         let new_dll_version = version_info.latest_dll();
-        let dll_needs_upgrade = current_dll_version != new_dll_version;
+        let dll_needs_upgrade = is_newer_version(&current_dll_version, new_dll_version);
         self.ui
             .upgrade_display_current_and_latest_dll(&current_dll_version, new_dll_version)?;
 
         // 检查 ResourceExample ZIP 是否需要升级
         let new_resourceex_version = version_info.latest_resourceex();
         let resourceex_needs_upgrade =
-            (current_resourceex_version != new_resourceex_version) && has_resourceex;
+            has_resourceex && is_newer_version(&current_resourceex_version, new_resourceex_version);
         if has_resourceex {
             self.ui.upgrade_display_current_and_latest_resourceex(
                 &current_resourceex_version,
@@ -245,7 +319,7 @@ impl<'a> Upgrader<'a> {
 
         if !dll_needs_upgrade && !resourceex_needs_upgrade {
             self.ui.upgrade_no_update_needed()?;
-            return Ok(());
+            return Ok(false);
         }
 
         // 显示升级信息
@@ -273,6 +347,29 @@ impl<'a> Upgrader<'a> {
             self.ui.blank_line()?;
         }
 
+        // 下载前一次性确认所有待升级组件
+        let mut pending = Vec::new();
+        if dll_needs_upgrade {
+            pending.push(PendingUpgrade {
+                component: "MetaMystia DLL".to_string(),
+                from_version: current_dll_version.clone(),
+                to_version: new_dll_version.to_string(),
+            });
+        }
+        if resourceex_needs_upgrade {
+            pending.push(PendingUpgrade {
+                component: "ResourceExample ZIP".to_string(),
+                from_version: current_resourceex_version.clone(),
+                to_version: new_resourceex_version.to_string(),
+            });
+        }
+
+        if !self.ui.confirm_batch_upgrade(&pending)? {
+            report_event("Upgrade.BatchCancelled", None);
+            return Err(ManagerError::UserCancelled);
+        }
+        report_event("Upgrade.BatchConfirmed", Some(&pending.len().to_string()));
+
         // 3. 获取分享码
         let share_code = self.downloader.get_share_code()?;
 
@@ -288,14 +385,25 @@ impl<'a> Upgrader<'a> {
                 format!("创建临时目录失败：{}", e),
             ))
         })?;
+        if is_cross_volume(&temp_dir, &self.game_root) {
+            self.ui
+                .temp_dir_warn_cross_volume(&temp_dir, &self.game_root)?;
+            report_event("Upgrade.TempDir.CrossVolume", None);
+        }
+
+        let mut download_reports = Vec::new();
 
         // 下载 DLL（仅当需要升级时）
         let temp_dll_path = if dll_needs_upgrade {
             let new_dll_filename = VersionInfo::metamystia_filename(new_dll_version);
             let path = temp_dir.join(&new_dll_filename);
 
-            self.downloader
-                .download_metamystia(&share_code, new_dll_version, &path, true)?;
+            download_reports.push(self.downloader.download_metamystia(
+                &share_code,
+                new_dll_version,
+                &path,
+                true,
+            )?);
 
             Some((path, new_dll_filename))
         } else {
@@ -309,14 +417,37 @@ impl<'a> Upgrader<'a> {
 
             self.ui.upgrade_downloading_resourceex()?;
 
-            self.downloader
-                .download_resourceex(&share_code, new_resourceex_version, &path)?;
+            download_reports.push(self.downloader.download_resourceex(
+                &share_code,
+                new_resourceex_version,
+                &path,
+            )?);
 
             Some((path, resourceex_filename))
         } else {
             None
         };
 
+        record_download_speed(&download_reports);
+
+        let mut summary = OperationSummary::default();
+        if !dll_needs_upgrade {
+            summary.push(
+                "MetaMystia DLL",
+                SummaryAction::Kept,
+                Some(&current_dll_version),
+                None,
+            );
+        }
+        if has_resourceex && !resourceex_needs_upgrade {
+            summary.push(
+                "ResourceExample ZIP",
+                SummaryAction::Kept,
+                Some(&current_resourceex_version),
+                None,
+            );
+        }
+
         // 5. 安装新版本 MetaMystia DLL（仅当需要升级时）
         if let Some((temp_path, filename)) = temp_dll_path {
             let plugins_dir = self.game_root.join("BepInEx").join("plugins");
@@ -370,6 +501,12 @@ impl<'a> Upgrader<'a> {
 
             self.ui.upgrade_install_success(&new_dll_path)?;
             report_event("Upgrade.Installed.DLL", Some(&filename));
+            summary.push(
+                "MetaMystia DLL",
+                SummaryAction::Upgraded,
+                Some(new_dll_version),
+                Some(new_dll_path),
+            );
 
             if backup_paths.is_empty() {
                 None
@@ -415,6 +552,11 @@ impl<'a> Upgrader<'a> {
                     format!("复制临时文件 {} 失败：{}", tmp_new.display(), e),
                 ))
             })?;
+            if let Err(e) = Extractor::validate_resourceex_zip(&tmp_new) {
+                let _ = std::fs::remove_file(&tmp_new);
+                report_event("Upgrade.ResourceEx.Invalid", Some(&format!("{}", e)));
+                return Err(e);
+            }
             atomic_rename_or_copy(&tmp_new, &new_zip_path).map_err(|e| {
                 ManagerError::from(std::io::Error::other(format!(
                     "安装新版本 {} 失败：{}",
@@ -423,17 +565,138 @@ impl<'a> Upgrader<'a> {
                 )))
             })?;
 
+            Extractor::write_resourceex_marker(&resourceex_dir, new_resourceex_version)?;
+
             self.ui.upgrade_install_success(&new_zip_path)?;
             report_event("Upgrade.Installed.ResourceEx", Some(&filename));
+            summary.push(
+                "ResourceExample ZIP",
+                SummaryAction::Upgraded,
+                Some(new_resourceex_version),
+                Some(new_zip_path),
+            );
         }
 
         // 7. 清理临时文件
         self.ui.upgrade_cleanup_start()?;
-        self.cleanup_old_files()?;
+        for removed in self.cleanup_old_files()? {
+            let component = removed
+                .strip_prefix(&self.game_root)
+                .unwrap_or(&removed)
+                .display()
+                .to_string();
+            summary.push(&component, SummaryAction::Removed, None, Some(removed));
+        }
 
+        self.ui.display_download_summary(&download_reports)?;
+        self.ui.display_operation_summary(&summary)?;
         self.ui.upgrade_done()?;
         report_event("Upgrade.Finished", None);
 
-        Ok(())
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{ScratchDir, TestUI, UiCall};
+
+    const PLUGINS: &str = "BepInEx/plugins";
+
+    /// 插件目录中仍以 .dll 结尾（会被 BepInEx 加载）的文件名
+    fn loaded_dlls(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .flatten()
+            .filter_map(|e| e.file_name().to_str().map(str::to_string))
+            .filter(|n| n.to_lowercase().ends_with(".dll"))
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn duplicates_reported(ui: &TestUI) -> Vec<String> {
+        let mut names: Vec<String> = ui
+            .calls()
+            .into_iter()
+            .filter_map(|c| match c {
+                UiCall::UpgradeFoundDuplicate(p) => {
+                    p.file_name().and_then(|n| n.to_str()).map(str::to_string)
+                }
+                _ => None,
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn duplicate_dlls_are_moved_aside() {
+        let game = ScratchDir::new("duplicate-dlls");
+        game.write(&format!("{}/MetaMystia-v1.2.0.dll", PLUGINS), b"new");
+        game.write(&format!("{}/MetaMystia-v1.2.0 - Copy.dll", PLUGINS), b"new");
+        game.write(&format!("{}/MetaMystia-v1.2.0 (1).dll", PLUGINS), b"new");
+        game.write(&format!("{}/METAMYSTIA-v1.1.0.dll", PLUGINS), b"old");
+        game.write(&format!("{}/OtherPlugin.dll", PLUGINS), b"other");
+
+        let ui = TestUI::new();
+        let upgrader = Upgrader::new(game.path().to_path_buf(), &ui).unwrap();
+        let (version, path) = upgrader.consolidate_installed_dlls().unwrap().unwrap();
+
+        assert_eq!(version, "1.2.0");
+        assert!(path.ends_with("MetaMystia-v1.2.0.dll"));
+        assert_eq!(
+            duplicates_reported(&ui),
+            vec![
+                "METAMYSTIA-v1.1.0.dll",
+                "MetaMystia-v1.2.0 (1).dll",
+                "MetaMystia-v1.2.0 - Copy.dll",
+            ]
+        );
+        assert_eq!(
+            loaded_dlls(&game.path().join(PLUGINS)),
+            vec!["MetaMystia-v1.2.0.dll", "OtherPlugin.dll"]
+        );
+        assert!(
+            game.path()
+                .join(PLUGINS)
+                .join("MetaMystia-v1.2.0 (1).dll.old")
+                .is_file()
+        );
+    }
+
+    #[test]
+    fn older_canonical_dlls_are_backed_up_without_duplicate_notice() {
+        let game = ScratchDir::new("older-dlls");
+        game.write(&format!("{}/MetaMystia-v1.1.0.dll", PLUGINS), b"old");
+        game.write(&format!("{}/MetaMystia-v1.2.0.dll", PLUGINS), b"new");
+
+        let ui = TestUI::new();
+        let upgrader = Upgrader::new(game.path().to_path_buf(), &ui).unwrap();
+        let (version, _) = upgrader.consolidate_installed_dlls().unwrap().unwrap();
+
+        assert_eq!(version, "1.2.0");
+        assert!(duplicates_reported(&ui).is_empty());
+        assert_eq!(
+            loaded_dlls(&game.path().join(PLUGINS)),
+            vec!["MetaMystia-v1.2.0.dll"]
+        );
+    }
+
+    #[test]
+    fn copies_are_kept_without_a_canonical_dll() {
+        let game = ScratchDir::new("only-copies");
+        game.write(&format!("{}/MetaMystia-v1.2.0 (1).dll", PLUGINS), b"new");
+
+        let ui = TestUI::new();
+        let upgrader = Upgrader::new(game.path().to_path_buf(), &ui).unwrap();
+        upgrader.consolidate_installed_dlls().unwrap();
+
+        assert!(duplicates_reported(&ui).is_empty());
+        assert_eq!(
+            loaded_dlls(&game.path().join(PLUGINS)),
+            vec!["MetaMystia-v1.2.0 (1).dll"]
+        );
     }
 }
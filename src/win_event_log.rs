@@ -0,0 +1,182 @@
+use crate::error::{ManagerError, Result};
+use crate::metrics::report_event;
+
+use std::os::windows::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use windows::Win32::System::EventLog::{
+    DeregisterEventSource, EVENTLOG_ERROR_TYPE, EVENTLOG_INFORMATION_TYPE, RegisterEventSourceW,
+    ReportEventW,
+};
+use windows::Win32::System::Threading::CREATE_NO_WINDOW;
+use windows::core::PCWSTR;
+
+/// 写入 Windows 应用程序事件日志时使用的事件源名称
+const EVENT_SOURCE: &str = "MetaMystia Manager";
+const EVENT_SOURCE_KEY: &str =
+    r"HKLM\SYSTEM\CurrentControlSet\Services\EventLog\Application\MetaMystia Manager";
+/// 管理工具内嵌了各事件 ID 的消息表（见 build.rs），注册时复制到固定位置作为消息文件，
+/// 管理工具自更新或移动后事件查看器仍能显示事件描述
+const EVENT_MESSAGE_DIR_NAME: &str = "meta-mystia-manager";
+const EVENT_MESSAGE_FILE_NAME: &str = "event-messages.exe";
+
+pub const EVENT_ID_INSTALL: u16 = 1000;
+pub const EVENT_ID_UPGRADE: u16 = 1001;
+pub const EVENT_ID_UNINSTALL: u16 = 1002;
+pub const EVENT_ID_ERROR: u16 = 1010;
+
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// 向应用程序事件日志写入一条事件（EVENT_ID_ERROR 记为错误，其余记为信息）
+pub fn write_event(event_id: u16, message: &str) -> Result<()> {
+    let source = to_wide(EVENT_SOURCE);
+    let message = to_wide(message);
+    let event_type = if event_id == EVENT_ID_ERROR {
+        EVENTLOG_ERROR_TYPE
+    } else {
+        EVENTLOG_INFORMATION_TYPE
+    };
+
+    let handle = unsafe { RegisterEventSourceW(PCWSTR::null(), PCWSTR(source.as_ptr())) }
+        .map_err(|e| ManagerError::Other(format!("打开事件日志失败：{}", e)))?;
+
+    let res = unsafe {
+        ReportEventW(
+            handle,
+            event_type,
+            0,
+            event_id as u32,
+            None,
+            0,
+            Some(&[PCWSTR(message.as_ptr())]),
+            None,
+        )
+    };
+    let _ = unsafe { DeregisterEventSource(handle) };
+
+    res.map_err(|e| ManagerError::Other(format!("写入事件日志失败：{}", e)))
+}
+
+/// 操作结果对应的事件 ID 与事件文本（用户取消的操作返回 None）
+fn operation_event<T>(
+    event_id: u16,
+    operation: &str,
+    game_root: &Path,
+    result: &Result<T>,
+) -> Option<(u16, String)> {
+    Some(match result {
+        Ok(_) => (
+            event_id,
+            format!("MetaMystia Mod {}完成：{}", operation, game_root.display()),
+        ),
+        Err(ManagerError::UserCancelled) => return None,
+        Err(e) => (
+            EVENT_ID_ERROR,
+            format!(
+                "MetaMystia Mod {}失败：{}\n{}",
+                operation,
+                game_root.display(),
+                e
+            ),
+        ),
+    })
+}
+
+/// 记录一次操作的结果（用户取消的操作不记录，写入失败时忽略）
+pub fn record_operation<T>(event_id: u16, operation: &str, game_root: &Path, result: &Result<T>) {
+    if let Some((event_id, message)) = operation_event(event_id, operation, game_root, result) {
+        let _ = write_event(event_id, &message);
+    }
+}
+
+/// 将管理工具复制到 %ProgramData% 下作为事件消息文件，返回复制后的路径
+fn install_message_file() -> Result<PathBuf> {
+    let dir = std::env::var_os("ProgramData")
+        .filter(|v| !v.is_empty())
+        .map(|dir| PathBuf::from(dir).join(EVENT_MESSAGE_DIR_NAME))
+        .ok_or_else(|| ManagerError::Other("无法确定 ProgramData 目录".to_string()))?;
+    let dest = dir.join(EVENT_MESSAGE_FILE_NAME);
+
+    std::fs::create_dir_all(&dir)
+        .and_then(|_| std::fs::copy(std::env::current_exe()?, &dest))
+        .map_err(|e| {
+            report_event("EventLog.Register.CopyFailed", Some(&format!("{}", e)));
+            ManagerError::PermissionDenied(format!(
+                "复制事件消息文件到 {} 失败（需要以管理员身份运行）：{}",
+                dest.display(),
+                e
+            ))
+        })?;
+
+    Ok(dest)
+}
+
+/// 在注册表中注册事件源（需要管理员权限）
+pub fn register_event_source() -> Result<()> {
+    let message_file = install_message_file()?.display().to_string();
+    let values: [(&str, &str, &str); 2] = [
+        ("EventMessageFile", "REG_EXPAND_SZ", &message_file),
+        ("TypesSupported", "REG_DWORD", "7"),
+    ];
+
+    for (name, kind, data) in values {
+        let output = Command::new("reg")
+            .args([
+                "add",
+                EVENT_SOURCE_KEY,
+                "/v",
+                name,
+                "/t",
+                kind,
+                "/d",
+                data,
+                "/f",
+            ])
+            .creation_flags(CREATE_NO_WINDOW.0)
+            .output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            report_event("EventLog.Register.Failed", Some(&stderr));
+            return Err(ManagerError::PermissionDenied(format!(
+                "注册事件源失败（需要以管理员身份运行）：{}",
+                stderr
+            )));
+        }
+    }
+
+    report_event("EventLog.Register.Success", None);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operation_results_map_to_events() {
+        let root = Path::new(r"C:\Games\Touhou Mystia Izakaya");
+
+        let (id, message) = operation_event(EVENT_ID_UPGRADE, "升级", root, &Ok(())).unwrap();
+        assert_eq!(id, EVENT_ID_UPGRADE);
+        assert_eq!(
+            message,
+            r"MetaMystia Mod 升级完成：C:\Games\Touhou Mystia Izakaya"
+        );
+
+        let failed: Result<()> = Err(ManagerError::GameRunning);
+        let (id, message) = operation_event(EVENT_ID_INSTALL, "安装", root, &failed).unwrap();
+        assert_eq!(id, EVENT_ID_ERROR);
+        assert!(
+            message.starts_with("MetaMystia Mod 安装失败："),
+            "{}",
+            message
+        );
+        assert!(message.ends_with(&ManagerError::GameRunning.to_string()));
+
+        let cancelled: Result<()> = Err(ManagerError::UserCancelled);
+        assert!(operation_event(EVENT_ID_UNINSTALL, "卸载", root, &cancelled).is_none());
+    }
+}